@@ -0,0 +1,352 @@
+// BLS signatures over BLS12-381 as in draft-irtf-cfrg-bls-signature, in both variants:
+// min-pk with public keys in G1 and signatures in G2, min-sig the other way around. Messages
+// are hashed to the curve in circuit with the hash_to_curve suite of the variant.
+//
+// Nothing in the circuits depends on the witness, so the same compiled circuit checks a
+// different signature in every SIMD lane, one assignment per lane in solve_witnesses.
+use crate::gnark::element::value_of;
+use crate::gnark::emparam::Bls12381Fp;
+use crate::gnark::emulated::field_bls12381::e2::GE2;
+use crate::gnark::emulated::sw_bls12381::g1::G1Affine;
+use crate::gnark::emulated::sw_bls12381::g2::{G2AffP, G2Affine, LineEvaluations};
+use crate::gnark::emulated::sw_bls12381::pairing::Pairing;
+use expander_compiler::frontend::{Config, Error, RootAPI, Variable};
+
+// Domain separation tags of the proof of possession scheme
+pub const DST_MIN_PK_POP: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+pub const DST_MIN_SIG_POP: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_";
+// Domain separation tags of the basic scheme
+pub const DST_MIN_PK_NUL: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+pub const DST_MIN_SIG_NUL: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+
+fn g2_generator<C: Config, B: RootAPI<C>>(native: &mut B) -> G2AffP {
+    let coordinates = [
+        "352701069587466618187139116011060144890029952792775240219908644239793785735715026873347600343865175952761926303160",
+        "3059144344244213709971259814753781636986470325476647558659373206291635324768958432433509563104347017837885763365758",
+        "1985150602287291935568054521177171638300868978215655730859378665066344726373823718423869104263333984641494340347905",
+        "927553665492332455747201965776037880757740193453592970025027978793976877002675564980949289727957565575433344219582",
+    ]
+    .map(|c| value_of::<C, B, Bls12381Fp>(native, Box::new(c.to_string())));
+    let [x0, x1, y0, y1] = coordinates;
+    G2AffP {
+        x: GE2 { a0: x0, a1: x1 },
+        y: GE2 { a0: y0, a1: y1 },
+    }
+}
+
+// Asserts Π e(p_i, q_i) == 1
+fn pairing_check<C: Config, B: RootAPI<C>>(
+    pairing: &mut Pairing,
+    native: &mut B,
+    p: &[G1Affine],
+    q: &[G2AffP],
+) -> Result<(), Error> {
+    let mut q = q
+        .iter()
+        .map(|q| G2Affine {
+            p: q.clone(),
+            lines: LineEvaluations::default(),
+        })
+        .collect::<Vec<_>>();
+    pairing.pairing_check(native, p, &mut q)
+}
+
+// The pairing keeps a separate emulated field per component, all of them need their
+// deferred multiplication and range checks
+fn finalize_pairing<C: Config, B: RootAPI<C>>(pairing: &mut Pairing, native: &mut B) {
+    for field in [
+        &mut pairing.ext12.ext6.ext2.curve_f,
+        &mut pairing.curve_f,
+        &mut pairing.curve.base_api,
+        &mut pairing.g1.curve_f,
+        &mut pairing.g2.ext2.curve_f,
+    ] {
+        field.check_mul(native);
+        field.table.final_check(native);
+    }
+}
+
+// Public keys in G1, signatures and message hashes in G2
+pub struct BlsMinPk {
+    pub pairing: Pairing,
+    dst: Vec<u8>,
+    neg_g1: G1Affine,
+}
+
+impl BlsMinPk {
+    pub fn new<C: Config, B: RootAPI<C>>(native: &mut B) -> Self {
+        Self::with_dst(native, DST_MIN_PK_POP)
+    }
+
+    pub fn with_dst<C: Config, B: RootAPI<C>>(native: &mut B, dst: &[u8]) -> Self {
+        let mut pairing = Pairing::new(native);
+        let g1 = G1Affine::one(native);
+        let neg_y = pairing.g1.curve_f.neg(native, &g1.y);
+        Self {
+            pairing,
+            dst: dst.to_vec(),
+            neg_g1: G1Affine { x: g1.x, y: neg_y },
+        }
+    }
+
+    pub fn hash_to_g2<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        msg: &[Variable],
+    ) -> G2AffP {
+        let g2 = &mut self.pairing.g2;
+        let u = g2
+            .ext2
+            .curve_f
+            .hash_to_fp_with_dst(native, msg, &self.dst, 4);
+        let u0 = GE2 {
+            a0: u[0].clone(),
+            a1: u[1].clone(),
+        };
+        let u1 = GE2 {
+            a0: u[2].clone(),
+            a1: u[3].clone(),
+        };
+        g2.map_to_g2(native, &u0, &u1)
+    }
+
+    // KeyValidate and the signature subgroup check
+    fn assert_inputs<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        pks: &[G1Affine],
+        sig: &G2AffP,
+    ) {
+        for pk in pks {
+            self.pairing.assert_is_on_g1(native, pk);
+        }
+        self.pairing.assert_is_on_g2(native, sig);
+    }
+
+    // e(pk, H(msg)) == e(g1, sig)
+    pub fn verify<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        pk: &G1Affine,
+        msg: &[Variable],
+        sig: &G2AffP,
+    ) -> Result<(), Error> {
+        self.aggregate_verify(native, &[pk.clone()], &[msg.to_vec()], sig)
+    }
+
+    // Π e(pk_i, H(msg_i)) == e(g1, sig) for sig the sum of the signatures of every msg_i under
+    // pk_i. The basic scheme needs the messages to be distinct, which is left to the caller.
+    pub fn aggregate_verify<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        pks: &[G1Affine],
+        msgs: &[Vec<Variable>],
+        sig: &G2AffP,
+    ) -> Result<(), Error> {
+        if pks.is_empty() || pks.len() != msgs.len() {
+            return Err(Error::UserError(
+                "expected one public key per message".to_string(),
+            ));
+        }
+        self.assert_inputs(native, pks, sig);
+        let mut p = pks.to_vec();
+        let mut q = msgs
+            .iter()
+            .map(|msg| self.hash_to_g2(native, msg))
+            .collect::<Vec<_>>();
+        p.push(self.neg_g1.clone());
+        q.push(sig.clone());
+        pairing_check(&mut self.pairing, native, &p, &q)
+    }
+
+    // The sum of the public keys, which must come with proofs of possession. The sum may not be
+    // the point at infinity, KeyValidate rejects it as a public key.
+    pub fn aggregate_public_keys<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        pks: &[G1Affine],
+    ) -> Result<G1Affine, Error> {
+        if pks.is_empty() {
+            return Err(Error::UserError(
+                "expected at least one public key".to_string(),
+            ));
+        }
+        let g1 = &mut self.pairing.g1;
+        let mut aggregate = pks[0].clone();
+        let mut is_infinity = native.constant(0);
+        for pk in &pks[1..] {
+            let (sum, is_sum_infinity) = g1.add_unified(native, &aggregate, pk);
+            // infinity + pk = pk
+            let x = g1.curve_f.select(native, is_infinity, &pk.x, &sum.x);
+            let y = g1.curve_f.select(native, is_infinity, &pk.y, &sum.y);
+            aggregate = G1Affine { x, y };
+            let is_finite = native.sub(1, is_infinity);
+            is_infinity = native.and(is_finite, is_sum_infinity);
+        }
+        native.assert_is_zero(is_infinity);
+        self.pairing.assert_is_on_curve(native, aggregate.clone());
+        Ok(aggregate)
+    }
+
+    // Verifies sig against the sum of the public keys, see aggregate_public_keys
+    pub fn fast_aggregate_verify<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        pks: &[G1Affine],
+        msg: &[Variable],
+        sig: &G2AffP,
+    ) -> Result<(), Error> {
+        self.assert_inputs(native, pks, sig);
+        let aggregate = self.aggregate_public_keys(native, pks)?;
+        let h = self.hash_to_g2(native, msg);
+        let p = [aggregate, self.neg_g1.clone()];
+        pairing_check(&mut self.pairing, native, &p, &[h, sig.clone()])
+    }
+
+    // Runs the deferred checks, call it once after all verifications
+    pub fn finalize<C: Config, B: RootAPI<C>>(&mut self, native: &mut B) {
+        finalize_pairing(&mut self.pairing, native);
+    }
+}
+
+// Public keys in G2, signatures and message hashes in G1
+pub struct BlsMinSig {
+    pub pairing: Pairing,
+    dst: Vec<u8>,
+    g2: G2AffP,
+}
+
+impl BlsMinSig {
+    pub fn new<C: Config, B: RootAPI<C>>(native: &mut B) -> Self {
+        Self::with_dst(native, DST_MIN_SIG_POP)
+    }
+
+    pub fn with_dst<C: Config, B: RootAPI<C>>(native: &mut B, dst: &[u8]) -> Self {
+        Self {
+            pairing: Pairing::new(native),
+            dst: dst.to_vec(),
+            g2: g2_generator(native),
+        }
+    }
+
+    pub fn hash_to_g1<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        msg: &[Variable],
+    ) -> G1Affine {
+        let g1 = &mut self.pairing.g1;
+        let u = g1.curve_f.hash_to_fp_with_dst(native, msg, &self.dst, 2);
+        g1.map_to_g1(native, &u[0], &u[1])
+    }
+
+    // KeyValidate and the signature subgroup check
+    fn assert_inputs<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        pks: &[G2AffP],
+        sig: &G1Affine,
+    ) {
+        for pk in pks {
+            self.pairing.assert_is_on_g2(native, pk);
+        }
+        self.pairing.assert_is_on_g1(native, sig);
+    }
+
+    fn neg_signature<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        sig: &G1Affine,
+    ) -> G1Affine {
+        let neg_y = self.pairing.g1.curve_f.neg(native, &sig.y);
+        G1Affine {
+            x: sig.x.clone(),
+            y: neg_y,
+        }
+    }
+
+    // e(H(msg), pk) == e(sig, g2)
+    pub fn verify<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        pk: &G2AffP,
+        msg: &[Variable],
+        sig: &G1Affine,
+    ) -> Result<(), Error> {
+        self.aggregate_verify(native, &[pk.clone()], &[msg.to_vec()], sig)
+    }
+
+    // Π e(H(msg_i), pk_i) == e(sig, g2) for sig the sum of the signatures of every msg_i under
+    // pk_i. The basic scheme needs the messages to be distinct, which is left to the caller.
+    pub fn aggregate_verify<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        pks: &[G2AffP],
+        msgs: &[Vec<Variable>],
+        sig: &G1Affine,
+    ) -> Result<(), Error> {
+        if pks.is_empty() || pks.len() != msgs.len() {
+            return Err(Error::UserError(
+                "expected one public key per message".to_string(),
+            ));
+        }
+        self.assert_inputs(native, pks, sig);
+        let mut p = msgs
+            .iter()
+            .map(|msg| self.hash_to_g1(native, msg))
+            .collect::<Vec<_>>();
+        let mut q = pks.to_vec();
+        p.push(self.neg_signature(native, sig));
+        q.push(self.g2.clone());
+        pairing_check(&mut self.pairing, native, &p, &q)
+    }
+
+    // The sum of the public keys, which must come with proofs of possession. The sum may not be
+    // the point at infinity, KeyValidate rejects it as a public key.
+    pub fn aggregate_public_keys<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        pks: &[G2AffP],
+    ) -> Result<G2AffP, Error> {
+        if pks.is_empty() {
+            return Err(Error::UserError(
+                "expected at least one public key".to_string(),
+            ));
+        }
+        let g2 = &mut self.pairing.g2;
+        let mut aggregate = pks[0].clone();
+        let mut is_infinity = native.constant(0);
+        for pk in &pks[1..] {
+            let (sum, is_sum_infinity) = g2.add_unified(native, &aggregate, pk);
+            // infinity + pk = pk
+            let x = g2.ext2.select(native, is_infinity, &pk.x, &sum.x);
+            let y = g2.ext2.select(native, is_infinity, &pk.y, &sum.y);
+            aggregate = G2AffP::new(x, y);
+            let is_finite = native.sub(1, is_infinity);
+            is_infinity = native.and(is_finite, is_sum_infinity);
+        }
+        native.assert_is_zero(is_infinity);
+        self.pairing.assert_is_on_twist(native, aggregate.clone());
+        Ok(aggregate)
+    }
+
+    // Verifies sig against the sum of the public keys, see aggregate_public_keys
+    pub fn fast_aggregate_verify<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        pks: &[G2AffP],
+        msg: &[Variable],
+        sig: &G1Affine,
+    ) -> Result<(), Error> {
+        self.assert_inputs(native, pks, sig);
+        let aggregate = self.aggregate_public_keys(native, pks)?;
+        let h = self.hash_to_g1(native, msg);
+        let neg_sig = self.neg_signature(native, sig);
+        let q = [aggregate, self.g2.clone()];
+        pairing_check(&mut self.pairing, native, &[h, neg_sig], &q)
+    }
+
+    // Runs the deferred checks, call it once after all verifications
+    pub fn finalize<C: Config, B: RootAPI<C>>(&mut self, native: &mut B) {
+        finalize_pairing(&mut self.pairing, native);
+    }
+}
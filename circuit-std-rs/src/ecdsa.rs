@@ -0,0 +1,8 @@
+// ECDSA over emulated short Weierstrass curves, secp256k1 and P-256 for now. The gadgets work
+// over any native field that fits the 8-bit limbs, M31 and BN254 included.
+pub mod curve;
+pub use curve::{EmulatedCurve, Secp256k1, P256};
+pub mod native;
+pub use native::{NativeCurve, NativePoint};
+pub mod verify;
+pub use verify::element_from_be_bytes;
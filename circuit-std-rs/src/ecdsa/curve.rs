@@ -0,0 +1,459 @@
+use crate::ecdsa::native::{NativeCurve, NativePoint};
+use crate::gnark::element::{value_of, Element};
+use crate::gnark::emparam::{CurveParams, FieldParams, P256Fp, P256Fr, Secp256k1Fp, Secp256k1Fr};
+use crate::gnark::emulated::sw_bls12381::point::AffinePoint;
+use crate::gnark::field::GField;
+use expander_compiler::frontend::{Config, RootAPI, Variable};
+use num_bigint::BigInt;
+
+// width in bits of the windows of scalar multiplication, tables hold 2^WINDOW points
+const WINDOW: usize = 4;
+
+// A table of the multiples d·P for every window digit d, with the unused entry 0 set to P
+type PointTable<Base> = Vec<AffinePoint<Base>>;
+
+struct MulTerm<Base: FieldParams> {
+    table: PointTable<Base>,
+    // the looked up points are negated when this bit is set
+    negate: Option<Variable>,
+    // little endian, a multiple of WINDOW long
+    bits: Vec<Variable>,
+}
+
+// A short Weierstrass curve over an emulated base field, with scalars in an emulated scalar field.
+// Additions use the incomplete affine formulas; scalar multiplications start from an offset
+// point so that no intermediate sum is the point at infinity or a doubling.
+pub struct EmulatedCurve<Base: FieldParams, Scalar: FieldParams> {
+    pub native_curve: NativeCurve,
+    pub base_api: GField<Base>,
+    pub scalar_api: GField<Scalar>,
+    pub a: Element<Base>,
+    pub b: Element<Base>,
+    add_a: bool,
+    g_table: PointTable<Base>,
+    // the table of φ(G) on curves with an endomorphism
+    g_endo_table: Option<PointTable<Base>>,
+}
+
+pub type Secp256k1 = EmulatedCurve<Secp256k1Fp, Secp256k1Fr>;
+pub type P256 = EmulatedCurve<P256Fp, P256Fr>;
+
+impl Secp256k1 {
+    pub fn new_secp256k1<C: Config, B: RootAPI<C>>(native: &mut B) -> Self {
+        Self::new(native, &CurveParams::get_secp256k1_params())
+    }
+}
+
+impl P256 {
+    pub fn new_p256<C: Config, B: RootAPI<C>>(native: &mut B) -> Self {
+        Self::new(native, &CurveParams::get_p256_params())
+    }
+}
+
+impl<Base: FieldParams + Default + Clone, Scalar: FieldParams + Default>
+    EmulatedCurve<Base, Scalar>
+{
+    pub fn new<C: Config, B: RootAPI<C>>(native: &mut B, params: &CurveParams) -> Self {
+        // base field elements are reinterpreted as scalars when checking signatures
+        if Base::bits_per_limb() != Scalar::bits_per_limb()
+            || Base::nb_limbs() != Scalar::nb_limbs()
+        {
+            panic!("base and scalar fields must have the same limb layout");
+        }
+        let native_curve = NativeCurve::new(params, Base::modulus(), Scalar::modulus());
+        let g = native_curve.generator();
+        let g_table = Self::constant_table(native, &native_curve, &g);
+        let g_endo_table = params.third_root_one.as_ref().map(|beta| {
+            let endo_g = NativePoint {
+                x: &g.x * beta % &native_curve.p,
+                y: g.y.clone(),
+            };
+            Self::constant_table(native, &native_curve, &endo_g)
+        });
+        Self {
+            base_api: GField::new(native, Base::default()),
+            scalar_api: GField::new(native, Scalar::default()),
+            a: value_of::<C, B, Base>(native, Box::new(params.a.clone())),
+            b: value_of::<C, B, Base>(native, Box::new(params.b.clone())),
+            add_a: params.a != BigInt::ZERO,
+            g_table,
+            g_endo_table,
+            native_curve,
+        }
+    }
+
+    pub fn constant_point<C: Config, B: RootAPI<C>>(
+        native: &mut B,
+        p: &NativePoint,
+    ) -> AffinePoint<Base> {
+        AffinePoint {
+            x: value_of::<C, B, Base>(native, Box::new(p.x.clone())),
+            y: value_of::<C, B, Base>(native, Box::new(p.y.clone())),
+        }
+    }
+
+    fn constant_table<C: Config, B: RootAPI<C>>(
+        native: &mut B,
+        curve: &NativeCurve,
+        p: &NativePoint,
+    ) -> PointTable<Base> {
+        let mut multiple = Some(p.clone());
+        let mut table = vec![Self::constant_point(native, p)];
+        for _ in 1..1 << WINDOW {
+            table.push(Self::constant_point(native, multiple.as_ref().unwrap()));
+            multiple = curve.add(&multiple, &Some(p.clone()));
+        }
+        table
+    }
+
+    pub fn generator<C: Config, B: RootAPI<C>>(&self, native: &mut B) -> AffinePoint<Base> {
+        Self::constant_point(native, &self.native_curve.generator())
+    }
+
+    pub fn assert_is_on_curve<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &AffinePoint<Base>,
+    ) {
+        let left = self.base_api.mul(native, &p.y, &p.y);
+        let right = self.curve_rhs(native, &p.x);
+        self.base_api.assert_is_equal(native, &left, &right);
+    }
+
+    // x³ + ax + b
+    pub fn curve_rhs<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        x: &Element<Base>,
+    ) -> Element<Base> {
+        let x_squared = self.base_api.mul(native, x, x);
+        let x_cubed = self.base_api.mul(native, &x_squared, x);
+        let mut right = self.base_api.add(native, &x_cubed, &self.b);
+        if self.add_a {
+            let ax = self.base_api.mul(native, &self.a, x);
+            right = self.base_api.add(native, &right, &ax);
+        }
+        right
+    }
+
+    pub fn neg<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &AffinePoint<Base>,
+    ) -> AffinePoint<Base> {
+        AffinePoint {
+            x: p.x.clone(),
+            y: self.base_api.neg(native, &p.y),
+        }
+    }
+
+    // p + q for p != ±q
+    pub fn add<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &AffinePoint<Base>,
+        q: &AffinePoint<Base>,
+    ) -> AffinePoint<Base> {
+        let dy = self.base_api.sub(native, &q.y, &p.y);
+        let dx = self.base_api.sub(native, &q.x, &p.x);
+        let lambda = self.base_api.div(native, &dy, &dx);
+        self.finish_add(native, &lambda, p, &q.x)
+    }
+
+    // 2p for p.y != 0
+    pub fn double<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &AffinePoint<Base>,
+    ) -> AffinePoint<Base> {
+        let x_squared = self.base_api.mul(native, &p.x, &p.x);
+        let mut num = self.base_api.mul_const(native, &x_squared, BigInt::from(3));
+        if self.add_a {
+            num = self.base_api.add(native, &num, &self.a);
+        }
+        let den = self.base_api.add(native, &p.y, &p.y);
+        let lambda = self.base_api.div(native, &num, &den);
+        self.finish_add(native, &lambda, p, &p.x)
+    }
+
+    // the sum of p and a point with x coordinate qx on the line of slope lambda through p
+    fn finish_add<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        lambda: &Element<Base>,
+        p: &AffinePoint<Base>,
+        qx: &Element<Base>,
+    ) -> AffinePoint<Base> {
+        let lambda_squared = self.base_api.mul(native, lambda, lambda);
+        let x = self.base_api.sub(native, &lambda_squared, &p.x);
+        let x = self.base_api.sub(native, &x, qx);
+        let dx = self.base_api.sub(native, &p.x, &x);
+        let y = self.base_api.mul(native, lambda, &dx);
+        let y = self.base_api.sub(native, &y, &p.y);
+        AffinePoint { x, y }
+    }
+
+    // return p if selector is 1 and q if selector is 0
+    pub fn select<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        selector: Variable,
+        p: &AffinePoint<Base>,
+        q: &AffinePoint<Base>,
+    ) -> AffinePoint<Base> {
+        AffinePoint {
+            x: self.base_api.select(native, selector, &p.x, &q.x),
+            y: self.base_api.select(native, selector, &p.y, &q.y),
+        }
+    }
+
+    // φ(p) = (βx, y) = λp on curves with an endomorphism
+    fn endomorphism<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &AffinePoint<Base>,
+    ) -> AffinePoint<Base> {
+        let beta = self.native_curve.params.third_root_one.clone().unwrap();
+        let beta = value_of::<C, B, Base>(native, Box::new(beta));
+        AffinePoint {
+            x: self.base_api.mul(native, &p.x, &beta),
+            y: p.y.clone(),
+        }
+    }
+
+    // Multiples of p, reduced to keep the looked up entries small
+    fn variable_table<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &AffinePoint<Base>,
+    ) -> PointTable<Base> {
+        let p = self.reduce_point(native, p);
+        let mut table = vec![p.clone(), p.clone()];
+        for d in 2..1 << WINDOW {
+            let multiple = if d == 2 {
+                self.double(native, &p)
+            } else {
+                self.add(native, &table[d - 1].clone(), &p)
+            };
+            table.push(self.reduce_point(native, &multiple));
+        }
+        table
+    }
+
+    fn reduce_point<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &AffinePoint<Base>,
+    ) -> AffinePoint<Base> {
+        AffinePoint {
+            x: self.base_api.reduce(native, &p.x, false),
+            y: self.base_api.reduce(native, &p.y, false),
+        }
+    }
+
+    fn lookup<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        table: &PointTable<Base>,
+        digit: &[Variable],
+    ) -> AffinePoint<Base> {
+        let xs = table.iter().map(|p| &p.x).collect::<Vec<_>>();
+        let ys = table.iter().map(|p| &p.y).collect::<Vec<_>>();
+        AffinePoint {
+            x: self.base_api.mux(native, digit, &xs),
+            y: self.base_api.mux(native, digit, &ys),
+        }
+    }
+
+    // The little endian bits of limbs with bits_per_limb bits each, asserting that the value
+    // fits in nb_bits
+    fn limb_bits<C: Config, B: RootAPI<C>>(
+        native: &mut B,
+        limbs: &[Variable],
+        nb_bits: usize,
+    ) -> Vec<Variable> {
+        let mut bits = vec![];
+        for limb in limbs {
+            bits.extend(native.to_binary(*limb, Scalar::bits_per_limb() as usize));
+        }
+        for bit in bits.iter().skip(nb_bits) {
+            native.assert_is_zero(*bit);
+        }
+        bits.resize(nb_bits, native.constant(0));
+        bits
+    }
+
+    fn round_to_window(nb_bits: u64) -> usize {
+        (nb_bits as usize).div_ceil(WINDOW) * WINDOW
+    }
+
+    // Scalars as the terms of a joint scalar multiplication, split in two halves with the
+    // endomorphism where the curve has one
+    fn scalar_terms<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        k: &Element<Scalar>,
+        table: PointTable<Base>,
+        endo_table: Option<PointTable<Base>>,
+    ) -> Vec<MulTerm<Base>> {
+        let endo_table = match endo_table {
+            Some(endo_table) => endo_table,
+            None => {
+                // a strictly reduced scalar may still be k + n, which multiplies to the same point
+                let k = self.scalar_api.reduce(native, k, true);
+                let nb_bits = Self::round_to_window(Scalar::modulus().bits());
+                let bits = Self::limb_bits(native, &k.limbs, nb_bits);
+                return vec![MulTerm {
+                    table,
+                    negate: None,
+                    bits,
+                }];
+            }
+        };
+        let lambda = self.native_curve.params.eigenvalue.clone().unwrap();
+        let lambda = value_of::<C, B, Scalar>(native, Box::new(lambda));
+        let outputs = self.scalar_api.new_hint(
+            native,
+            "myhint.glvdecomposehint",
+            4,
+            vec![k.clone(), lambda.clone()],
+        );
+        let signs = [outputs[2].limbs[0], outputs[3].limbs[0]];
+        native.assert_is_bool(signs[0]);
+        native.assert_is_bool(signs[1]);
+        // ±k1 ± k2·λ == k
+        let mut halves = vec![];
+        for i in 0..2 {
+            let neg_half = self.scalar_api.neg(native, &outputs[i]);
+            halves.push(
+                self.scalar_api
+                    .select(native, signs[i], &neg_half, &outputs[i]),
+            );
+        }
+        let k2_lambda = self.scalar_api.mul(native, &halves[1], &lambda);
+        let sum = self.scalar_api.add(native, &halves[0], &k2_lambda);
+        self.scalar_api.assert_is_equal(native, &sum, k);
+        // the halves have about half the bits of the group order, with some slack
+        let nb_bits = Self::round_to_window(Scalar::modulus().bits() / 2 + 8);
+        vec![
+            MulTerm {
+                table,
+                negate: Some(signs[0]),
+                bits: Self::limb_bits(native, &outputs[0].limbs, nb_bits),
+            },
+            MulTerm {
+                table: endo_table,
+                negate: Some(signs[1]),
+                bits: Self::limb_bits(native, &outputs[1].limbs, nb_bits),
+            },
+        ]
+    }
+
+    // Σ k_i·P_i, adding the terms window by window into a shared accumulator
+    fn joint_scalar_mul<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        terms: &[MulTerm<Base>],
+    ) -> AffinePoint<Base> {
+        let nb_bits = terms.iter().map(|t| t.bits.len()).max().unwrap();
+        let offset = self.native_curve.offset_point();
+        let mut acc = Self::constant_point(native, &offset);
+        for window in (0..nb_bits / WINDOW).rev() {
+            for _ in 0..WINDOW {
+                acc = self.double(native, &acc);
+            }
+            for term in terms {
+                if term.bits.len() < (window + 1) * WINDOW {
+                    continue;
+                }
+                let digit = &term.bits[window * WINDOW..(window + 1) * WINDOW];
+                let mut entry = self.lookup(native, &term.table, digit);
+                if let Some(negate) = term.negate {
+                    let neg_y = self.base_api.neg(native, &entry.y);
+                    entry.y = self.base_api.select(native, negate, &neg_y, &entry.y);
+                }
+                let sum = self.add(native, &acc, &entry);
+                let digit_value = native.from_binary(digit);
+                let digit_is_zero = native.is_zero(digit_value);
+                acc = self.select(native, digit_is_zero, &acc, &sum);
+            }
+        }
+        // remove the offset, which has been doubled once per bit
+        let shift = BigInt::from(1) << nb_bits;
+        let correction = self
+            .native_curve
+            .neg(&self.native_curve.mul(&shift, &Some(offset)))
+            .unwrap();
+        let correction = Self::constant_point(native, &correction);
+        self.add(native, &acc, &correction)
+    }
+
+    // k·p
+    pub fn scalar_mul<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        k: &Element<Scalar>,
+        p: &AffinePoint<Base>,
+    ) -> AffinePoint<Base> {
+        let table = self.variable_table(native, p);
+        let endo_table = if self.g_endo_table.is_some() {
+            let endo_p = self.endomorphism(native, p);
+            Some(self.variable_table(native, &endo_p))
+        } else {
+            None
+        };
+        let terms = self.scalar_terms(native, k, table, endo_table);
+        self.joint_scalar_mul(native, &terms)
+    }
+
+    // k·G
+    pub fn scalar_mul_base<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        k: &Element<Scalar>,
+    ) -> AffinePoint<Base> {
+        let terms = self.scalar_terms(native, k, self.g_table.clone(), self.g_endo_table.clone());
+        self.joint_scalar_mul(native, &terms)
+    }
+
+    // k1·G + k2·p, sharing the doublings of both multiplications
+    pub fn joint_scalar_mul_base<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        k1: &Element<Scalar>,
+        k2: &Element<Scalar>,
+        p: &AffinePoint<Base>,
+    ) -> AffinePoint<Base> {
+        self.multi_scalar_mul_base(native, k1, &[(k2.clone(), p.clone())])
+    }
+
+    // k·G + Σ k_i·p_i, sharing the doublings of all multiplications
+    pub fn multi_scalar_mul_base<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        k: &Element<Scalar>,
+        terms: &[(Element<Scalar>, AffinePoint<Base>)],
+    ) -> AffinePoint<Base> {
+        let mut mul_terms =
+            self.scalar_terms(native, k, self.g_table.clone(), self.g_endo_table.clone());
+        for (k_i, p_i) in terms {
+            let table = self.variable_table(native, p_i);
+            let endo_table = if self.g_endo_table.is_some() {
+                let endo_p = self.endomorphism(native, p_i);
+                Some(self.variable_table(native, &endo_p))
+            } else {
+                None
+            };
+            mul_terms.extend(self.scalar_terms(native, k_i, table, endo_table));
+        }
+        self.joint_scalar_mul(native, &mul_terms)
+    }
+
+    // Runs the deferred multiplication and range checks of both fields, call it once at the end
+    pub fn finalize<C: Config, B: RootAPI<C>>(&mut self, native: &mut B) {
+        self.base_api.check_mul(native);
+        self.scalar_api.check_mul(native);
+        self.base_api.table.final_check(native);
+        self.scalar_api.table.final_check(native);
+    }
+}
@@ -0,0 +1,255 @@
+use crate::gnark::emparam::{
+    BN254Fp, BN254Fr, CurveParams, FieldParams, P256Fp, P256Fr, Secp256k1Fp, Secp256k1Fr,
+};
+use crate::gnark::utils::sqrt_mod;
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Signed, Zero};
+use sha2::{Digest, Sha256};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NativePoint {
+    pub x: BigInt,
+    pub y: BigInt,
+}
+
+// Reference implementation of the curve and of ECDSA over plain integers, used to compute
+// circuit constants and to produce test vectors. None is the point at infinity.
+#[derive(Clone, Debug)]
+pub struct NativeCurve {
+    pub p: BigInt,
+    pub n: BigInt,
+    pub params: CurveParams,
+}
+
+impl NativeCurve {
+    pub fn new(params: &CurveParams, p: BigInt, n: BigInt) -> Self {
+        Self {
+            p,
+            n,
+            params: params.clone(),
+        }
+    }
+
+    pub fn secp256k1() -> Self {
+        Self::new(
+            &CurveParams::get_secp256k1_params(),
+            Secp256k1Fp::modulus(),
+            Secp256k1Fr::modulus(),
+        )
+    }
+
+    pub fn p256() -> Self {
+        Self::new(
+            &CurveParams::get_p256_params(),
+            P256Fp::modulus(),
+            P256Fr::modulus(),
+        )
+    }
+
+    pub fn bn254() -> Self {
+        Self::new(
+            &CurveParams::get_bn254_params(),
+            BN254Fp::modulus(),
+            BN254Fr::modulus(),
+        )
+    }
+
+    pub fn generator(&self) -> NativePoint {
+        NativePoint {
+            x: self.params.gx.clone(),
+            y: self.params.gy.clone(),
+        }
+    }
+
+    pub fn is_on_curve(&self, q: &NativePoint) -> bool {
+        let rhs = &q.x * &q.x * &q.x + &self.params.a * &q.x + &self.params.b;
+        modulo(&(&q.y * &q.y - rhs), &self.p).is_zero()
+    }
+
+    pub fn neg(&self, q: &Option<NativePoint>) -> Option<NativePoint> {
+        q.as_ref().map(|q| NativePoint {
+            x: q.x.clone(),
+            y: modulo(&-&q.y, &self.p),
+        })
+    }
+
+    pub fn add(&self, a: &Option<NativePoint>, b: &Option<NativePoint>) -> Option<NativePoint> {
+        let (a, b) = match (a, b) {
+            (None, _) => return b.clone(),
+            (_, None) => return a.clone(),
+            (Some(a), Some(b)) => (a, b),
+        };
+        let p = &self.p;
+        let lambda = if a.x == b.x {
+            if modulo(&(&a.y + &b.y), p).is_zero() {
+                return None;
+            }
+            let num = BigInt::from(3) * &a.x * &a.x + &self.params.a;
+            num * (BigInt::from(2) * &a.y).modinv(p).unwrap()
+        } else {
+            (&b.y - &a.y) * modulo(&(&b.x - &a.x), p).modinv(p).unwrap()
+        };
+        let x = modulo(&(&lambda * &lambda - &a.x - &b.x), p);
+        let y = modulo(&(lambda * (&a.x - &x) - &a.y), p);
+        Some(NativePoint { x, y })
+    }
+
+    pub fn double(&self, a: &Option<NativePoint>) -> Option<NativePoint> {
+        self.add(a, a)
+    }
+
+    pub fn mul(&self, k: &BigInt, q: &Option<NativePoint>) -> Option<NativePoint> {
+        let k = modulo(k, &self.n);
+        let mut acc = None;
+        for i in (0..k.bits()).rev() {
+            acc = self.double(&acc);
+            if k.bit(i) {
+                acc = self.add(&acc, q);
+            }
+        }
+        acc
+    }
+
+    pub fn public_key(&self, private_key: &BigInt) -> NativePoint {
+        self.mul(private_key, &Some(self.generator())).unwrap()
+    }
+
+    // Signs with the given nonce, returning (r, s, v) where v is the parity of R.y
+    pub fn sign(
+        &self,
+        private_key: &BigInt,
+        msg_hash: &BigInt,
+        nonce: &BigInt,
+    ) -> (BigInt, BigInt, bool) {
+        let n = &self.n;
+        let big_r = self.mul(nonce, &Some(self.generator())).unwrap();
+        let r = modulo(&big_r.x, n);
+        let s = modulo(
+            &(nonce.modinv(n).unwrap() * (msg_hash + &r * private_key)),
+            n,
+        );
+        assert!(!r.is_zero() && !s.is_zero(), "degenerate nonce");
+        (r, s, big_r.y.bit(0))
+    }
+
+    pub fn verify(&self, msg_hash: &BigInt, r: &BigInt, s: &BigInt, pubkey: &NativePoint) -> bool {
+        let n = &self.n;
+        let in_range = |x: &BigInt| x.is_positive() && x < n;
+        if !in_range(r) || !in_range(s) || !self.is_on_curve(pubkey) {
+            return false;
+        }
+        let s_inv = s.modinv(n).unwrap();
+        let u1 = msg_hash * &s_inv;
+        let u2 = r * &s_inv;
+        let big_r = self.add(
+            &self.mul(&u1, &Some(self.generator())),
+            &self.mul(&u2, &Some(pubkey.clone())),
+        );
+        match big_r {
+            Some(big_r) => modulo(&big_r.x, n) == *r,
+            None => false,
+        }
+    }
+
+    // Recovers the public key of a signature whose R.x is r and R.y has parity v
+    pub fn recover(
+        &self,
+        msg_hash: &BigInt,
+        r: &BigInt,
+        s: &BigInt,
+        v: bool,
+    ) -> Option<NativePoint> {
+        let (p, n) = (&self.p, &self.n);
+        let rhs = r * r * r + &self.params.a * r + &self.params.b;
+        let mut y = sqrt_mod(&rhs, p)?;
+        if y.bit(0) != v {
+            y = p - y;
+        }
+        let big_r = Some(NativePoint { x: r.clone(), y });
+        let r_inv = r.modinv(n)?;
+        let u1 = modulo(&(-msg_hash * &r_inv), n);
+        let u2 = s * &r_inv;
+        self.add(
+            &self.mul(&u1, &Some(self.generator())),
+            &self.mul(&u2, &big_r),
+        )
+    }
+
+    // A point with no known discrete logarithm, hashed from a fixed label. Windowed scalar
+    // multiplication starts from it so that the incomplete addition formulas never meet
+    // the point at infinity.
+    pub fn offset_point(&self) -> NativePoint {
+        let digest = Sha256::digest(b"circuit-std-rs ecdsa offset point");
+        let mut x = modulo(&BigInt::from_bytes_be(Sign::Plus, &digest), &self.p);
+        loop {
+            let rhs = &x * &x * &x + &self.params.a * &x + &self.params.b;
+            if let Some(y) = sqrt_mod(&rhs, &self.p) {
+                return NativePoint { x, y };
+            }
+            x += 1;
+        }
+    }
+
+    // Splits k into (k1, k2) of about half the bit length with k ≡ k1 + k2·λ mod n
+    pub fn glv_decompose(&self, k: &BigInt) -> (BigInt, BigInt) {
+        let lambda = self
+            .params
+            .eigenvalue
+            .as_ref()
+            .expect("curve has no endomorphism");
+        glv_decompose(&self.n, lambda, k)
+    }
+}
+
+pub fn modulo(a: &BigInt, m: &BigInt) -> BigInt {
+    ((a % m) + m) % m
+}
+
+// round(a / b) for b > 0
+fn div_round(a: &BigInt, b: &BigInt) -> BigInt {
+    let num = BigInt::from(2) * a + b;
+    let den = BigInt::from(2) * b;
+    let q = &num / &den;
+    if (&num % &den).is_negative() {
+        q - 1
+    } else {
+        q
+    }
+}
+
+// A short basis (a1, b1), (a2, b2) of the lattice {(x, y) : x + y·λ ≡ 0 mod n}, found with the
+// extended Euclidean algorithm as in Guide to Elliptic Curve Cryptography, algorithm 3.74
+pub fn glv_basis(n: &BigInt, lambda: &BigInt) -> [BigInt; 4] {
+    let sqrt_n = n.sqrt();
+    let (mut r, mut t) = (
+        vec![n.clone(), lambda.clone()],
+        vec![BigInt::zero(), BigInt::one()],
+    );
+    while !r[r.len() - 1].is_zero() {
+        let i = r.len() - 1;
+        let q = &r[i - 1] / &r[i];
+        let (next_r, next_t) = (&r[i - 1] - &q * &r[i], &t[i - 1] - &q * &t[i]);
+        r.push(next_r);
+        t.push(next_t);
+    }
+    // l is the largest index with r_l >= sqrt(n)
+    let l = r.iter().rposition(|x| *x >= sqrt_n).unwrap();
+    let (a1, b1) = (r[l + 1].clone(), -&t[l + 1]);
+    let norm = |a: &BigInt, b: &BigInt| a * a + b * b;
+    let (a2, b2) = if norm(&r[l], &t[l]) <= norm(&r[l + 2], &t[l + 2]) {
+        (r[l].clone(), -&t[l])
+    } else {
+        (r[l + 2].clone(), -&t[l + 2])
+    };
+    [a1, b1, a2, b2]
+}
+
+pub fn glv_decompose(n: &BigInt, lambda: &BigInt, k: &BigInt) -> (BigInt, BigInt) {
+    let [a1, b1, a2, b2] = glv_basis(n, lambda);
+    let k = modulo(k, n);
+    let c1 = div_round(&(&b2 * &k), n);
+    let c2 = div_round(&(-&b1 * &k), n);
+    let k1 = &k - &c1 * &a1 - &c2 * &a2;
+    let k2 = -&c1 * &b1 - &c2 * &b2;
+    (k1, k2)
+}
@@ -0,0 +1,87 @@
+use crate::ecdsa::curve::EmulatedCurve;
+use crate::gnark::element::{new_internal_element, Element};
+use crate::gnark::emparam::FieldParams;
+use crate::gnark::emulated::sw_bls12381::point::AffinePoint;
+use expander_compiler::frontend::{Config, RootAPI, Variable};
+
+// An element from its big endian bytes, as hashes, signatures and keys are usually encoded.
+// The bytes are range checked on first use.
+pub fn element_from_be_bytes<T: FieldParams>(bytes: &[Variable]) -> Element<T> {
+    if T::bits_per_limb() != 8 || bytes.len() != T::nb_limbs() as usize {
+        panic!("expected {} bytes", T::nb_limbs());
+    }
+    let limbs = bytes.iter().rev().cloned().collect();
+    Element::new(limbs, 0, false, false, false, Variable::default())
+}
+
+impl<Base: FieldParams + Default + Clone, Scalar: FieldParams + Default>
+    EmulatedCurve<Base, Scalar>
+{
+    // r and s must be canonical and non zero, as verifiers reject the other encodings
+    fn assert_signature_range<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        r: &Element<Scalar>,
+        s: &Element<Scalar>,
+    ) {
+        for x in [r, s] {
+            self.scalar_api.assert_is_canonical(native, x);
+            let is_zero = self.scalar_api.is_zero(native, x);
+            native.assert_is_zero(is_zero);
+        }
+    }
+
+    // Asserts that (r, s) signs msg_hash under pubkey. msg_hash is the message hash truncated
+    // to the bit length of the group order.
+    pub fn verify<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        msg_hash: &Element<Scalar>,
+        r: &Element<Scalar>,
+        s: &Element<Scalar>,
+        pubkey: &AffinePoint<Base>,
+    ) {
+        self.assert_signature_range(native, r, s);
+        self.assert_is_on_curve(native, pubkey);
+        let s_inv = self.scalar_api.inverse(native, s);
+        let u1 = self.scalar_api.mul(native, msg_hash, &s_inv);
+        let u2 = self.scalar_api.mul(native, r, &s_inv);
+        let big_r = self.joint_scalar_mul_base(native, &u1, &u2, pubkey);
+        // R.x mod n == r, with R.x canonical so that its reduction is unique
+        let x = self.base_api.reduce(native, &big_r.x, true);
+        self.base_api.assert_is_canonical(native, &x);
+        let x = new_internal_element::<Scalar>(x.limbs, 0);
+        self.scalar_api.assert_is_equal(native, &x, r);
+    }
+
+    // Recovers the public key that (r, s) signs msg_hash under, v is the parity of R.y.
+    // Signatures whose R.x was reduced modulo the group order can't be recovered, which
+    // happens with negligible probability.
+    pub fn recover_public_key<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        msg_hash: &Element<Scalar>,
+        r: &Element<Scalar>,
+        s: &Element<Scalar>,
+        v: Variable,
+    ) -> AffinePoint<Base> {
+        self.assert_signature_range(native, r, s);
+        native.assert_is_bool(v);
+        // r < n < p is also a canonical base field element
+        let x = new_internal_element::<Base>(r.limbs.clone(), 0);
+        let y_squared = self.curve_rhs(native, &x);
+        let root = self.base_api.sqrt(native, &y_squared);
+        self.base_api.assert_is_canonical(native, &root);
+        let parity = native.to_binary(root.limbs[0], Base::bits_per_limb() as usize)[0];
+        let flip = native.xor(parity, v);
+        let neg_root = self.base_api.neg(native, &root);
+        let y = self.base_api.select(native, flip, &neg_root, &root);
+        let big_r = AffinePoint { x, y };
+        // Q = r⁻¹(s·R - z·G)
+        let r_inv = self.scalar_api.inverse(native, r);
+        let u1 = self.scalar_api.mul(native, msg_hash, &r_inv);
+        let u1 = self.scalar_api.neg(native, &u1);
+        let u2 = self.scalar_api.mul(native, s, &r_inv);
+        self.joint_scalar_mul_base(native, &u1, &u2, &big_r)
+    }
+}
@@ -0,0 +1,146 @@
+// Ed25519 signature verification as in RFC 8032, with the curve arithmetic emulated over
+// the native field and the challenge hashed in circuit with SHA-512.
+pub mod native;
+
+use arith::Field;
+use ethnum::U256;
+use expander_compiler::frontend::{
+    declare_circuit, CircuitField, Config, Define, RootAPI, Variable,
+};
+use num_bigint::BigInt;
+use rand::RngCore;
+
+use crate::gnark::element::{new_internal_element, value_of, Element};
+use crate::gnark::emparam::{Ed25519Fp, Ed25519Fr, FieldParams};
+use crate::gnark::emulated::sw_bls12381::point::AffinePoint;
+use crate::gnark::emulated::twisted_edwards::point::Ed25519;
+use crate::sha512::sha512;
+use crate::StdCircuit;
+
+// An element from its little endian bytes, the bytes are range checked on first use
+fn element_from_le_bytes<T: FieldParams>(bytes: &[Variable]) -> Element<T> {
+    Element::new(bytes.to_vec(), 0, false, false, false, Variable::default())
+}
+
+// Decodes a compressed point: y in little endian with the parity of x in the top bit. Non
+// canonical y, x = 0 with the parity bit set and y without a matching x are all rejected.
+pub fn decompress<C: Config, B: RootAPI<C>>(
+    native: &mut B,
+    curve: &mut Ed25519,
+    bytes: &[Variable],
+) -> AffinePoint<Ed25519Fp> {
+    if bytes.len() != 32 {
+        panic!("expected 32 bytes");
+    }
+    let top = native.to_binary(bytes[31], 8);
+    let sign = top[7];
+    let mut limbs = bytes[..31].to_vec();
+    limbs.push(native.from_binary(&top[..7]));
+    let y = element_from_le_bytes::<Ed25519Fp>(&limbs);
+    curve.base_api.assert_is_canonical(native, &y);
+
+    // x² = (y² - 1) / (dy² + 1)
+    let one = curve.base_api.one_const.clone();
+    let yy = curve.base_api.mul(native, &y, &y);
+    let num = curve.base_api.sub(native, &yy, &one);
+    let dyy = curve.base_api.mul(native, &curve.d, &yy);
+    let den = curve.base_api.add(native, &dyy, &one);
+    let xx = curve.base_api.div(native, &num, &den);
+    let root = curve.base_api.sqrt(native, &xx);
+    curve.base_api.assert_is_canonical(native, &root);
+
+    let root_is_zero = curve.base_api.is_zero(native, &root);
+    let negative_zero = native.mul(root_is_zero, sign);
+    native.assert_is_zero(negative_zero);
+    let parity = native.to_binary(root.limbs[0], 8)[0];
+    let flip = native.xor(parity, sign);
+    let neg_root = curve.base_api.neg(native, &root);
+    let x = curve.base_api.select(native, flip, &neg_root, &root);
+    AffinePoint { x, y }
+}
+
+// Asserts that signature (R || S) signs msg under pubkey, all as bytes in the RFC 8032
+// encoding. The check is cofactorless, [S]B == R + [k]A with k = SHA-512(R || A || msg).
+// Call curve.finalize once all signatures are verified.
+pub fn verify<C: Config, B: RootAPI<C>>(
+    native: &mut B,
+    curve: &mut Ed25519,
+    pubkey: &[Variable],
+    msg: &[Variable],
+    signature: &[Variable],
+) {
+    if signature.len() != 64 {
+        panic!("expected 64 signature bytes");
+    }
+    let a = decompress(native, curve, pubkey);
+    let big_r = decompress(native, curve, &signature[..32]);
+    let s = element_from_le_bytes::<Ed25519Fr>(&signature[32..]);
+    curve.scalar_api.assert_is_canonical(native, &s);
+
+    // the 512-bit digest is lo + hi·2^256, reduced modulo the group order
+    let digest = sha512(native, &[&signature[..32], pubkey, msg].concat());
+    let lo = new_internal_element::<Ed25519Fr>(digest[..32].to_vec(), 0);
+    let hi = new_internal_element::<Ed25519Fr>(digest[32..].to_vec(), 0);
+    let shift = (BigInt::from(1) << 256) % Ed25519Fr::modulus();
+    let shift = value_of::<C, B, Ed25519Fr>(native, Box::new(shift));
+    let hi = curve.scalar_api.mul(native, &hi, &shift);
+    let k = curve.scalar_api.add(native, &lo, &hi);
+
+    let neg_a = curve.neg(native, &a);
+    let sb_minus_ka = curve.joint_scalar_mul_base(native, &s, &k, &neg_a);
+    curve.assert_is_equal(native, &sb_minus_ka, &big_r);
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Ed25519Params {
+    pub msg_len: usize,
+}
+
+declare_circuit!(_Ed25519Circuit {
+    pubkey: [Variable; 32],
+    msg: [Variable],
+    signature: [Variable; 64],
+});
+
+pub type Ed25519Circuit = _Ed25519Circuit<Variable>;
+
+impl<C: Config> Define<C> for Ed25519Circuit {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        let mut curve = Ed25519::new_ed25519(builder);
+        verify(
+            builder,
+            &mut curve,
+            &self.pubkey,
+            &self.msg,
+            &self.signature,
+        );
+        curve.finalize(builder);
+    }
+}
+
+impl<C: Config> StdCircuit<C> for Ed25519Circuit {
+    type Params = Ed25519Params;
+    type Assignment = _Ed25519Circuit<CircuitField<C>>;
+
+    fn new_circuit(params: &Self::Params) -> Self {
+        let mut circuit = Self::default();
+        circuit.msg.resize(params.msg_len, Variable::default());
+        circuit
+    }
+
+    fn new_assignment(params: &Self::Params, mut rng: impl RngCore) -> Self::Assignment {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        let mut msg = vec![0u8; params.msg_len];
+        rng.fill_bytes(&mut msg);
+        let pubkey = native::public_key(&seed);
+        let signature = native::sign(&seed, &msg);
+
+        let to_field = |byte: &u8| CircuitField::<C>::from_u256(U256::from(*byte));
+        let mut assignment = _Ed25519Circuit::<CircuitField<C>>::default();
+        assignment.pubkey = std::array::from_fn(|i| to_field(&pubkey[i]));
+        assignment.msg = msg.iter().map(to_field).collect();
+        assignment.signature = std::array::from_fn(|i| to_field(&signature[i]));
+        assignment
+    }
+}
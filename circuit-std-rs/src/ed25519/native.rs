@@ -0,0 +1,129 @@
+use crate::gnark::emparam::{Ed25519Fp, Ed25519Fr, FieldParams, TwistedEdwardsParams};
+use crate::gnark::utils::sqrt_mod;
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Zero};
+use sha2::{Digest, Sha512};
+
+// Reference Ed25519 as in RFC 8032 over plain integers, used to produce test vectors
+pub type NativePoint = (BigInt, BigInt);
+
+fn modulo(a: &BigInt, m: &BigInt) -> BigInt {
+    ((a % m) + m) % m
+}
+
+pub fn add(p: &NativePoint, q: &NativePoint) -> NativePoint {
+    let params = TwistedEdwardsParams::get_ed25519_params();
+    let modulus = Ed25519Fp::modulus();
+    let x1x2 = &p.0 * &q.0;
+    let y1y2 = &p.1 * &q.1;
+    let t = modulo(&(&params.d * &x1x2 * &y1y2), &modulus);
+    let x_den = (BigInt::one() + &t).modinv(&modulus).unwrap();
+    let y_den = modulo(&(BigInt::one() - &t), &modulus)
+        .modinv(&modulus)
+        .unwrap();
+    let x = modulo(&((&p.0 * &q.1 + &p.1 * &q.0) * x_den), &modulus);
+    let y = modulo(&((y1y2 - &params.a * x1x2) * y_den), &modulus);
+    (x, y)
+}
+
+pub fn scalar_mul(k: &BigInt, p: &NativePoint) -> NativePoint {
+    let mut acc = (BigInt::zero(), BigInt::one());
+    for i in (0..k.bits()).rev() {
+        acc = add(&acc, &acc);
+        if k.bit(i) {
+            acc = add(&acc, p);
+        }
+    }
+    acc
+}
+
+pub fn base_point() -> NativePoint {
+    let params = TwistedEdwardsParams::get_ed25519_params();
+    (params.gx, params.gy)
+}
+
+// y in little endian with the parity of x in the top bit
+pub fn encode_point(p: &NativePoint) -> [u8; 32] {
+    let mut bytes = to_le_bytes(&p.1);
+    if p.0.bit(0) {
+        bytes[31] |= 0x80;
+    }
+    bytes
+}
+
+pub fn decode_point(bytes: &[u8; 32]) -> Option<NativePoint> {
+    let params = TwistedEdwardsParams::get_ed25519_params();
+    let modulus = Ed25519Fp::modulus();
+    let sign = bytes[31] >> 7 == 1;
+    let mut y_bytes = *bytes;
+    y_bytes[31] &= 0x7f;
+    let y = BigInt::from_bytes_le(Sign::Plus, &y_bytes);
+    if y >= modulus {
+        return None;
+    }
+    let yy = &y * &y;
+    let num = modulo(&(&yy - 1), &modulus);
+    let den = modulo(&(&params.d * &yy + 1), &modulus);
+    let x = sqrt_mod(&(num * den.modinv(&modulus)?), &modulus)?;
+    if x.is_zero() && sign {
+        return None;
+    }
+    let x = if x.bit(0) != sign { &modulus - x } else { x };
+    Some((x, y))
+}
+
+pub fn to_le_bytes(x: &BigInt) -> [u8; 32] {
+    let (_, bytes) = x.to_bytes_le();
+    std::array::from_fn(|i| bytes.get(i).copied().unwrap_or(0))
+}
+
+fn hash_to_scalar(chunks: &[&[u8]]) -> BigInt {
+    let mut hasher = Sha512::new();
+    for chunk in chunks {
+        hasher.update(chunk);
+    }
+    BigInt::from_bytes_le(Sign::Plus, &hasher.finalize()) % Ed25519Fr::modulus()
+}
+
+// The secret scalar and the nonce prefix expanded from a 32-byte seed
+fn expand_seed(seed: &[u8; 32]) -> (BigInt, Vec<u8>) {
+    let h = Sha512::digest(seed);
+    let mut scalar = [0u8; 32];
+    scalar.copy_from_slice(&h[..32]);
+    scalar[0] &= 248;
+    scalar[31] &= 127;
+    scalar[31] |= 64;
+    (BigInt::from_bytes_le(Sign::Plus, &scalar), h[32..].to_vec())
+}
+
+pub fn public_key(seed: &[u8; 32]) -> [u8; 32] {
+    let (scalar, _) = expand_seed(seed);
+    encode_point(&scalar_mul(&scalar, &base_point()))
+}
+
+pub fn sign(seed: &[u8; 32], msg: &[u8]) -> [u8; 64] {
+    let (scalar, prefix) = expand_seed(seed);
+    let pubkey = encode_point(&scalar_mul(&scalar, &base_point()));
+    let r = hash_to_scalar(&[&prefix, msg]);
+    let big_r = encode_point(&scalar_mul(&r, &base_point()));
+    let k = hash_to_scalar(&[&big_r, &pubkey, msg]);
+    let s = (r + k * scalar) % Ed25519Fr::modulus();
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(&big_r);
+    signature[32..].copy_from_slice(&to_le_bytes(&s));
+    signature
+}
+
+// Checks [S]B == R + [k]A without multiplying by the cofactor
+pub fn verify(pubkey: &[u8; 32], msg: &[u8], signature: &[u8; 64]) -> bool {
+    let big_r_bytes: [u8; 32] = signature[..32].try_into().unwrap();
+    let (Some(a), Some(big_r)) = (decode_point(pubkey), decode_point(&big_r_bytes)) else {
+        return false;
+    };
+    let s = BigInt::from_bytes_le(Sign::Plus, &signature[32..]);
+    if s >= Ed25519Fr::modulus() {
+        return false;
+    }
+    let k = hash_to_scalar(&[&big_r_bytes, pubkey, msg]);
+    scalar_mul(&s, &base_point()) == add(&big_r, &scalar_mul(&k, &a))
+}
@@ -1,8 +1,7 @@
 use crate::gnark::emparam::FieldParams;
-use crate::gnark::limbs::decompose;
-use expander_compiler::frontend::{Config, RootAPI, Variable};
+use crate::gnark::limbs::{bigint_to_field, decompose};
+use expander_compiler::frontend::{CircuitField, Config, RootAPI, Variable};
 use num_bigint::BigInt;
-use num_traits::ToPrimitive;
 use std::any::Any;
 use std::cmp::Ordering;
 
@@ -91,7 +90,7 @@ pub fn new_const_element<C: Config, B: RootAPI<C>, T: FieldParams>(
     }
     // assign limb values
     for i in 0..limbs.len() {
-        limbs[i] = api.constant(blimbs[i].to_u64().unwrap() as u32);
+        limbs[i] = api.constant(bigint_to_field::<CircuitField<C>>(&blimbs[i]));
     }
     Element::new(limbs, 0, true, false, false, Variable::default())
 }
@@ -1,3 +1,6 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
 use num_bigint::BigInt;
 
 #[derive(Default, Clone, Copy, Debug)]
@@ -71,6 +74,122 @@ impl FieldParams for Bls12381Fp {
     }
 }
 
+// Declares a prime field emulated with 32 limbs of 8 bits, enough for moduli up to 256 bits
+macro_rules! emulated_field_params {
+    ($(#[$attr:meta])* $name:ident, $modulus_hex:expr) => {
+        $(#[$attr])*
+        #[derive(Default, Clone, Copy, Debug)]
+        pub struct $name {}
+        impl FieldParams for $name {
+            fn nb_limbs() -> u32 {
+                32
+            }
+            fn bits_per_limb() -> u32 {
+                8
+            }
+            fn is_prime() -> bool {
+                true
+            }
+            fn modulus() -> BigInt {
+                BigInt::parse_bytes($modulus_hex.as_bytes(), 16).unwrap()
+            }
+        }
+    };
+}
+
+emulated_field_params!(
+    /// Base field of secp256k1
+    Secp256k1Fp,
+    "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f"
+);
+emulated_field_params!(
+    /// Scalar field of secp256k1
+    Secp256k1Fr,
+    "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141"
+);
+emulated_field_params!(
+    /// Base field of P-256
+    P256Fp,
+    "ffffffff00000001000000000000000000000000ffffffffffffffffffffffff"
+);
+emulated_field_params!(
+    /// Scalar field of P-256
+    P256Fr,
+    "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551"
+);
+emulated_field_params!(
+    /// Base field of BN254
+    BN254Fp,
+    "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47"
+);
+emulated_field_params!(
+    /// Scalar field of BN254
+    BN254Fr,
+    "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001"
+);
+emulated_field_params!(
+    /// Base field of Ed25519, 2^255 - 19
+    Ed25519Fp,
+    "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed"
+);
+emulated_field_params!(
+    /// Scalar field of Ed25519, the order of its prime subgroup
+    Ed25519Fr,
+    "1000000000000000000000000000000014def9dea2f79cd65812631a5cf5d3ed"
+);
+
+// modulus, bits per limb and number of limbs of each runtime field slot
+static RUNTIME_FIELDS: RwLock<BTreeMap<usize, (BigInt, u32, u32)>> = RwLock::new(BTreeMap::new());
+
+/// A prime field whose modulus is only known at runtime.
+///
+/// The modulus is set once per `SLOT` with `RuntimeFieldParams::<SLOT>::set`, before
+/// building any circuit that uses it. The caller is responsible for the modulus being prime.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct RuntimeFieldParams<const SLOT: usize> {}
+
+impl<const SLOT: usize> RuntimeFieldParams<SLOT> {
+    pub fn set(modulus: BigInt, bits_per_limb: u32) {
+        if modulus <= BigInt::from(2) {
+            panic!("the modulus of an emulated field must be an odd prime");
+        }
+        let nb_limbs = (modulus.bits() as u32).div_ceil(bits_per_limb);
+        let mut fields = RUNTIME_FIELDS.write().unwrap();
+        match fields.get(&SLOT) {
+            Some(params) if *params != (modulus.clone(), bits_per_limb, nb_limbs) => {
+                panic!("runtime field slot {SLOT} is already set to another modulus")
+            }
+            _ => {
+                fields.insert(SLOT, (modulus, bits_per_limb, nb_limbs));
+            }
+        }
+    }
+
+    fn get() -> (BigInt, u32, u32) {
+        RUNTIME_FIELDS
+            .read()
+            .unwrap()
+            .get(&SLOT)
+            .cloned()
+            .unwrap_or_else(|| panic!("runtime field slot {SLOT} is not set"))
+    }
+}
+
+impl<const SLOT: usize> FieldParams for RuntimeFieldParams<SLOT> {
+    fn nb_limbs() -> u32 {
+        Self::get().2
+    }
+    fn bits_per_limb() -> u32 {
+        Self::get().1
+    }
+    fn is_prime() -> bool {
+        true
+    }
+    fn modulus() -> BigInt {
+        Self::get().0
+    }
+}
+
 // CurveParams defines parameters of an elliptic curve in short Weierstrass form
 // given by the equation
 //
@@ -85,8 +204,9 @@ pub struct CurveParams {
     pub gy: BigInt,
     // Enable following items if needed in the future.
     // pub gm: Vec<[BigInt; 2]>, m*base point coords
-    // pub eigenvalue: Option<BigInt>, endomorphism eigenvalue
-    // pub third_root_one: Option<BigInt>, endomorphism image scaler
+    // eigenvalue is the scalar λ of the endomorphism (x, y) -> (third_root_one * x, y)
+    pub eigenvalue: Option<BigInt>,
+    pub third_root_one: Option<BigInt>,
 }
 
 impl CurveParams {
@@ -100,10 +220,74 @@ impl CurveParams {
             gx,
             gy,
             // gm: compute_bls12381_table(),
-            // eigenvalue: Some(lambda),
-            // third_root_one: Some(omega),
+            eigenvalue: None,
+            third_root_one: None,
         }
     }
 
-    // TODO add get_bn254_params, get_secp256k1_params etc. in the future.
+    pub fn get_secp256k1_params() -> Self {
+        let hex = |s: &str| BigInt::parse_bytes(s.as_bytes(), 16).unwrap();
+        CurveParams {
+            a: BigInt::ZERO,
+            b: BigInt::from(7),
+            gx: hex("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"),
+            gy: hex("483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"),
+            eigenvalue: Some(hex(
+                "5363ad4cc05c30e0a5261c028812645a122e22ea20816678df02967c1b23bd72",
+            )),
+            third_root_one: Some(hex(
+                "7ae96a2b657c07106e64479eac3434e99cf0497512f58995c1396c28719501ee",
+            )),
+        }
+    }
+
+    pub fn get_p256_params() -> Self {
+        let hex = |s: &str| BigInt::parse_bytes(s.as_bytes(), 16).unwrap();
+        CurveParams {
+            a: P256Fp::modulus() - 3,
+            b: hex("5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b"),
+            gx: hex("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"),
+            gy: hex("4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"),
+            eigenvalue: None,
+            third_root_one: None,
+        }
+    }
+
+    pub fn get_bn254_params() -> Self {
+        let hex = |s: &str| BigInt::parse_bytes(s.as_bytes(), 16).unwrap();
+        CurveParams {
+            a: BigInt::ZERO,
+            b: BigInt::from(3),
+            gx: BigInt::from(1),
+            gy: BigInt::from(2),
+            eigenvalue: Some(hex("b3c4d79d41a917585bfc41088d8daaa78b17ea66b99c90dd")),
+            third_root_one: Some(hex("59e26bcea0d48bacd4f263f1acdb5c4f5763473177fffffe")),
+        }
+    }
+}
+
+// TwistedEdwardsParams defines parameters of an elliptic curve in twisted Edwards form
+// given by the equation
+//
+//	aX² + Y² = 1 + dX²Y²
+//
+// The base point is defined by (Gx, Gy).
+#[derive(Clone, Debug)]
+pub struct TwistedEdwardsParams {
+    pub a: BigInt,
+    pub d: BigInt,
+    pub gx: BigInt,
+    pub gy: BigInt,
+}
+
+impl TwistedEdwardsParams {
+    pub fn get_ed25519_params() -> Self {
+        let hex = |s: &str| BigInt::parse_bytes(s.as_bytes(), 16).unwrap();
+        TwistedEdwardsParams {
+            a: Ed25519Fp::modulus() - 1,
+            d: hex("52036cee2b6ffe738cc740797779e89800700a4d4141d8ab75eb4dca135978a3"),
+            gx: hex("216936d3cd6e53fec0a4e231fdd6dc5c692cc7609525a7b2c9562d608f25d51a"),
+            gy: hex("6666666666666666666666666666666666666666666666666666666666666658"),
+        }
+    }
 }
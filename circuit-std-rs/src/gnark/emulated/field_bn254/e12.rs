@@ -0,0 +1,286 @@
+use expander_compiler::frontend::{Config, RootAPI, Variable};
+
+use super::e2::GE2;
+use super::e6::{Ext6, GE6};
+
+// the seed x of the curve, p and r are polynomials in x
+pub const SEED: u64 = 4965661367192848881;
+
+// Fp12 = Fp6[w]/(w² - v)
+#[derive(Default, Clone, Debug)]
+pub struct GE12 {
+    pub c0: GE6,
+    pub c1: GE6,
+}
+
+pub struct Ext12 {
+    pub ext6: Ext6,
+}
+
+impl Ext12 {
+    pub fn new<C: Config, B: RootAPI<C>>(api: &mut B) -> Self {
+        Self {
+            ext6: Ext6::new(api),
+        }
+    }
+    pub fn one(&mut self) -> GE12 {
+        GE12 {
+            c0: self.ext6.one(),
+            c1: self.ext6.zero(),
+        }
+    }
+    pub fn add<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE12, y: &GE12) -> GE12 {
+        let c0 = self.ext6.add(native, &x.c0, &y.c0);
+        let c1 = self.ext6.add(native, &x.c1, &y.c1);
+        GE12 { c0, c1 }
+    }
+    pub fn sub<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE12, y: &GE12) -> GE12 {
+        let c0 = self.ext6.sub(native, &x.c0, &y.c0);
+        let c1 = self.ext6.sub(native, &x.c1, &y.c1);
+        GE12 { c0, c1 }
+    }
+    pub fn conjugate<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE12) -> GE12 {
+        GE12 {
+            c0: x.c0.clone(),
+            c1: self.ext6.neg(native, &x.c1),
+        }
+    }
+    pub fn mul<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE12, y: &GE12) -> GE12 {
+        let a = self.ext6.add(native, &x.c0, &x.c1);
+        let b = self.ext6.add(native, &y.c0, &y.c1);
+        let a = self.ext6.mul(native, &a, &b);
+        let b = self.ext6.mul(native, &x.c0, &y.c0);
+        let c = self.ext6.mul(native, &x.c1, &y.c1);
+        let d = self.ext6.add(native, &c, &b);
+        let c1 = self.ext6.sub(native, &a, &d);
+        let c0 = self.ext6.mul_by_non_residue(native, &c);
+        let c0 = self.ext6.add(native, &c0, &b);
+        GE12 { c0, c1 }
+    }
+    pub fn square<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE12) -> GE12 {
+        let c0 = self.ext6.sub(native, &x.c0, &x.c1);
+        let c3 = self.ext6.mul_by_non_residue(native, &x.c1);
+        let c3 = self.ext6.sub(native, &x.c0, &c3);
+        let c2 = self.ext6.mul(native, &x.c0, &x.c1);
+        let c0 = self.ext6.mul(native, &c0, &c3);
+        let c0 = self.ext6.add(native, &c0, &c2);
+        let z1 = self.ext6.double(native, &c2);
+        let c2 = self.ext6.mul_by_non_residue(native, &c2);
+        let z0 = self.ext6.add(native, &c0, &c2);
+        GE12 { c0: z0, c1: z1 }
+    }
+    // Granger-Scott squaring, only valid in the cyclotomic subgroup
+    pub fn cyclotomic_square<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        x: &GE12,
+    ) -> GE12 {
+        let ext2 = &mut self.ext6.ext2;
+        let t0 = ext2.square(native, &x.c1.b1);
+        let t1 = ext2.square(native, &x.c0.b0);
+        let mut t6 = ext2.add(native, &x.c1.b1, &x.c0.b0);
+        t6 = ext2.square(native, &t6);
+        t6 = ext2.sub(native, &t6, &t0);
+        t6 = ext2.sub(native, &t6, &t1);
+        let t2 = ext2.square(native, &x.c0.b2);
+        let t3 = ext2.square(native, &x.c1.b0);
+        let mut t7 = ext2.add(native, &x.c0.b2, &x.c1.b0);
+        t7 = ext2.square(native, &t7);
+        t7 = ext2.sub(native, &t7, &t2);
+        t7 = ext2.sub(native, &t7, &t3);
+        let t4 = ext2.square(native, &x.c1.b2);
+        let t5 = ext2.square(native, &x.c0.b1);
+        let mut t8 = ext2.add(native, &x.c1.b2, &x.c0.b1);
+        t8 = ext2.square(native, &t8);
+        t8 = ext2.sub(native, &t8, &t4);
+        t8 = ext2.sub(native, &t8, &t5);
+        t8 = ext2.mul_by_non_residue(native, &t8);
+        let t0 = ext2.mul_by_non_residue(native, &t0);
+        let t0 = ext2.add(native, &t0, &t1);
+        let t2 = ext2.mul_by_non_residue(native, &t2);
+        let t2 = ext2.add(native, &t2, &t3);
+        let t4 = ext2.mul_by_non_residue(native, &t4);
+        let t4 = ext2.add(native, &t4, &t5);
+        let z00 = ext2.sub(native, &t0, &x.c0.b0);
+        let z00 = ext2.double(native, &z00);
+        let z00 = ext2.add(native, &z00, &t0);
+        let z01 = ext2.sub(native, &t2, &x.c0.b1);
+        let z01 = ext2.double(native, &z01);
+        let z01 = ext2.add(native, &z01, &t2);
+        let z02 = ext2.sub(native, &t4, &x.c0.b2);
+        let z02 = ext2.double(native, &z02);
+        let z02 = ext2.add(native, &z02, &t4);
+        let z10 = ext2.add(native, &t8, &x.c1.b0);
+        let z10 = ext2.double(native, &z10);
+        let z10 = ext2.add(native, &z10, &t8);
+        let z11 = ext2.add(native, &t6, &x.c1.b1);
+        let z11 = ext2.double(native, &z11);
+        let z11 = ext2.add(native, &z11, &t6);
+        let z12 = ext2.add(native, &t7, &x.c1.b2);
+        let z12 = ext2.double(native, &z12);
+        let z12 = ext2.add(native, &z12, &t7);
+        GE12 {
+            c0: GE6 {
+                b0: z00,
+                b1: z01,
+                b2: z02,
+            },
+            c1: GE6 {
+                b0: z10,
+                b1: z11,
+                b2: z12,
+            },
+        }
+    }
+    // 1/(c0 + c1·w) = (c0 - c1·w)/(c0² - v·c1²)
+    pub fn inverse<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE12) -> GE12 {
+        let c0_squared = self.ext6.square(native, &x.c0);
+        let c1_squared = self.ext6.square(native, &x.c1);
+        let c1_squared = self.ext6.mul_by_non_residue(native, &c1_squared);
+        let norm = self.ext6.sub(native, &c0_squared, &c1_squared);
+        let norm_inv = self.ext6.inverse(native, &norm);
+        let c0 = self.ext6.mul(native, &x.c0, &norm_inv);
+        let c1 = self.ext6.mul(native, &x.c1, &norm_inv);
+        let c1 = self.ext6.neg(native, &c1);
+        GE12 { c0, c1 }
+    }
+    pub fn assert_isequal<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE12, y: &GE12) {
+        self.ext6.assert_isequal(native, &x.c0, &y.c0);
+        self.ext6.assert_isequal(native, &x.c1, &y.c1);
+    }
+    pub fn select<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        selector: Variable,
+        z1: &GE12,
+        z0: &GE12,
+    ) -> GE12 {
+        let c0 = self.ext6.select(native, selector, &z1.c0, &z0.c0);
+        let c1 = self.ext6.select(native, selector, &z1.c1, &z0.c1);
+        GE12 { c0, c1 }
+    }
+
+    // x^(p^power): the coefficient of w^k is conjugated for odd powers and then multiplied by
+    // ξ^(k·(p^power - 1)/6)
+    fn frobenius_generic<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        x: &GE12,
+        power: u32,
+    ) -> GE12 {
+        let ext2 = &mut self.ext6.ext2;
+        // the coefficients in the order of their power of w
+        let coefficients = [&x.c0.b0, &x.c1.b0, &x.c0.b1, &x.c1.b1, &x.c0.b2, &x.c1.b2];
+        let mut z = vec![];
+        for (k, a) in coefficients.into_iter().enumerate() {
+            let a = if power % 2 == 1 {
+                ext2.conjugate(native, a)
+            } else {
+                a.clone()
+            };
+            if k == 0 {
+                z.push(a);
+            } else {
+                z.push(ext2.mul_by_non_residue_generic(native, &a, power, k as u32));
+            }
+        }
+        GE12 {
+            c0: GE6 {
+                b0: z[0].clone(),
+                b1: z[2].clone(),
+                b2: z[4].clone(),
+            },
+            c1: GE6 {
+                b0: z[1].clone(),
+                b1: z[3].clone(),
+                b2: z[5].clone(),
+            },
+        }
+    }
+    pub fn frobenius<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE12) -> GE12 {
+        self.frobenius_generic(native, x, 1)
+    }
+    pub fn frobenius_square<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE12) -> GE12 {
+        self.frobenius_generic(native, x, 2)
+    }
+    pub fn frobenius_cube<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE12) -> GE12 {
+        self.frobenius_generic(native, x, 3)
+    }
+
+    /////// pairing ///////
+    // z·(1 + (c0 + c1·v)·w), the shape of the lines of the Miller loop
+    pub fn mul_by_line<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        z: &GE12,
+        c0: &GE2,
+        c1: &GE2,
+    ) -> GE12 {
+        let a = self.ext6.mul_by_01(native, &z.c1, c0, c1);
+        let a = self.ext6.mul_by_non_residue(native, &a);
+        let b = self.ext6.mul_by_01(native, &z.c0, c0, c1);
+        GE12 {
+            c0: self.ext6.add(native, &z.c0, &a),
+            c1: self.ext6.add(native, &z.c1, &b),
+        }
+    }
+    // x^SEED in the cyclotomic subgroup
+    pub fn expt<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE12) -> GE12 {
+        let mut z = x.clone();
+        for i in (0..63 - SEED.leading_zeros()).rev() {
+            z = self.cyclotomic_square(native, &z);
+            if (SEED >> i) & 1 == 1 {
+                z = self.mul(native, &z, x);
+            }
+        }
+        z
+    }
+    // x^((p¹² - 1)/r) up to a power coprime to r, the hard part follows Fuentes et al.,
+    // "Faster hashing to G2", algorithm 6
+    pub fn final_exponentiation<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        x: &GE12,
+    ) -> GE12 {
+        // easy part, x^((p⁶ - 1)(p² + 1))
+        let t0 = self.conjugate(native, x);
+        let t1 = self.inverse(native, x);
+        let t0 = self.mul(native, &t0, &t1);
+        let t1 = self.frobenius_square(native, &t0);
+        let result = self.mul(native, &t1, &t0);
+
+        // hard part
+        let t0 = self.expt(native, &result);
+        let t0 = self.conjugate(native, &t0);
+        let t0 = self.cyclotomic_square(native, &t0);
+        let t1 = self.cyclotomic_square(native, &t0);
+        let t1 = self.mul(native, &t0, &t1);
+        let t2 = self.expt(native, &t1);
+        let t2 = self.conjugate(native, &t2);
+        let t3 = self.conjugate(native, &t1);
+        let t1 = self.mul(native, &t2, &t3);
+        let t3 = self.cyclotomic_square(native, &t2);
+        let t4 = self.expt(native, &t3);
+        let t4 = self.mul(native, &t1, &t4);
+        let t3 = self.mul(native, &t0, &t4);
+        let t0 = self.mul(native, &t2, &t4);
+        let t0 = self.mul(native, &result, &t0);
+        let t2 = self.frobenius(native, &t3);
+        let t0 = self.mul(native, &t2, &t0);
+        let t2 = self.frobenius_square(native, &t4);
+        let t0 = self.mul(native, &t2, &t0);
+        let t2 = self.conjugate(native, &result);
+        let t2 = self.mul(native, &t2, &t3);
+        let t2 = self.frobenius_cube(native, &t2);
+        self.mul(native, &t2, &t0)
+    }
+    pub fn assert_final_exponentiation_is_one<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        x: &GE12,
+    ) {
+        let result = self.final_exponentiation(native, x);
+        let one = self.one();
+        self.assert_isequal(native, &result, &one);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::gnark::element::{value_of, Element};
+use crate::gnark::emparam::BN254Fp;
+use crate::gnark::field::GField;
+use expander_compiler::frontend::{Config, RootAPI, Variable};
+use num_bigint::BigInt;
+
+pub type CurveF = GField<BN254Fp>;
+
+// Fp2 = Fp[u]/(u² + 1)
+#[derive(Default, Clone, Debug)]
+pub struct GE2 {
+    pub a0: Element<BN254Fp>,
+    pub a1: Element<BN254Fp>,
+}
+impl GE2 {
+    pub fn from_vars(x: Vec<Variable>, y: Vec<Variable>) -> Self {
+        GE2 {
+            a0: Element::new(x, 0, false, false, false, Variable::default()),
+            a1: Element::new(y, 0, false, false, false, Variable::default()),
+        }
+    }
+}
+
+// ξ^(coef·(p^power - 1)/6) for power 1, 2, 3 and coef 1 to 5, the Frobenius coefficients
+const NON_RESIDUES: [[[&str; 2]; 5]; 3] = [
+    [
+        [
+            "8376118865763821496583973867626364092589906065868298776909617916018768340080",
+            "16469823323077808223889137241176536799009286646108169935659301613961712198316",
+        ],
+        [
+            "21575463638280843010398324269430826099269044274347216827212613867836435027261",
+            "10307601595873709700152284273816112264069230130616436755625194854815875713954",
+        ],
+        [
+            "2821565182194536844548159561693502659359617185244120367078079554186484126554",
+            "3505843767911556378687030309984248845540243509899259641013678093033130930403",
+        ],
+        [
+            "2581911344467009335267311115468803099551665605076196740867805258568234346338",
+            "19937756971775647987995932169929341994314640652964949448313374472400716661030",
+        ],
+        [
+            "685108087231508774477564247770172212460312782337200605669322048753928464687",
+            "8447204650696766136447902020341177575205426561248465145919723016860428151883",
+        ],
+    ],
+    [
+        [
+            "21888242871839275220042445260109153167277707414472061641714758635765020556617",
+            "0",
+        ],
+        [
+            "21888242871839275220042445260109153167277707414472061641714758635765020556616",
+            "0",
+        ],
+        [
+            "21888242871839275222246405745257275088696311157297823662689037894645226208582",
+            "0",
+        ],
+        [
+            "2203960485148121921418603742825762020974279258880205651966",
+            "0",
+        ],
+        [
+            "2203960485148121921418603742825762020974279258880205651967",
+            "0",
+        ],
+    ],
+    [
+        [
+            "11697423496358154304825782922584725312912383441159505038794027105778954184319",
+            "303847389135065887422783454877609941456349188919719272345083954437860409601",
+        ],
+        [
+            "3772000881919853776433695186713858239009073593817195771773381919316419345261",
+            "2236595495967245188281701248203181795121068902605861227855261137820944008926",
+        ],
+        [
+            "19066677689644738377698246183563772429336693972053703295610958340458742082029",
+            "18382399103927718843559375435273026243156067647398564021675359801612095278180",
+        ],
+        [
+            "5324479202449903542726783395506214481928257762400643279780343368557297135718",
+            "16208900380737693084919495127334387981393726419856888799917914180988844123039",
+        ],
+        [
+            "8941241848238582420466759817324047081148088512956452953208002715982955420483",
+            "10338197737521362862238855242243140895517409139741313354160881284257516364953",
+        ],
+    ],
+];
+
+pub struct Ext2 {
+    pub curve_f: CurveF,
+    non_residues: Vec<Vec<GE2>>,
+}
+
+impl Ext2 {
+    pub fn new<C: Config, B: RootAPI<C>>(api: &mut B) -> Self {
+        let non_residues = NON_RESIDUES
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|[a0, a1]| GE2 {
+                        a0: value_of::<C, B, BN254Fp>(api, Box::new(a0.to_string())),
+                        a1: value_of::<C, B, BN254Fp>(api, Box::new(a1.to_string())),
+                    })
+                    .collect()
+            })
+            .collect();
+        Ext2 {
+            curve_f: CurveF::new(api, BN254Fp {}),
+            non_residues,
+        }
+    }
+    pub fn one(&mut self) -> GE2 {
+        let z0 = self.curve_f.one_const.clone();
+        let z1 = self.curve_f.zero_const.clone();
+        GE2 { a0: z0, a1: z1 }
+    }
+    pub fn zero(&mut self) -> GE2 {
+        let z0 = self.curve_f.zero_const.clone();
+        let z1 = self.curve_f.zero_const.clone();
+        GE2 { a0: z0, a1: z1 }
+    }
+    pub fn is_zero<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, z: &GE2) -> Variable {
+        let a0 = self.curve_f.is_zero(native, &z.a0);
+        let a1 = self.curve_f.is_zero(native, &z.a1);
+        native.and(a0, a1)
+    }
+    pub fn add<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE2, y: &GE2) -> GE2 {
+        let z0 = self.curve_f.add(native, &x.a0, &y.a0);
+        let z1 = self.curve_f.add(native, &x.a1, &y.a1);
+        GE2 { a0: z0, a1: z1 }
+    }
+    pub fn sub<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE2, y: &GE2) -> GE2 {
+        let z0 = self.curve_f.sub(native, &x.a0, &y.a0);
+        let z1 = self.curve_f.sub(native, &x.a1, &y.a1);
+        GE2 { a0: z0, a1: z1 }
+    }
+    pub fn double<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE2) -> GE2 {
+        self.mul_by_const_element(native, x, &BigInt::from(2))
+    }
+    pub fn neg<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE2) -> GE2 {
+        let z0 = self.curve_f.neg(native, &x.a0);
+        let z1 = self.curve_f.neg(native, &x.a1);
+        GE2 { a0: z0, a1: z1 }
+    }
+    pub fn mul<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE2, y: &GE2) -> GE2 {
+        let v0 = self.curve_f.mul(native, &x.a0, &y.a0);
+        let v1 = self.curve_f.mul(native, &x.a1, &y.a1);
+        let b0 = self.curve_f.sub(native, &v0, &v1);
+        let mut b1 = self.curve_f.add(native, &x.a0, &x.a1);
+        let mut tmp = self.curve_f.add(native, &y.a0, &y.a1);
+        b1 = self.curve_f.mul(native, &b1, &tmp);
+        tmp = self.curve_f.add(native, &v0, &v1);
+        b1 = self.curve_f.sub(native, &b1, &tmp);
+        GE2 { a0: b0, a1: b1 }
+    }
+    pub fn mul_by_element<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        x: &GE2,
+        y: &Element<BN254Fp>,
+    ) -> GE2 {
+        let z0 = self.curve_f.mul(native, &x.a0, y);
+        let z1 = self.curve_f.mul(native, &x.a1, y);
+        GE2 { a0: z0, a1: z1 }
+    }
+    pub fn mul_by_const_element<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        x: &GE2,
+        y: &BigInt,
+    ) -> GE2 {
+        let z0 = self.curve_f.mul_const(native, &x.a0, y.clone());
+        let z1 = self.curve_f.mul_const(native, &x.a1, y.clone());
+        GE2 { a0: z0, a1: z1 }
+    }
+    // x·ξ with ξ = 9 + u
+    pub fn mul_by_non_residue<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE2) -> GE2 {
+        let nine = BigInt::from(9);
+        let a0 = self.curve_f.mul_const(native, &x.a0, nine.clone());
+        let a0 = self.curve_f.sub(native, &a0, &x.a1);
+        let a1 = self.curve_f.mul_const(native, &x.a1, nine);
+        let a1 = self.curve_f.add(native, &a1, &x.a0);
+        GE2 { a0, a1 }
+    }
+    // x·ξ^(coef·(p^power - 1)/6)
+    pub fn mul_by_non_residue_generic<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        x: &GE2,
+        power: u32,
+        coef: u32,
+    ) -> GE2 {
+        let y = self.non_residues[power as usize - 1][coef as usize - 1].clone();
+        if power == 2 {
+            // the coefficients of the second power lie in Fp
+            return self.mul_by_element(native, x, &y.a0);
+        }
+        self.mul(native, x, &y)
+    }
+    pub fn square<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE2) -> GE2 {
+        let a = self.curve_f.add(native, &x.a0, &x.a1);
+        let b = self.curve_f.sub(native, &x.a0, &x.a1);
+        let a = self.curve_f.mul(native, &a, &b);
+        let b = self.curve_f.mul(native, &x.a0, &x.a1);
+        let b = self.curve_f.mul_const(native, &b, BigInt::from(2));
+        GE2 { a0: a, a1: b }
+    }
+    // 1/x = conj(x)/(a0² + a1²), the inverse in Fp asserts that x is not zero
+    pub fn inverse<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE2) -> GE2 {
+        let a0_squared = self.curve_f.mul(native, &x.a0, &x.a0);
+        let a1_squared = self.curve_f.mul(native, &x.a1, &x.a1);
+        let norm = self.curve_f.add(native, &a0_squared, &a1_squared);
+        let norm_inv = self.curve_f.inverse(native, &norm);
+        let conj = self.conjugate(native, x);
+        self.mul_by_element(native, &conj, &norm_inv)
+    }
+    pub fn div<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE2, y: &GE2) -> GE2 {
+        let y_inv = self.inverse(native, y);
+        self.mul(native, x, &y_inv)
+    }
+    pub fn assert_isequal<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE2, y: &GE2) {
+        self.curve_f.assert_is_equal(native, &x.a0, &y.a0);
+        self.curve_f.assert_is_equal(native, &x.a1, &y.a1);
+    }
+    pub fn select<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        selector: Variable,
+        z1: &GE2,
+        z0: &GE2,
+    ) -> GE2 {
+        let a0 = self.curve_f.select(native, selector, &z1.a0, &z0.a0);
+        let a1 = self.curve_f.select(native, selector, &z1.a1, &z0.a1);
+        GE2 { a0, a1 }
+    }
+    pub fn conjugate<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE2) -> GE2 {
+        let z0 = x.a0.clone();
+        let z1 = self.curve_f.neg(native, &x.a1);
+        GE2 { a0: z0, a1: z1 }
+    }
+}
@@ -0,0 +1,186 @@
+use expander_compiler::frontend::{Config, RootAPI, Variable};
+
+use super::e2::{Ext2, GE2};
+
+// Fp6 = Fp2[v]/(v³ - ξ)
+#[derive(Default, Clone, Debug)]
+pub struct GE6 {
+    pub b0: GE2,
+    pub b1: GE2,
+    pub b2: GE2,
+}
+
+pub struct Ext6 {
+    pub ext2: Ext2,
+}
+
+impl Ext6 {
+    pub fn new<C: Config, B: RootAPI<C>>(api: &mut B) -> Self {
+        Self {
+            ext2: Ext2::new(api),
+        }
+    }
+    pub fn one(&mut self) -> GE6 {
+        let b0 = self.ext2.one();
+        let b1 = self.ext2.zero();
+        let b2 = self.ext2.zero();
+        GE6 { b0, b1, b2 }
+    }
+    pub fn zero(&mut self) -> GE6 {
+        let b0 = self.ext2.zero();
+        let b1 = self.ext2.zero();
+        let b2 = self.ext2.zero();
+        GE6 { b0, b1, b2 }
+    }
+    pub fn is_zero<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, z: &GE6) -> Variable {
+        let b0 = self.ext2.is_zero(native, &z.b0);
+        let b1 = self.ext2.is_zero(native, &z.b1);
+        let b2 = self.ext2.is_zero(native, &z.b2);
+        let tmp = native.and(b0, b1);
+        native.and(tmp, b2)
+    }
+    pub fn add<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE6, y: &GE6) -> GE6 {
+        let b0 = self.ext2.add(native, &x.b0, &y.b0);
+        let b1 = self.ext2.add(native, &x.b1, &y.b1);
+        let b2 = self.ext2.add(native, &x.b2, &y.b2);
+        GE6 { b0, b1, b2 }
+    }
+    pub fn neg<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE6) -> GE6 {
+        let b0 = self.ext2.neg(native, &x.b0);
+        let b1 = self.ext2.neg(native, &x.b1);
+        let b2 = self.ext2.neg(native, &x.b2);
+        GE6 { b0, b1, b2 }
+    }
+    pub fn sub<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE6, y: &GE6) -> GE6 {
+        let b0 = self.ext2.sub(native, &x.b0, &y.b0);
+        let b1 = self.ext2.sub(native, &x.b1, &y.b1);
+        let b2 = self.ext2.sub(native, &x.b2, &y.b2);
+        GE6 { b0, b1, b2 }
+    }
+    pub fn double<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE6) -> GE6 {
+        let b0 = self.ext2.double(native, &x.b0);
+        let b1 = self.ext2.double(native, &x.b1);
+        let b2 = self.ext2.double(native, &x.b2);
+        GE6 { b0, b1, b2 }
+    }
+    pub fn mul_by_e2<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE6, y: &GE2) -> GE6 {
+        let b0 = self.ext2.mul(native, &x.b0, y);
+        let b1 = self.ext2.mul(native, &x.b1, y);
+        let b2 = self.ext2.mul(native, &x.b2, y);
+        GE6 { b0, b1, b2 }
+    }
+    // z·(c0 + c1·v)
+    pub fn mul_by_01<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        z: &GE6,
+        c0: &GE2,
+        c1: &GE2,
+    ) -> GE6 {
+        let a = self.ext2.mul(native, &z.b0, c0);
+        let b = self.ext2.mul(native, &z.b1, c1);
+        let tmp = self.ext2.add(native, &z.b1, &z.b2);
+        let mut t0 = self.ext2.mul(native, c1, &tmp);
+        t0 = self.ext2.sub(native, &t0, &b);
+        t0 = self.ext2.mul_by_non_residue(native, &t0);
+        t0 = self.ext2.add(native, &t0, &a);
+        let mut t2 = self.ext2.mul(native, &z.b2, c0);
+        t2 = self.ext2.add(native, &t2, &b);
+        let mut t1 = self.ext2.add(native, c0, c1);
+        let tmp = self.ext2.add(native, &z.b0, &z.b1);
+        t1 = self.ext2.mul(native, &t1, &tmp);
+        let tmp = self.ext2.add(native, &a, &b);
+        t1 = self.ext2.sub(native, &t1, &tmp);
+        GE6 {
+            b0: t0,
+            b1: t1,
+            b2: t2,
+        }
+    }
+    // x·v
+    pub fn mul_by_non_residue<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE6) -> GE6 {
+        let b0 = self.ext2.mul_by_non_residue(native, &x.b2);
+        GE6 {
+            b0,
+            b1: x.b0.clone(),
+            b2: x.b1.clone(),
+        }
+    }
+    pub fn mul<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE6, y: &GE6) -> GE6 {
+        let t0 = self.ext2.mul(native, &x.b0, &y.b0);
+        let t1 = self.ext2.mul(native, &x.b1, &y.b1);
+        let t2 = self.ext2.mul(native, &x.b2, &y.b2);
+        let mut c0 = self.ext2.add(native, &x.b1, &x.b2);
+        let mut tmp = self.ext2.add(native, &y.b1, &y.b2);
+        c0 = self.ext2.mul(native, &c0, &tmp);
+        tmp = self.ext2.add(native, &t2, &t1);
+        c0 = self.ext2.sub(native, &c0, &tmp);
+        c0 = self.ext2.mul_by_non_residue(native, &c0);
+        c0 = self.ext2.add(native, &c0, &t0);
+        let mut c1 = self.ext2.add(native, &x.b0, &x.b1);
+        tmp = self.ext2.add(native, &y.b0, &y.b1);
+        c1 = self.ext2.mul(native, &c1, &tmp);
+        tmp = self.ext2.add(native, &t0, &t1);
+        c1 = self.ext2.sub(native, &c1, &tmp);
+        tmp = self.ext2.mul_by_non_residue(native, &t2);
+        c1 = self.ext2.add(native, &c1, &tmp);
+        let mut c2 = self.ext2.add(native, &x.b0, &x.b2);
+        tmp = self.ext2.add(native, &y.b0, &y.b2);
+        c2 = self.ext2.mul(native, &c2, &tmp);
+        tmp = self.ext2.add(native, &t0, &t2);
+        c2 = self.ext2.sub(native, &c2, &tmp);
+        c2 = self.ext2.add(native, &c2, &t1);
+        GE6 {
+            b0: c0,
+            b1: c1,
+            b2: c2,
+        }
+    }
+    pub fn square<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE6) -> GE6 {
+        self.mul(native, x, x)
+    }
+    // The adjugate over the norm, which only needs an inverse in Fp2
+    pub fn inverse<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE6) -> GE6 {
+        let b1b2 = self.ext2.mul(native, &x.b1, &x.b2);
+        let b1b2 = self.ext2.mul_by_non_residue(native, &b1b2);
+        let c0 = self.ext2.square(native, &x.b0);
+        let c0 = self.ext2.sub(native, &c0, &b1b2);
+        let b2b2 = self.ext2.square(native, &x.b2);
+        let b2b2 = self.ext2.mul_by_non_residue(native, &b2b2);
+        let b0b1 = self.ext2.mul(native, &x.b0, &x.b1);
+        let c1 = self.ext2.sub(native, &b2b2, &b0b1);
+        let b1b1 = self.ext2.square(native, &x.b1);
+        let b0b2 = self.ext2.mul(native, &x.b0, &x.b2);
+        let c2 = self.ext2.sub(native, &b1b1, &b0b2);
+        let t = self.ext2.mul(native, &x.b2, &c1);
+        let tmp = self.ext2.mul(native, &x.b1, &c2);
+        let t = self.ext2.add(native, &t, &tmp);
+        let t = self.ext2.mul_by_non_residue(native, &t);
+        let tmp = self.ext2.mul(native, &x.b0, &c0);
+        let t = self.ext2.add(native, &t, &tmp);
+        let t_inv = self.ext2.inverse(native, &t);
+        let c = GE6 {
+            b0: c0,
+            b1: c1,
+            b2: c2,
+        };
+        self.mul_by_e2(native, &c, &t_inv)
+    }
+    pub fn assert_isequal<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, x: &GE6, y: &GE6) {
+        self.ext2.assert_isequal(native, &x.b0, &y.b0);
+        self.ext2.assert_isequal(native, &x.b1, &y.b1);
+        self.ext2.assert_isequal(native, &x.b2, &y.b2);
+    }
+    pub fn select<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        selector: Variable,
+        z1: &GE6,
+        z0: &GE6,
+    ) -> GE6 {
+        let b0 = self.ext2.select(native, selector, &z1.b0, &z0.b0);
+        let b1 = self.ext2.select(native, selector, &z1.b1, &z0.b1);
+        let b2 = self.ext2.select(native, selector, &z1.b2, &z0.b2);
+        GE6 { b0, b1, b2 }
+    }
+}
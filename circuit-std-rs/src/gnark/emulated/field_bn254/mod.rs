@@ -0,0 +1,3 @@
+pub mod e12;
+pub mod e2;
+pub mod e6;
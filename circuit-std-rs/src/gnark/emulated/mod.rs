@@ -1,2 +1,5 @@
 pub mod field_bls12381;
+pub mod field_bn254;
 pub mod sw_bls12381;
+pub mod sw_bn254;
+pub mod twisted_edwards;
@@ -82,6 +82,47 @@ impl G1 {
         G1Affine { x: xr, y: yr }
     }
 
+    // p + q for any two points of the curve, add above needs p.x != q.x. The second value is
+    // 1 when the sum is the point at infinity, the coordinates are meaningless then.
+    pub fn add_unified<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &G1Affine,
+        q: &G1Affine,
+    ) -> (G1Affine, Variable) {
+        let qypy = self.curve_f.sub(native, &q.y, &p.y);
+        let qxpx = self.curve_f.sub(native, &q.x, &p.x);
+        let is_dx_zero = self.curve_f.is_zero(native, &qxpx);
+        let is_dy_zero = self.curve_f.is_zero(native, &qypy);
+
+        // λ = 3p.x²/2p.y when p == q, (q.y-p.y)/(q.x-p.x) otherwise
+        let two = value_of::<C, B, Bls12381Fp>(native, Box::new(2));
+        let three = value_of::<C, B, Bls12381Fp>(native, Box::new(3));
+        let xx = self.curve_f.mul(native, &p.x, &p.x);
+        let xx3 = self.curve_f.mul(native, &xx, &three);
+        let y2 = self.curve_f.mul(native, &p.y, &two);
+        let num = self.curve_f.select(native, is_dx_zero, &xx3, &qypy);
+        let den = self.curve_f.select(native, is_dx_zero, &y2, &qxpx);
+        let is_den_zero = self.curve_f.is_zero(native, &den);
+        let one = self.curve_f.one_const.clone();
+        let den = self.curve_f.select(native, is_den_zero, &one, &den);
+        let λ = self.curve_f.div(native, &num, &den);
+
+        let λλ = self.curve_f.mul(native, &λ, &λ);
+        let qxpx = self.curve_f.add(native, &p.x, &q.x);
+        let xr = self.curve_f.sub(native, &λλ, &qxpx);
+
+        let pxrx = self.curve_f.sub(native, &p.x, &xr);
+        let λpxrx = self.curve_f.mul(native, &λ, &pxrx);
+        let yr = self.curve_f.sub(native, &λpxrx, &p.y);
+
+        // q == -p, or p == q of order 2
+        let is_dy_non_zero = native.sub(1, is_dy_zero);
+        let is_neg = native.and(is_dx_zero, is_dy_non_zero);
+        let is_infinity = native.or(is_neg, is_den_zero);
+        (G1Affine { x: xr, y: yr }, is_infinity)
+    }
+
     pub fn phi<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, q: &G1Affine) -> G1Affine {
         let x = self.curve_f.mul(native, &q.x, &self.w);
         G1Affine { x, y: q.y.clone() }
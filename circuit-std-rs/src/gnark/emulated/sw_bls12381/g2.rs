@@ -231,6 +231,47 @@ impl G2 {
 
         G2AffP::new(xr, yr)
     }
+    // p + q for any two points of the twist, add above needs p.x != q.x. The second value is
+    // 1 when the sum is the point at infinity, the coordinates are meaningless then.
+    pub fn add_unified<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &G2AffP,
+        q: &G2AffP,
+    ) -> (G2AffP, Variable) {
+        let qypy = self.ext2.sub(native, &q.y, &p.y);
+        let qxpx = self.ext2.sub(native, &q.x, &p.x);
+        let is_dx_zero = self.ext2.is_zero(native, &qxpx);
+        let is_dy_zero = self.ext2.is_zero(native, &qypy);
+
+        // λ = 3p.x²/2p.y when p == q, (q.y-p.y)/(q.x-p.x) otherwise
+        let xx = self.ext2.square(native, &p.x);
+        let xx3 = self
+            .ext2
+            .mul_by_const_element(native, &xx, &BigInt::from(3));
+        let y2 = self.ext2.double(native, &p.y);
+        let num = self.ext2.select(native, is_dx_zero, &xx3, &qypy);
+        let den = self.ext2.select(native, is_dx_zero, &y2, &qxpx);
+        let is_den_zero = self.ext2.is_zero(native, &den);
+        let one = self.ext2.one();
+        let den = self.ext2.select(native, is_den_zero, &one, &den);
+        let λ = self.ext2.div(native, &num, &den);
+
+        let λλ = self.ext2.square(native, &λ);
+        let qxpx = self.ext2.add(native, &p.x, &q.x);
+        let xr = self.ext2.sub(native, &λλ, &qxpx);
+
+        let pxrx = self.ext2.sub(native, &p.x, &xr);
+        let λpxrx = self.ext2.mul(native, &λ, &pxrx);
+        let yr = self.ext2.sub(native, &λpxrx, &p.y);
+
+        // q == -p, or p == q of order 2
+        let is_dy_non_zero = native.sub(1, is_dy_zero);
+        let is_neg = native.and(is_dx_zero, is_dy_non_zero);
+        let is_infinity = native.or(is_neg, is_den_zero);
+        (G2AffP::new(xr, yr), is_infinity)
+    }
+
     pub fn psi<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, q: &G2AffP) -> G2AffP {
         let x = self.ext2.mul_by_element(native, &q.x, &self.u1);
         let y = self.ext2.conjugate(native, &q.y);
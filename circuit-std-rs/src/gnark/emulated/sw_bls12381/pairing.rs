@@ -53,7 +53,7 @@ impl Pairing {
         p: &[G1Affine],
         q: &mut [G2Affine],
     ) -> Result<(), Error> {
-        let f = self.miller_loop(native, p, q).unwrap();
+        let f = self.miller_loop(native, p, q).map_err(Error::UserError)?;
         let buf = self.ext12.conjugate(native, &f);
 
         let buf = self.ext12.div(native, &buf, &f);
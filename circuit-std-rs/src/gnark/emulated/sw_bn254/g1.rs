@@ -0,0 +1,25 @@
+use crate::ecdsa::curve::EmulatedCurve;
+use crate::gnark::element::Element;
+use crate::gnark::emparam::{BN254Fp, BN254Fr, CurveParams};
+use crate::gnark::emulated::sw_bls12381::point::AffinePoint;
+use expander_compiler::frontend::{Config, RootAPI, Variable};
+
+pub type G1Affine = AffinePoint<BN254Fp>;
+
+impl G1Affine {
+    pub fn from_vars(x: Vec<Variable>, y: Vec<Variable>) -> Self {
+        Self {
+            x: Element::new(x, 0, false, false, false, Variable::default()),
+            y: Element::new(y, 0, false, false, false, Variable::default()),
+        }
+    }
+}
+
+// G1 is the whole curve y² = x³ + 3, its arithmetic is the generic emulated curve
+pub type G1 = EmulatedCurve<BN254Fp, BN254Fr>;
+
+impl G1 {
+    pub fn new_bn254<C: Config, B: RootAPI<C>>(native: &mut B) -> Self {
+        Self::new(native, &CurveParams::get_bn254_params())
+    }
+}
@@ -0,0 +1,165 @@
+use crate::gnark::element::value_of;
+use crate::gnark::emparam::BN254Fp;
+use crate::gnark::emulated::field_bn254::e2::{Ext2, GE2};
+use expander_compiler::frontend::{Config, RootAPI, Variable};
+use num_bigint::BigInt;
+
+// A point of the D-type twist y² = x³ + 3/ξ, mapped into E(Fp12) by (x, y) -> (x·w², y·w³)
+#[derive(Default, Clone, Debug)]
+pub struct G2Affine {
+    pub x: GE2,
+    pub y: GE2,
+}
+
+impl G2Affine {
+    pub fn from_vars(
+        x0: Vec<Variable>,
+        x1: Vec<Variable>,
+        y0: Vec<Variable>,
+        y1: Vec<Variable>,
+    ) -> Self {
+        Self {
+            x: GE2::from_vars(x0, x1),
+            y: GE2::from_vars(y0, y1),
+        }
+    }
+}
+
+pub struct G2 {
+    pub ext2: Ext2,
+    // 3/ξ
+    pub btwist: GE2,
+}
+
+impl G2 {
+    pub fn new<C: Config, B: RootAPI<C>>(native: &mut B) -> Self {
+        let ext2 = Ext2::new(native);
+        let btwist = GE2 {
+            a0: value_of::<C, B, BN254Fp>(
+                native,
+                Box::new(
+                    "19485874751759354771024239261021720505790618469301721065564631296452457478373"
+                        .to_string(),
+                ),
+            ),
+            a1: value_of::<C, B, BN254Fp>(
+                native,
+                Box::new(
+                    "266929791119991161246907387137283842545076965332900288569378510910307636690"
+                        .to_string(),
+                ),
+            ),
+        };
+        Self { ext2, btwist }
+    }
+    pub fn constant_point<C: Config, B: RootAPI<C>>(
+        native: &mut B,
+        x: [&BigInt; 2],
+        y: [&BigInt; 2],
+    ) -> G2Affine {
+        let mut constant = |v: &BigInt| value_of::<C, B, BN254Fp>(native, Box::new(v.clone()));
+        G2Affine {
+            x: GE2 {
+                a0: constant(x[0]),
+                a1: constant(x[1]),
+            },
+            y: GE2 {
+                a0: constant(y[0]),
+                a1: constant(y[1]),
+            },
+        }
+    }
+    pub fn neg<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, p: &G2Affine) -> G2Affine {
+        G2Affine {
+            x: p.x.clone(),
+            y: self.ext2.neg(native, &p.y),
+        }
+    }
+    pub fn assert_is_on_curve<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, p: &G2Affine) {
+        let left = self.ext2.square(native, &p.y);
+        let right = self.ext2.square(native, &p.x);
+        let right = self.ext2.mul(native, &right, &p.x);
+        let right = self.ext2.add(native, &right, &self.btwist);
+        self.ext2.assert_isequal(native, &left, &right);
+    }
+    pub fn assert_is_equal<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &G2Affine,
+        q: &G2Affine,
+    ) {
+        self.ext2.assert_isequal(native, &p.x, &q.x);
+        self.ext2.assert_isequal(native, &p.y, &q.y);
+    }
+    // p + q for p != ±q, along with the slope of the line through them
+    pub fn add_step<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &G2Affine,
+        q: &G2Affine,
+    ) -> (G2Affine, GE2) {
+        let dy = self.ext2.sub(native, &q.y, &p.y);
+        let dx = self.ext2.sub(native, &q.x, &p.x);
+        let lambda = self.ext2.div(native, &dy, &dx);
+        let sum = self.finish_add(native, &lambda, p, &q.x);
+        (sum, lambda)
+    }
+    // 2p for p.y != 0, along with the slope of the tangent at p
+    pub fn double_step<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &G2Affine,
+    ) -> (G2Affine, GE2) {
+        let x_squared = self.ext2.square(native, &p.x);
+        let num = self
+            .ext2
+            .mul_by_const_element(native, &x_squared, &BigInt::from(3));
+        let den = self.ext2.double(native, &p.y);
+        let lambda = self.ext2.div(native, &num, &den);
+        let sum = self.finish_add(native, &lambda, p, &p.x);
+        (sum, lambda)
+    }
+    // the sum of p and a point with x coordinate qx on the line of slope lambda through p
+    fn finish_add<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        lambda: &GE2,
+        p: &G2Affine,
+        qx: &GE2,
+    ) -> G2Affine {
+        let lambda_squared = self.ext2.square(native, lambda);
+        let x = self.ext2.sub(native, &lambda_squared, &p.x);
+        let x = self.ext2.sub(native, &x, qx);
+        let dx = self.ext2.sub(native, &p.x, &x);
+        let y = self.ext2.mul(native, lambda, &dx);
+        let y = self.ext2.sub(native, &y, &p.y);
+        G2Affine { x, y }
+    }
+    pub fn add<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &G2Affine,
+        q: &G2Affine,
+    ) -> G2Affine {
+        self.add_step(native, p, q).0
+    }
+    pub fn double<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, p: &G2Affine) -> G2Affine {
+        self.double_step(native, p).0
+    }
+    // The Frobenius endomorphism of the curve seen through the twist, [p]q on G2
+    pub fn psi<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, q: &G2Affine) -> G2Affine {
+        let x = self.ext2.conjugate(native, &q.x);
+        let y = self.ext2.conjugate(native, &q.y);
+        G2Affine {
+            x: self.ext2.mul_by_non_residue_generic(native, &x, 1, 2),
+            y: self.ext2.mul_by_non_residue_generic(native, &y, 1, 3),
+        }
+    }
+    // ψ²(q), [p²]q on G2
+    pub fn psi2<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, q: &G2Affine) -> G2Affine {
+        G2Affine {
+            x: self.ext2.mul_by_non_residue_generic(native, &q.x, 2, 2),
+            y: self.ext2.mul_by_non_residue_generic(native, &q.y, 2, 3),
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod g1;
+pub mod g2;
+pub mod pairing;
@@ -0,0 +1,132 @@
+use super::g1::G1Affine;
+use super::g2::{G2Affine, G2};
+use crate::gnark::element::Element;
+use crate::gnark::emparam::BN254Fp;
+use crate::gnark::emulated::field_bn254::e12::{Ext12, GE12};
+use crate::gnark::emulated::field_bn254::e2::GE2;
+use expander_compiler::frontend::{Config, Error, RootAPI};
+
+// 6x + 2 in non-adjacent form, little endian
+const LOOP_COUNTER: [i8; 66] = [
+    0, 0, 0, 1, 0, 1, 0, -1, 0, 0, -1, 0, 0, 0, 1, 0, 0, -1, 0, -1, 0, 0, 0, 1, 0, -1, 0, 0, 0, 0,
+    -1, 0, 0, 1, 0, -1, 0, 0, 1, 0, 0, 0, 0, 0, -1, 0, 0, -1, 0, 1, 0, -1, 0, 0, 0, -1, 0, -1, 0,
+    0, 0, 1, 0, -1, 0, 1,
+];
+
+// The optimal ate pairing on BN254. The Miller loop runs on affine points with the lines
+// divided by yP, a factor the final exponentiation removes. Neither p nor q may be the point
+// at infinity, and q must be in G2, as the points of a trusted setup are.
+pub struct Pairing {
+    pub ext12: Ext12,
+    pub g2: G2,
+}
+
+// -xP/yP and 1/yP
+struct PrecomputedG1 {
+    x_neg_over_y: Element<BN254Fp>,
+    y_inv: Element<BN254Fp>,
+}
+
+impl Pairing {
+    pub fn new<C: Config, B: RootAPI<C>>(native: &mut B) -> Self {
+        Self {
+            ext12: Ext12::new(native),
+            g2: G2::new(native),
+        }
+    }
+    // Asserts Π e(p_i, q_i) == 1
+    pub fn pairing_check<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &[G1Affine],
+        q: &[G2Affine],
+    ) -> Result<(), Error> {
+        let f = self.miller_loop(native, p, q)?;
+        self.ext12.assert_final_exponentiation_is_one(native, &f);
+        Ok(())
+    }
+    // Π f_{6x+2,q_i}(p_i)·ℓ(p_i)·ℓ'(p_i), sharing the squarings between the pairs
+    pub fn miller_loop<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &[G1Affine],
+        q: &[G2Affine],
+    ) -> Result<GE12, Error> {
+        if p.is_empty() || p.len() != q.len() {
+            return Err(Error::UserError("invalid inputs sizes".to_string()));
+        }
+        let p = p
+            .iter()
+            .map(|p| self.precompute_g1(native, p))
+            .collect::<Vec<_>>();
+        let neg_q = q.iter().map(|q| self.g2.neg(native, q)).collect::<Vec<_>>();
+        let mut t = q.to_vec();
+        let mut f = self.ext12.one();
+        for i in (0..LOOP_COUNTER.len() - 1).rev() {
+            if i != LOOP_COUNTER.len() - 2 {
+                f = self.ext12.square(native, &f);
+            }
+            for k in 0..p.len() {
+                let (doubled, lambda) = self.g2.double_step(native, &t[k]);
+                f = self.mul_by_line(native, &f, &t[k], &lambda, &p[k]);
+                t[k] = doubled;
+                let addend = match LOOP_COUNTER[i] {
+                    1 => &q[k],
+                    -1 => &neg_q[k],
+                    _ => continue,
+                };
+                let (sum, lambda) = self.g2.add_step(native, &t[k], addend);
+                f = self.mul_by_line(native, &f, &t[k], &lambda, &p[k]);
+                t[k] = sum;
+            }
+        }
+        // the lines through ψ(q) and -ψ²(q)
+        for k in 0..p.len() {
+            let q1 = self.g2.psi(native, &q[k]);
+            let q2 = self.g2.psi2(native, &q[k]);
+            let q2 = self.g2.neg(native, &q2);
+            let (sum, lambda) = self.g2.add_step(native, &t[k], &q1);
+            f = self.mul_by_line(native, &f, &t[k], &lambda, &p[k]);
+            let (_, lambda) = self.g2.add_step(native, &sum, &q2);
+            f = self.mul_by_line(native, &f, &sum, &lambda, &p[k]);
+        }
+        Ok(f)
+    }
+    fn precompute_g1<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &G1Affine,
+    ) -> PrecomputedG1 {
+        let curve_f = &mut self.ext12.ext6.ext2.curve_f;
+        let y_inv = curve_f.inverse(native, &p.y);
+        let x_over_y = curve_f.mul(native, &p.x, &y_inv);
+        PrecomputedG1 {
+            x_neg_over_y: curve_f.neg(native, &x_over_y),
+            y_inv,
+        }
+    }
+    // f times the line of slope lambda through t evaluated at p. Through the twist the line
+    // is yP - λ·xP·w + (λ·xT - yT)·w³, which over yP is 1 + (λ·(-xP/yP) + (λ·xT - yT)/yP·v)·w.
+    fn mul_by_line<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        f: &GE12,
+        t: &G2Affine,
+        lambda: &GE2,
+        p: &PrecomputedG1,
+    ) -> GE12 {
+        let ext2 = &mut self.g2.ext2;
+        let mu = ext2.mul(native, lambda, &t.x);
+        let mu = ext2.sub(native, &mu, &t.y);
+        let c0 = ext2.mul_by_element(native, lambda, &p.x_neg_over_y);
+        let c1 = ext2.mul_by_element(native, &mu, &p.y_inv);
+        self.ext12.mul_by_line(native, f, &c0, &c1)
+    }
+    // Runs the deferred multiplication and range checks of both fields, call it once at the end
+    pub fn finalize<C: Config, B: RootAPI<C>>(&mut self, native: &mut B) {
+        for field in [&mut self.ext12.ext6.ext2.curve_f, &mut self.g2.ext2.curve_f] {
+            field.check_mul(native);
+            field.table.final_check(native);
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use crate::gnark::element::{value_of, Element};
+use crate::gnark::emparam::{Ed25519Fp, Ed25519Fr, FieldParams, TwistedEdwardsParams};
+use crate::gnark::emulated::sw_bls12381::point::AffinePoint;
+use crate::gnark::field::GField;
+use expander_compiler::frontend::{Config, RootAPI, Variable};
+use num_bigint::BigInt;
+
+// width in bits of the windows of scalar multiplication, tables hold 2^WINDOW points
+const WINDOW: usize = 4;
+
+// The multiples d·P for every window digit d, entry 0 is the identity
+type PointTable<Base> = Vec<AffinePoint<Base>>;
+
+// A twisted Edwards curve over an emulated base field. The addition law is complete when a is
+// a square and d is not, as on Ed25519, so sums need no special cases.
+pub struct TwistedEdwardsCurve<Base: FieldParams, Scalar: FieldParams> {
+    pub params: TwistedEdwardsParams,
+    pub base_api: GField<Base>,
+    pub scalar_api: GField<Scalar>,
+    pub a: Element<Base>,
+    pub d: Element<Base>,
+    a_is_minus_one: bool,
+    g_table: PointTable<Base>,
+}
+
+pub type Ed25519 = TwistedEdwardsCurve<Ed25519Fp, Ed25519Fr>;
+
+impl Ed25519 {
+    pub fn new_ed25519<C: Config, B: RootAPI<C>>(native: &mut B) -> Self {
+        Self::new(native, &TwistedEdwardsParams::get_ed25519_params())
+    }
+}
+
+impl<Base: FieldParams + Default + Clone, Scalar: FieldParams + Default>
+    TwistedEdwardsCurve<Base, Scalar>
+{
+    pub fn new<C: Config, B: RootAPI<C>>(native: &mut B, params: &TwistedEdwardsParams) -> Self {
+        let g = (params.gx.clone(), params.gy.clone());
+        let mut multiple = (BigInt::ZERO, BigInt::from(1));
+        let mut g_table = vec![];
+        for _ in 0..1 << WINDOW {
+            g_table.push(Self::constant_point(native, &multiple.0, &multiple.1));
+            multiple = native_add(params, &Base::modulus(), &multiple, &g);
+        }
+        Self {
+            params: params.clone(),
+            base_api: GField::new(native, Base::default()),
+            scalar_api: GField::new(native, Scalar::default()),
+            a: value_of::<C, B, Base>(native, Box::new(params.a.clone())),
+            d: value_of::<C, B, Base>(native, Box::new(params.d.clone())),
+            a_is_minus_one: params.a == Base::modulus() - 1,
+            g_table,
+        }
+    }
+
+    pub fn constant_point<C: Config, B: RootAPI<C>>(
+        native: &mut B,
+        x: &BigInt,
+        y: &BigInt,
+    ) -> AffinePoint<Base> {
+        AffinePoint {
+            x: value_of::<C, B, Base>(native, Box::new(x.clone())),
+            y: value_of::<C, B, Base>(native, Box::new(y.clone())),
+        }
+    }
+
+    pub fn identity<C: Config, B: RootAPI<C>>(&self, native: &mut B) -> AffinePoint<Base> {
+        Self::constant_point(native, &BigInt::ZERO, &BigInt::from(1))
+    }
+
+    pub fn generator<C: Config, B: RootAPI<C>>(&self, native: &mut B) -> AffinePoint<Base> {
+        Self::constant_point(native, &self.params.gx, &self.params.gy)
+    }
+
+    pub fn assert_is_on_curve<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &AffinePoint<Base>,
+    ) {
+        let xx = self.base_api.mul(native, &p.x, &p.x);
+        let yy = self.base_api.mul(native, &p.y, &p.y);
+        let axx = self.mul_by_a(native, &xx);
+        let left = self.base_api.add(native, &axx, &yy);
+        let xxyy = self.base_api.mul(native, &xx, &yy);
+        let dxxyy = self.base_api.mul(native, &self.d, &xxyy);
+        let one = self.base_api.one_const.clone();
+        let right = self.base_api.add(native, &one, &dxxyy);
+        self.base_api.assert_is_equal(native, &left, &right);
+    }
+
+    fn mul_by_a<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        x: &Element<Base>,
+    ) -> Element<Base> {
+        if self.a_is_minus_one {
+            self.base_api.neg(native, x)
+        } else {
+            self.base_api.mul(native, &self.a, x)
+        }
+    }
+
+    pub fn neg<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &AffinePoint<Base>,
+    ) -> AffinePoint<Base> {
+        AffinePoint {
+            x: self.base_api.neg(native, &p.x),
+            y: p.y.clone(),
+        }
+    }
+
+    // x3 = (x1y2 + y1x2) / (1 + dx1x2y1y2), y3 = (y1y2 - ax1x2) / (1 - dx1x2y1y2)
+    pub fn add<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &AffinePoint<Base>,
+        q: &AffinePoint<Base>,
+    ) -> AffinePoint<Base> {
+        let x1y2 = self.base_api.mul(native, &p.x, &q.y);
+        let y1x2 = self.base_api.mul(native, &p.y, &q.x);
+        let x1x2 = self.base_api.mul(native, &p.x, &q.x);
+        let y1y2 = self.base_api.mul(native, &p.y, &q.y);
+        let x1x2y1y2 = self.base_api.mul(native, &x1x2, &y1y2);
+        let t = self.base_api.mul(native, &self.d, &x1x2y1y2);
+        let one = self.base_api.one_const.clone();
+
+        let x_num = self.base_api.add(native, &x1y2, &y1x2);
+        let x_den = self.base_api.add(native, &one, &t);
+        let ax1x2 = self.mul_by_a(native, &x1x2);
+        let y_num = self.base_api.sub(native, &y1y2, &ax1x2);
+        let y_den = self.base_api.sub(native, &one, &t);
+        AffinePoint {
+            x: self.base_api.div(native, &x_num, &x_den),
+            y: self.base_api.div(native, &y_num, &y_den),
+        }
+    }
+
+    pub fn double<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &AffinePoint<Base>,
+    ) -> AffinePoint<Base> {
+        self.add(native, p, p)
+    }
+
+    pub fn assert_is_equal<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &AffinePoint<Base>,
+        q: &AffinePoint<Base>,
+    ) {
+        self.base_api.assert_is_equal(native, &p.x, &q.x);
+        self.base_api.assert_is_equal(native, &p.y, &q.y);
+    }
+
+    // return p if selector is 1 and q if selector is 0
+    pub fn select<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        selector: Variable,
+        p: &AffinePoint<Base>,
+        q: &AffinePoint<Base>,
+    ) -> AffinePoint<Base> {
+        AffinePoint {
+            x: self.base_api.select(native, selector, &p.x, &q.x),
+            y: self.base_api.select(native, selector, &p.y, &q.y),
+        }
+    }
+
+    fn variable_table<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        p: &AffinePoint<Base>,
+    ) -> PointTable<Base> {
+        let mut table = vec![self.identity(native), p.clone()];
+        for d in 2..1 << WINDOW {
+            let multiple = if d % 2 == 0 {
+                self.double(native, &table[d / 2].clone())
+            } else {
+                self.add(native, &table[d - 1].clone(), p)
+            };
+            table.push(multiple);
+        }
+        table
+    }
+
+    fn lookup<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        table: &PointTable<Base>,
+        digit: &[Variable],
+    ) -> AffinePoint<Base> {
+        let xs = table.iter().map(|p| &p.x).collect::<Vec<_>>();
+        let ys = table.iter().map(|p| &p.y).collect::<Vec<_>>();
+        AffinePoint {
+            x: self.base_api.mux(native, digit, &xs),
+            y: self.base_api.mux(native, digit, &ys),
+        }
+    }
+
+    // The little endian bits of the canonical representative of k. Points outside the prime
+    // order subgroup depend on it, unlike on prime order curves.
+    pub fn scalar_bits<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        k: &Element<Scalar>,
+    ) -> Vec<Variable> {
+        let k = self.scalar_api.reduce(native, k, true);
+        self.scalar_api.assert_is_canonical(native, &k);
+        let nb_bits = (Scalar::modulus().bits() as usize).div_ceil(WINDOW) * WINDOW;
+        let mut bits = vec![];
+        for limb in &k.limbs {
+            bits.extend(native.to_binary(*limb, Scalar::bits_per_limb() as usize));
+        }
+        bits.resize(nb_bits, native.constant(0));
+        bits
+    }
+
+    // Σ k_i·P_i given the tables of the P_i and the bits of the k_i, sharing the doublings
+    fn joint_scalar_mul<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        terms: &[(PointTable<Base>, Vec<Variable>)],
+    ) -> AffinePoint<Base> {
+        let nb_bits = terms[0].1.len();
+        let mut acc = self.identity(native);
+        for window in (0..nb_bits / WINDOW).rev() {
+            if window + 1 < nb_bits / WINDOW {
+                for _ in 0..WINDOW {
+                    acc = self.double(native, &acc);
+                }
+            }
+            for (table, bits) in terms {
+                let digit = &bits[window * WINDOW..(window + 1) * WINDOW];
+                let entry = self.lookup(native, table, digit);
+                acc = self.add(native, &acc, &entry);
+            }
+        }
+        acc
+    }
+
+    // k·p
+    pub fn scalar_mul<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        k: &Element<Scalar>,
+        p: &AffinePoint<Base>,
+    ) -> AffinePoint<Base> {
+        let table = self.variable_table(native, p);
+        let bits = self.scalar_bits(native, k);
+        self.joint_scalar_mul(native, &[(table, bits)])
+    }
+
+    // k·G
+    pub fn scalar_mul_base<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        k: &Element<Scalar>,
+    ) -> AffinePoint<Base> {
+        let bits = self.scalar_bits(native, k);
+        self.joint_scalar_mul(native, &[(self.g_table.clone(), bits)])
+    }
+
+    // k1·G + k2·p
+    pub fn joint_scalar_mul_base<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        k1: &Element<Scalar>,
+        k2: &Element<Scalar>,
+        p: &AffinePoint<Base>,
+    ) -> AffinePoint<Base> {
+        let bits1 = self.scalar_bits(native, k1);
+        let table = self.variable_table(native, p);
+        let bits2 = self.scalar_bits(native, k2);
+        self.joint_scalar_mul(native, &[(self.g_table.clone(), bits1), (table, bits2)])
+    }
+
+    // Runs the deferred multiplication and range checks of both fields, call it once at the end
+    pub fn finalize<C: Config, B: RootAPI<C>>(&mut self, native: &mut B) {
+        self.base_api.check_mul(native);
+        self.scalar_api.check_mul(native);
+        self.base_api.table.final_check(native);
+        self.scalar_api.table.final_check(native);
+    }
+}
+
+fn native_add(
+    params: &TwistedEdwardsParams,
+    modulus: &BigInt,
+    p: &(BigInt, BigInt),
+    q: &(BigInt, BigInt),
+) -> (BigInt, BigInt) {
+    let x1x2 = &p.0 * &q.0;
+    let y1y2 = &p.1 * &q.1;
+    let t = &params.d * &x1x2 * &y1y2 % modulus;
+    let x_den = (BigInt::from(1) + &t).modinv(modulus).unwrap();
+    let y_den = (modulus + 1 - &t).modinv(modulus).unwrap();
+    let x = (&p.0 * &q.1 + &p.1 * &q.0) * x_den % modulus;
+    let y_num = ((y1y2 - &params.a * x1x2) % modulus + modulus) % modulus;
+    let y = y_num * y_den % modulus;
+    (x, y)
+}
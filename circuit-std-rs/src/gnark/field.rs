@@ -1,12 +1,14 @@
 use crate::gnark::element::{new_internal_element, value_of, Element};
 use crate::gnark::emparam::FieldParams;
-use crate::gnark::utils::{hash_to_fp_variable, nb_multiplication_res_limbs, sub_padding};
+use crate::gnark::limbs::{bigint_to_field, decompose};
+use crate::gnark::utils::{
+    hash_to_fp_variable, native_field_bits, nb_multiplication_res_limbs, sub_padding,
+};
 use crate::logup::LogUpRangeProofTable;
 use crate::utils::simple_select;
-use expander_compiler::frontend::{Config, RootAPI, Variable};
+use expander_compiler::frontend::{CircuitField, Config, RootAPI, ToHintId, Variable};
 use num_bigint::BigInt;
 use num_traits::Signed;
-use num_traits::ToPrimitive;
 use num_traits::Zero;
 use std::collections::HashMap;
 
@@ -60,6 +62,8 @@ impl<T: FieldParams> MulCheck<T> {
 }
 pub struct GField<T: FieldParams> {
     _f_params: T,
+    // limbs and their overflow must stay below 2^native_bits
+    native_bits: u32,
     max_of: u32,
     n_const: Element<T>,
     nprev_const: Element<T>,
@@ -74,9 +78,17 @@ pub struct GField<T: FieldParams> {
 
 impl<T: FieldParams> GField<T> {
     pub fn new<C: Config, B: RootAPI<C>>(native: &mut B, f_params: T) -> Self {
+        let native_bits = native_field_bits::<C>();
+        if native_bits < T::bits_per_limb() + 3 {
+            panic!(
+                "{}-bit limbs don't fit in a {native_bits}-bit native field",
+                T::bits_per_limb()
+            );
+        }
         let mut field = GField {
             _f_params: f_params,
-            max_of: 30 - 2 - T::bits_per_limb(),
+            native_bits,
+            max_of: native_bits - 2 - T::bits_per_limb(),
             n_const: Element::<T>::default(),
             nprev_const: Element::<T>::default(),
             zero_const: Element::<T>::default(),
@@ -95,7 +107,7 @@ impl<T: FieldParams> GField<T> {
         field
     }
     pub fn max_overflow(&self) -> u64 {
-        30 - 2 - 8
+        self.max_of as u64
     }
     pub fn is_zero<C: Config, B: RootAPI<C>>(
         &mut self,
@@ -125,7 +137,7 @@ impl<T: FieldParams> GField<T> {
         native: &mut B,
         x: &Element<T>,
     ) -> Variable {
-        native.to_binary(x.limbs[0], 30)[0]
+        native.to_binary(x.limbs[0], self.native_bits as usize)[0]
     }
     pub fn select<C: Config, B: RootAPI<C>>(
         &mut self,
@@ -156,6 +168,29 @@ impl<T: FieldParams> GField<T> {
         }
         new_internal_element::<T>(limbs, overflow)
     }
+    // return elements[i] where i is given by the little endian bits in selectors
+    pub fn mux<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        selectors: &[Variable],
+        elements: &[&Element<T>],
+    ) -> Element<T> {
+        for e in elements {
+            self.enforce_width_conditional(native, e);
+        }
+        let overflow = elements.iter().map(|e| e.overflow).max().unwrap();
+        let nb_limbs = elements.iter().map(|e| e.limbs.len()).max().unwrap();
+        let zero = native.constant(0);
+        let mut limbs = vec![];
+        for i in 0..nb_limbs {
+            let values = elements
+                .iter()
+                .map(|e| e.limbs.get(i).copied().unwrap_or(zero))
+                .collect::<Vec<_>>();
+            limbs.push(native.mux(selectors, &values));
+        }
+        new_internal_element::<T>(limbs, overflow)
+    }
     pub fn enforce_width_conditional<C: Config, B: RootAPI<C>>(
         &mut self,
         native: &mut B,
@@ -214,13 +249,13 @@ impl<T: FieldParams> GField<T> {
     pub fn new_hint<C: Config, B: RootAPI<C>>(
         &mut self,
         native: &mut B,
-        hf_name: &str,
+        hint: impl ToHintId,
         nb_outputs: usize,
         inputs: Vec<Element<T>>,
     ) -> Vec<Element<T>> {
         let native_inputs = self.wrap_hint(native, inputs);
         let nb_native_outputs = T::nb_limbs() as usize * nb_outputs;
-        let native_outputs = native.new_hint(hf_name, &native_inputs, nb_native_outputs);
+        let native_outputs = native.new_hint(hint, &native_inputs, nb_native_outputs);
         let mut outputs = vec![];
         for i in 0..nb_outputs {
             let tmp_output = self.pack_limbs(
@@ -441,7 +476,7 @@ impl<T: FieldParams> GField<T> {
         );
         let mut limbs = vec![native.constant(0); nb_limbs];
         for i in 0..limbs.len() {
-            limbs[i] = native.constant(pad_limbs[i].to_u64().unwrap() as u32);
+            limbs[i] = native.constant(bigint_to_field::<CircuitField<C>>(&pad_limbs[i]));
             if i < new_a.limbs.len() {
                 limbs[i] = native.add(limbs[i], new_a.limbs[i]);
             }
@@ -545,6 +580,46 @@ impl<T: FieldParams> GField<T> {
         self.assert_is_equal(native, &res, &one);
         e
     }
+    // Square root of a, which must be a square in the field. Either root may be returned.
+    pub fn sqrt<C: Config, B: RootAPI<C>>(&mut self, native: &mut B, a: &Element<T>) -> Element<T> {
+        self.enforce_width_conditional(native, a);
+        let output = self.new_hint(native, "myhint.sqrthint", 1, vec![a.clone()]);
+        let root = output[0].clone();
+        let square = self.mul(native, &root, &root);
+        self.assert_is_equal(native, &square, a);
+        root
+    }
+    // Asserts that a is below the modulus, i.e. its limbs are the canonical representation.
+    // a must have a full set of limbs and no overflow, as strictly reduced elements do.
+    pub fn assert_is_canonical<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        a: &Element<T>,
+    ) {
+        if a.overflow != 0 || a.limbs.len() != T::nb_limbs() as usize {
+            panic!("assert_is_canonical needs a reduced element");
+        }
+        self.enforce_width_conditional(native, a);
+        let mut modulus_limbs = vec![BigInt::default(); T::nb_limbs() as usize];
+        if let Err(err) = decompose(&T::modulus(), T::bits_per_limb(), &mut modulus_limbs) {
+            panic!("decompose modulus: {err}");
+        }
+        // compare limb by limb from the most significant one, a < p iff the first differing
+        // limb is smaller
+        let mut prefix_equal = native.constant(1);
+        let mut less = native.constant(0);
+        for i in (0..a.limbs.len()).rev() {
+            let limb = native.constant(bigint_to_field::<CircuitField<C>>(&modulus_limbs[i]));
+            let cmp = native.cmp_bounded(a.limbs[i], limb, T::bits_per_limb() as usize);
+            let cmp_plus_one = native.add(cmp, 1);
+            let limb_less = native.is_zero(cmp_plus_one);
+            let first_less = native.mul(prefix_equal, limb_less);
+            less = native.add(less, first_less);
+            let limb_equal = native.is_zero(cmp);
+            prefix_equal = native.mul(prefix_equal, limb_equal);
+        }
+        native.assert_is_equal(less, 1);
+    }
     pub fn compute_inverse_hint<C: Config, B: RootAPI<C>>(
         &mut self,
         native: &mut B,
@@ -604,7 +679,7 @@ impl<T: FieldParams> GField<T> {
         }
         let mut limbs = vec![native.constant(0); new_a.limbs.len()];
         for i in 0..new_a.limbs.len() {
-            limbs[i] = native.mul(new_a.limbs[i], c.to_u64().unwrap() as u32);
+            limbs[i] = native.mul(new_a.limbs[i], bigint_to_field::<CircuitField<C>>(&c));
         }
         new_internal_element::<T>(limbs, new_a.overflow + cbl as u32)
     }
@@ -630,7 +705,7 @@ impl<T: FieldParams> GField<T> {
         }
         let pval = eval_with_challenge(native, self.n_const.clone(), at.clone());
         let coef = BigInt::from(1) << T::bits_per_limb();
-        let ccoef = native.sub(coef.to_u64().unwrap() as u32, commitment);
+        let ccoef = native.sub(bigint_to_field::<CircuitField<C>>(&coef), commitment);
         for i in 0..self.mul_checks.len() {
             self.mul_checks[i].check(native, pval.evaluation, ccoef);
         }
@@ -644,7 +719,17 @@ impl<T: FieldParams> GField<T> {
         msg: &[Variable],
         len: usize,
     ) -> Vec<Element<T>> {
-        let signature_dst: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+        self.hash_to_fp_with_dst(native, msg, b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_", len)
+    }
+    // hash_to_field of RFC 9380 with expand_message_xmd over SHA-256 and the given domain
+    // separation tag
+    pub fn hash_to_fp_with_dst<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        msg: &[Variable],
+        signature_dst: &[u8],
+        len: usize,
+    ) -> Vec<Element<T>> {
         let mut dst = vec![];
         for c in signature_dst {
             dst.push(native.constant(*c as u32));
@@ -7,44 +7,33 @@ use ark_bls12_381::Fq2;
 use ark_bls12_381::Fq6;
 use ark_ff::fields::Field;
 use ark_ff::Zero;
-use expander_compiler::frontend::{Error, M31};
+use expander_compiler::frontend::Error;
 use num_bigint::BigInt;
 use num_bigint::BigUint;
 use num_traits::One;
 use num_traits::Signed;
 use num_traits::ToPrimitive;
 
+use crate::ecdsa::native::glv_decompose;
 use crate::gnark::limbs::{
-    bigint_to_m31, decompose, m31_to_bigint, m31_to_bigint_array, recompose,
+    bigint_to_field, decompose, field_to_bigint, field_to_bigint_array, recompose,
 };
 use crate::gnark::utils::{
-    fq2_has_sqrt, fq_has_sqrt, get_fq2_sign, get_fq_sign, nb_multiplication_res_limbs,
+    fq2_has_sqrt, fq_has_sqrt, get_fq2_sign, get_fq_sign, nb_multiplication_res_limbs, sqrt_mod,
 };
 
-pub fn mul_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn mul_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     let nb_bits = inputs[0].to_u256().as_usize();
     let nb_limbs = inputs[1].to_u256().as_usize();
     let nb_a_len = inputs[2].to_u256().as_usize();
     let nb_quo_len = inputs[3].to_u256().as_usize();
     let nb_b_len = inputs.len() - 4 - nb_limbs - nb_a_len;
     let mut ptr = 4;
-    let plimbs_m31 = &inputs[ptr..ptr + nb_limbs];
-    let plimbs_u32: Vec<u32> = (0..nb_limbs)
-        .map(|i| plimbs_m31[i].to_u256().as_u32())
-        .collect();
-    let plimbs: Vec<BigInt> = plimbs_u32.iter().map(|x| BigInt::from(*x)).collect();
+    let plimbs = field_to_bigint_array(&inputs[ptr..ptr + nb_limbs]);
     ptr += nb_limbs;
-    let alimbs_m31 = &inputs[ptr..ptr + nb_a_len];
-    let alimbs_u32: Vec<u32> = (0..nb_a_len)
-        .map(|i| alimbs_m31[i].to_u256().as_u32())
-        .collect();
-    let alimbs: Vec<BigInt> = alimbs_u32.iter().map(|x| BigInt::from(*x)).collect();
+    let alimbs = field_to_bigint_array(&inputs[ptr..ptr + nb_a_len]);
     ptr += nb_a_len;
-    let blimbs_m31 = &inputs[ptr..ptr + nb_b_len];
-    let blimbs_u32: Vec<u32> = (0..nb_b_len)
-        .map(|i| blimbs_m31[i].to_u256().as_u32())
-        .collect();
-    let blimbs: Vec<BigInt> = blimbs_u32.iter().map(|x| BigInt::from(*x)).collect();
+    let blimbs = field_to_bigint_array(&inputs[ptr..ptr + nb_b_len]);
 
     let nb_carry_len = std::cmp::max(
         nb_multiplication_res_limbs(nb_a_len, nb_b_len),
@@ -105,48 +94,32 @@ pub fn mul_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
         //if carry is negative, we need to add 2^nb_bits to it
         carry_limbs[i] = carry.clone();
     }
-    //convert limbs to m31 output
+    //convert limbs to native outputs, negative carries are negated in the native field
     let mut outptr = 0;
     for i in 0..nb_quo_len {
-        outputs[outptr + i] = M31::from(quo_limbs[i].to_u64().unwrap() as u32);
+        outputs[outptr + i] = bigint_to_field(&quo_limbs[i]);
     }
     outptr += nb_quo_len;
     for i in 0..nb_limbs {
-        outputs[outptr + i] = M31::from(rem_limbs[i].to_u64().unwrap() as u32);
+        outputs[outptr + i] = bigint_to_field(&rem_limbs[i]);
     }
     outptr += nb_limbs;
     for i in 0..nb_carry_len {
-        if carry_limbs[i] < BigInt::default() {
-            outputs[outptr + i] = -M31::from(carry_limbs[i].abs().to_u64().unwrap() as u32);
-        } else {
-            outputs[outptr + i] = M31::from(carry_limbs[i].to_u64().unwrap() as u32);
-        }
+        outputs[outptr + i] = bigint_to_field(&carry_limbs[i]);
     }
     Ok(())
 }
-pub fn div_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn div_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     let nb_bits = inputs[0].to_u256().as_usize();
     let nb_limbs = inputs[1].to_u256().as_usize();
     let nb_denom_limbs = inputs[2].to_u256().as_usize();
     let nb_nom_limbs = inputs[3].to_u256().as_usize();
     let mut ptr = 4;
-    let plimbs_m31 = &inputs[ptr..ptr + nb_limbs];
-    let plimbs_u32: Vec<u32> = (0..nb_limbs)
-        .map(|i| plimbs_m31[i].to_u256().as_u32())
-        .collect();
-    let plimbs: Vec<BigInt> = plimbs_u32.iter().map(|x| BigInt::from(*x)).collect();
+    let plimbs = field_to_bigint_array(&inputs[ptr..ptr + nb_limbs]);
     ptr += nb_limbs;
-    let nomlimbs_m31 = &inputs[ptr..ptr + nb_nom_limbs];
-    let nomlimbs_u32: Vec<u32> = (0..nb_nom_limbs)
-        .map(|i| nomlimbs_m31[i].to_u256().as_u32())
-        .collect();
-    let nomlimbs: Vec<BigInt> = nomlimbs_u32.iter().map(|x| BigInt::from(*x)).collect();
+    let nomlimbs = field_to_bigint_array(&inputs[ptr..ptr + nb_nom_limbs]);
     ptr += nb_nom_limbs;
-    let denomlimbs_m31 = &inputs[ptr..ptr + nb_denom_limbs];
-    let denomlimbs_u32: Vec<u32> = (0..nb_denom_limbs)
-        .map(|i| denomlimbs_m31[i].to_u256().as_u32())
-        .collect();
-    let denomlimbs: Vec<BigInt> = denomlimbs_u32.iter().map(|x| BigInt::from(*x)).collect();
+    let denomlimbs = field_to_bigint_array(&inputs[ptr..ptr + nb_denom_limbs]);
 
     let p = recompose(plimbs.clone(), nb_bits as u32);
     let nom = recompose(nomlimbs.clone(), nb_bits as u32);
@@ -159,26 +132,18 @@ pub fn div_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
         panic!("decompose value: {err}");
     }
     for i in 0..nb_limbs {
-        outputs[i] = M31::from(res_limbs[i].to_u64().unwrap() as u32);
+        outputs[i] = bigint_to_field(&res_limbs[i]);
     }
     Ok(())
 }
 
-pub fn inv_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn inv_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     let nb_bits = inputs[0].to_u256().as_usize();
     let nb_limbs = inputs[1].to_u256().as_usize();
     let mut ptr = 2;
-    let plimbs_m31 = &inputs[ptr..ptr + nb_limbs];
-    let plimbs_u32: Vec<u32> = (0..nb_limbs)
-        .map(|i| plimbs_m31[i].to_u256().as_u32())
-        .collect();
-    let plimbs: Vec<BigInt> = plimbs_u32.iter().map(|x| BigInt::from(*x)).collect();
+    let plimbs = field_to_bigint_array(&inputs[ptr..ptr + nb_limbs]);
     ptr += nb_limbs;
-    let xlimbs_m31 = &inputs[ptr..ptr + nb_limbs];
-    let xlimbs_u32: Vec<u32> = (0..nb_limbs)
-        .map(|i| xlimbs_m31[i].to_u256().as_u32())
-        .collect();
-    let xlimbs: Vec<BigInt> = xlimbs_u32.iter().map(|x| BigInt::from(*x)).collect();
+    let xlimbs = field_to_bigint_array(&inputs[ptr..ptr + nb_limbs]);
 
     let p = recompose(plimbs.clone(), nb_bits as u32);
     let x = recompose(xlimbs.clone(), nb_bits as u32);
@@ -188,11 +153,11 @@ pub fn inv_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
         panic!("decompose value: {err}");
     }
     for i in 0..nb_limbs {
-        outputs[i] = M31::from(res_limbs[i].to_u64().unwrap() as u32);
+        outputs[i] = bigint_to_field(&res_limbs[i]);
     }
     Ok(())
 }
-pub fn div_e2_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn div_e2_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     if let Err(err) = unwrap_hint(
         true,
         true,
@@ -229,7 +194,7 @@ pub fn div_e2_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
     Ok(())
 }
 
-pub fn inverse_e2_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn inverse_e2_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     if let Err(err) = unwrap_hint(
         true,
         true,
@@ -262,7 +227,7 @@ pub fn inverse_e2_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error>
     Ok(())
 }
 
-pub fn div_e6_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn div_e6_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     if let Err(err) = unwrap_hint(
         true,
         true,
@@ -347,7 +312,7 @@ pub fn div_e6_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
     Ok(())
 }
 
-pub fn inverse_e6_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn inverse_e6_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     if let Err(err) = unwrap_hint(
         true,
         true,
@@ -418,7 +383,7 @@ pub fn inverse_e6_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error>
     Ok(())
 }
 
-pub fn div_e6_by_6_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn div_e6_by_6_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     if let Err(err) = unwrap_hint(
         true,
         true,
@@ -492,7 +457,7 @@ pub fn div_e6_by_6_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error
     Ok(())
 }
 
-pub fn div_e12_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn div_e12_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     if let Err(err) = unwrap_hint(
         true,
         true,
@@ -656,7 +621,7 @@ pub fn div_e12_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
     Ok(())
 }
 
-pub fn inverse_e12_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn inverse_e12_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     if let Err(err) = unwrap_hint(
         true,
         true,
@@ -791,11 +756,11 @@ pub fn inverse_e12_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error
     }
     Ok(())
 }
-pub fn copy_vars_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn copy_vars_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     outputs.copy_from_slice(&inputs[..outputs.len()]);
     Ok(())
 }
-pub fn copy_element_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn copy_element_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     if let Err(err) = unwrap_hint(
         true,
         true,
@@ -808,7 +773,7 @@ pub fn copy_element_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Erro
     }
     Ok(())
 }
-pub fn copy_e2_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn copy_e2_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     if let Err(err) = unwrap_hint(
         true,
         true,
@@ -821,7 +786,7 @@ pub fn copy_e2_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
     }
     Ok(())
 }
-pub fn get_element_sqrt_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn get_element_sqrt_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     if let Err(err) = unwrap_hint(
         true,
         true,
@@ -846,7 +811,31 @@ pub fn get_element_sqrt_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(),
     }
     Ok(())
 }
-pub fn get_e2_sqrt_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+// Square root in any prime field, zero when the input is not a square
+pub fn sqrt_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
+    if let Err(err) = unwrap_hint_with_modulus(true, true, inputs, outputs, |p, inputs| {
+        vec![sqrt_mod(&inputs[0], p).unwrap_or_default()]
+    }) {
+        panic!("sqrtHint: {err}");
+    }
+    Ok(())
+}
+// Splits a scalar k into |k1|, |k2| and their signs with k = k1 + k2·λ, given inputs [k, λ]
+pub fn glv_decompose_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
+    if let Err(err) = unwrap_hint_with_modulus(true, true, inputs, outputs, |n, inputs| {
+        let (k1, k2) = glv_decompose(n, &inputs[1], &inputs[0]);
+        vec![
+            k1.abs(),
+            k2.abs(),
+            BigInt::from(k1.is_negative() as u8),
+            BigInt::from(k2.is_negative() as u8),
+        ]
+    }) {
+        panic!("glvDecomposeHint: {err}");
+    }
+    Ok(())
+}
+pub fn get_e2_sqrt_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     if let Err(err) = unwrap_hint(
         true,
         true,
@@ -879,7 +868,10 @@ pub fn get_e2_sqrt_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error
     }
     Ok(())
 }
-pub fn get_sqrt_x0x1_fq_new_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn get_sqrt_x0x1_fq_new_hint<F: ArithField>(
+    inputs: &[F],
+    outputs: &mut [F],
+) -> Result<(), Error> {
     if let Err(err) = unwrap_hint(
         true,
         true,
@@ -921,7 +913,10 @@ pub fn get_sqrt_x0x1_fq_new_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<
     }
     Ok(())
 }
-pub fn get_sqrt_x0x1_fq2_new_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn get_sqrt_x0x1_fq2_new_hint<F: ArithField>(
+    inputs: &[F],
+    outputs: &mut [F],
+) -> Result<(), Error> {
     if let Err(err) = unwrap_hint(
         true,
         true,
@@ -975,7 +970,7 @@ pub fn get_sqrt_x0x1_fq2_new_hint(inputs: &[M31], outputs: &mut [M31]) -> Result
     }
     Ok(())
 }
-pub fn copy_e12_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn copy_e12_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     if let Err(err) = unwrap_hint(
         true,
         true,
@@ -988,7 +983,7 @@ pub fn copy_e12_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
     }
     Ok(())
 }
-pub fn final_exp_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error> {
+pub fn final_exp_hint<F: ArithField>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
     if let Err(err) = unwrap_hint(
         true,
         true,
@@ -1240,7 +1235,10 @@ pub fn final_exp_hint(inputs: &[M31], outputs: &mut [M31]) -> Result<(), Error>
     Ok(())
 }
 
-pub fn simple_rangecheck_hint(inputs: &[M31], _outputs: &mut [M31]) -> Result<(), Error> {
+pub fn simple_rangecheck_hint<F: ArithField>(
+    inputs: &[F],
+    _outputs: &mut [F],
+) -> Result<(), Error> {
     let nb_bits = inputs[0].to_u256().as_u32();
     let number = inputs[1].to_u256().as_f64();
     let number_bit = if number > 1.0 {
@@ -1255,12 +1253,29 @@ pub fn simple_rangecheck_hint(inputs: &[M31], _outputs: &mut [M31]) -> Result<()
     Ok(())
 }
 
-pub fn unwrap_hint(
+pub fn unwrap_hint<F: ArithField>(
     is_emulated_input: bool,
     is_emulated_output: bool,
-    native_inputs: &[M31],
-    native_outputs: &mut [M31],
+    native_inputs: &[F],
+    native_outputs: &mut [F],
     nonnative_hint: fn(Vec<BigInt>) -> Vec<BigInt>,
+) -> Result<(), String> {
+    unwrap_hint_with_modulus(
+        is_emulated_input,
+        is_emulated_output,
+        native_inputs,
+        native_outputs,
+        |_, inputs| nonnative_hint(inputs),
+    )
+}
+
+// Like unwrap_hint, for hints that work in any emulated field and need its modulus
+pub fn unwrap_hint_with_modulus<F: ArithField>(
+    is_emulated_input: bool,
+    is_emulated_output: bool,
+    native_inputs: &[F],
+    native_outputs: &mut [F],
+    nonnative_hint: impl Fn(&BigInt, Vec<BigInt>) -> Vec<BigInt>,
 ) -> Result<(), String> {
     if native_inputs.len() < 2 {
         return Err("hint wrapper header is 2 elements".to_string());
@@ -1275,7 +1290,7 @@ pub fn unwrap_hint(
         return Err("hint wrapper header is 2+nbLimbs elements".to_string());
     }
     let nonnative_mod_limbs =
-        m31_to_bigint_array(native_inputs[2..2 + nb_limbs].to_vec().as_slice());
+        field_to_bigint_array(native_inputs[2..2 + nb_limbs].to_vec().as_slice());
     let nonnative_mod = recompose(nonnative_mod_limbs, nb_bits);
     let mut nonnative_inputs;
     if is_emulated_input {
@@ -1296,7 +1311,7 @@ pub fn unwrap_hint(
             if native_inputs.len() < read_ptr + 1 + current_input_len {
                 return Err(format!("cannot read {i}-th nonnative element"));
             }
-            let tmp_inputs = m31_to_bigint_array(
+            let tmp_inputs = field_to_bigint_array(
                 native_inputs[read_ptr + 1..read_ptr + 1 + current_input_len]
                     .to_vec()
                     .as_slice(),
@@ -1309,10 +1324,10 @@ pub fn unwrap_hint(
         let read_ptr = 2 + nb_limbs;
         nonnative_inputs = vec![BigInt::default(); nb_inputs];
         for i in 0..nb_inputs {
-            nonnative_inputs[i] = m31_to_bigint(native_inputs[read_ptr + i]);
+            nonnative_inputs[i] = field_to_bigint(native_inputs[read_ptr + i]);
         }
     }
-    let nonnative_outputs = nonnative_hint(nonnative_inputs);
+    let nonnative_outputs = nonnative_hint(&nonnative_mod, nonnative_inputs);
     let mut tmp_outputs = vec![BigInt::default(); nb_limbs * nonnative_outputs.len()];
     if is_emulated_output {
         if native_outputs.len() % nb_limbs != 0 {
@@ -1332,7 +1347,7 @@ pub fn unwrap_hint(
         tmp_outputs[..nonnative_outputs.len()].clone_from_slice(&nonnative_outputs[..]);
     }
     for i in 0..tmp_outputs.len() {
-        native_outputs[i] = bigint_to_m31(&tmp_outputs[i]);
+        native_outputs[i] = bigint_to_field(&tmp_outputs[i]);
     }
     Ok(())
 }
@@ -1,7 +1,8 @@
 use arith::Field;
+use ethnum::U256;
 use expander_compiler::frontend::M31;
-use num_bigint::BigInt;
-use num_traits::ToPrimitive;
+use num_bigint::{BigInt, Sign};
+use num_traits::Signed;
 
 pub fn recompose(inputs: Vec<BigInt>, nb_bits: u32) -> BigInt {
     if inputs.is_empty() {
@@ -28,14 +29,42 @@ pub fn decompose(input: &BigInt, nb_bits: u32, res: &mut [BigInt]) -> Result<(),
     Ok(())
 }
 
+pub fn field_to_bigint<F: Field>(input: F) -> BigInt {
+    BigInt::from_bytes_le(Sign::Plus, &input.to_u256().to_le_bytes())
+}
+
+// negative inputs map to their opposite in the field, |input| must be below the modulus
+pub fn bigint_to_field<F: Field>(input: &BigInt) -> F {
+    let (_, bytes) = input.abs().to_bytes_le();
+    if bytes.len() > 32 {
+        panic!("{input} doesn't fit in the native field");
+    }
+    let mut le = [0u8; 32];
+    le[..bytes.len()].copy_from_slice(&bytes);
+    let abs = U256::from_le_bytes(le);
+    if abs >= F::MODULUS {
+        panic!("{input} doesn't fit in the native field");
+    }
+    let x = F::from_u256(abs);
+    if input.is_negative() {
+        -x
+    } else {
+        x
+    }
+}
+
+pub fn field_to_bigint_array<F: Field>(input: &[F]) -> Vec<BigInt> {
+    input.iter().map(|x| field_to_bigint(*x)).collect()
+}
+
 pub fn m31_to_bigint(input: M31) -> BigInt {
-    BigInt::from(input.to_u256().as_u32())
+    field_to_bigint(input)
 }
 
 pub fn bigint_to_m31(input: &BigInt) -> M31 {
-    M31::from(input.to_u32().unwrap())
+    bigint_to_field(input)
 }
 
 pub fn m31_to_bigint_array(input: &[M31]) -> Vec<BigInt> {
-    input.iter().map(|x| m31_to_bigint(*x)).collect()
+    field_to_bigint_array(input)
 }
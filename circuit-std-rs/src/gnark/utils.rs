@@ -11,7 +11,13 @@ use crate::sha256::m31::sha256_var_bytes;
 use crate::sha256::m31_utils::from_binary;
 use ark_bls12_381::Fq2;
 use ark_ff::Zero;
-use expander_compiler::frontend::{Config, RootAPI, Variable};
+use expander_compiler::frontend::{CircuitField, Config, FieldArith, RootAPI, Variable};
+
+// Values below 2^native_field_bits are represented exactly in the native field
+pub fn native_field_bits<C: Config>() -> u32 {
+    let modulus = CircuitField::<C>::MODULUS;
+    255 - modulus.leading_zeros()
+}
 
 pub fn nb_multiplication_res_limbs(len_left: usize, len_right: usize) -> usize {
     let res = len_left + len_right - 1;
@@ -82,6 +88,49 @@ pub fn fq2_has_sqrt(x: &Fq2) -> (Fq2, bool) {
         None => (*x, false),
     }
 }
+// Tonelli-Shanks square root modulo an odd prime p, None if a is not a square
+pub fn sqrt_mod(a: &BigInt, p: &BigInt) -> Option<BigInt> {
+    let one = BigInt::from(1);
+    let a = ((a % p) + p) % p;
+    if a.is_zero() {
+        return Some(a);
+    }
+    let half = (p - &one) >> 1;
+    if a.modpow(&half, p) != one {
+        return None;
+    }
+    // p - 1 = q * 2^s with q odd
+    let mut q = p - &one;
+    let mut s = 0u32;
+    while !q.bit(0) {
+        q >>= 1;
+        s += 1;
+    }
+    let mut z = BigInt::from(2);
+    while z.modpow(&half, p) != p - &one {
+        z += 1;
+    }
+    let mut m = s;
+    let mut c = z.modpow(&q, p);
+    let mut t = a.modpow(&q, p);
+    let mut r = a.modpow(&((&q + &one) >> 1), p);
+    while t != one {
+        // least i with t^(2^i) == 1
+        let mut i = 0;
+        let mut t2 = t.clone();
+        while t2 != one {
+            t2 = &t2 * &t2 % p;
+            i += 1;
+        }
+        let b = c.modpow(&(BigInt::from(1) << (m - i - 1)), p);
+        m = i;
+        c = &b * &b % p;
+        t = t * &c % p;
+        r = r * b % p;
+    }
+    Some(r)
+}
+
 pub fn xor_variable<C: Config, B: RootAPI<C>>(
     api: &mut B,
     nbits: usize,
@@ -0,0 +1,303 @@
+// Keccak-f[1600] and the Keccak-256 / SHA3-256 sponges over bit variables. Messages and digests
+// are 0/1 bits, eight per byte, least significant bit first as Keccak reads them. Over GF2 the
+// state keeps the bits as they are, over larger fields it uses the ±1 form (0 -> 1, 1 -> -1)
+// where xor is a single multiplication.
+pub mod kernel;
+
+use expander_compiler::frontend::{Config, RootAPI, Variable};
+
+use crate::utils::is_gf2;
+
+const LANE_BITS: usize = 64;
+const NUM_ROUNDS: usize = 24;
+
+const ROUND_CONSTANTS: [u64; NUM_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808A,
+    0x8000000080008000,
+    0x000000000000808B,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008A,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000A,
+    0x000000008000808B,
+    0x800000000000008B,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800A,
+    0x800000008000000A,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+// Rotation offsets of the rho step, lane (x, y) at x + 5y
+const ROTATIONS: [usize; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+// Rate in bytes of the 256-bit variants
+pub const RATE_256: usize = 136;
+// First padding byte, holding the domain separation bits
+pub const KECCAK_DELIMITER: u8 = 0x01;
+pub const SHA3_DELIMITER: u8 = 0x06;
+
+// The bit operations of the state representation
+#[derive(Clone, Copy, Debug)]
+struct BitForm {
+    gf2: bool,
+}
+
+impl BitForm {
+    fn constant<C: Config>(&self, api: &mut impl RootAPI<C>, bit: bool) -> Variable {
+        match (self.gf2, bit) {
+            (true, _) => api.constant(bit as u32),
+            (false, false) => api.constant(1),
+            (false, true) => api.neg(1),
+        }
+    }
+
+    // Asserts a message bit is 0 or 1 and brings it into the state representation
+    fn encode<C: Config>(&self, api: &mut impl RootAPI<C>, x: Variable) -> Variable {
+        if self.gf2 {
+            return x;
+        }
+        api.assert_is_bool(x);
+        let t = api.mul(x, 2);
+        api.sub(1, t)
+    }
+
+    fn decode<C: Config>(&self, api: &mut impl RootAPI<C>, x: Variable) -> Variable {
+        if self.gf2 {
+            return x;
+        }
+        let t = api.sub(1, x);
+        api.div(t, 2, true)
+    }
+
+    fn xor<C: Config>(&self, api: &mut impl RootAPI<C>, a: Variable, b: Variable) -> Variable {
+        if self.gf2 {
+            api.add(a, b)
+        } else {
+            api.mul(a, b)
+        }
+    }
+
+    fn not<C: Config>(&self, api: &mut impl RootAPI<C>, a: Variable) -> Variable {
+        if self.gf2 {
+            api.add(a, 1)
+        } else {
+            api.neg(a)
+        }
+    }
+
+    // a ^ (!b & c), the chi step on one bit. In the ±1 form !b & c is (c - b + b·c + 1)/2.
+    fn chi<C: Config>(
+        &self,
+        api: &mut impl RootAPI<C>,
+        a: Variable,
+        b: Variable,
+        c: Variable,
+    ) -> Variable {
+        let t = if self.gf2 {
+            let not_b = api.add(b, 1);
+            api.mul(not_b, c)
+        } else {
+            let bc = api.mul(b, c);
+            let t = api.sub(c, b);
+            let t = api.add(t, bc);
+            let t = api.add(t, 1);
+            api.div(t, 2, true)
+        };
+        self.xor(api, a, t)
+    }
+
+    fn xor_lane<C: Config>(
+        &self,
+        api: &mut impl RootAPI<C>,
+        a: &[Variable],
+        b: &[Variable],
+    ) -> Vec<Variable> {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| self.xor(api, *x, *y))
+            .collect()
+    }
+}
+
+fn rotate_left(lane: &[Variable], k: usize) -> Vec<Variable> {
+    let s = k % LANE_BITS;
+    let mut out = lane[LANE_BITS - s..].to_vec();
+    out.extend_from_slice(&lane[..LANE_BITS - s]);
+    out
+}
+
+// The Keccak-f[1600] permutation on 25 lanes of 64 bits, lane (x, y) at x + 5y
+fn keccak_f<C: Config>(api: &mut impl RootAPI<C>, form: BitForm, a: &mut [Vec<Variable>]) {
+    for rc in ROUND_CONSTANTS {
+        // theta
+        let mut c = Vec::with_capacity(5);
+        for x in 0..5 {
+            let t = form.xor_lane(api, &a[x], &a[x + 5]);
+            let t = form.xor_lane(api, &t, &a[x + 10]);
+            let t = form.xor_lane(api, &t, &a[x + 15]);
+            c.push(form.xor_lane(api, &t, &a[x + 20]));
+        }
+        for x in 0..5 {
+            let d = form.xor_lane(api, &c[(x + 4) % 5], &rotate_left(&c[(x + 1) % 5], 1));
+            for y in 0..5 {
+                a[x + 5 * y] = form.xor_lane(api, &a[x + 5 * y], &d);
+            }
+        }
+        // rho and pi
+        let mut b = vec![Vec::new(); 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                let lane = rotate_left(&a[x + 5 * y], ROTATIONS[x + 5 * y]);
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = lane;
+            }
+        }
+        // chi
+        for x in 0..5 {
+            for y in 0..5 {
+                a[x + 5 * y] = (0..LANE_BITS)
+                    .map(|i| {
+                        form.chi(
+                            api,
+                            b[x + 5 * y][i],
+                            b[(x + 1) % 5 + 5 * y][i],
+                            b[(x + 2) % 5 + 5 * y][i],
+                        )
+                    })
+                    .collect();
+            }
+        }
+        // iota
+        for i in 0..LANE_BITS {
+            if (rc >> i) & 1 == 1 {
+                a[0][i] = form.not(api, a[0][i]);
+            }
+        }
+    }
+}
+
+// A Keccak sponge with a fixed rate and padding. Absorb any whole number of bytes in as many
+// calls as needed, the first squeeze pads the message and later squeezes continue the output.
+#[derive(Clone, Debug)]
+pub struct KeccakSponge {
+    rate: usize,
+    delimiter: u8,
+    form: BitForm,
+    state: Vec<Vec<Variable>>,
+    // message bits not yet absorbed, in the state representation
+    pending: Vec<Variable>,
+    // bytes of the current block already squeezed, None while absorbing
+    squeezed: Option<usize>,
+}
+
+impl KeccakSponge {
+    // rate is in bytes, delimiter is the first padding byte
+    pub fn new<C: Config>(api: &mut impl RootAPI<C>, rate: usize, delimiter: u8) -> Self {
+        assert!(
+            rate > 0 && rate < 200 && rate % 8 == 0,
+            "rate must be a whole number of lanes smaller than the state"
+        );
+        let form = BitForm { gf2: is_gf2::<C>() };
+        let zero = form.constant(api, false);
+        Self {
+            rate,
+            delimiter,
+            form,
+            state: vec![vec![zero; LANE_BITS]; 25],
+            pending: Vec::new(),
+            squeezed: None,
+        }
+    }
+
+    pub fn keccak256<C: Config>(api: &mut impl RootAPI<C>) -> Self {
+        Self::new(api, RATE_256, KECCAK_DELIMITER)
+    }
+
+    pub fn sha3_256<C: Config>(api: &mut impl RootAPI<C>) -> Self {
+        Self::new(api, RATE_256, SHA3_DELIMITER)
+    }
+
+    pub fn absorb<C: Config>(&mut self, api: &mut impl RootAPI<C>, bits: &[Variable]) {
+        assert!(self.squeezed.is_none(), "cannot absorb after squeezing");
+        assert!(bits.len() % 8 == 0, "input must be a whole number of bytes");
+        for bit in bits {
+            let bit = self.form.encode(api, *bit);
+            self.pending.push(bit);
+        }
+        let block_bits = self.rate * 8;
+        while self.pending.len() >= block_bits {
+            let block = self.pending.drain(..block_bits).collect::<Vec<_>>();
+            self.absorb_block(api, &block);
+        }
+    }
+
+    // Returns the next len bytes of output, as bits
+    pub fn squeeze<C: Config>(&mut self, api: &mut impl RootAPI<C>, len: usize) -> Vec<Variable> {
+        if self.squeezed.is_none() {
+            self.pad(api);
+            self.squeezed = Some(0);
+        }
+        let mut out = Vec::with_capacity(len * 8);
+        for _ in 0..len {
+            let mut offset = self.squeezed.unwrap();
+            if offset == self.rate {
+                keccak_f(api, self.form, &mut self.state);
+                offset = 0;
+            }
+            let lane = &self.state[offset / 8];
+            let byte = lane[(offset % 8) * 8..(offset % 8 + 1) * 8].to_vec();
+            for bit in byte {
+                out.push(self.form.decode(api, bit));
+            }
+            self.squeezed = Some(offset + 1);
+        }
+        out
+    }
+
+    fn pad<C: Config>(&mut self, api: &mut impl RootAPI<C>) {
+        let mut padding = vec![0u8; self.rate - self.pending.len() / 8];
+        padding[0] = self.delimiter;
+        *padding.last_mut().unwrap() |= 0x80;
+        for byte in padding {
+            for i in 0..8 {
+                let bit = self.form.constant(api, (byte >> i) & 1 == 1);
+                self.pending.push(bit);
+            }
+        }
+        let block = std::mem::take(&mut self.pending);
+        self.absorb_block(api, &block);
+    }
+
+    fn absorb_block<C: Config>(&mut self, api: &mut impl RootAPI<C>, block: &[Variable]) {
+        for (i, lane) in block.chunks(LANE_BITS).enumerate() {
+            self.state[i] = self.form.xor_lane(api, &self.state[i], lane);
+        }
+        keccak_f(api, self.form, &mut self.state);
+    }
+}
+
+// The 256 bits of the Keccak-256 digest, as used by Ethereum
+pub fn keccak256<C: Config>(api: &mut impl RootAPI<C>, bits: &[Variable]) -> Vec<Variable> {
+    let mut sponge = KeccakSponge::keccak256(api);
+    sponge.absorb(api, bits);
+    sponge.squeeze(api, 32)
+}
+
+// The 256 bits of the FIPS 202 SHA3-256 digest
+pub fn sha3_256<C: Config>(api: &mut impl RootAPI<C>, bits: &[Variable]) -> Vec<Variable> {
+    let mut sponge = KeccakSponge::sha3_256(api);
+    sponge.absorb(api, bits);
+    sponge.squeeze(api, 32)
+}
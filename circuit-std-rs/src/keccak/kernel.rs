@@ -0,0 +1,37 @@
+// zkcuda kernels hashing one 64-byte message per instance, two digests as in a Merkle tree
+// node. Inputs and outputs are bits in the same order as the circuit functions.
+use expander_compiler::frontend::*;
+use expander_compiler::zkcuda::kernel::*;
+
+use super::{keccak256, sha3_256};
+
+pub const KERNEL_INPUT_BITS: usize = 64 * 8;
+pub const KERNEL_OUTPUT_BITS: usize = 256;
+
+#[kernel]
+fn keccak256_64<C: Config>(
+    api: &mut API<C>,
+    input: &[InputVariable; KERNEL_INPUT_BITS],
+    output: &mut [OutputVariable; KERNEL_OUTPUT_BITS],
+) {
+    let digest = api.memorized_simple_call(|api, input| keccak256(api, input), input);
+    output.copy_from_slice(&digest);
+}
+
+#[kernel]
+fn sha3_256_64<C: Config>(
+    api: &mut API<C>,
+    input: &[InputVariable; KERNEL_INPUT_BITS],
+    output: &mut [OutputVariable; KERNEL_OUTPUT_BITS],
+) {
+    let digest = api.memorized_simple_call(|api, input| sha3_256(api, input), input);
+    output.copy_from_slice(&digest);
+}
+
+pub fn compile_keccak256_kernel<C: Config>() -> Result<KernelPrimitive<C>, Error> {
+    compile_keccak256_64()
+}
+
+pub fn compile_sha3_256_kernel<C: Config>() -> Result<KernelPrimitive<C>, Error> {
+    compile_sha3_256_64()
+}
@@ -0,0 +1,232 @@
+// Verification of KZG openings over BN254, univariate ones and the HyperKZG multilinear openings
+// of the ZKCuda KZG backend, with the pairing emulated over the native field.
+pub mod expander;
+pub mod native;
+
+use crate::gnark::element::{value_of, Element};
+use crate::gnark::emparam::BN254Fr;
+use crate::gnark::emulated::sw_bn254::g1::{G1Affine, G1};
+use crate::gnark::emulated::sw_bn254::g2::{G2Affine, G2};
+use crate::gnark::emulated::sw_bn254::pairing::Pairing;
+use expander_compiler::frontend::{Config, RootAPI};
+use native::VerifyingKey;
+use num_bigint::BigInt;
+
+// A HyperKZG opening, see native::NativeHyperKzgOpening
+pub struct HyperKzgOpening {
+    pub folded_oracle_commitments: Vec<G1Affine>,
+    pub beta2_evals: Vec<Element<BN254Fr>>,
+    pub pos_beta_evals: Vec<Element<BN254Fr>>,
+    pub neg_beta_evals: Vec<Element<BN254Fr>>,
+    pub beta_x_commitment: G1Affine,
+    pub quotient_delta_x_commitment: G1Affine,
+}
+
+pub struct HyperKzgChallenges {
+    pub beta: Element<BN254Fr>,
+    pub gamma: Element<BN254Fr>,
+    pub delta: Element<BN254Fr>,
+}
+
+pub struct KzgVerifier {
+    pub curve: G1,
+    pub pairing: Pairing,
+    g2: G2Affine,
+    tau_g2: G2Affine,
+}
+
+impl KzgVerifier {
+    // The verifying key is fixed by the setup and becomes a circuit constant
+    pub fn new<C: Config, B: RootAPI<C>>(native: &mut B, vk: &VerifyingKey) -> Self {
+        let g2 = G2::constant_point(
+            native,
+            [&vk.g2.x[0], &vk.g2.x[1]],
+            [&vk.g2.y[0], &vk.g2.y[1]],
+        );
+        let tau_g2 = G2::constant_point(
+            native,
+            [&vk.tau_g2.x[0], &vk.tau_g2.x[1]],
+            [&vk.tau_g2.y[0], &vk.tau_g2.y[1]],
+        );
+        Self {
+            curve: G1::new_bn254(native),
+            pairing: Pairing::new(native),
+            g2,
+            tau_g2,
+        }
+    }
+
+    // Asserts that the polynomial committed to evaluates to value at point, with proof the
+    // commitment to the quotient (f(X) - value)/(X - point):
+    // e(C - [value]G1 + [point]π, G2)·e(-π, [τ]G2) == 1.
+    // The additions are incomplete, so C - [value]G1 + [point]π and π must not be zero.
+    pub fn verify<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        commitment: &G1Affine,
+        point: &Element<BN254Fr>,
+        value: &Element<BN254Fr>,
+        proof: &G1Affine,
+    ) {
+        self.curve.assert_is_on_curve(native, commitment);
+        self.curve.assert_is_on_curve(native, proof);
+        let neg_value = self.curve.scalar_api.neg(native, value);
+        let shift = self
+            .curve
+            .joint_scalar_mul_base(native, &neg_value, point, proof);
+        let lhs = self.curve.add(native, commitment, &shift);
+        self.pairing_check(native, lhs, proof);
+    }
+
+    // Asserts that the multilinear polynomial whose evaluations over the hypercube are the
+    // coefficients committed to evaluates to value at point, the first coordinate folding
+    // adjacent evaluations as in HyperUniKZGPCS. The challenges are those of the opening's
+    // transcript and must be bound to it by the caller, e.g. as public inputs recomputed by
+    // expander::hyper_kzg_challenges. The folded commitments, γ^i·C_i, -Z(δ)·Q and δ·π are summed
+    // with incomplete additions, so none of them may collide.
+    pub fn verify_multilinear<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        commitment: &G1Affine,
+        point: &[Element<BN254Fr>],
+        value: &Element<BN254Fr>,
+        opening: &HyperKzgOpening,
+        challenges: &HyperKzgChallenges,
+    ) {
+        let num_vars = point.len();
+        assert_eq!(opening.folded_oracle_commitments.len() + 1, num_vars);
+        assert_eq!(opening.beta2_evals.len(), num_vars);
+        assert_eq!(opening.pos_beta_evals.len(), num_vars);
+        assert_eq!(opening.neg_beta_evals.len(), num_vars);
+        self.curve.assert_is_on_curve(native, commitment);
+        for c in &opening.folded_oracle_commitments {
+            self.curve.assert_is_on_curve(native, c);
+        }
+        self.curve
+            .assert_is_on_curve(native, &opening.beta_x_commitment);
+        self.curve
+            .assert_is_on_curve(native, &opening.quotient_delta_x_commitment);
+
+        let HyperKzgChallenges { beta, gamma, delta } = challenges;
+        let s = &mut self.curve.scalar_api;
+        // f_{i+1}(β²) from the even and odd parts of f_i at ±β
+        let two_beta = s.add(native, beta, beta);
+        let half =
+            value_of::<C, B, BN254Fr>(native, Box::new(native::fr_inverse(&BigInt::from(2))));
+        for i in 0..num_vars {
+            let (pos, neg) = (&opening.pos_beta_evals[i], &opening.neg_beta_evals[i]);
+            let sum = s.add(native, pos, neg);
+            let even = s.mul(native, &sum, &half);
+            let diff = s.sub(native, pos, neg);
+            let odd = s.div(native, &diff, &two_beta);
+            let odd_minus_even = s.sub(native, &odd, &even);
+            let folded = s.mul(native, &point[i], &odd_minus_even);
+            let folded = s.add(native, &even, &folded);
+            let expected = opening.beta2_evals.get(i + 1).unwrap_or(value);
+            s.assert_is_equal(native, &folded, expected);
+        }
+
+        // the evaluations of f_γ = Σ γ^i·f_i at β, -β and β²
+        let mut combine = |native: &mut B, evals: &[Element<BN254Fr>]| {
+            let mut acc = evals[num_vars - 1].clone();
+            for e in evals[..num_vars - 1].iter().rev() {
+                acc = s.mul(native, &acc, gamma);
+                acc = s.add(native, &acc, e);
+            }
+            acc
+        };
+        let pos = combine(native, &opening.pos_beta_evals);
+        let neg = combine(native, &opening.neg_beta_evals);
+        let at_beta2 = combine(native, &opening.beta2_evals);
+
+        // L(δ) for the degree 2 polynomial L through them, and Z(δ) = (δ - β)(δ + β)(δ - β²)
+        let beta2 = s.mul(native, beta, beta);
+        let delta_minus_beta = s.sub(native, delta, beta);
+        let delta_plus_beta = s.add(native, delta, beta);
+        let delta_minus_beta2 = s.sub(native, delta, &beta2);
+        let beta_minus_beta2 = s.sub(native, beta, &beta2);
+        let beta_plus_beta2 = s.add(native, beta, &beta2);
+        let neg_beta_minus_beta2 = s.neg(native, &beta_minus_beta2);
+        let mut lagrange_term =
+            |native: &mut B, value: &Element<BN254Fr>, factors: [&Element<BN254Fr>; 4]| {
+                let num = s.mul(native, value, factors[0]);
+                let num = s.mul(native, &num, factors[1]);
+                let den = s.mul(native, factors[2], factors[3]);
+                s.div(native, &num, &den)
+            };
+        let l_pos = lagrange_term(
+            native,
+            &pos,
+            [
+                &delta_plus_beta,
+                &delta_minus_beta2,
+                &two_beta,
+                &beta_minus_beta2,
+            ],
+        );
+        let l_neg = lagrange_term(
+            native,
+            &neg,
+            [
+                &delta_minus_beta,
+                &delta_minus_beta2,
+                &two_beta,
+                &beta_plus_beta2,
+            ],
+        );
+        let l_beta2 = lagrange_term(
+            native,
+            &at_beta2,
+            [
+                &delta_minus_beta,
+                &delta_plus_beta,
+                &neg_beta_minus_beta2,
+                &beta_plus_beta2,
+            ],
+        );
+        let lagrange = s.add(native, &l_pos, &l_neg);
+        let lagrange = s.add(native, &lagrange, &l_beta2);
+        let vanishing = s.mul(native, &delta_minus_beta, &delta_plus_beta);
+        let vanishing = s.mul(native, &vanishing, &delta_minus_beta2);
+
+        // C_0 + Σ γ^i·C_i - Z(δ)·Q - L(δ)·G + δ·π
+        let mut terms = vec![];
+        let mut gamma_i = gamma.clone();
+        for c in &opening.folded_oracle_commitments {
+            terms.push((gamma_i.clone(), c.clone()));
+            gamma_i = s.mul(native, &gamma_i, gamma);
+        }
+        let neg_vanishing = s.neg(native, &vanishing);
+        terms.push((neg_vanishing, opening.beta_x_commitment.clone()));
+        terms.push((delta.clone(), opening.quotient_delta_x_commitment.clone()));
+        let neg_lagrange = s.neg(native, &lagrange);
+        let shift = self
+            .curve
+            .multi_scalar_mul_base(native, &neg_lagrange, &terms);
+        let lhs = self.curve.add(native, commitment, &shift);
+        self.pairing_check(native, lhs, &opening.quotient_delta_x_commitment);
+    }
+
+    // e(lhs, G2)·e(-π, [τ]G2) == 1
+    fn pairing_check<C: Config, B: RootAPI<C>>(
+        &mut self,
+        native: &mut B,
+        lhs: G1Affine,
+        proof: &G1Affine,
+    ) {
+        let neg_proof = self.curve.neg(native, proof);
+        self.pairing
+            .pairing_check(
+                native,
+                &[lhs, neg_proof],
+                &[self.g2.clone(), self.tau_g2.clone()],
+            )
+            .unwrap();
+    }
+
+    // Runs the deferred checks, call it once after all verifications
+    pub fn finalize<C: Config, B: RootAPI<C>>(&mut self, native: &mut B) {
+        self.curve.finalize(native);
+        self.pairing.finalize(native);
+    }
+}
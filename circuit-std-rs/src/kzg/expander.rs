@@ -0,0 +1,104 @@
+// Conversions from the commitments, verifier parameters and HyperKZG openings of Expander's
+// HyperUniKZGPCS over BN254, the PCS of the ZKCuda KZG backend (BN254ConfigSha2UniKZG).
+use super::native::{NativeG2, NativeHyperKzgChallenges, NativeHyperKzgOpening, VerifyingKey};
+use crate::ecdsa::native::NativePoint;
+use gkr_engine::Transcript;
+use halo2curves::bn256::{Bn256, Fq, Fq2, Fr, G1Affine, G2Affine};
+use halo2curves::ff::PrimeField;
+use halo2curves::group::prime::PrimeCurveAffine;
+use halo2curves::group::GroupEncoding;
+use num_bigint::{BigInt, Sign};
+use poly_commit::{HyperUniKZGOpening, KZGCommitment, UniKZGVerifierParams};
+
+pub fn fq_to_bigint(a: &Fq) -> BigInt {
+    BigInt::from_bytes_le(Sign::Plus, a.to_repr().as_ref())
+}
+
+pub fn fr_to_bigint(a: &Fr) -> BigInt {
+    BigInt::from_bytes_le(Sign::Plus, a.to_repr().as_ref())
+}
+
+fn fq2_to_native(a: &Fq2) -> [BigInt; 2] {
+    [fq_to_bigint(&a.c0), fq_to_bigint(&a.c1)]
+}
+
+// None for the point at infinity
+pub fn g1_to_native(p: &G1Affine) -> Option<NativePoint> {
+    if bool::from(p.is_identity()) {
+        return None;
+    }
+    Some(NativePoint {
+        x: fq_to_bigint(&p.x),
+        y: fq_to_bigint(&p.y),
+    })
+}
+
+pub fn g2_to_native(p: &G2Affine) -> Option<NativeG2> {
+    if bool::from(p.is_identity()) {
+        return None;
+    }
+    Some(NativeG2 {
+        x: fq2_to_native(&p.x),
+        y: fq2_to_native(&p.y),
+    })
+}
+
+pub fn commitment_to_native(commitment: &KZGCommitment<Bn256>) -> Option<NativePoint> {
+    g1_to_native(&commitment.0)
+}
+
+// The pairing is checked against the generator of G2, as in the PCS
+pub fn verifying_key_from(vk: &UniKZGVerifierParams<Bn256>) -> VerifyingKey {
+    VerifyingKey {
+        g2: g2_to_native(&G2Affine::generator()).unwrap(),
+        tau_g2: g2_to_native(&vk.tau_g2).expect("τ·G2 is the point at infinity"),
+    }
+}
+
+// None if one of the commitments is the point at infinity, which the incomplete additions of
+// the verifier can't handle
+pub fn hyper_opening_from(opening: &HyperUniKZGOpening<Bn256>) -> Option<NativeHyperKzgOpening> {
+    let evals = &opening.evals_at_x;
+    let to_bigints = |v: &[Fr]| v.iter().map(fr_to_bigint).collect();
+    Some(NativeHyperKzgOpening {
+        folded_oracle_commitments: opening
+            .folded_oracle_commitments
+            .iter()
+            .map(g1_to_native)
+            .collect::<Option<_>>()?,
+        beta2_evals: to_bigints(&evals.beta2_evals),
+        pos_beta_evals: to_bigints(&evals.pos_beta_evals),
+        neg_beta_evals: to_bigints(&evals.neg_beta_evals),
+        beta_x_commitment: g1_to_native(&opening.beta_x_commitment)?,
+        quotient_delta_x_commitment: g1_to_native(&opening.quotient_delta_x_commitment)?,
+    })
+}
+
+// Replays the verifier's transcript over the opening to draw β, γ and δ. The transcript must be
+// in the state the opening started from, i.e. with the claimed evaluation already appended.
+pub fn hyper_kzg_challenges<T: Transcript>(
+    opening: &HyperUniKZGOpening<Bn256>,
+    transcript: &mut T,
+) -> NativeHyperKzgChallenges {
+    for c in &opening.folded_oracle_commitments {
+        transcript.append_u8_slice(c.to_bytes().as_ref());
+    }
+    let beta = transcript.generate_field_element::<Fr>();
+    let evals = &opening.evals_at_x;
+    for e in evals
+        .beta2_evals
+        .iter()
+        .chain(&evals.pos_beta_evals)
+        .chain(&evals.neg_beta_evals)
+    {
+        transcript.append_field_element(e);
+    }
+    let gamma = transcript.generate_field_element::<Fr>();
+    transcript.append_u8_slice(opening.beta_x_commitment.to_bytes().as_ref());
+    let delta = transcript.generate_field_element::<Fr>();
+    NativeHyperKzgChallenges {
+        beta: fr_to_bigint(&beta),
+        gamma: fr_to_bigint(&gamma),
+        delta: fr_to_bigint(&delta),
+    }
+}
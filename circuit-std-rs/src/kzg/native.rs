@@ -0,0 +1,385 @@
+use crate::ecdsa::native::{modulo, NativeCurve, NativePoint};
+use crate::gnark::emparam::{BN254Fp, BN254Fr, FieldParams};
+use num_bigint::BigInt;
+use num_traits::Zero;
+
+// Reference univariate KZG over BN254 on plain integers, used to produce test vectors
+
+// a0 + a1·u in Fp2 = Fp[u]/(u² + 1)
+pub type Fp2 = [BigInt; 2];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NativeG2 {
+    pub x: Fp2,
+    pub y: Fp2,
+}
+
+fn fp2_add(a: &Fp2, b: &Fp2) -> Fp2 {
+    let p = BN254Fp::modulus();
+    [modulo(&(&a[0] + &b[0]), &p), modulo(&(&a[1] + &b[1]), &p)]
+}
+
+fn fp2_sub(a: &Fp2, b: &Fp2) -> Fp2 {
+    let p = BN254Fp::modulus();
+    [modulo(&(&a[0] - &b[0]), &p), modulo(&(&a[1] - &b[1]), &p)]
+}
+
+fn fp2_mul(a: &Fp2, b: &Fp2) -> Fp2 {
+    let p = BN254Fp::modulus();
+    [
+        modulo(&(&a[0] * &b[0] - &a[1] * &b[1]), &p),
+        modulo(&(&a[0] * &b[1] + &a[1] * &b[0]), &p),
+    ]
+}
+
+fn fp2_inverse(a: &Fp2) -> Fp2 {
+    let p = BN254Fp::modulus();
+    let norm = (&a[0] * &a[0] + &a[1] * &a[1]) % &p;
+    let norm_inv = norm.modinv(&p).unwrap();
+    [
+        modulo(&(&a[0] * &norm_inv), &p),
+        modulo(&(-&a[1] * &norm_inv), &p),
+    ]
+}
+
+// Affine addition on the twist, None is the point at infinity
+pub fn g2_add(a: &Option<NativeG2>, b: &Option<NativeG2>) -> Option<NativeG2> {
+    let (a, b) = match (a, b) {
+        (None, _) => return b.clone(),
+        (_, None) => return a.clone(),
+        (Some(a), Some(b)) => (a, b),
+    };
+    let lambda = if a.x == b.x {
+        if fp2_add(&a.y, &b.y).iter().all(|c| c.is_zero()) {
+            return None;
+        }
+        let x_squared = fp2_mul(&a.x, &a.x);
+        let num = fp2_add(&fp2_add(&x_squared, &x_squared), &x_squared);
+        let den = fp2_add(&a.y, &a.y);
+        fp2_mul(&num, &fp2_inverse(&den))
+    } else {
+        fp2_mul(&fp2_sub(&b.y, &a.y), &fp2_inverse(&fp2_sub(&b.x, &a.x)))
+    };
+    let x = fp2_sub(&fp2_sub(&fp2_mul(&lambda, &lambda), &a.x), &b.x);
+    let y = fp2_sub(&fp2_mul(&lambda, &fp2_sub(&a.x, &x)), &a.y);
+    Some(NativeG2 { x, y })
+}
+
+pub fn g2_mul(k: &BigInt, q: &Option<NativeG2>) -> Option<NativeG2> {
+    let k = modulo(k, &BN254Fr::modulus());
+    let mut acc = None;
+    for i in (0..k.bits()).rev() {
+        acc = g2_add(&acc, &acc);
+        if k.bit(i) {
+            acc = g2_add(&acc, q);
+        }
+    }
+    acc
+}
+
+pub fn g2_generator() -> NativeG2 {
+    let dec = |s: &str| BigInt::parse_bytes(s.as_bytes(), 10).unwrap();
+    NativeG2 {
+        x: [
+            dec("10857046999023057135944570762232829481370756359578518086990519993285655852781"),
+            dec("11559732032986387107991004021392285783925812861821192530917403151452391805634"),
+        ],
+        y: [
+            dec("8495653923123431417604973247489272438418190587263600148770280649306958101930"),
+            dec("4082367875863433681332203403145435568316851327593401208105741076214120093531"),
+        ],
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct VerifyingKey {
+    pub g2: NativeG2,
+    pub tau_g2: NativeG2,
+}
+
+#[derive(Clone, Debug)]
+pub struct Srs {
+    // [τ^i]G1 for i up to the degree
+    pub powers: Vec<NativePoint>,
+    pub vk: VerifyingKey,
+}
+
+// A setup with a known trapdoor, only fit for tests
+pub fn setup(tau: &BigInt, degree: usize) -> Srs {
+    let curve = NativeCurve::bn254();
+    let n = BN254Fr::modulus();
+    let mut powers = vec![];
+    let mut tau_i = BigInt::from(1);
+    for _ in 0..=degree {
+        powers.push(curve.mul(&tau_i, &Some(curve.generator())).unwrap());
+        tau_i = tau_i * tau % &n;
+    }
+    let g2 = g2_generator();
+    let tau_g2 = g2_mul(tau, &Some(g2.clone())).unwrap();
+    Srs {
+        powers,
+        vk: VerifyingKey { g2, tau_g2 },
+    }
+}
+
+// Σ coeffs[i]·[τ^i]G1, the coefficients from the lowest degree up
+pub fn commit(srs: &Srs, coeffs: &[BigInt]) -> Option<NativePoint> {
+    if coeffs.len() > srs.powers.len() {
+        panic!("polynomial degree exceeds the setup");
+    }
+    let curve = NativeCurve::bn254();
+    coeffs
+        .iter()
+        .zip(&srs.powers)
+        .fold(None, |acc, (c, power)| {
+            curve.add(&acc, &curve.mul(c, &Some(power.clone())))
+        })
+}
+
+// The evaluation at z and the commitment to (f(X) - f(z))/(X - z)
+pub fn open(srs: &Srs, coeffs: &[BigInt], z: &BigInt) -> (BigInt, Option<NativePoint>) {
+    let n = BN254Fr::modulus();
+    // synthetic division by X - z, the remainder is f(z)
+    let mut quotient = vec![BigInt::zero(); coeffs.len().saturating_sub(1)];
+    let mut acc = BigInt::zero();
+    for i in (0..coeffs.len()).rev() {
+        acc = modulo(&(acc * z + &coeffs[i]), &n);
+        if i > 0 {
+            quotient[i - 1] = acc.clone();
+        }
+    }
+    (acc, commit(srs, &quotient))
+}
+
+// e(C - [y]G1 + [z]π, G2) == e(π, [τ]G2), checked with the trapdoor: C - [y]G1 == [τ - z]π
+pub fn verify_with_trapdoor(
+    srs: &Srs,
+    tau: &BigInt,
+    commitment: &Option<NativePoint>,
+    z: &BigInt,
+    y: &BigInt,
+    proof: &Option<NativePoint>,
+) -> bool {
+    let curve = NativeCurve::bn254();
+    let y_g = curve.mul(y, &Some(srs.powers[0].clone()));
+    let lhs = curve.add(commitment, &curve.neg(&y_g));
+    lhs == curve.mul(&(tau - z), proof)
+}
+
+// A HyperKZG opening of a multilinear polynomial, whose evaluations over the hypercube are the
+// coefficients of the committed univariate polynomial f_0. f_{i+1} folds f_i with the i-th
+// coordinate of the point, and f_0, ..., f_{n-1} are all opened at β, -β and β²: their
+// combination with powers of γ is reduced to a single univariate opening at δ.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NativeHyperKzgOpening {
+    // commitments to f_1, ..., f_{n-1}
+    pub folded_oracle_commitments: Vec<NativePoint>,
+    pub beta2_evals: Vec<BigInt>,
+    pub pos_beta_evals: Vec<BigInt>,
+    pub neg_beta_evals: Vec<BigInt>,
+    // commitment to the quotient of the combination by (X - β)(X + β)(X - β²)
+    pub beta_x_commitment: NativePoint,
+    // the univariate opening proof at δ
+    pub quotient_delta_x_commitment: NativePoint,
+}
+
+// The Fiat-Shamir challenges of a HyperKZG opening, in the order they are drawn
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NativeHyperKzgChallenges {
+    pub beta: BigInt,
+    pub gamma: BigInt,
+    pub delta: BigInt,
+}
+
+pub fn fr_inverse(a: &BigInt) -> BigInt {
+    let n = BN254Fr::modulus();
+    modulo(a, &n).modinv(&n).unwrap()
+}
+
+fn evaluate(coeffs: &[BigInt], x: &BigInt) -> BigInt {
+    let n = BN254Fr::modulus();
+    coeffs
+        .iter()
+        .rev()
+        .fold(BigInt::zero(), |acc, c| modulo(&(acc * x + c), &n))
+}
+
+// Exact division by X - root
+fn divide_by_root(coeffs: &[BigInt], root: &BigInt) -> Vec<BigInt> {
+    let n = BN254Fr::modulus();
+    let mut quotient = vec![BigInt::zero(); coeffs.len().saturating_sub(1)];
+    let mut acc = BigInt::zero();
+    for i in (1..coeffs.len()).rev() {
+        acc = modulo(&(acc * root + &coeffs[i]), &n);
+        quotient[i - 1] = acc.clone();
+    }
+    quotient
+}
+
+// The degree 2 polynomial through (β, pos), (-β, neg) and (β², beta2), evaluated at x
+pub fn degree2_lagrange_at(
+    beta: &BigInt,
+    pos: &BigInt,
+    neg: &BigInt,
+    beta2: &BigInt,
+    x: &BigInt,
+) -> BigInt {
+    let n = BN254Fr::modulus();
+    let roots = [beta.clone(), modulo(&-beta, &n), beta * beta % &n];
+    let values = [pos, neg, beta2];
+    (0..3).fold(BigInt::zero(), |acc, j| {
+        let (num, den) = (0..3)
+            .filter(|k| *k != j)
+            .fold((BigInt::from(1), BigInt::from(1)), |(num, den), k| {
+                (num * (x - &roots[k]), den * (&roots[j] - &roots[k]))
+            });
+        modulo(&(acc + values[j] * num * fr_inverse(&den)), &n)
+    })
+}
+
+// Opens the multilinear polynomial with the given evaluations over the hypercube at point, with
+// the first coordinate folding adjacent evaluations. Returns the evaluation and the opening.
+pub fn hyper_open(
+    srs: &Srs,
+    evals: &[BigInt],
+    point: &[BigInt],
+    challenges: &NativeHyperKzgChallenges,
+) -> (BigInt, NativeHyperKzgOpening) {
+    assert!(!point.is_empty());
+    assert_eq!(evals.len(), 1 << point.len());
+    let n = BN254Fr::modulus();
+    let fold = |f: &[BigInt], alpha: &BigInt| {
+        f.chunks(2)
+            .map(|c| modulo(&(&c[0] + alpha * (&c[1] - &c[0])), &n))
+            .collect::<Vec<_>>()
+    };
+    let mut oracles = vec![evals.to_vec()];
+    for alpha in &point[..point.len() - 1] {
+        let folded = fold(oracles.last().unwrap(), alpha);
+        oracles.push(folded);
+    }
+    let value = fold(oracles.last().unwrap(), point.last().unwrap()).remove(0);
+    let folded_oracle_commitments = oracles[1..]
+        .iter()
+        .map(|f| commit(srs, f).expect("folded oracle commits to zero"))
+        .collect();
+
+    let NativeHyperKzgChallenges { beta, gamma, delta } = challenges;
+    let beta2 = beta * beta % &n;
+    let evals_at = |x: &BigInt| oracles.iter().map(|f| evaluate(f, x)).collect::<Vec<_>>();
+    let beta2_evals = evals_at(&beta2);
+    let pos_beta_evals = evals_at(beta);
+    let neg_beta_evals = evals_at(&modulo(&-beta, &n));
+
+    // f_γ = Σ γ^i·f_i and its degree 2 interpolation L at β, -β and β²
+    let mut f_gamma = vec![BigInt::zero(); evals.len()];
+    let mut gamma_i = BigInt::from(1);
+    for f in &oracles {
+        for (acc, c) in f_gamma.iter_mut().zip(f) {
+            *acc = modulo(&(&*acc + &gamma_i * c), &n);
+        }
+        gamma_i = gamma_i * gamma % &n;
+    }
+    let combine = |evals: &[BigInt]| evaluate(evals, gamma);
+    let (pos, neg, at_beta2) = (
+        combine(&pos_beta_evals),
+        combine(&neg_beta_evals),
+        combine(&beta2_evals),
+    );
+    let lagrange = |x: &BigInt| degree2_lagrange_at(beta, &pos, &neg, &at_beta2, x);
+    // L is determined by its values at 0, 1 and -1
+    let (l0, l1, l_neg1) = (
+        lagrange(&BigInt::zero()),
+        lagrange(&BigInt::from(1)),
+        lagrange(&BigInt::from(-1)),
+    );
+    let half = fr_inverse(&BigInt::from(2));
+    let lagrange_coeffs = [
+        l0.clone(),
+        modulo(&((&l1 - &l_neg1) * &half), &n),
+        modulo(&((&l1 + &l_neg1 - 2 * &l0) * &half), &n),
+    ];
+    let mut numerator = f_gamma.clone();
+    for (acc, c) in numerator.iter_mut().zip(&lagrange_coeffs) {
+        *acc = modulo(&(&*acc - c), &n);
+    }
+    let quotient = [beta.clone(), modulo(&-beta, &n), beta2.clone()]
+        .iter()
+        .fold(numerator, |q, root| divide_by_root(&q, root));
+    let beta_x_commitment = commit(srs, &quotient).expect("quotient commits to zero");
+
+    // f_γ - Z(δ)·q - L(δ) vanishes at δ, with Z the vanishing polynomial of β, -β and β²
+    let vanishing = (delta - beta) * (delta + beta) * (delta - &beta2) % &n;
+    let mut at_delta = f_gamma;
+    for (acc, c) in at_delta.iter_mut().zip(&quotient) {
+        *acc = modulo(&(&*acc - &vanishing * c), &n);
+    }
+    at_delta[0] = modulo(&(&at_delta[0] - lagrange(delta)), &n);
+    let quotient_delta_x_commitment =
+        commit(srs, &divide_by_root(&at_delta, delta)).expect("opening proof commits to zero");
+
+    (
+        value,
+        NativeHyperKzgOpening {
+            folded_oracle_commitments,
+            beta2_evals,
+            pos_beta_evals,
+            neg_beta_evals,
+            beta_x_commitment,
+            quotient_delta_x_commitment,
+        },
+    )
+}
+
+// Checks the folding of a HyperKZG opening and reduces it to the univariate opening
+// (commitment, δ, value) it proves with quotient_delta_x_commitment. None if the folded
+// evaluations are inconsistent with point and value.
+pub fn hyper_reduce(
+    commitment: &NativePoint,
+    point: &[BigInt],
+    value: &BigInt,
+    opening: &NativeHyperKzgOpening,
+    challenges: &NativeHyperKzgChallenges,
+) -> Option<(Option<NativePoint>, BigInt, BigInt)> {
+    let num_vars = point.len();
+    assert_eq!(opening.folded_oracle_commitments.len() + 1, num_vars);
+    assert_eq!(opening.beta2_evals.len(), num_vars);
+    assert_eq!(opening.pos_beta_evals.len(), num_vars);
+    assert_eq!(opening.neg_beta_evals.len(), num_vars);
+    let n = BN254Fr::modulus();
+    let NativeHyperKzgChallenges { beta, gamma, delta } = challenges;
+    let beta2 = beta * beta % &n;
+
+    // f_{i+1}(β²) from the even and odd parts of f_i
+    let half = fr_inverse(&BigInt::from(2));
+    let half_beta_inv = fr_inverse(&(2 * beta));
+    for i in 0..num_vars {
+        let (pos, neg) = (&opening.pos_beta_evals[i], &opening.neg_beta_evals[i]);
+        let even = (pos + neg) * &half;
+        let odd = (pos - neg) * &half_beta_inv;
+        let folded = modulo(&(&even + &point[i] * (odd - &even)), &n);
+        let expected = opening.beta2_evals.get(i + 1).unwrap_or(value);
+        if folded != modulo(expected, &n) {
+            return None;
+        }
+    }
+
+    let combine = |evals: &[BigInt]| evaluate(evals, gamma);
+    let lagrange = degree2_lagrange_at(
+        beta,
+        &combine(&opening.pos_beta_evals),
+        &combine(&opening.neg_beta_evals),
+        &combine(&opening.beta2_evals),
+        delta,
+    );
+    let vanishing = (delta - beta) * (delta + beta) * (delta - &beta2) % &n;
+    let curve = NativeCurve::bn254();
+    let mut acc = Some(commitment.clone());
+    let mut gamma_i = BigInt::from(1);
+    for c in &opening.folded_oracle_commitments {
+        gamma_i = gamma_i * gamma % &n;
+        acc = curve.add(&acc, &curve.mul(&gamma_i, &Some(c.clone())));
+    }
+    let shift = curve.mul(&-vanishing, &Some(opening.beta_x_commitment.clone()));
+    Some((curve.add(&acc, &shift), delta.clone(), lagrange))
+}
@@ -12,9 +12,18 @@ pub mod non_native;
 pub use non_native::*;
 pub mod matmul;
 
+pub mod bls;
+pub mod ecdsa;
+pub mod ed25519;
+pub use ed25519::{Ed25519Circuit, Ed25519Params};
 pub mod gnark;
+pub mod keccak;
+pub mod kzg;
+pub mod merkle;
+pub mod nn;
 pub mod poseidon;
 pub mod sha256;
+pub mod sha512;
 pub mod utils;
 
 #[cfg(test)]
@@ -0,0 +1,15 @@
+// Merkle tree membership, multi-proof and update gadgets, generic over the hash, with a native
+// tree that produces the assignments. Leaves are hashed as H(0x00 || leaf) and inner nodes as
+// H(0x01 || left || right), so a leaf can never pass for a node.
+pub mod hasher;
+pub use hasher::{KeccakHasher, MerkleHasher, PoseidonM31Hasher, Sha256Hasher};
+pub mod native;
+pub use native::{
+    MerklePath, MerkleTree, NativeKeccakHasher, NativeMerkleHasher, NativePoseidonM31Hasher,
+    NativeSha256Hasher,
+};
+pub mod path;
+pub use path::{compute_root, update_leaf, verify_multi_proof, verify_path};
+
+pub const LEAF_TAG: u8 = 0x00;
+pub const NODE_TAG: u8 = 0x01;
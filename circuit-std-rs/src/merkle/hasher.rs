@@ -0,0 +1,107 @@
+use expander_compiler::frontend::{Config, M31Config, RootAPI, Variable};
+
+use crate::keccak::keccak256;
+use crate::poseidon::poseidon_m31::PoseidonM31Params;
+use crate::poseidon::utils::{
+    POSEIDON_M31X16_FULL_ROUNDS, POSEIDON_M31X16_PARTIAL_ROUNDS, POSEIDON_M31X16_RATE,
+};
+use crate::sha256::Sha256;
+
+// A hash the tree gadgets can use. Digests are digest_len variables and can be hashed again as
+// data, the tag is the domain separation byte in front of the data.
+pub trait MerkleHasher<C: Config> {
+    fn digest_len(&self) -> usize;
+
+    fn hash<B: RootAPI<C>>(&mut self, api: &mut B, tag: u8, data: &[Variable]) -> Vec<Variable>;
+
+    // Checks deferred by the hashes, called once after the last one
+    fn final_check<B: RootAPI<C>>(&mut self, _api: &mut B) {}
+}
+
+// Poseidon over M31 with width 16. Data and digests are field elements, the tag is one more
+// element in front, the digest is the first 8 elements of the state.
+pub struct PoseidonM31Hasher {
+    params: PoseidonM31Params,
+}
+
+impl PoseidonM31Hasher {
+    pub fn new<C: Config, B: RootAPI<C>>(api: &mut B) -> Self {
+        let params = PoseidonM31Params::new(
+            api,
+            POSEIDON_M31X16_RATE,
+            16,
+            POSEIDON_M31X16_FULL_ROUNDS,
+            POSEIDON_M31X16_PARTIAL_ROUNDS,
+        );
+        Self { params }
+    }
+}
+
+impl MerkleHasher<M31Config> for PoseidonM31Hasher {
+    fn digest_len(&self) -> usize {
+        self.params.rate
+    }
+
+    fn hash<B: RootAPI<M31Config>>(
+        &mut self,
+        api: &mut B,
+        tag: u8,
+        data: &[Variable],
+    ) -> Vec<Variable> {
+        let mut inputs = vec![api.constant(tag as u32)];
+        inputs.extend_from_slice(data);
+        let state = self.params.hash_to_state(api, &inputs);
+        state[..self.params.rate].to_vec()
+    }
+}
+
+// SHA-256 on bits, most significant bit of each byte first. One Sha256 serves every hash, so
+// over large fields they share its lookup table.
+pub struct Sha256Hasher {
+    sha256: Sha256,
+}
+
+impl Sha256Hasher {
+    pub fn new<C: Config, B: RootAPI<C>>(api: &mut B) -> Self {
+        Self {
+            sha256: Sha256::new(api),
+        }
+    }
+}
+
+impl<C: Config> MerkleHasher<C> for Sha256Hasher {
+    fn digest_len(&self) -> usize {
+        256
+    }
+
+    fn hash<B: RootAPI<C>>(&mut self, api: &mut B, tag: u8, data: &[Variable]) -> Vec<Variable> {
+        let tag = (0..8)
+            .map(|i| api.constant(((tag >> (7 - i)) & 1) as u32))
+            .collect::<Vec<_>>();
+        self.sha256.update(api, &tag);
+        self.sha256.update(api, data);
+        self.sha256.finalize(api)
+    }
+
+    fn final_check<B: RootAPI<C>>(&mut self, api: &mut B) {
+        self.sha256.final_check(api);
+    }
+}
+
+// Keccak-256 on bits, least significant bit of each byte first
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KeccakHasher;
+
+impl<C: Config> MerkleHasher<C> for KeccakHasher {
+    fn digest_len(&self) -> usize {
+        256
+    }
+
+    fn hash<B: RootAPI<C>>(&mut self, api: &mut B, tag: u8, data: &[Variable]) -> Vec<Variable> {
+        let mut bits = (0..8)
+            .map(|i| api.constant(((tag >> i) & 1) as u32))
+            .collect::<Vec<_>>();
+        bits.extend_from_slice(data);
+        keccak256(api, &bits)
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::BTreeSet;
+
+use sha2::Digest;
+use tiny_keccak::{Hasher, Keccak};
+
+use super::{LEAF_TAG, NODE_TAG};
+use crate::poseidon::poseidon_u32::PoseidonParams;
+use crate::poseidon::utils::{
+    POSEIDON_M31X16_FULL_ROUNDS, POSEIDON_M31X16_PARTIAL_ROUNDS, POSEIDON_M31X16_RATE,
+};
+
+// The native counterpart of a MerkleHasher. Data and digests are the values of the circuit
+// variables: field elements below the modulus, or 0/1 for the bit hashes.
+pub trait NativeMerkleHasher {
+    fn hash(&self, tag: u8, data: &[u32]) -> Vec<u32>;
+}
+
+pub struct NativePoseidonM31Hasher {
+    params: PoseidonParams,
+}
+
+impl NativePoseidonM31Hasher {
+    pub fn new() -> Self {
+        Self {
+            params: PoseidonParams::new(
+                POSEIDON_M31X16_RATE,
+                16,
+                POSEIDON_M31X16_FULL_ROUNDS,
+                POSEIDON_M31X16_PARTIAL_ROUNDS,
+            ),
+        }
+    }
+}
+
+impl Default for NativePoseidonM31Hasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NativeMerkleHasher for NativePoseidonM31Hasher {
+    fn hash(&self, tag: u8, data: &[u32]) -> Vec<u32> {
+        let mut inputs = vec![tag as u32];
+        inputs.extend_from_slice(data);
+        self.params.hash(&inputs)
+    }
+}
+
+fn bits_to_bytes(bits: &[u32], msb_first: bool) -> Vec<u8> {
+    assert!(bits.len() % 8 == 0, "data must be a whole number of bytes");
+    bits.chunks(8)
+        .map(|byte| {
+            (0..8).fold(0u8, |acc, i| {
+                let shift = if msb_first { 7 - i } else { i };
+                acc | ((byte[i] as u8 & 1) << shift)
+            })
+        })
+        .collect()
+}
+
+fn bytes_to_bits(bytes: &[u8], msb_first: bool) -> Vec<u32> {
+    bytes
+        .iter()
+        .flat_map(|b| {
+            (0..8).map(move |i| {
+                let shift = if msb_first { 7 - i } else { i };
+                ((b >> shift) & 1) as u32
+            })
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NativeSha256Hasher;
+
+impl NativeMerkleHasher for NativeSha256Hasher {
+    fn hash(&self, tag: u8, data: &[u32]) -> Vec<u32> {
+        let mut hasher = sha2::Sha256::new();
+        hasher.update([tag]);
+        hasher.update(bits_to_bytes(data, true));
+        bytes_to_bits(&hasher.finalize(), true)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NativeKeccakHasher;
+
+impl NativeMerkleHasher for NativeKeccakHasher {
+    fn hash(&self, tag: u8, data: &[u32]) -> Vec<u32> {
+        let mut hasher = Keccak::v256();
+        hasher.update(&[tag]);
+        hasher.update(&bits_to_bytes(data, false));
+        let mut digest = [0u8; 32];
+        hasher.finalize(&mut digest);
+        bytes_to_bits(&digest, false)
+    }
+}
+
+// The witness of verify_path and update_leaf for one leaf
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath {
+    // the leaf index, least significant bit first
+    pub index_bits: Vec<u32>,
+    // from the leaf level up to the children of the root
+    pub siblings: Vec<Vec<u32>>,
+}
+
+// A complete tree of 2^depth leaves, keeping every level of digests. levels[0] holds the leaf
+// digests and levels[depth] the root.
+pub struct MerkleTree<H: NativeMerkleHasher> {
+    hasher: H,
+    depth: usize,
+    leaves: Vec<Vec<u32>>,
+    levels: Vec<Vec<Vec<u32>>>,
+}
+
+impl<H: NativeMerkleHasher> MerkleTree<H> {
+    pub fn new(hasher: H, depth: usize, leaves: Vec<Vec<u32>>) -> Self {
+        assert_eq!(leaves.len(), 1 << depth, "the tree needs 2^depth leaves");
+        let digests = leaves
+            .iter()
+            .map(|leaf| hasher.hash(LEAF_TAG, leaf))
+            .collect::<Vec<_>>();
+        let mut levels = vec![digests];
+        for level in 0..depth {
+            let next = levels[level]
+                .chunks(2)
+                .map(|pair| hasher.hash(NODE_TAG, &pair.concat()))
+                .collect();
+            levels.push(next);
+        }
+        Self {
+            hasher,
+            depth,
+            leaves,
+            levels,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn root(&self) -> Vec<u32> {
+        self.levels[self.depth][0].clone()
+    }
+
+    pub fn leaf(&self, index: usize) -> &[u32] {
+        &self.leaves[index]
+    }
+
+    pub fn path(&self, index: usize) -> MerklePath {
+        MerklePath {
+            index_bits: (0..self.depth).map(|i| ((index >> i) & 1) as u32).collect(),
+            siblings: (0..self.depth)
+                .map(|level| self.levels[level][(index >> level) ^ 1].clone())
+                .collect(),
+        }
+    }
+
+    // Replaces a leaf and recomputes the digests on its path
+    pub fn update(&mut self, index: usize, leaf: Vec<u32>) {
+        self.levels[0][index] = self.hasher.hash(LEAF_TAG, &leaf);
+        self.leaves[index] = leaf;
+        for level in 0..self.depth {
+            let i = (index >> level) & !1;
+            let pair = [
+                self.levels[level][i].as_slice(),
+                self.levels[level][i + 1].as_slice(),
+            ]
+            .concat();
+            self.levels[level + 1][i / 2] = self.hasher.hash(NODE_TAG, &pair);
+        }
+    }
+
+    // The nodes verify_multi_proof needs besides the leaves at indices: level by level from the
+    // leaves up, within a level by increasing index, every sibling that cannot be computed
+    pub fn multi_proof(&self, indices: &[usize]) -> Vec<Vec<u32>> {
+        let mut known = indices.iter().copied().collect::<BTreeSet<_>>();
+        assert_eq!(known.len(), indices.len(), "duplicate leaf index");
+        let mut proof = vec![];
+        for level in 0..self.depth {
+            for &index in &known {
+                if !known.contains(&(index ^ 1)) {
+                    proof.push(self.levels[level][index ^ 1].clone());
+                }
+            }
+            known = known.iter().map(|index| index / 2).collect();
+        }
+        proof
+    }
+}
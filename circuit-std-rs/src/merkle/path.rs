@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use expander_compiler::frontend::{Config, RootAPI, Variable};
+
+use super::{MerkleHasher, LEAF_TAG, NODE_TAG};
+
+fn hash_node<C: Config, B: RootAPI<C>, H: MerkleHasher<C>>(
+    api: &mut B,
+    hasher: &mut H,
+    left: &[Variable],
+    right: &[Variable],
+) -> Vec<Variable> {
+    hasher.hash(api, NODE_TAG, &[left, right].concat())
+}
+
+fn assert_digests_equal<C: Config, B: RootAPI<C>>(api: &mut B, a: &[Variable], b: &[Variable]) {
+    assert_eq!(a.len(), b.len());
+    a.iter()
+        .zip(b.iter())
+        .for_each(|(a, b)| api.assert_is_equal(*a, *b))
+}
+
+// The root of the tree holding leaf at the index given by index_bits, least significant bit
+// first, with the siblings from the leaf level up. The bits are asserted to be boolean.
+pub fn compute_root<C: Config, B: RootAPI<C>, H: MerkleHasher<C>>(
+    api: &mut B,
+    hasher: &mut H,
+    leaf: &[Variable],
+    index_bits: &[Variable],
+    siblings: &[Vec<Variable>],
+) -> Vec<Variable> {
+    assert_eq!(index_bits.len(), siblings.len());
+    let mut node = hasher.hash(api, LEAF_TAG, leaf);
+    for (bit, sibling) in index_bits.iter().zip(siblings) {
+        assert_eq!(sibling.len(), hasher.digest_len());
+        api.assert_is_bool(*bit);
+        // a set bit means the node is a right child
+        let mut left = Vec::with_capacity(node.len());
+        let mut right = Vec::with_capacity(node.len());
+        for (x, s) in node.iter().zip(sibling) {
+            left.push(api.select(*bit, *s, *x));
+            right.push(api.select(*bit, *x, *s));
+        }
+        node = hash_node(api, hasher, &left, &right);
+    }
+    node
+}
+
+pub fn verify_path<C: Config, B: RootAPI<C>, H: MerkleHasher<C>>(
+    api: &mut B,
+    hasher: &mut H,
+    root: &[Variable],
+    leaf: &[Variable],
+    index_bits: &[Variable],
+    siblings: &[Vec<Variable>],
+) {
+    let computed = compute_root(api, hasher, leaf, index_bits, siblings);
+    assert_digests_equal(api, &computed, root);
+}
+
+// Checks old_leaf is in the tree of old_root and returns the root once new_leaf replaces it.
+// Both roots come from the same siblings, so nothing else in the tree can change.
+pub fn update_leaf<C: Config, B: RootAPI<C>, H: MerkleHasher<C>>(
+    api: &mut B,
+    hasher: &mut H,
+    old_root: &[Variable],
+    old_leaf: &[Variable],
+    new_leaf: &[Variable],
+    index_bits: &[Variable],
+    siblings: &[Vec<Variable>],
+) -> Vec<Variable> {
+    verify_path(api, hasher, old_root, old_leaf, index_bits, siblings);
+    compute_root(api, hasher, new_leaf, index_bits, siblings)
+}
+
+// Checks the leaves at the given indices against root in one pass, hashing the nodes the paths
+// share once. The indices are fixed by the circuit, proof holds the other nodes in the order of
+// MerkleTree::multi_proof. Use verify_path for indices only known to the witness.
+pub fn verify_multi_proof<C: Config, B: RootAPI<C>, H: MerkleHasher<C>>(
+    api: &mut B,
+    hasher: &mut H,
+    root: &[Variable],
+    depth: usize,
+    indices: &[usize],
+    leaves: &[Vec<Variable>],
+    proof: &[Vec<Variable>],
+) {
+    assert_eq!(indices.len(), leaves.len());
+    let mut known = BTreeMap::new();
+    for (index, leaf) in indices.iter().zip(leaves) {
+        assert!(*index < 1 << depth, "leaf index out of range");
+        let digest = hasher.hash(api, LEAF_TAG, leaf);
+        assert!(
+            known.insert(*index, digest).is_none(),
+            "duplicate leaf index"
+        );
+    }
+    let mut proof = proof.iter();
+    for _ in 0..depth {
+        let mut next = BTreeMap::new();
+        let mut nodes = known.into_iter().peekable();
+        while let Some((index, node)) = nodes.next() {
+            let paired = index % 2 == 0 && nodes.peek().is_some_and(|(j, _)| *j == index + 1);
+            let sibling = if paired {
+                nodes.next().unwrap().1
+            } else {
+                proof.next().expect("multi-proof is too short").clone()
+            };
+            let parent = if index % 2 == 0 {
+                hash_node(api, hasher, &node, &sibling)
+            } else {
+                hash_node(api, hasher, &sibling, &node)
+            };
+            next.insert(index / 2, parent);
+        }
+        known = next;
+    }
+    assert!(proof.next().is_none(), "multi-proof is too long");
+    assert_digests_equal(api, &known[&0], root);
+}
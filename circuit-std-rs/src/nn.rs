@@ -0,0 +1,15 @@
+// Quantized neural network operators. Values are signed fixed-point integers: a value v of a
+// FixedPoint { bits, frac_bits } stands for v / 2^frac_bits with |v| < 2^(bits - 1), negative
+// values taking their field representative p - |v|. Every gadget has a reference in native and
+// the common layers come as zkcuda kernels.
+pub mod activation;
+pub use activation::{nn_activation_hint, Activation, ActivationTable};
+pub mod kernel;
+pub mod native;
+pub mod ops;
+pub use ops::{
+    argmax, avg_pool2d, conv2d, div_round, is_non_negative, layer_norm, max_pool2d, relu,
+    requantize, rescale, softmax,
+};
+pub mod tensor;
+pub use tensor::{FixedPoint, Tensor};
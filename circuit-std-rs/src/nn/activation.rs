@@ -0,0 +1,207 @@
+use expander_compiler::frontend::{CircuitField, Config, Error, Field, RootAPI, Variable};
+
+use super::native::{from_field, to_field};
+use super::tensor::{bit_len, Tensor};
+use crate::logup::LogUpSingleKeyTable;
+
+// Largest domain of a table, which holds a row for every input
+pub(crate) const MAX_DOMAIN_BITS: usize = 20;
+
+// Functions computed by lookup. Exp is only tabulated on non-positive inputs as in softmax,
+// Gelu uses the tanh approximation and Rsqrt is 0 outside of the positive inputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Activation {
+    Exp = 0,
+    Sigmoid = 1,
+    Gelu = 2,
+    Rsqrt = 3,
+}
+
+impl Activation {
+    pub fn from_id(id: u32) -> Option<Self> {
+        match id {
+            0 => Some(Activation::Exp),
+            1 => Some(Activation::Sigmoid),
+            2 => Some(Activation::Gelu),
+            3 => Some(Activation::Rsqrt),
+            _ => None,
+        }
+    }
+}
+
+// Fractional bits of the fixed-point arithmetic the tables are computed with, so that they are
+// the same on every platform
+const PRECISION: usize = 48;
+const ONE: i128 = 1 << PRECISION;
+// ln(2), sqrt(2 / π) and the cubic coefficient of the gelu approximation
+const LN_2: i128 = 195103586505167;
+const SQRT_2_OVER_PI: i128 = 224584538169779;
+const GELU_CUBIC: i128 = 12586153583617;
+// fractional bits of the extra precision of rsqrt, rounded away at the end
+const RSQRT_EXTRA_BITS: usize = 16;
+
+// e^-t for t >= 0: e^-r / 2^k with t = k·ln(2) + r, e^-r by its Taylor series
+fn exp_neg(t: i128) -> i128 {
+    let k = t / LN_2;
+    if k > PRECISION as i128 {
+        return 0;
+    }
+    let r = t - k * LN_2;
+    let mut term = ONE;
+    let mut sum = ONE;
+    for n in 1..=24 {
+        term = ((term * -r) >> PRECISION) / n;
+        sum += term;
+    }
+    sum >> k
+}
+
+// (1 - e^-2u) / (1 + e^-2u)
+fn tanh(u: i128) -> i128 {
+    if u < 0 {
+        return -tanh(-u);
+    }
+    let e = exp_neg(2 * u);
+    ((ONE - e) << PRECISION) / (ONE + e)
+}
+
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    // Newton's iteration from above
+    let mut x = 1u128 << (n.ilog2() / 2 + 1);
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+// The fixed-point value of func at x, both with frac_bits fractional bits, rounded to the nearest
+// with halves up
+pub fn activation_value(func: Activation, x: i64, frac_bits: usize) -> i64 {
+    assert!(frac_bits <= 24, "at most 24 fractional bits");
+    if func == Activation::Rsqrt {
+        // 2^f / sqrt(x / 2^f) = sqrt(2^(3f) / x)
+        if x <= 0 {
+            return 0;
+        }
+        let extra = RSQRT_EXTRA_BITS;
+        let s = isqrt((1u128 << (3 * frac_bits + 2 * extra)) / x as u128);
+        return ((s + (1 << (extra - 1))) >> extra) as i64;
+    }
+    let v = (x as i128) << (PRECISION - frac_bits);
+    let y = match func {
+        Activation::Exp => exp_neg(-v.min(0)),
+        Activation::Sigmoid => {
+            if v >= 0 {
+                (ONE << PRECISION) / (ONE + exp_neg(v))
+            } else {
+                let e = exp_neg(-v);
+                (e << PRECISION) / (ONE + e)
+            }
+        }
+        Activation::Gelu => {
+            // tanh is 1 to the precision beyond 8
+            let t = if v.abs() >= 8 * ONE {
+                v.signum() * ONE
+            } else {
+                let cube = (((v * v) >> PRECISION) * v) >> PRECISION;
+                tanh((SQRT_2_OVER_PI * (v + ((GELU_CUBIC * cube) >> PRECISION))) >> PRECISION)
+            };
+            (v * (ONE + t)) >> (PRECISION + 1)
+        }
+        Activation::Rsqrt => unreachable!(),
+    };
+    let d = 1i128 << (PRECISION - frac_bits);
+    (y + d / 2).div_euclid(d) as i64
+}
+
+// inputs: function id, domain bits, fractional bits, then the keys, one output per key
+pub fn nn_activation_hint<F: Field>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
+    if inputs.len() != outputs.len() + 3 {
+        return Err(Error::UserError("invalid inputs sizes".to_string()));
+    }
+    let func = Activation::from_id(inputs[0].to_u256().as_u32())
+        .ok_or_else(|| Error::UserError("unknown activation".to_string()))?;
+    let domain_bits = inputs[1].to_u256().as_usize();
+    let frac_bits = inputs[2].to_u256().as_usize();
+    for (key, output) in inputs[3..].iter().zip(outputs.iter_mut()) {
+        let x = from_field(*key - F::from(1u32 << (domain_bits - 1)));
+        *output = to_field(activation_value(func, x, frac_bits));
+    }
+    Ok(())
+}
+
+// A lookup table of func on the signed inputs of domain_bits bits. A row maps the key
+// x + 2^(domain_bits - 1) to func(x), so a query also checks its input is in the domain.
+pub struct ActivationTable {
+    pub func: Activation,
+    pub domain_bits: usize,
+    pub frac_bits: usize,
+    // signed bits of the largest output
+    pub value_bits: usize,
+    table: LogUpSingleKeyTable,
+}
+
+impl ActivationTable {
+    pub fn new<C: Config, B: RootAPI<C>>(
+        api: &mut B,
+        func: Activation,
+        domain_bits: usize,
+        frac_bits: usize,
+    ) -> Self {
+        assert!(
+            (1..=MAX_DOMAIN_BITS).contains(&domain_bits),
+            "the domain has at most {MAX_DOMAIN_BITS} bits"
+        );
+        let mut table = LogUpSingleKeyTable::new(domain_bits);
+        let half = 1i64 << (domain_bits - 1);
+        let mut max_abs = 0;
+        for key in 0..1 << domain_bits {
+            let value = activation_value(func, key - half, frac_bits);
+            max_abs = max_abs.max(value.unsigned_abs());
+            let key = api.constant(key as u32);
+            let value = api.constant(to_field::<CircuitField<C>>(value));
+            table.add_table_row(key, vec![value]);
+        }
+        Self {
+            func,
+            domain_bits,
+            frac_bits,
+            value_bits: bit_len(max_abs as u128) + 1,
+            table,
+        }
+    }
+
+    pub fn apply<C: Config, B: RootAPI<C>>(&mut self, api: &mut B, x: Variable) -> Variable {
+        let key = api.add(x, 1u32 << (self.domain_bits - 1));
+        let inputs = [
+            api.constant(self.func as u32),
+            api.constant(self.domain_bits as u32),
+            api.constant(self.frac_bits as u32),
+            key,
+        ];
+        let y = api.new_hint("myhint.nnactivationhint", &inputs, 1)[0];
+        self.table.query(key, vec![y]);
+        y
+    }
+
+    pub fn apply_tensor<C: Config, B: RootAPI<C>>(
+        &mut self,
+        api: &mut B,
+        x: &Tensor<Variable>,
+    ) -> Tensor<Variable> {
+        x.map(|v| self.apply(api, v))
+    }
+
+    // Checks every query against the table, called once after the last one
+    pub fn final_check<C: Config, B: RootAPI<C>>(&mut self, api: &mut B) {
+        if !self.table.query_keys.is_empty() {
+            self.table.final_check(api);
+        }
+    }
+}
@@ -0,0 +1,257 @@
+// zkcuda kernels of the common layers, compiled for the shapes and quantization given at runtime
+// like the builders of zkcuda::tensor_kernels. Shapes are those of one instance, images are
+// [C, H, W], and every builder returns a KernelPrimitive whose arguments are the inputs followed
+// by the output.
+use expander_compiler::frontend::*;
+use expander_compiler::zkcuda::kernel::{compile_with_spec_and_shapes, IOVecSpec, KernelPrimitive};
+use expander_compiler::zkcuda::shape::shape_vec_len;
+
+use super::{
+    activation::{Activation, ActivationTable, MAX_DOMAIN_BITS},
+    ops,
+    tensor::{conv_taps, pool_windows},
+    FixedPoint, Tensor,
+};
+
+fn check_shape(shape: &[usize]) -> Result<(), Error> {
+    if shape.is_empty() || shape.contains(&0) {
+        return Err(Error::UserError(format!("empty shape {shape:?}")));
+    }
+    Ok(())
+}
+
+// The quantization must fit the comparisons of the gadgets and the tables of its activations
+fn check_fixed_point(q: FixedPoint, table_bits: usize) -> Result<(), Error> {
+    if q.bits < 2 || q.frac_bits >= q.bits || table_bits > MAX_DOMAIN_BITS {
+        return Err(Error::UserError(format!(
+            "unsupported fixed point {q:?} with tables of {table_bits} bits"
+        )));
+    }
+    Ok(())
+}
+
+fn check_image(shape: &[usize]) -> Result<(), Error> {
+    check_shape(shape)?;
+    if shape.len() != 3 {
+        return Err(Error::UserError(format!(
+            "expected an image of [C, H, W], got {shape:?}"
+        )));
+    }
+    Ok(())
+}
+
+fn check_window(shape: &[usize], kernel: usize, stride: usize) -> Result<(), Error> {
+    if kernel == 0 || stride == 0 || kernel > shape[1] || kernel > shape[2] {
+        return Err(Error::UserError(format!(
+            "invalid window of {kernel} with stride {stride} over {shape:?}"
+        )));
+    }
+    Ok(())
+}
+
+// Compiles f from the inputs to the output, with the given shapes
+fn compile<C, F>(
+    input_shapes: &[Vec<usize>],
+    output_shape: Vec<usize>,
+    f: F,
+) -> Result<KernelPrimitive<C>, Error>
+where
+    C: Config,
+    F: Fn(&mut API<C>, &[Vec<Variable>]) -> Vec<Variable>,
+{
+    let n = input_shapes.len();
+    let mut specs = input_shapes
+        .iter()
+        .map(|shape| IOVecSpec {
+            len: shape_vec_len(shape),
+            is_input: true,
+            is_output: false,
+        })
+        .collect::<Vec<_>>();
+    specs.push(IOVecSpec {
+        len: shape_vec_len(&output_shape),
+        is_input: false,
+        is_output: true,
+    });
+    let mut shapes = input_shapes.to_vec();
+    shapes.push(output_shape);
+    compile_with_spec_and_shapes(
+        |api: &mut API<C>, io: &mut Vec<Vec<Variable>>| {
+            let output = f(api, &io[..n]);
+            io[n].copy_from_slice(&output);
+        },
+        &specs,
+        &shapes,
+    )
+}
+
+pub fn relu_kernel<C: Config>(q: FixedPoint, shape: &[usize]) -> Result<KernelPrimitive<C>, Error> {
+    check_shape(shape)?;
+    check_fixed_point(q, 0)?;
+    compile(&[shape.to_vec()], shape.to_vec(), |api, io| {
+        ops::relu(api, q, &Tensor::new(shape.to_vec(), io[0].clone())).data
+    })
+}
+
+// Requantizes products of two values of q, with 2 * frac_bits fractional bits and 2 * bits - 1
+// bits, back to q
+pub fn requantize_kernel<C: Config>(
+    q: FixedPoint,
+    shape: &[usize],
+) -> Result<KernelPrimitive<C>, Error> {
+    check_shape(shape)?;
+    check_fixed_point(q, 0)?;
+    compile(&[shape.to_vec()], shape.to_vec(), |api, io| {
+        io[0]
+            .iter()
+            .map(|x| ops::requantize(api, q, *x, 2 * q.bits - 1, q.frac_bits))
+            .collect()
+    })
+}
+
+// func applied elementwise by lookup, on a table of the inputs of q
+pub fn activation_kernel<C: Config>(
+    q: FixedPoint,
+    func: Activation,
+    shape: &[usize],
+) -> Result<KernelPrimitive<C>, Error> {
+    check_shape(shape)?;
+    check_fixed_point(q, q.bits)?;
+    compile(&[shape.to_vec()], shape.to_vec(), |api, io| {
+        let mut table = ActivationTable::new(api, func, q.bits, q.frac_bits);
+        let y = io[0].iter().map(|x| table.apply(api, *x)).collect();
+        table.final_check(api);
+        y
+    })
+}
+
+pub fn gelu_kernel<C: Config>(q: FixedPoint, shape: &[usize]) -> Result<KernelPrimitive<C>, Error> {
+    activation_kernel(q, Activation::Gelu, shape)
+}
+
+pub fn sigmoid_kernel<C: Config>(
+    q: FixedPoint,
+    shape: &[usize],
+) -> Result<KernelPrimitive<C>, Error> {
+    activation_kernel(q, Activation::Sigmoid, shape)
+}
+
+pub fn max_pool_kernel<C: Config>(
+    q: FixedPoint,
+    shape: &[usize],
+    kernel: usize,
+    stride: usize,
+) -> Result<KernelPrimitive<C>, Error> {
+    check_image(shape)?;
+    check_window(shape, kernel, stride)?;
+    check_fixed_point(q, 0)?;
+    let (output_shape, _) = pool_windows(shape, kernel, stride);
+    compile(&[shape.to_vec()], output_shape, |api, io| {
+        let x = Tensor::new(shape.to_vec(), io[0].clone());
+        ops::max_pool2d(api, q, &x, kernel, stride).data
+    })
+}
+
+pub fn avg_pool_kernel<C: Config>(
+    q: FixedPoint,
+    shape: &[usize],
+    kernel: usize,
+    stride: usize,
+) -> Result<KernelPrimitive<C>, Error> {
+    check_image(shape)?;
+    check_window(shape, kernel, stride)?;
+    check_fixed_point(q, 0)?;
+    let (output_shape, _) = pool_windows(shape, kernel, stride);
+    compile(&[shape.to_vec()], output_shape, |api, io| {
+        let x = Tensor::new(shape.to_vec(), io[0].clone());
+        ops::avg_pool2d(api, q, &x, kernel, stride).data
+    })
+}
+
+// A convolution of an input of [C_in, H, W] by weights of [C_out, C_in, K, K], with a bias of
+// [C_out] and zero padding
+pub fn conv2d_kernel<C: Config>(
+    q: FixedPoint,
+    input_shape: &[usize],
+    weight_shape: &[usize],
+    stride: usize,
+    padding: usize,
+) -> Result<KernelPrimitive<C>, Error> {
+    check_image(input_shape)?;
+    check_shape(weight_shape)?;
+    check_fixed_point(q, 0)?;
+    let (h, w) = (input_shape[1] + 2 * padding, input_shape[2] + 2 * padding);
+    match weight_shape {
+        [_, c_in, k, k2] if *c_in == input_shape[0] && k == k2 && *k <= h && *k <= w => {}
+        _ => {
+            return Err(Error::UserError(format!(
+                "weights of {weight_shape:?} do not match an input of {input_shape:?}"
+            )))
+        }
+    }
+    if stride == 0 {
+        return Err(Error::UserError("zero stride".to_string()));
+    }
+    let (output_shape, _) = conv_taps(input_shape, weight_shape, stride, padding);
+    let bias_shape = vec![weight_shape[0]];
+    compile(
+        &[input_shape.to_vec(), weight_shape.to_vec(), bias_shape],
+        output_shape,
+        |api, io| {
+            let x = Tensor::new(input_shape.to_vec(), io[0].clone());
+            let weight = Tensor::new(weight_shape.to_vec(), io[1].clone());
+            ops::conv2d(api, q, &x, &weight, &io[2], stride, padding).data
+        },
+    )
+}
+
+// The index of the first maximum of len values, into a scalar
+pub fn argmax_kernel<C: Config>(q: FixedPoint, len: usize) -> Result<KernelPrimitive<C>, Error> {
+    check_shape(&[len])?;
+    check_fixed_point(q, 0)?;
+    compile(&[vec![len]], vec![], |api, io| {
+        vec![ops::argmax(api, q, &io[0])]
+    })
+}
+
+pub fn softmax_kernel<C: Config>(q: FixedPoint, len: usize) -> Result<KernelPrimitive<C>, Error> {
+    check_shape(&[len])?;
+    // the exp table covers the differences to the maximum
+    check_fixed_point(q, q.bits + 1)?;
+    compile(&[vec![len]], vec![len], |api, io| {
+        let mut exp = ActivationTable::new(api, Activation::Exp, q.bits + 1, q.frac_bits);
+        let y = ops::softmax(api, q, &io[0], &mut exp);
+        exp.final_check(api);
+        y
+    })
+}
+
+// Normalizes len values with gamma and beta, the variance plus eps looked up in an rsqrt table of
+// rsqrt_domain_bits bits
+pub fn layer_norm_kernel<C: Config>(
+    q: FixedPoint,
+    len: usize,
+    eps: u64,
+    rsqrt_domain_bits: usize,
+) -> Result<KernelPrimitive<C>, Error> {
+    check_shape(&[len])?;
+    check_fixed_point(q, rsqrt_domain_bits)?;
+    // the table covers the variance plus eps, which stays below 2^(2 * bits - 2 - frac_bits)
+    if rsqrt_domain_bits < 2 * q.bits - q.frac_bits {
+        return Err(Error::UserError(format!(
+            "an rsqrt table of {rsqrt_domain_bits} bits is too small for {q:?}"
+        )));
+    }
+    if eps == 0 || eps >= 1 << (2 * q.bits - 2 - q.frac_bits) {
+        return Err(Error::UserError(format!(
+            "eps {eps} out of range for {q:?}"
+        )));
+    }
+    compile(&[vec![len], vec![len], vec![len]], vec![len], |api, io| {
+        let mut rsqrt =
+            ActivationTable::new(api, Activation::Rsqrt, rsqrt_domain_bits, q.frac_bits);
+        let y = ops::layer_norm(api, q, &io[0], &io[1], &io[2], eps, &mut rsqrt);
+        rsqrt.final_check(api);
+        y
+    })
+}
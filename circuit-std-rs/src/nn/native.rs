@@ -0,0 +1,125 @@
+use ethnum::U256;
+use expander_compiler::frontend::FieldArith;
+
+use super::activation::{activation_value, Activation};
+use super::tensor::{conv_taps, pool_windows, FixedPoint, Tensor};
+
+// Reference implementation of the gadgets on plain integers, used to produce assignments and
+// expected outputs. The results are exactly those of the circuits.
+
+pub fn to_field<F: FieldArith>(v: i64) -> F {
+    let abs = F::from_u256(U256::from(v.unsigned_abs()));
+    if v < 0 {
+        -abs
+    } else {
+        abs
+    }
+}
+
+// The signed value of a field element, representatives above p / 2 being negative
+pub fn from_field<F: FieldArith>(x: F) -> i64 {
+    let v = x.to_u256();
+    if v > F::MODULUS / U256::from(2u32) {
+        -((F::MODULUS - v).as_u64() as i64)
+    } else {
+        v.as_u64() as i64
+    }
+}
+
+pub fn quantize(x: f64, q: FixedPoint) -> i64 {
+    let v = (x * (1u64 << q.frac_bits) as f64).round() as i64;
+    let bound = 1i64 << (q.bits - 1);
+    v.clamp(-bound, bound - 1)
+}
+
+pub fn dequantize(v: i64, q: FixedPoint) -> f64 {
+    v as f64 / (1u64 << q.frac_bits) as f64
+}
+
+// round(x / n), halves rounded up
+pub fn div_round(x: i64, n: i64) -> i64 {
+    (x + n / 2).div_euclid(n)
+}
+
+pub fn rescale(x: i64, shift: usize) -> i64 {
+    div_round(x, 1 << shift)
+}
+
+pub fn relu(x: &Tensor<i64>) -> Tensor<i64> {
+    x.map(|v| v.max(0))
+}
+
+pub fn max_pool2d(x: &Tensor<i64>, kernel: usize, stride: usize) -> Tensor<i64> {
+    let (shape, windows) = pool_windows(&x.shape, kernel, stride);
+    let data = windows
+        .iter()
+        .map(|w| w.iter().map(|i| x.data[*i]).max().unwrap())
+        .collect();
+    Tensor::new(shape, data)
+}
+
+pub fn avg_pool2d(x: &Tensor<i64>, kernel: usize, stride: usize) -> Tensor<i64> {
+    let (shape, windows) = pool_windows(&x.shape, kernel, stride);
+    let data = windows
+        .iter()
+        .map(|w| div_round(w.iter().map(|i| x.data[*i]).sum(), w.len() as i64))
+        .collect();
+    Tensor::new(shape, data)
+}
+
+pub fn conv2d(
+    q: FixedPoint,
+    x: &Tensor<i64>,
+    weight: &Tensor<i64>,
+    bias: &[i64],
+    stride: usize,
+    padding: usize,
+) -> Tensor<i64> {
+    let (shape, taps) = conv_taps(&x.shape, &weight.shape, stride, padding);
+    let per_channel = shape[1] * shape[2];
+    let data = taps
+        .iter()
+        .enumerate()
+        .map(|(i, products)| {
+            let acc = products.iter().fold(bias[i / per_channel], |acc, (a, b)| {
+                acc + x.data[*a] * weight.data[*b]
+            });
+            rescale(acc, q.frac_bits)
+        })
+        .collect();
+    Tensor::new(shape, data)
+}
+
+// The index of the first maximum
+pub fn argmax(x: &[i64]) -> usize {
+    (1..x.len()).fold(0, |best, i| if x[i] > x[best] { i } else { best })
+}
+
+pub fn activation(func: Activation, q: FixedPoint, x: &Tensor<i64>) -> Tensor<i64> {
+    x.map(|v| activation_value(func, v, q.frac_bits))
+}
+
+pub fn softmax(q: FixedPoint, x: &[i64]) -> Vec<i64> {
+    let m = *x.iter().max().unwrap();
+    let e = x
+        .iter()
+        .map(|v| activation_value(Activation::Exp, v - m, q.frac_bits))
+        .collect::<Vec<_>>();
+    let sum = e.iter().sum::<i64>();
+    e.iter()
+        .map(|v| (2 * (v << q.frac_bits) + sum).div_euclid(2 * sum))
+        .collect()
+}
+
+pub fn layer_norm(q: FixedPoint, x: &[i64], gamma: &[i64], beta: &[i64], eps: i64) -> Vec<i64> {
+    let n = x.len() as i64;
+    let mean = div_round(x.iter().sum(), n);
+    let centered = x.iter().map(|v| v - mean).collect::<Vec<_>>();
+    let var = div_round(centered.iter().map(|c| c * c).sum(), n << q.frac_bits);
+    let r = activation_value(Activation::Rsqrt, var + eps, q.frac_bits);
+    centered
+        .iter()
+        .zip(gamma.iter().zip(beta))
+        .map(|(c, (g, b))| rescale(rescale(c * r, q.frac_bits) * g, q.frac_bits) + b)
+        .collect()
+}
@@ -0,0 +1,337 @@
+use ethnum::U256;
+use expander_compiler::frontend::{CircuitField, Config, FieldArith, RootAPI, Variable};
+
+use super::activation::{Activation, ActivationTable};
+use super::tensor::{bit_len, conv_taps, pool_windows, FixedPoint, Tensor};
+
+// Largest number of bits of a range check, so that no sum of checked values wraps around
+fn max_range_bits<C: Config>() -> usize {
+    (256 - CircuitField::<C>::MODULUS.leading_zeros() as usize) - 1
+}
+
+fn constant<C: Config, B: RootAPI<C>>(api: &mut B, v: u128) -> Variable {
+    api.constant(CircuitField::<C>::from_u256(U256::from(v)))
+}
+
+// Asserts -2^(bits - 1) <= x < 2^(bits - 1)
+fn assert_signed_range<C: Config, B: RootAPI<C>>(api: &mut B, x: Variable, bits: usize) {
+    let offset = constant(api, 1 << (bits - 1));
+    let shifted = api.add(x, offset);
+    api.assert_range(shifted, bits);
+}
+
+// Asserts 0 <= r < n for a constant n
+fn assert_below<C: Config, B: RootAPI<C>>(api: &mut B, r: Variable, n: u128) {
+    let bits = bit_len(n - 1);
+    if bits == 0 {
+        api.assert_is_zero(r);
+        return;
+    }
+    api.assert_range(r, bits);
+    if !n.is_power_of_two() {
+        let top = constant(api, n - 1);
+        let rest = api.sub(top, r);
+        api.assert_range(rest, bits);
+    }
+}
+
+// round(x / n) with the halves rounded up, for a signed x of x_bits bits and a constant n.
+// The quotient and remainder are range checked, which also bounds x.
+pub fn div_round<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    x: Variable,
+    x_bits: usize,
+    n: u64,
+) -> Variable {
+    assert!(n > 0, "division by zero");
+    if n == 1 {
+        return x;
+    }
+    let n = n as u128;
+    // t = x + n / 2 + k * n is non-negative and floor(t / n) - k is the result
+    let half = 1u128 << (x_bits - 1);
+    let k = half.div_ceil(n);
+    let max_t = half - 1 + n / 2 + k * n;
+    let q_bits = bit_len(max_t / n);
+    assert!(
+        q_bits + bit_len(n) <= max_range_bits::<C>(),
+        "division too large for the field"
+    );
+    let offset = constant(api, n / 2 + k * n);
+    let t = api.add(x, offset);
+    let n_var = constant(api, n);
+    let q = api.unconstrained_int_div(t, n_var);
+    let qn = api.mul(q, n_var);
+    let r = api.sub(t, qn);
+    api.assert_range(q, q_bits);
+    assert_below(api, r, n);
+    let k = constant(api, k);
+    api.sub(q, k)
+}
+
+// round(num / den) for 0 <= num < 2^num_bits and 0 < den < 2^den_bits, both variables
+fn div_round_var<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    num: Variable,
+    num_bits: usize,
+    den: Variable,
+    den_bits: usize,
+) -> Variable {
+    assert!(
+        num_bits + den_bits + 3 <= max_range_bits::<C>(),
+        "division too large for the field"
+    );
+    // q = floor((2 * num + den) / (2 * den)), 0 <= r < 2 * den also ensures den > 0
+    let two_num = api.add(num, num);
+    let t = api.add(two_num, den);
+    let two_den = api.add(den, den);
+    let q = api.unconstrained_int_div(t, two_den);
+    let q_den = api.mul(q, two_den);
+    let r = api.sub(t, q_den);
+    api.assert_range(q, num_bits + 1);
+    api.assert_range(r, den_bits + 1);
+    let top = api.sub(two_den, 1u32);
+    let rest = api.sub(top, r);
+    api.assert_range(rest, den_bits + 1);
+    q
+}
+
+// Divides by 2^shift with rounding
+pub fn rescale<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    x: Variable,
+    x_bits: usize,
+    shift: usize,
+) -> Variable {
+    div_round(api, x, x_bits, 1 << shift)
+}
+
+// Rescales and asserts the result fits q
+pub fn requantize<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    q: FixedPoint,
+    x: Variable,
+    x_bits: usize,
+    shift: usize,
+) -> Variable {
+    let y = rescale(api, x, x_bits, shift);
+    assert_signed_range(api, y, q.bits);
+    y
+}
+
+// 1 if x >= 0 and 0 otherwise, asserting -2^(bits - 1) <= x < 2^(bits - 1)
+pub fn is_non_negative<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    x: Variable,
+    bits: usize,
+) -> Variable {
+    assert!(
+        (2..=max_range_bits::<C>()).contains(&bits),
+        "comparison too large for the field"
+    );
+    let half = constant(api, 1 << (bits - 1));
+    let t = api.add(x, half);
+    let s = api.unconstrained_int_div(t, half);
+    api.assert_is_bool(s);
+    let sh = api.mul(s, half);
+    let r = api.sub(t, sh);
+    api.assert_range(r, bits - 1);
+    s
+}
+
+// The larger of two values of bits bits
+fn max2<C: Config, B: RootAPI<C>>(api: &mut B, a: Variable, b: Variable, bits: usize) -> Variable {
+    let diff = api.sub(a, b);
+    let a_larger = is_non_negative(api, diff, bits + 1);
+    api.select(a_larger, a, b)
+}
+
+pub fn relu<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    q: FixedPoint,
+    x: &Tensor<Variable>,
+) -> Tensor<Variable> {
+    x.map(|v| {
+        let s = is_non_negative(api, v, q.bits);
+        api.mul(s, v)
+    })
+}
+
+// Pooling over an input of [C, H, W] without padding
+pub fn max_pool2d<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    q: FixedPoint,
+    x: &Tensor<Variable>,
+    kernel: usize,
+    stride: usize,
+) -> Tensor<Variable> {
+    let (shape, windows) = pool_windows(&x.shape, kernel, stride);
+    let data = windows
+        .iter()
+        .map(|w| {
+            w[1..]
+                .iter()
+                .fold(x.data[w[0]], |acc, i| max2(api, acc, x.data[*i], q.bits))
+        })
+        .collect();
+    Tensor::new(shape, data)
+}
+
+pub fn avg_pool2d<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    q: FixedPoint,
+    x: &Tensor<Variable>,
+    kernel: usize,
+    stride: usize,
+) -> Tensor<Variable> {
+    let (shape, windows) = pool_windows(&x.shape, kernel, stride);
+    let area = (kernel * kernel) as u64;
+    let data = windows
+        .iter()
+        .map(|w| {
+            let sum = w[1..]
+                .iter()
+                .fold(x.data[w[0]], |acc, i| api.add(acc, x.data[*i]));
+            div_round(api, sum, q.bits + bit_len(area as u128), area)
+        })
+        .collect();
+    Tensor::new(shape, data)
+}
+
+// Convolution of an input of [C_in, H, W] by weights of [C_out, C_in, K, K], both in q, with
+// zero padding. The bias of each output channel has 2 * frac_bits fractional bits, as the
+// products, and the output is requantized to q.
+pub fn conv2d<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    q: FixedPoint,
+    x: &Tensor<Variable>,
+    weight: &Tensor<Variable>,
+    bias: &[Variable],
+    stride: usize,
+    padding: usize,
+) -> Tensor<Variable> {
+    let (shape, taps) = conv_taps(&x.shape, &weight.shape, stride, padding);
+    assert_eq!(bias.len(), shape[0], "one bias per output channel");
+    let per_channel = shape[1] * shape[2];
+    // every product and the bias are below 2^(2 * bits - 2) in absolute value
+    let terms = taps.iter().map(|t| t.len()).max().unwrap_or(0);
+    let acc_bits = 2 * q.bits - 1 + bit_len(terms as u128);
+    let data = taps
+        .iter()
+        .enumerate()
+        .map(|(i, products)| {
+            let acc = products.iter().fold(bias[i / per_channel], |acc, (a, b)| {
+                let p = api.mul(x.data[*a], weight.data[*b]);
+                api.add(acc, p)
+            });
+            requantize(api, q, acc, acc_bits, q.frac_bits)
+        })
+        .collect();
+    Tensor::new(shape, data)
+}
+
+// The index of the first maximum of x
+pub fn argmax<C: Config, B: RootAPI<C>>(api: &mut B, q: FixedPoint, x: &[Variable]) -> Variable {
+    let mut best = x[0];
+    let mut index = api.constant(0u32);
+    for (i, v) in x.iter().enumerate().skip(1) {
+        // strictly larger, so that ties keep the first index
+        let diff = api.sub(*v, best);
+        let diff = api.sub(diff, 1u32);
+        let larger = is_non_negative(api, diff, q.bits + 1);
+        best = api.select(larger, *v, best);
+        index = api.select(larger, i as u32, index);
+    }
+    index
+}
+
+// Softmax of x in q, with the exponentials of x - max(x) looked up in exp, a table of
+// Activation::Exp with q.bits + 1 domain bits. The output is in q's scale.
+pub fn softmax<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    q: FixedPoint,
+    x: &[Variable],
+    exp: &mut ActivationTable,
+) -> Vec<Variable> {
+    assert!(
+        exp.func == Activation::Exp && exp.frac_bits == q.frac_bits,
+        "softmax needs an exp table in the scale of q"
+    );
+    assert!(exp.domain_bits > q.bits, "the exp table is too small");
+    let m = x[1..]
+        .iter()
+        .fold(x[0], |acc, v| max2(api, acc, *v, q.bits));
+    // exp(0) = 2^frac_bits bounds every exponential, so the sum is at least that
+    let e = x
+        .iter()
+        .map(|v| {
+            let d = api.sub(*v, m);
+            exp.apply(api, d)
+        })
+        .collect::<Vec<_>>();
+    let sum = e[1..].iter().fold(e[0], |acc, v| api.add(acc, *v));
+    let one = constant(api, 1 << q.frac_bits);
+    let sum_bits = q.frac_bits + bit_len(x.len() as u128);
+    e.iter()
+        .map(|v| {
+            let num = api.mul(*v, one);
+            div_round_var(api, num, 2 * q.frac_bits + 1, sum, sum_bits)
+        })
+        .collect()
+}
+
+// Layer normalization of x in q: (x - mean) / sqrt(var + eps) * gamma + beta, with gamma,
+// beta and eps in q's scale. The inverse square root is looked up in rsqrt, a table of
+// Activation::Rsqrt with at least 2 * bits - frac_bits domain bits.
+pub fn layer_norm<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    q: FixedPoint,
+    x: &[Variable],
+    gamma: &[Variable],
+    beta: &[Variable],
+    eps: u64,
+    rsqrt: &mut ActivationTable,
+) -> Vec<Variable> {
+    assert!(x.len() == gamma.len() && x.len() == beta.len());
+    assert!(
+        rsqrt.func == Activation::Rsqrt && rsqrt.frac_bits == q.frac_bits,
+        "layer_norm needs an rsqrt table in the scale of q"
+    );
+    assert!(
+        rsqrt.domain_bits >= 2 * q.bits - q.frac_bits,
+        "the rsqrt table is too small"
+    );
+    // the variance is below 2^(2 * bits - 2 - frac_bits), leaving that much room for eps
+    assert!(
+        eps > 0 && eps < 1 << (2 * q.bits - 2 - q.frac_bits),
+        "eps out of range"
+    );
+    let n = x.len() as u64;
+    let n_bits = bit_len(n as u128);
+    let sum = x[1..].iter().fold(x[0], |acc, v| api.add(acc, *v));
+    let mean = div_round(api, sum, q.bits + n_bits, n);
+    let centered = x.iter().map(|v| api.sub(*v, mean)).collect::<Vec<_>>();
+    let squares = centered.iter().map(|c| api.mul(*c, *c)).collect::<Vec<_>>();
+    let sum_squares = squares[1..]
+        .iter()
+        .fold(squares[0], |acc, v| api.add(acc, *v));
+    let var = div_round(api, sum_squares, 2 * q.bits + n_bits + 1, n << q.frac_bits);
+    let eps = constant(api, eps as u128);
+    let var_eps = api.add(var, eps);
+    let r = rsqrt.apply(api, var_eps);
+    let y_bits = q.bits + 1 + rsqrt.value_bits;
+    let gamma_bits = y_bits - q.frac_bits + q.bits;
+    centered
+        .iter()
+        .zip(gamma.iter().zip(beta))
+        .map(|(c, (g, b))| {
+            let y = api.mul(*c, r);
+            let y = rescale(api, y, y_bits, q.frac_bits);
+            let z = api.mul(y, *g);
+            let z = rescale(api, z, gamma_bits, q.frac_bits);
+            let z = api.add(z, *b);
+            assert_signed_range(api, z, q.bits);
+            z
+        })
+        .collect()
+}
@@ -0,0 +1,113 @@
+// The quantization of a tensor: values are v / 2^frac_bits with |v| < 2^(bits - 1)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedPoint {
+    pub bits: usize,
+    pub frac_bits: usize,
+}
+
+// A dense tensor in row-major order, of variables in circuits and of i64 natively
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tensor<T> {
+    pub shape: Vec<usize>,
+    pub data: Vec<T>,
+}
+
+impl<T: Copy> Tensor<T> {
+    pub fn new(shape: Vec<usize>, data: Vec<T>) -> Self {
+        assert_eq!(
+            shape.iter().product::<usize>(),
+            data.len(),
+            "data does not match the shape"
+        );
+        Self { shape, data }
+    }
+
+    pub fn at(&self, index: &[usize]) -> T {
+        assert_eq!(index.len(), self.shape.len());
+        let offset = index
+            .iter()
+            .zip(&self.shape)
+            .fold(0, |acc, (i, dim)| acc * dim + i);
+        self.data[offset]
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(T) -> U) -> Tensor<U> {
+        Tensor {
+            shape: self.shape.clone(),
+            data: self.data.iter().copied().map(f).collect(),
+        }
+    }
+}
+
+// The output shape of a pooling over [C, H, W] and the offsets of the window of every output
+pub(crate) fn pool_windows(
+    shape: &[usize],
+    kernel: usize,
+    stride: usize,
+) -> (Vec<usize>, Vec<Vec<usize>>) {
+    let [c, h, w]: [usize; 3] = shape.try_into().expect("pooling expects [C, H, W]");
+    assert!(kernel <= h && kernel <= w, "kernel larger than the input");
+    let (out_h, out_w) = ((h - kernel) / stride + 1, (w - kernel) / stride + 1);
+    let mut windows = Vec::with_capacity(c * out_h * out_w);
+    for ch in 0..c {
+        for i in 0..out_h {
+            for j in 0..out_w {
+                let window = (0..kernel * kernel)
+                    .map(|t| (ch * h + i * stride + t / kernel) * w + j * stride + t % kernel)
+                    .collect();
+                windows.push(window);
+            }
+        }
+    }
+    (vec![c, out_h, out_w], windows)
+}
+
+// The output shape of a convolution of [C_in, H, W] by [C_out, C_in, K, K] with zero padding,
+// and for every output the (input, weight) offsets of its products
+pub(crate) fn conv_taps(
+    x_shape: &[usize],
+    w_shape: &[usize],
+    stride: usize,
+    padding: usize,
+) -> (Vec<usize>, Vec<Vec<(usize, usize)>>) {
+    let [c_in, h, w]: [usize; 3] = x_shape
+        .try_into()
+        .expect("conv2d expects an input of [C, H, W]");
+    let [c_out, w_in, k, k2]: [usize; 4] = w_shape
+        .try_into()
+        .expect("conv2d expects weights of [C_out, C_in, K, K]");
+    assert!(c_in == w_in && k == k2, "weights do not match the input");
+    assert!(
+        k <= h + 2 * padding && k <= w + 2 * padding,
+        "kernel larger than the input"
+    );
+    let out_h = (h + 2 * padding - k) / stride + 1;
+    let out_w = (w + 2 * padding - k) / stride + 1;
+    let mut taps = Vec::with_capacity(c_out * out_h * out_w);
+    for o in 0..c_out {
+        for i in 0..out_h {
+            for j in 0..out_w {
+                let mut products = vec![];
+                for c in 0..c_in {
+                    for (di, dj) in (0..k).flat_map(|di| (0..k).map(move |dj| (di, dj))) {
+                        // positions in the padding only multiply zeros
+                        let (y, x) = (i * stride + di, j * stride + dj);
+                        if y < padding || x < padding || y >= h + padding || x >= w + padding {
+                            continue;
+                        }
+                        let input = (c * h + y - padding) * w + x - padding;
+                        let weight = ((o * c_in + c) * k + di) * k + dj;
+                        products.push((input, weight));
+                    }
+                }
+                taps.push(products);
+            }
+        }
+    }
+    (vec![c_out, out_h, out_w], taps)
+}
+
+// The number of bits of n
+pub(crate) fn bit_len(n: u128) -> usize {
+    (128 - n.leading_zeros()) as usize
+}
@@ -1,3 +1,6 @@
+pub mod poseidon2;
+pub mod poseidon2_native;
+pub mod poseidon2_utils;
 pub mod poseidon_m31;
 pub mod poseidon_u32;
 pub mod utils;
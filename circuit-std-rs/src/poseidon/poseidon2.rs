@@ -0,0 +1,153 @@
+use super::poseidon2_utils::{Poseidon2Mds4, Poseidon2Params};
+use expander_compiler::frontend::{CircuitField, Config, RootAPI, Variable};
+
+fn sbox<C: Config, B: RootAPI<C>>(api: &mut B, x: Variable, degree: usize) -> Variable {
+    let x2 = api.mul(x, x);
+    let x4 = api.mul(x2, x2);
+    match degree {
+        5 => api.mul(x4, x),
+        7 => {
+            let x3 = api.mul(x2, x);
+            api.mul(x4, x3)
+        }
+        _ => panic!("unsupported s-box degree"),
+    }
+}
+
+fn sum<C: Config, B: RootAPI<C>>(api: &mut B, xs: &[Variable]) -> Variable {
+    xs[1..].iter().fold(xs[0], |acc, x| api.add(acc, x))
+}
+
+fn apply_hl_m4<C: Config, B: RootAPI<C>>(api: &mut B, x: &mut [Variable]) {
+    let t0 = api.add(x[0], x[1]);
+    let t1 = api.add(x[2], x[3]);
+    let t2 = api.mul(x[1], 2);
+    let t2 = api.add(t2, t1);
+    let t3 = api.mul(x[3], 2);
+    let t3 = api.add(t3, t0);
+    let t4 = api.mul(t1, 4);
+    let t4 = api.add(t4, t3);
+    let t5 = api.mul(t0, 4);
+    let t5 = api.add(t5, t2);
+    x[0] = api.add(t3, t5);
+    x[1] = t5;
+    x[2] = api.add(t2, t4);
+    x[3] = t4;
+}
+
+fn apply_plonky3_m4<C: Config, B: RootAPI<C>>(api: &mut B, x: &mut [Variable]) {
+    let t01 = api.add(x[0], x[1]);
+    let t23 = api.add(x[2], x[3]);
+    let t0123 = api.add(t01, t23);
+    let t01123 = api.add(t0123, x[1]);
+    let t01233 = api.add(t0123, x[3]);
+    let x0_double = api.mul(x[0], 2);
+    let x2_double = api.mul(x[2], 2);
+    x[3] = api.add(t01233, x0_double);
+    x[1] = api.add(t01123, x2_double);
+    x[0] = api.add(t01123, t01);
+    x[2] = api.add(t01233, t23);
+}
+
+// The Poseidon2 permutation, sponge and compression over the native field of C, matching
+// Poseidon2Params::permute, hash and compress
+pub struct Poseidon2<C: Config> {
+    pub params: Poseidon2Params<CircuitField<C>>,
+}
+
+impl<C: Config> Poseidon2<C> {
+    pub fn new() -> Self {
+        Self {
+            params: Poseidon2Params::new(),
+        }
+    }
+
+    fn apply_external_matrix<B: RootAPI<C>>(&self, api: &mut B, state: &mut [Variable]) {
+        if self.params.width < 4 {
+            let total = sum(api, state);
+            state.iter_mut().for_each(|x| *x = api.add(*x, total));
+            return;
+        }
+        let apply_m4 = match self.params.mds4 {
+            Poseidon2Mds4::HorizenLabs => apply_hl_m4,
+            Poseidon2Mds4::Plonky3 => apply_plonky3_m4,
+        };
+        state.chunks_mut(4).for_each(|chunk| apply_m4(api, chunk));
+        let sums = (0..4)
+            .map(|i| {
+                let column = state.iter().skip(i).step_by(4).copied().collect::<Vec<_>>();
+                sum(api, &column)
+            })
+            .collect::<Vec<_>>();
+        (0..self.params.width).for_each(|i| state[i] = api.add(state[i], sums[i % 4]));
+    }
+
+    fn apply_internal_matrix<B: RootAPI<C>>(&self, api: &mut B, state: &mut [Variable]) {
+        let total = sum(api, state);
+        (0..self.params.width).for_each(|i| {
+            let scaled = api.mul(state[i], self.params.internal_diag[i]);
+            state[i] = api.add(scaled, total);
+        });
+    }
+
+    fn full_round<B: RootAPI<C>>(&self, api: &mut B, state: &mut [Variable], round: usize) {
+        (0..self.params.width).for_each(|i| {
+            let x = api.add(state[i], self.params.round_constants[round][i]);
+            state[i] = sbox(api, x, self.params.sbox_degree);
+        });
+        self.apply_external_matrix(api, state);
+    }
+
+    pub fn permute<B: RootAPI<C>>(&self, api: &mut B, state: &mut [Variable]) {
+        let half_full_rounds = self.params.full_rounds / 2;
+        let partial_ends = half_full_rounds + self.params.partial_rounds;
+
+        assert_eq!(self.params.width, state.len());
+        self.apply_external_matrix(api, state);
+        (0..half_full_rounds).for_each(|i| self.full_round(api, state, i));
+        (half_full_rounds..partial_ends).for_each(|i| {
+            let x = api.add(state[0], self.params.round_constants[i][0]);
+            state[0] = sbox(api, x, self.params.sbox_degree);
+            self.apply_internal_matrix(api, state);
+        });
+        (partial_ends..self.params.full_rounds + self.params.partial_rounds)
+            .for_each(|i| self.full_round(api, state, i));
+    }
+
+    pub fn hash<B: RootAPI<C>>(&self, api: &mut B, inputs: &[Variable]) -> Vec<Variable> {
+        let rate = self.params.rate;
+        let mut elts = inputs.to_vec();
+        elts.resize(inputs.len().max(1).next_multiple_of(rate), api.constant(0));
+
+        let mut state = vec![api.constant(0); self.params.width];
+        state[rate] = api.constant(inputs.len() as u32);
+        elts.chunks(rate).for_each(|chunk| {
+            (0..rate).for_each(|i| state[i] = api.add(state[i], chunk[i]));
+            self.permute(api, &mut state)
+        });
+
+        state[..self.params.digest_len].to_vec()
+    }
+
+    pub fn compress<B: RootAPI<C>>(
+        &self,
+        api: &mut B,
+        left: &[Variable],
+        right: &[Variable],
+    ) -> Vec<Variable> {
+        assert_eq!(left.len(), self.params.digest_len);
+        assert_eq!(right.len(), self.params.digest_len);
+
+        let mut state = [left, right].concat();
+        state.resize(self.params.width, api.constant(0));
+        self.permute(api, &mut state);
+
+        state[..self.params.digest_len].to_vec()
+    }
+}
+
+impl<C: Config> Default for Poseidon2<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,117 @@
+use expander_compiler::frontend::FieldArith;
+
+use super::poseidon2_utils::{Poseidon2Mds4, Poseidon2Params};
+
+fn sbox<F: FieldArith>(x: F, degree: usize) -> F {
+    let x2 = x * x;
+    let x4 = x2 * x2;
+    match degree {
+        5 => x4 * x,
+        7 => x4 * x2 * x,
+        _ => panic!("unsupported s-box degree"),
+    }
+}
+
+// The 4x4 MDS matrix [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]]
+fn apply_hl_m4<F: FieldArith>(x: &mut [F]) {
+    let t0 = x[0] + x[1];
+    let t1 = x[2] + x[3];
+    let t2 = x[1].double() + t1;
+    let t3 = x[3].double() + t0;
+    let t4 = t1.double().double() + t3;
+    let t5 = t0.double().double() + t2;
+    x[0] = t3 + t5;
+    x[1] = t5;
+    x[2] = t2 + t4;
+    x[3] = t4;
+}
+
+// The circulant 4x4 MDS matrix [[2, 3, 1, 1], [1, 2, 3, 1], [1, 1, 2, 3], [3, 1, 1, 2]]
+fn apply_plonky3_m4<F: FieldArith>(x: &mut [F]) {
+    let t01 = x[0] + x[1];
+    let t23 = x[2] + x[3];
+    let t0123 = t01 + t23;
+    let t01123 = t0123 + x[1];
+    let t01233 = t0123 + x[3];
+    x[3] = t01233 + x[0].double();
+    x[1] = t01123 + x[2].double();
+    x[0] = t01123 + t01;
+    x[2] = t01233 + t23;
+}
+
+impl<F: FieldArith> Poseidon2Params<F> {
+    fn apply_external_matrix(&self, state: &mut [F]) {
+        if self.width < 4 {
+            let sum = state.iter().fold(F::zero(), |acc, x| acc + *x);
+            state.iter_mut().for_each(|x| *x += sum);
+            return;
+        }
+        let apply_m4 = match self.mds4 {
+            Poseidon2Mds4::HorizenLabs => apply_hl_m4,
+            Poseidon2Mds4::Plonky3 => apply_plonky3_m4,
+        };
+        state.chunks_mut(4).for_each(apply_m4);
+        let sums = (0..4)
+            .map(|i| {
+                (i..self.width)
+                    .step_by(4)
+                    .fold(F::zero(), |acc, j| acc + state[j])
+            })
+            .collect::<Vec<_>>();
+        (0..self.width).for_each(|i| state[i] += sums[i % 4]);
+    }
+
+    fn apply_internal_matrix(&self, state: &mut [F]) {
+        let sum = state.iter().fold(F::zero(), |acc, x| acc + *x);
+        (0..self.width).for_each(|i| state[i] = state[i] * self.internal_diag[i] + sum);
+    }
+
+    fn full_round(&self, state: &mut [F], constants: &[F]) {
+        (0..self.width).for_each(|i| state[i] = sbox(state[i] + constants[i], self.sbox_degree));
+        self.apply_external_matrix(state);
+    }
+
+    pub fn permute(&self, state: &mut [F]) {
+        let half_full_rounds = self.full_rounds / 2;
+        let partial_ends = half_full_rounds + self.partial_rounds;
+
+        assert_eq!(self.width, state.len());
+        self.apply_external_matrix(state);
+        (0..half_full_rounds).for_each(|i| self.full_round(state, &self.round_constants[i]));
+        (half_full_rounds..partial_ends).for_each(|i| {
+            state[0] = sbox(state[0] + self.round_constants[i][0], self.sbox_degree);
+            self.apply_internal_matrix(state);
+        });
+        (partial_ends..self.full_rounds + self.partial_rounds)
+            .for_each(|i| self.full_round(state, &self.round_constants[i]));
+    }
+
+    // Sponge hash of any number of elements. The input length goes into the first capacity
+    // element, so zero padding the last block keeps inputs of different lengths apart.
+    pub fn hash(&self, inputs: &[F]) -> Vec<F> {
+        let mut elts = inputs.to_vec();
+        elts.resize(inputs.len().max(1).next_multiple_of(self.rate), F::zero());
+
+        let mut state = vec![F::zero(); self.width];
+        state[self.rate] = F::from(inputs.len() as u32);
+        elts.chunks(self.rate).for_each(|chunk| {
+            (0..self.rate).for_each(|i| state[i] += chunk[i]);
+            self.permute(&mut state)
+        });
+
+        state[..self.digest_len].to_vec()
+    }
+
+    // Two-to-one compression of digests for Merkle nodes, the truncated permutation of
+    // left || right
+    pub fn compress(&self, left: &[F], right: &[F]) -> Vec<F> {
+        assert_eq!(left.len(), self.digest_len);
+        assert_eq!(right.len(), self.digest_len);
+
+        let mut state = [left, right].concat();
+        state.resize(self.width, F::zero());
+        self.permute(&mut state);
+
+        state[..self.digest_len].to_vec()
+    }
+}
@@ -0,0 +1,273 @@
+use ethnum::U256;
+use expander_compiler::frontend::FieldArith;
+
+const M31_MODULUS: u32 = 2147483647;
+const BABYBEAR_MODULUS: u32 = 2013265921;
+const GOLDILOCKS_MODULUS: u64 = 18446744069414584321;
+const BN254_MODULUS: &str = "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+
+pub const POSEIDON2_FULL_ROUNDS: usize = 8;
+
+pub const POSEIDON2_M31_WIDTH: usize = 16;
+pub const POSEIDON2_M31_PARTIAL_ROUNDS: usize = 14;
+pub const POSEIDON2_BABYBEAR_WIDTH: usize = 16;
+pub const POSEIDON2_BABYBEAR_PARTIAL_ROUNDS: usize = 13;
+pub const POSEIDON2_GOLDILOCKS_WIDTH: usize = 8;
+pub const POSEIDON2_GOLDILOCKS_PARTIAL_ROUNDS: usize = 22;
+pub const POSEIDON2_BN254_WIDTH: usize = 3;
+pub const POSEIDON2_BN254_PARTIAL_ROUNDS: usize = 56;
+
+// Diagonals of the internal matrices, which are the all-ones matrix plus these. BabyBear,
+// Goldilocks and BN254 use the ones of the reference implementation, M31 the powers of two of
+// Plonky3 after a first entry of -2.
+const M31_INTERNAL_DIAG_SHIFTS: [u32; 15] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 10, 12, 13, 14, 15, 16];
+const BABYBEAR_INTERNAL_DIAG: [u64; POSEIDON2_BABYBEAR_WIDTH] = [
+    0x0a632d94, 0x6db657b7, 0x56fbdc9e, 0x052b3d8a, 0x33745201, 0x5c03108c, 0x0beba37b, 0x258c2e8b,
+    0x12029f39, 0x694909ce, 0x6d231724, 0x21c3b222, 0x3c0904a5, 0x01d6acda, 0x27705c83, 0x5231c802,
+];
+const GOLDILOCKS_INTERNAL_DIAG: [u64; POSEIDON2_GOLDILOCKS_WIDTH] = [
+    0xa98811a1fed4e3a5,
+    0x1cc48b54f377e2a0,
+    0xe40cd4f6c5609a26,
+    0x11de79ebca97a4a3,
+    0x9177c73d8b7e929c,
+    0x2a6fe8085797e791,
+    0x3de6e93329f8d5ad,
+    0x3f7af9125da962fe,
+];
+const BN254_INTERNAL_DIAG: [u64; POSEIDON2_BN254_WIDTH] = [1, 1, 2];
+
+// The Grain LFSR of the Poseidon reference, seeded with the field size, the width and the round
+// numbers. Yields the bits kept by its self-shrinking output.
+struct Grain {
+    state: u128,
+}
+
+impl Grain {
+    fn new(field_bits: usize, width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        // prime field (2 bits), x^alpha s-box (4 bits), then the sizes, then 30 ones
+        let fields = [
+            (1, 2),
+            (0, 4),
+            (field_bits, 12),
+            (width, 12),
+            (full_rounds, 10),
+            (partial_rounds, 10),
+            ((1 << 30) - 1, 30),
+        ];
+        let mut bits = Vec::with_capacity(80);
+        for (value, len) in fields {
+            (0..len)
+                .rev()
+                .for_each(|i| bits.push((value >> i) & 1 == 1));
+        }
+        let state = bits
+            .iter()
+            .enumerate()
+            .fold(0u128, |acc, (i, b)| acc | ((*b as u128) << i));
+        let mut grain = Self { state };
+        (0..160).for_each(|_| {
+            grain.next_raw();
+        });
+        grain
+    }
+
+    fn next_raw(&mut self) -> bool {
+        let s = self.state;
+        let bit = ((s >> 62) ^ (s >> 51) ^ (s >> 38) ^ (s >> 23) ^ (s >> 13) ^ s) & 1;
+        self.state = (s >> 1) | (bit << 79);
+        bit == 1
+    }
+
+    fn next_bit(&mut self) -> bool {
+        while !self.next_raw() {
+            self.next_raw();
+        }
+        self.next_raw()
+    }
+
+    // Most significant bit first, rejecting values outside the field
+    fn next_element(&mut self, modulus: U256, field_bits: usize) -> U256 {
+        loop {
+            let value = (0..field_bits).fold(U256::ZERO, |acc, _| {
+                (acc << 1) | U256::from(self.next_bit() as u32)
+            });
+            if value < modulus {
+                return value;
+            }
+        }
+    }
+}
+
+// The xoroshiro128+ generator, seeded through splitmix64 like rand_xoshiro's seed_from_u64
+struct Xoroshiro128Plus {
+    s0: u64,
+    s1: u64,
+}
+
+impl Xoroshiro128Plus {
+    fn seed_from_u64(mut seed: u64) -> Self {
+        let mut splitmix = || {
+            seed = seed.wrapping_add(0x9e3779b97f4a7c15);
+            let z = seed;
+            let z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            let z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        };
+        Self {
+            s0: splitmix(),
+            s1: splitmix(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let res = self.s0.wrapping_add(self.s1);
+        let s1 = self.s1 ^ self.s0;
+        self.s0 = self.s0.rotate_left(24) ^ s1 ^ (s1 << 16);
+        self.s1 = s1.rotate_left(37);
+        res
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    // Top 31 bits of each output, rejecting the modulus
+    fn next_m31(&mut self) -> U256 {
+        loop {
+            let value = self.next_u32() >> 1;
+            if value != M31_MODULUS {
+                return U256::from(value);
+            }
+        }
+    }
+}
+
+// The round constants of Plonky3's Mersenne31 instance, drawn from xoroshiro128+ seeded with 1:
+// first the full rounds, then the partial rounds. Returned in round order like
+// get_poseidon2_round_constants.
+pub fn get_poseidon2_m31_round_constants(
+    width: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+) -> Vec<Vec<U256>> {
+    let mut rng = Xoroshiro128Plus::seed_from_u64(1);
+    let mut full = (0..full_rounds)
+        .map(|_| (0..width).map(|_| rng.next_m31()).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let partial = (0..partial_rounds).map(|_| vec![rng.next_m31()]);
+    let terminal = full.split_off(full_rounds / 2);
+    full.into_iter().chain(partial).chain(terminal).collect()
+}
+
+// The round constants of the reference implementation: width constants for each full round and
+// one for each partial round, in round order
+pub fn get_poseidon2_round_constants(
+    modulus: U256,
+    width: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+) -> Vec<Vec<U256>> {
+    let field_bits = (256 - modulus.leading_zeros()) as usize;
+    let mut grain = Grain::new(field_bits, width, full_rounds, partial_rounds);
+    let half_full_rounds = full_rounds / 2;
+    (0..full_rounds + partial_rounds)
+        .map(|i| {
+            let len = if i < half_full_rounds || i >= half_full_rounds + partial_rounds {
+                width
+            } else {
+                1
+            };
+            (0..len)
+                .map(|_| grain.next_element(modulus, field_bits))
+                .collect()
+        })
+        .collect()
+}
+
+// The 4x4 block of the external matrix
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Poseidon2Mds4 {
+    // [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]] of the reference implementation
+    HorizenLabs,
+    // the circulant [[2, 3, 1, 1], [1, 2, 3, 1], [1, 1, 2, 3], [3, 1, 1, 2]] of Plonky3
+    Plonky3,
+}
+
+// The Poseidon2 instance of a field. The sponge keeps the capacity in the last width - rate
+// elements, hash and compress outputs have digest_len elements.
+#[derive(Clone, Debug)]
+pub struct Poseidon2Params<F: FieldArith> {
+    pub width: usize,
+    pub rate: usize,
+    pub digest_len: usize,
+    pub sbox_degree: usize,
+    pub full_rounds: usize,
+    pub partial_rounds: usize,
+    pub mds4: Poseidon2Mds4,
+    pub internal_diag: Vec<F>,
+    pub round_constants: Vec<Vec<F>>,
+}
+
+impl<F: FieldArith> Poseidon2Params<F> {
+    // Panics for fields other than M31, BabyBear, Goldilocks and BN254. M31 uses the instance
+    // of Plonky3, the other fields the one of the reference implementation.
+    pub fn new() -> Self {
+        let modulus = F::MODULUS;
+        let (width, rate, digest_len, sbox_degree, partial_rounds, internal_diag) =
+            if modulus == U256::from(M31_MODULUS) {
+                let mut diag = vec![modulus - U256::from(2u32)];
+                diag.extend(M31_INTERNAL_DIAG_SHIFTS.map(|s| U256::ONE << s));
+                let partial_rounds = POSEIDON2_M31_PARTIAL_ROUNDS;
+                (POSEIDON2_M31_WIDTH, 8, 8, 5, partial_rounds, diag)
+            } else if modulus == U256::from(BABYBEAR_MODULUS) {
+                let diag = BABYBEAR_INTERNAL_DIAG.map(U256::from).to_vec();
+                let partial_rounds = POSEIDON2_BABYBEAR_PARTIAL_ROUNDS;
+                (POSEIDON2_BABYBEAR_WIDTH, 8, 8, 7, partial_rounds, diag)
+            } else if modulus == U256::from(GOLDILOCKS_MODULUS) {
+                let diag = GOLDILOCKS_INTERNAL_DIAG.map(U256::from).to_vec();
+                let partial_rounds = POSEIDON2_GOLDILOCKS_PARTIAL_ROUNDS;
+                (POSEIDON2_GOLDILOCKS_WIDTH, 4, 4, 7, partial_rounds, diag)
+            } else if modulus == U256::from_str_radix(BN254_MODULUS, 16).unwrap() {
+                let diag = BN254_INTERNAL_DIAG.map(U256::from).to_vec();
+                let partial_rounds = POSEIDON2_BN254_PARTIAL_ROUNDS;
+                (POSEIDON2_BN254_WIDTH, 2, 1, 5, partial_rounds, diag)
+            } else {
+                panic!("no Poseidon2 instance for this field")
+            };
+        let (mds4, round_constants) = if modulus == U256::from(M31_MODULUS) {
+            let round_constants =
+                get_poseidon2_m31_round_constants(width, POSEIDON2_FULL_ROUNDS, partial_rounds);
+            (Poseidon2Mds4::Plonky3, round_constants)
+        } else {
+            let round_constants = get_poseidon2_round_constants(
+                modulus,
+                width,
+                POSEIDON2_FULL_ROUNDS,
+                partial_rounds,
+            );
+            (Poseidon2Mds4::HorizenLabs, round_constants)
+        };
+        let round_constants = round_constants
+            .into_iter()
+            .map(|round| round.into_iter().map(F::from_u256).collect())
+            .collect();
+        Self {
+            width,
+            rate,
+            digest_len,
+            sbox_degree,
+            full_rounds: POSEIDON2_FULL_ROUNDS,
+            partial_rounds,
+            mds4,
+            internal_diag: internal_diag.into_iter().map(F::from_u256).collect(),
+            round_constants,
+        }
+    }
+}
+
+impl<F: FieldArith> Default for Poseidon2Params<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,4 +1,8 @@
-// The implementation of sha256 for the M31 and GF2 field
+// The implementation of sha256 for the M31 and GF2 field, and of a Sha256 gadget for any config
+pub mod generic;
+pub mod hmac;
+pub use generic::Sha256;
+pub use hmac::HmacSha256;
 
 // The Std trait for M31 haven't been implemented yet, see test_m31.rs for the usage
 pub mod m31;
@@ -0,0 +1,482 @@
+// SHA-256 over any config, for messages of a fixed or a runtime-bounded length. Messages and
+// digests are bits, most significant bit of each byte first. Over GF2 words are 32 bits and use
+// the circuits of gf2_utils. Over larger fields words are 8 limbs of 4 bits, little endian, and
+// the bit operations on limbs are looked up in a LogUp table shared by every hash of a Sha256.
+use arith::Field;
+use expander_compiler::frontend::{Config, Error, RootAPI, Variable};
+
+use super::gf2::{SHA256_INIT_STATE, SHA256_K};
+use super::gf2_utils::{
+    add_const, capital_sigma0, capital_sigma1, ch, lower_case_sigma0, lower_case_sigma1, maj,
+    sum_all, u32_to_bit, Sha256Word,
+};
+use crate::logup::LogUpSingleKeyTable;
+use crate::utils::is_gf2;
+
+const LIMB_BITS: usize = 4;
+const LIMBS: usize = 8;
+// limbs per chunk in word additions, a chunk sum and its carry fit in 5 limbs
+const ADD_CHUNK_LIMBS: usize = 4;
+// rows 16a + b hold (a ^ b, a & b), rows SPLIT_OFFSET + 16(s - 1) + n hold (n mod 2^s, n >> s)
+const SPLIT_OFFSET: usize = 256;
+const TABLE_SIZE: usize = SPLIT_OFFSET + 16 * (LIMB_BITS - 1);
+
+// 32 big endian bits over GF2, 8 little endian 4-bit limbs otherwise
+pub type Word = Vec<Variable>;
+
+fn table_row(key: usize) -> (u32, u32) {
+    if key < SPLIT_OFFSET {
+        let (a, b) = (key >> LIMB_BITS, key & 15);
+        ((a ^ b) as u32, (a & b) as u32)
+    } else if key < TABLE_SIZE {
+        let (s, n) = ((key - SPLIT_OFFSET) / 16 + 1, (key - SPLIT_OFFSET) % 16);
+        ((n & ((1 << s) - 1)) as u32, (n >> s) as u32)
+    } else {
+        (0, 0)
+    }
+}
+
+// The values of the table row with the given key
+pub fn sha256_table_hint<F: Field>(inputs: &[F], outputs: &mut [F]) -> Result<(), Error> {
+    let (v0, v1) = table_row(inputs[0].to_u256().as_usize());
+    outputs[0] = F::from(v0);
+    outputs[1] = F::from(v1);
+    Ok(())
+}
+
+// 1 if the little endian bits encode value, 0 otherwise
+fn equals_const<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    bits: &[Variable],
+    value: usize,
+) -> Variable {
+    if value.checked_shr(bits.len() as u32).unwrap_or(0) != 0 {
+        return api.constant(0);
+    }
+    let mut res = api.constant(1);
+    for (k, bit) in bits.iter().enumerate() {
+        let matches = if value.checked_shr(k as u32).unwrap_or(0) & 1 == 1 {
+            *bit
+        } else {
+            api.sub(1, *bit)
+        };
+        res = api.mul(res, matches);
+    }
+    res
+}
+
+fn gf2_word(x: &Word) -> Sha256Word {
+    x.as_slice().try_into().unwrap()
+}
+
+pub struct Sha256 {
+    // None over GF2
+    table: Option<LogUpSingleKeyTable>,
+    data: Vec<Variable>,
+}
+
+impl Sha256 {
+    pub fn new<C: Config, B: RootAPI<C>>(api: &mut B) -> Self {
+        let table = if is_gf2::<C>() {
+            None
+        } else {
+            let mut table = LogUpSingleKeyTable::new(0);
+            for key in 0..TABLE_SIZE {
+                let (v0, v1) = table_row(key);
+                let values = vec![api.constant(v0), api.constant(v1)];
+                let key = api.constant(key as u32);
+                table.add_table_row(key, values);
+            }
+            Some(table)
+        };
+        Self {
+            table,
+            data: Vec::new(),
+        }
+    }
+
+    // data can have arbitrary length, as long as it is a whole number of bytes
+    pub fn update<C: Config, B: RootAPI<C>>(&mut self, api: &mut B, data: &[Variable]) {
+        assert!(data.len() % 8 == 0, "data must be a whole number of bytes");
+        if self.table.is_some() {
+            for bit in data {
+                api.assert_is_bool(*bit);
+            }
+        }
+        self.data.extend_from_slice(data);
+    }
+
+    // The digest of all the data since the last finalize, the hasher can be reused afterwards
+    pub fn finalize<C: Config, B: RootAPI<C>>(&mut self, api: &mut B) -> Vec<Variable> {
+        let mut bits = std::mem::take(&mut self.data);
+        let bit_len = bits.len() as u64;
+        bits.push(api.constant(1));
+        while bits.len() % 512 != 448 {
+            bits.push(api.constant(0));
+        }
+        for i in (0..64).rev() {
+            bits.push(api.constant(((bit_len >> i) & 1) as u32));
+        }
+        let mut state = self.init_state(api);
+        for block in bits.chunks_exact(512) {
+            state = self.compress(api, &state, block);
+        }
+        self.digest_bits(api, &state)
+    }
+
+    // The digest of the first len bytes of the data, len given in little endian bits. len must
+    // not exceed the length of the data, every hash costs as many blocks as the longest one.
+    pub fn finalize_var_len<C: Config, B: RootAPI<C>>(
+        &mut self,
+        api: &mut B,
+        len: &[Variable],
+    ) -> Vec<Variable> {
+        let data = std::mem::take(&mut self.data);
+        let max_len = data.len() / 8;
+        let n_blocks = (max_len + 9).div_ceil(64);
+        for bit in len {
+            api.assert_is_bool(*bit);
+        }
+        // eq[i] is 1 if len == i, exactly one of them is set when len <= max_len
+        let eq = (0..=max_len)
+            .map(|i| equals_const(api, len, i))
+            .collect::<Vec<_>>();
+        let total = eq.iter().fold(api.constant(0), |acc, x| api.add(acc, *x));
+        api.assert_is_equal(total, 1);
+        // lt[i] is 1 if i < len
+        let mut lt = Vec::with_capacity(max_len);
+        let mut seen = api.constant(0);
+        for i in 0..max_len {
+            seen = api.add(seen, eq[i]);
+            lt.push(api.sub(1, seen));
+        }
+
+        let zero = api.constant(0);
+        let mut state = self.init_state(api);
+        let mut digest = state
+            .iter()
+            .map(|word| vec![zero; word.len()])
+            .collect::<Vec<_>>();
+        for b in 0..n_blocks {
+            // the padding of len bytes ends in block b if 64b - 8 <= len <= 64b + 55
+            let is_final = eq[(64 * b).saturating_sub(8)..=(64 * b + 55).min(max_len)]
+                .iter()
+                .fold(zero, |acc, x| api.add(acc, *x));
+            let mut block = Vec::with_capacity(512);
+            for p in 0..64 {
+                let i = 64 * b + p;
+                for t in 0..8 {
+                    let mut bit = zero;
+                    if i < max_len {
+                        bit = api.mul(lt[i], data[8 * i + t]);
+                    }
+                    if t == 0 && i <= max_len {
+                        bit = api.add(bit, eq[i]);
+                    }
+                    // the last 8 bytes of the final block hold 8·len, big endian
+                    if p >= 56 {
+                        let pos = 63 - (8 * (p - 56) + t);
+                        if pos >= 3 && pos - 3 < len.len() {
+                            let len_bit = api.mul(is_final, len[pos - 3]);
+                            bit = api.add(bit, len_bit);
+                        }
+                    }
+                    block.push(bit);
+                }
+            }
+            state = self.compress(api, &state, &block);
+            for (acc, word) in digest.iter_mut().zip(&state) {
+                for (a, x) in acc.iter_mut().zip(word) {
+                    let t = api.mul(is_final, *x);
+                    *a = api.add(*a, t);
+                }
+            }
+        }
+        self.digest_bits(api, &digest)
+    }
+
+    // Checks the table lookups of every hash, call it once after the last finalize
+    pub fn final_check<C: Config, B: RootAPI<C>>(&mut self, api: &mut B) {
+        if let Some(table) = &mut self.table {
+            table.final_check(api);
+        }
+    }
+
+    // The compress function on a block of 512 bits, usually not used directly
+    pub fn compress<C: Config, B: RootAPI<C>>(
+        &mut self,
+        api: &mut B,
+        state: &[Word],
+        block: &[Variable],
+    ) -> Vec<Word> {
+        let mut w = block
+            .chunks_exact(32)
+            .map(|bits| self.word_from_bits(api, bits))
+            .collect::<Vec<_>>();
+        for i in 16..64 {
+            let s0 = self.small_sigma0(api, &w[i - 15]);
+            let s1 = self.small_sigma1(api, &w[i - 2]);
+            let next = self.add(api, &[&s1, &w[i - 7], &s0, &w[i - 16]], 0);
+            w.push(next);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h]: [Word; 8] =
+            state.to_vec().try_into().unwrap();
+        for i in 0..64 {
+            let s1 = self.big_sigma1(api, &e);
+            let ch_efg = self.ch(api, &e, &f, &g);
+            let t1 = self.add(api, &[&h, &s1, &ch_efg, &w[i]], SHA256_K[i]);
+            let s0 = self.big_sigma0(api, &a);
+            let maj_abc = self.maj(api, &a, &b, &c);
+            let t2 = self.add(api, &[&s0, &maj_abc], 0);
+
+            h = g;
+            g = f;
+            f = e;
+            e = self.add(api, &[&d, &t1], 0);
+            d = c;
+            c = b;
+            b = a;
+            a = self.add(api, &[&t1, &t2], 0);
+        }
+
+        state
+            .iter()
+            .zip([a, b, c, d, e, f, g, h])
+            .map(|(x, y)| self.add(api, &[x, &y], 0))
+            .collect()
+    }
+
+    fn init_state<C: Config, B: RootAPI<C>>(&self, api: &mut B) -> Vec<Word> {
+        SHA256_INIT_STATE
+            .iter()
+            .map(|x| self.const_word(api, *x))
+            .collect()
+    }
+
+    fn const_word<C: Config, B: RootAPI<C>>(&self, api: &mut B, x: u32) -> Word {
+        match self.table {
+            None => u32_to_bit(api, x).to_vec(),
+            Some(_) => (0..LIMBS)
+                .map(|k| api.constant((x >> (LIMB_BITS * k)) & 15))
+                .collect(),
+        }
+    }
+
+    // 32 bits, most significant first
+    fn word_from_bits<C: Config, B: RootAPI<C>>(&self, api: &mut B, bits: &[Variable]) -> Word {
+        match self.table {
+            None => bits.to_vec(),
+            Some(_) => (0..LIMBS)
+                .map(|k| {
+                    let mut limb = api.constant(0);
+                    for t in 0..LIMB_BITS {
+                        let bit = api.mul(bits[31 - LIMB_BITS * k - t], 1u32 << t);
+                        limb = api.add(limb, bit);
+                    }
+                    limb
+                })
+                .collect(),
+        }
+    }
+
+    fn digest_bits<C: Config, B: RootAPI<C>>(&self, api: &mut B, state: &[Word]) -> Vec<Variable> {
+        match self.table {
+            None => state.concat(),
+            Some(_) => state
+                .iter()
+                .flat_map(|word| word.iter().rev())
+                .flat_map(|limb| api.to_binary(*limb, LIMB_BITS).into_iter().rev())
+                .collect(),
+        }
+    }
+
+    fn big_sigma0<C: Config, B: RootAPI<C>>(&mut self, api: &mut B, x: &Word) -> Word {
+        match self.table {
+            None => capital_sigma0(api, &gf2_word(x)).to_vec(),
+            Some(_) => self.limb_sigma(api, x, [2, 13, 22], false),
+        }
+    }
+
+    fn big_sigma1<C: Config, B: RootAPI<C>>(&mut self, api: &mut B, x: &Word) -> Word {
+        match self.table {
+            None => capital_sigma1(api, &gf2_word(x)).to_vec(),
+            Some(_) => self.limb_sigma(api, x, [6, 11, 25], false),
+        }
+    }
+
+    fn small_sigma0<C: Config, B: RootAPI<C>>(&mut self, api: &mut B, x: &Word) -> Word {
+        match self.table {
+            None => lower_case_sigma0(api, &gf2_word(x)).to_vec(),
+            Some(_) => self.limb_sigma(api, x, [7, 18, 3], true),
+        }
+    }
+
+    fn small_sigma1<C: Config, B: RootAPI<C>>(&mut self, api: &mut B, x: &Word) -> Word {
+        match self.table {
+            None => lower_case_sigma1(api, &gf2_word(x)).to_vec(),
+            Some(_) => self.limb_sigma(api, x, [17, 19, 10], true),
+        }
+    }
+
+    // (x & y) ^ (!x & z), the two terms never share a bit so the limbs are added
+    fn ch<C: Config, B: RootAPI<C>>(&mut self, api: &mut B, x: &Word, y: &Word, z: &Word) -> Word {
+        if self.table.is_none() {
+            return ch(api, &gf2_word(x), &gf2_word(y), &gf2_word(z)).to_vec();
+        }
+        (0..LIMBS)
+            .map(|k| {
+                let [_, xy] = self.lookup_pair(api, x[k], y[k]);
+                let not_x = api.sub(15, x[k]);
+                let [_, not_xz] = self.lookup_pair(api, not_x, z[k]);
+                api.add(xy, not_xz)
+            })
+            .collect()
+    }
+
+    // (x & y) + (z & (x ^ y)), again without common bits
+    fn maj<C: Config, B: RootAPI<C>>(&mut self, api: &mut B, x: &Word, y: &Word, z: &Word) -> Word {
+        if self.table.is_none() {
+            return maj(api, &gf2_word(x), &gf2_word(y), &gf2_word(z)).to_vec();
+        }
+        (0..LIMBS)
+            .map(|k| {
+                let [x_xor_y, xy] = self.lookup_pair(api, x[k], y[k]);
+                let [_, z_and] = self.lookup_pair(api, z[k], x_xor_y);
+                api.add(xy, z_and)
+            })
+            .collect()
+    }
+
+    // The sum of the words and of the constant k modulo 2^32
+    fn add<C: Config, B: RootAPI<C>>(&mut self, api: &mut B, words: &[&Word], k: u32) -> Word {
+        if self.table.is_none() {
+            let mut words = words.iter().map(|x| gf2_word(x)).collect::<Vec<_>>();
+            if k != 0 {
+                words[0] = add_const(api, &words[0], k);
+            }
+            return sum_all(api, &words).to_vec();
+        }
+        assert!(words.len() < 15, "too many words in one addition");
+        let chunk_bits = LIMB_BITS * ADD_CHUNK_LIMBS;
+        let mut res = Vec::with_capacity(LIMBS);
+        let mut carry = api.constant(0);
+        for chunk in 0..LIMBS / ADD_CHUNK_LIMBS {
+            let mut sum = api.add(carry, (k >> (chunk_bits * chunk)) & 0xffff);
+            for word in words {
+                for t in 0..ADD_CHUNK_LIMBS {
+                    let limb = api.mul(word[ADD_CHUNK_LIMBS * chunk + t], 1u32 << (LIMB_BITS * t));
+                    sum = api.add(sum, limb);
+                }
+            }
+            let hint_inputs = [
+                api.constant((LIMB_BITS * (ADD_CHUNK_LIMBS + 1)) as u32),
+                api.constant(LIMB_BITS as u32),
+                sum,
+            ];
+            let limbs = api.new_hint("myhint.rangeproofhint", &hint_inputs, ADD_CHUNK_LIMBS + 1);
+            let mut recomposed = api.constant(0);
+            for (t, limb) in limbs.iter().enumerate() {
+                self.range_check_limb(api, *limb);
+                let shifted = api.mul(*limb, 1u32 << (LIMB_BITS * t));
+                recomposed = api.add(recomposed, shifted);
+            }
+            api.assert_is_equal(recomposed, sum);
+            res.extend_from_slice(&limbs[..ADD_CHUNK_LIMBS]);
+            carry = limbs[ADD_CHUNK_LIMBS];
+        }
+        res
+    }
+
+    // The xor of x rotated right by the first two amounts and rotated, or shifted if
+    // shift_last is set, by the third one
+    fn limb_sigma<C: Config, B: RootAPI<C>>(
+        &mut self,
+        api: &mut B,
+        x: &Word,
+        amounts: [usize; 3],
+        shift_last: bool,
+    ) -> Word {
+        let zero = api.constant(0);
+        // the limbs of x split at bit s, computed once for every s in use
+        let mut splits: [Option<(Word, Word)>; LIMB_BITS] = Default::default();
+        let mut terms = Vec::with_capacity(3);
+        for (j, r) in amounts.into_iter().enumerate() {
+            let (q, s) = (r / LIMB_BITS, r % LIMB_BITS);
+            let shift = shift_last && j == 2;
+            // the limb moved to position k, or None if it was shifted out
+            let source = |k: usize| {
+                if shift && k >= LIMBS {
+                    None
+                } else {
+                    Some(k % LIMBS)
+                }
+            };
+            let term = if s == 0 {
+                (0..LIMBS)
+                    .map(|k| source(k + q).map_or(zero, |i| x[i]))
+                    .collect::<Word>()
+            } else {
+                if splits[s].is_none() {
+                    splits[s] = Some(self.split(api, x, s));
+                }
+                let (lo, hi) = splits[s].as_ref().unwrap();
+                (0..LIMBS)
+                    .map(|k| {
+                        let mut limb = source(k + q).map_or(zero, |i| hi[i]);
+                        if let Some(i) = source(k + q + 1) {
+                            let t = api.mul(lo[i], 1u32 << (LIMB_BITS - s));
+                            limb = api.add(limb, t);
+                        }
+                        limb
+                    })
+                    .collect()
+            };
+            terms.push(term);
+        }
+        let t = self.limb_xor(api, &terms[0], &terms[1]);
+        self.limb_xor(api, &t, &terms[2])
+    }
+
+    fn limb_xor<C: Config, B: RootAPI<C>>(&mut self, api: &mut B, x: &Word, y: &Word) -> Word {
+        (0..LIMBS)
+            .map(|k| self.lookup_pair(api, x[k], y[k])[0])
+            .collect()
+    }
+
+    // The low s bits and the high 4 - s bits of every limb
+    fn split<C: Config, B: RootAPI<C>>(&mut self, api: &mut B, x: &Word, s: usize) -> (Word, Word) {
+        x.iter()
+            .map(|limb| {
+                let key = api.add(*limb, (SPLIT_OFFSET + 16 * (s - 1)) as u32);
+                let [lo, hi] = self.lookup(api, key);
+                (lo, hi)
+            })
+            .unzip()
+    }
+
+    // (x ^ y, x & y) for two limbs
+    fn lookup_pair<C: Config, B: RootAPI<C>>(
+        &mut self,
+        api: &mut B,
+        x: Variable,
+        y: Variable,
+    ) -> [Variable; 2] {
+        let key = api.mul(x, 16);
+        let key = api.add(key, y);
+        self.lookup(api, key)
+    }
+
+    fn lookup<C: Config, B: RootAPI<C>>(&mut self, api: &mut B, key: Variable) -> [Variable; 2] {
+        let values = api.new_hint("myhint.sha256tablehint", &[key], 2);
+        self.table.as_mut().unwrap().query(key, values.clone());
+        [values[0], values[1]]
+    }
+
+    // Row 16x holds (x, 0), so it is only found for x < 16
+    fn range_check_limb<C: Config, B: RootAPI<C>>(&mut self, api: &mut B, x: Variable) {
+        let key = api.mul(x, 16);
+        let zero = api.constant(0);
+        self.table.as_mut().unwrap().query(key, vec![x, zero]);
+    }
+}
@@ -0,0 +1,107 @@
+// HMAC-SHA256 (RFC 2104) over any config, keys, messages and tags as bits like Sha256
+use expander_compiler::frontend::{Config, RootAPI, Variable};
+
+use super::generic::Sha256;
+
+const BLOCK_BITS: usize = 512;
+// a block is 2^6 bytes
+const BLOCK_LEN_LOG: usize = 6;
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+pub struct HmacSha256 {
+    hasher: Sha256,
+    inner_key: Vec<Variable>,
+    outer_key: Vec<Variable>,
+}
+
+// bits xor the byte pad repeated, bits being most significant first
+fn xor_pad<C: Config, B: RootAPI<C>>(api: &mut B, bits: &[Variable], pad: u8) -> Vec<Variable> {
+    bits.iter()
+        .enumerate()
+        .map(|(i, bit)| {
+            if (pad >> (7 - i % 8)) & 1 == 1 {
+                api.sub(1, *bit)
+            } else {
+                *bit
+            }
+        })
+        .collect()
+}
+
+// len + 64 in little endian bits, one bit longer than len
+fn add_block_len<C: Config, B: RootAPI<C>>(api: &mut B, len: &[Variable]) -> Vec<Variable> {
+    let low_bits = BLOCK_LEN_LOG;
+    let mut res = len.iter().take(low_bits).copied().collect::<Vec<_>>();
+    while res.len() < low_bits {
+        res.push(api.constant(0));
+    }
+    let mut carry = api.constant(1);
+    for bit in len.iter().skip(low_bits) {
+        res.push(api.xor(*bit, carry));
+        carry = api.and(*bit, carry);
+    }
+    res.push(carry);
+    res
+}
+
+impl HmacSha256 {
+    // Keys longer than a block are hashed first
+    pub fn new<C: Config, B: RootAPI<C>>(api: &mut B, key: &[Variable]) -> Self {
+        let mut hasher = Sha256::new(api);
+        let mut key = if key.len() > BLOCK_BITS {
+            hasher.update(api, key);
+            hasher.finalize(api)
+        } else {
+            key.to_vec()
+        };
+        key.resize(BLOCK_BITS, api.constant(0));
+        let inner_key = xor_pad(api, &key, IPAD);
+        let outer_key = xor_pad(api, &key, OPAD);
+        hasher.update(api, &inner_key);
+        Self {
+            hasher,
+            inner_key,
+            outer_key,
+        }
+    }
+
+    pub fn update<C: Config, B: RootAPI<C>>(&mut self, api: &mut B, data: &[Variable]) {
+        self.hasher.update(api, data);
+    }
+
+    // The tag of all the data since the last finalize, the same key can then tag another message
+    pub fn finalize<C: Config, B: RootAPI<C>>(&mut self, api: &mut B) -> Vec<Variable> {
+        let inner = self.hasher.finalize(api);
+        self.outer(api, &inner)
+    }
+
+    // The tag of the first len bytes of the data, see Sha256::finalize_var_len
+    pub fn finalize_var_len<C: Config, B: RootAPI<C>>(
+        &mut self,
+        api: &mut B,
+        len: &[Variable],
+    ) -> Vec<Variable> {
+        // the inner hash also covers the key block
+        let inner_len = add_block_len(api, len);
+        let inner = self.hasher.finalize_var_len(api, &inner_len);
+        self.outer(api, &inner)
+    }
+
+    // Checks the table lookups of every tag, call it once after the last finalize
+    pub fn final_check<C: Config, B: RootAPI<C>>(&mut self, api: &mut B) {
+        self.hasher.final_check(api);
+    }
+
+    fn outer<C: Config, B: RootAPI<C>>(
+        &mut self,
+        api: &mut B,
+        inner: &[Variable],
+    ) -> Vec<Variable> {
+        self.hasher.update(api, &self.outer_key);
+        self.hasher.update(api, inner);
+        let tag = self.hasher.finalize(api);
+        self.hasher.update(api, &self.inner_key);
+        tag
+    }
+}
@@ -0,0 +1,290 @@
+// SHA-512 over any native field, with words as 64 little endian bits. Additions are carried
+// out on 16-bit chunks so that they fit in small fields like M31.
+use expander_compiler::frontend::{Config, RootAPI, Variable};
+
+pub type Sha512Word = [Variable; 64];
+
+const SHA512_INIT_STATE: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const SHA512_K: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+// bits per chunk in word additions
+const CHUNK_BITS: usize = 16;
+
+// The SHA-512 digest of data, given and returned as bytes. data has a fixed length, its
+// bytes are range checked.
+pub fn sha512<C: Config, B: RootAPI<C>>(api: &mut B, data: &[Variable]) -> Vec<Variable> {
+    let mut bytes = data.to_vec();
+    // padding: a 1 bit, zeros up to 112 mod 128 bytes, then the bit length in 128 bits
+    bytes.push(api.constant(0x80));
+    while bytes.len() % 128 != 112 {
+        bytes.push(api.constant(0));
+    }
+    let bit_len = (data.len() as u128) * 8;
+    for byte in bit_len.to_be_bytes() {
+        bytes.push(api.constant(byte as u32));
+    }
+
+    let mut state = SHA512_INIT_STATE.map(|x| const_word(api, x));
+    for block in bytes.chunks_exact(128) {
+        let words: Vec<Sha512Word> = block
+            .chunks_exact(8)
+            .map(|word| word_from_be_bytes(api, word))
+            .collect();
+        sha512_compress(api, &mut state, &words.try_into().unwrap());
+    }
+    state
+        .iter()
+        .flat_map(|word| word_to_be_bytes(api, word))
+        .collect()
+}
+
+// The compress function, usually not used directly
+pub fn sha512_compress<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    state: &mut [Sha512Word; 8],
+    block: &[Sha512Word; 16],
+) {
+    let mut w = block.to_vec();
+    for i in 16..80 {
+        let s0 = xor3(
+            api,
+            &rotate_right(&w[i - 15], 1),
+            &rotate_right(&w[i - 15], 8),
+            &shift_right(api, &w[i - 15], 7),
+        );
+        let s1 = xor3(
+            api,
+            &rotate_right(&w[i - 2], 19),
+            &rotate_right(&w[i - 2], 61),
+            &shift_right(api, &w[i - 2], 6),
+        );
+        let next = add(api, &[&w[i - 16], &s0, &w[i - 7], &s1]);
+        w.push(next);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..80 {
+        let sigma1 = xor3(
+            api,
+            &rotate_right(&e, 14),
+            &rotate_right(&e, 18),
+            &rotate_right(&e, 41),
+        );
+        let ch_efg = ch(api, &e, &f, &g);
+        let k = const_word(api, SHA512_K[i]);
+        let t1 = add(api, &[&h, &sigma1, &ch_efg, &k, &w[i]]);
+        let sigma0 = xor3(
+            api,
+            &rotate_right(&a, 28),
+            &rotate_right(&a, 34),
+            &rotate_right(&a, 39),
+        );
+        let maj_abc = maj(api, &a, &b, &c);
+        let t2 = add(api, &[&sigma0, &maj_abc]);
+
+        h = g;
+        g = f;
+        f = e;
+        e = add(api, &[&d, &t1]);
+        d = c;
+        c = b;
+        b = a;
+        a = add(api, &[&t1, &t2]);
+    }
+
+    for (word, x) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        let sum = add(api, &[&*word, &x]);
+        *word = sum;
+    }
+}
+
+pub fn const_word<C: Config, B: RootAPI<C>>(api: &mut B, x: u64) -> Sha512Word {
+    std::array::from_fn(|i| api.constant(((x >> i) & 1) as u32))
+}
+
+pub fn word_from_be_bytes<C: Config, B: RootAPI<C>>(api: &mut B, bytes: &[Variable]) -> Sha512Word {
+    let bits: Vec<Variable> = bytes
+        .iter()
+        .rev()
+        .flat_map(|byte| api.to_binary(*byte, 8))
+        .collect();
+    bits.try_into().unwrap()
+}
+
+pub fn word_to_be_bytes<C: Config, B: RootAPI<C>>(api: &mut B, word: &Sha512Word) -> Vec<Variable> {
+    word.chunks_exact(8)
+        .rev()
+        .map(|bits| api.from_binary(bits))
+        .collect()
+}
+
+fn rotate_right(word: &Sha512Word, shift: usize) -> Sha512Word {
+    std::array::from_fn(|i| word[(i + shift) % 64])
+}
+
+fn shift_right<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    word: &Sha512Word,
+    shift: usize,
+) -> Sha512Word {
+    let zero = api.constant(0);
+    std::array::from_fn(|i| {
+        if i + shift < 64 {
+            word[i + shift]
+        } else {
+            zero
+        }
+    })
+}
+
+fn xor3<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    x: &Sha512Word,
+    y: &Sha512Word,
+    z: &Sha512Word,
+) -> Sha512Word {
+    std::array::from_fn(|i| {
+        let tmp = api.xor(x[i], y[i]);
+        api.xor(tmp, z[i])
+    })
+}
+
+// z ^ (x & (y ^ z)), picks y where x is set and z elsewhere
+fn ch<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    x: &Sha512Word,
+    y: &Sha512Word,
+    z: &Sha512Word,
+) -> Sha512Word {
+    std::array::from_fn(|i| {
+        let tmp = api.xor(y[i], z[i]);
+        let tmp = api.and(x[i], tmp);
+        api.xor(z[i], tmp)
+    })
+}
+
+// (x & y) ^ (z & (x ^ y)), the majority of each bit
+fn maj<C: Config, B: RootAPI<C>>(
+    api: &mut B,
+    x: &Sha512Word,
+    y: &Sha512Word,
+    z: &Sha512Word,
+) -> Sha512Word {
+    std::array::from_fn(|i| {
+        let xy = api.and(x[i], y[i]);
+        let tmp = api.xor(x[i], y[i]);
+        let tmp = api.and(z[i], tmp);
+        api.xor(xy, tmp)
+    })
+}
+
+// The sum of words modulo 2^64
+fn add<C: Config, B: RootAPI<C>>(api: &mut B, words: &[&Sha512Word]) -> Sha512Word {
+    // a chunk sum with its carry stays below words.len() * 2^CHUNK_BITS
+    let sum_bits = CHUNK_BITS + (usize::BITS - words.len().leading_zeros()) as usize;
+    let mut res = vec![];
+    let mut carry = api.constant(0);
+    for chunk in 0..64 / CHUNK_BITS {
+        let range = chunk * CHUNK_BITS..(chunk + 1) * CHUNK_BITS;
+        let mut sum = carry;
+        for word in words {
+            let value = api.from_binary(&word[range.clone()]);
+            sum = api.add(sum, value);
+        }
+        let bits = api.to_binary(sum, sum_bits);
+        res.extend_from_slice(&bits[..CHUNK_BITS]);
+        carry = api.from_binary(&bits[CHUNK_BITS..]);
+    }
+    res.try_into().unwrap()
+}
@@ -1,24 +1,34 @@
-use expander_compiler::frontend::{Config, HintRegistry, RootAPI, Variable, M31};
+use ethnum::U256;
+use expander_compiler::frontend::{
+    CircuitField, Config, Field, FieldArith, HintRegistry, RootAPI, Variable,
+};
 
 use crate::{
     gnark::hints::{
         copy_e12_hint, copy_e2_hint, copy_element_hint, copy_vars_hint, div_e12_hint, div_e2_hint,
         div_e6_by_6_hint, div_e6_hint, div_hint, final_exp_hint, get_e2_sqrt_hint,
-        get_element_sqrt_hint, get_sqrt_x0x1_fq2_new_hint, get_sqrt_x0x1_fq_new_hint, inv_hint,
-        inverse_e12_hint, inverse_e2_hint, inverse_e6_hint, mul_hint, simple_rangecheck_hint,
+        get_element_sqrt_hint, get_sqrt_x0x1_fq2_new_hint, get_sqrt_x0x1_fq_new_hint,
+        glv_decompose_hint, inv_hint, inverse_e12_hint, inverse_e2_hint, inverse_e6_hint, mul_hint,
+        simple_rangecheck_hint, sqrt_hint,
     },
     logup::{query_count_by_key_hint, query_count_hint, rangeproof_hint},
+    nn::nn_activation_hint,
+    sha256::generic::sha256_table_hint,
 };
 
+// Whether the native field is GF2, where bits are added without carries
+pub fn is_gf2<C: Config>() -> bool {
+    CircuitField::<C>::MODULUS == U256::from(2u32)
+}
+
+// return a if selector is 1 and b if selector is 0
 pub fn simple_select<C: Config, B: RootAPI<C>>(
     native: &mut B,
     selector: Variable,
     a: Variable,
     b: Variable,
 ) -> Variable {
-    let tmp = native.sub(a, b);
-    let tmp2 = native.mul(tmp, selector);
-    native.add(b, tmp2)
+    native.select(selector, a, b)
 }
 
 //return i0 if selector0 and selector 1 are 0
@@ -34,12 +44,11 @@ pub fn simple_lookup2<C: Config, B: RootAPI<C>>(
     i2: Variable,
     i3: Variable,
 ) -> Variable {
-    let tmp0 = simple_select(native, selector0, i1, i0);
-    let tmp1 = simple_select(native, selector0, i3, i2);
-    simple_select(native, selector1, tmp1, tmp0)
+    native.mux(&[selector0, selector1], &[i0, i1, i2, i3])
 }
 
-pub fn register_hint(hint_registry: &mut HintRegistry<M31>) {
+// The emulated field hints work over any native field
+pub fn register_hint<F: Field>(hint_registry: &mut HintRegistry<F>) {
     hint_registry.register("myhint.mulhint", mul_hint);
     hint_registry.register("myhint.simple_rangecheck_hint", simple_rangecheck_hint);
     hint_registry.register("myhint.querycounthint", query_count_hint);
@@ -63,4 +72,8 @@ pub fn register_hint(hint_registry: &mut HintRegistry<M31>) {
     hint_registry.register("myhint.getsqrtx0x1fqnewhint", get_sqrt_x0x1_fq_new_hint);
     hint_registry.register("myhint.getelementsqrthint", get_element_sqrt_hint);
     hint_registry.register("myhint.gete2sqrthint", get_e2_sqrt_hint);
+    hint_registry.register("myhint.sqrthint", sqrt_hint);
+    hint_registry.register("myhint.glvdecomposehint", glv_decompose_hint);
+    hint_registry.register("myhint.sha256tablehint", sha256_table_hint);
+    hint_registry.register("myhint.nnactivationhint", nn_activation_hint);
 }
@@ -0,0 +1,68 @@
+mod common;
+
+use circuit_std_rs::{
+    ed25519::native, utils::register_hint, Ed25519Circuit, Ed25519Params, StdCircuit,
+};
+use expander_compiler::frontend::{extra::debug_eval, *};
+
+fn hex_bytes<const N: usize>(s: &str) -> [u8; N] {
+    std::array::from_fn(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+}
+
+fn hints<C: Config>() -> HintRegistry<CircuitField<C>> {
+    let mut hint_registry = HintRegistry::<CircuitField<C>>::new();
+    register_hint(&mut hint_registry);
+    hint_registry
+}
+
+// RFC 8032 section 7.1, tests 1 and 2
+#[test]
+fn test_native_rfc8032_vectors() {
+    let vectors: [(&str, &str, &[u8], &str); 2] = [
+        (
+            "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            &[],
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        ),
+        (
+            "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            &[0x72],
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+        ),
+    ];
+    for (seed, pubkey, msg, signature) in vectors {
+        let seed = hex_bytes::<32>(seed);
+        let pubkey = hex_bytes::<32>(pubkey);
+        let signature = hex_bytes::<64>(signature);
+        assert_eq!(native::public_key(&seed), pubkey);
+        assert_eq!(native::sign(&seed, msg), signature);
+        assert!(native::verify(&pubkey, msg, &signature));
+        assert!(!native::verify(&pubkey, b"another message", &signature));
+    }
+}
+
+#[test]
+fn test_ed25519_circuit() {
+    let params = Ed25519Params { msg_len: 40 };
+    common::circuit_test_helper_with_hint::<M31Config, Ed25519Circuit>(
+        &params,
+        &mut hints::<M31Config>(),
+    );
+    common::circuit_test_helper_with_hint::<BN254Config, Ed25519Circuit>(
+        &params,
+        &mut hints::<BN254Config>(),
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_ed25519_wrong_signature() {
+    let params = Ed25519Params { msg_len: 5 };
+    let circuit = <Ed25519Circuit as StdCircuit<M31Config>>::new_circuit(&params);
+    let mut assignment =
+        <Ed25519Circuit as StdCircuit<M31Config>>::new_assignment(&params, rand::thread_rng());
+    assignment.signature[40] += M31::from(1);
+    debug_eval::<M31Config, _, _, _>(&circuit, &assignment, hints::<M31Config>());
+}
@@ -0,0 +1,342 @@
+use ark_bls12_381::{
+    g1, g2, Fr, G1Affine as BlsG1Affine, G1Projective, G2Affine as BlsG2Affine, G2Projective,
+};
+use ark_ec::hashing::{
+    curve_maps::wb::WBMap, map_to_curve_hasher::MapToCurveBasedHasher, HashToCurve,
+};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{field_hashers::DefaultFieldHasher, UniformRand};
+use ark_serialize::CanonicalSerialize;
+use circuit_std_rs::{
+    bls::{BlsMinPk, BlsMinSig, DST_MIN_PK_POP, DST_MIN_SIG_POP},
+    gnark::emulated::sw_bls12381::{g1::G1Affine, g2::G2AffP},
+    utils::register_hint,
+};
+use expander_compiler::frontend::{extra::debug_eval, *};
+use rand::{rngs::StdRng, SeedableRng};
+use sha2::Sha256;
+
+const MSG_LEN: usize = 32;
+
+type G1Vars<T> = [[T; 48]; 2];
+type G2Vars<T> = [[[T; 48]; 2]; 2];
+
+fn g1_from_vars(p: &G1Vars<Variable>) -> G1Affine {
+    G1Affine::from_vars(p[0].to_vec(), p[1].to_vec())
+}
+
+fn g2_from_vars(p: &G2Vars<Variable>) -> G2AffP {
+    G2AffP::from_vars(
+        p[0][0].to_vec(),
+        p[0][1].to_vec(),
+        p[1][0].to_vec(),
+        p[1][1].to_vec(),
+    )
+}
+
+declare_circuit!(BlsMinPkVerifyCircuit {
+    pk: [[Variable; 48]; 2],
+    msg: [Variable; 32],
+    sig: [[[Variable; 48]; 2]; 2],
+});
+
+impl Define<M31Config> for BlsMinPkVerifyCircuit<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, builder: &mut Builder) {
+        let mut bls = BlsMinPk::new(builder);
+        bls.verify(
+            builder,
+            &g1_from_vars(&self.pk),
+            &self.msg,
+            &g2_from_vars(&self.sig),
+        )
+        .unwrap();
+        bls.finalize(builder);
+    }
+}
+
+declare_circuit!(BlsMinPkFastAggregateCircuit {
+    pks: [[[Variable; 48]; 2]; 2],
+    msg: [Variable; 32],
+    sig: [[[Variable; 48]; 2]; 2],
+});
+
+impl Define<M31Config> for BlsMinPkFastAggregateCircuit<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, builder: &mut Builder) {
+        let mut bls = BlsMinPk::new(builder);
+        let pks = self.pks.iter().map(g1_from_vars).collect::<Vec<_>>();
+        bls.fast_aggregate_verify(builder, &pks, &self.msg, &g2_from_vars(&self.sig))
+            .unwrap();
+        bls.finalize(builder);
+    }
+}
+
+declare_circuit!(BlsMinSigVerifyCircuit {
+    pk: [[[Variable; 48]; 2]; 2],
+    msg: [Variable; 32],
+    sig: [[Variable; 48]; 2],
+});
+
+impl Define<M31Config> for BlsMinSigVerifyCircuit<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, builder: &mut Builder) {
+        let mut bls = BlsMinSig::new(builder);
+        bls.verify(
+            builder,
+            &g2_from_vars(&self.pk),
+            &self.msg,
+            &g1_from_vars(&self.sig),
+        )
+        .unwrap();
+        bls.finalize(builder);
+    }
+}
+
+declare_circuit!(BlsMinSigAggregateCircuit {
+    pks: [[[[Variable; 48]; 2]; 2]; 2],
+    msgs: [[Variable; 32]; 2],
+    sig: [[Variable; 48]; 2],
+});
+
+impl Define<M31Config> for BlsMinSigAggregateCircuit<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, builder: &mut Builder) {
+        let mut bls = BlsMinSig::new(builder);
+        let pks = self.pks.iter().map(g2_from_vars).collect::<Vec<_>>();
+        let msgs = self.msgs.iter().map(|m| m.to_vec()).collect::<Vec<_>>();
+        bls.aggregate_verify(builder, &pks, &msgs, &g1_from_vars(&self.sig))
+            .unwrap();
+        bls.finalize(builder);
+    }
+}
+
+declare_circuit!(BlsMinSigHashCircuit {
+    msg: [Variable; 32],
+    h: [[Variable; 48]; 2],
+});
+
+impl Define<M31Config> for BlsMinSigHashCircuit<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, builder: &mut Builder) {
+        let mut bls = BlsMinSig::new(builder);
+        let h = bls.hash_to_g1(builder, &self.msg);
+        bls.pairing
+            .g1
+            .assert_is_equal(builder, &h, &g1_from_vars(&self.h));
+        bls.finalize(builder);
+    }
+}
+
+fn hash_to_g1(msg: &[u8]) -> BlsG1Affine {
+    MapToCurveBasedHasher::<G1Projective, DefaultFieldHasher<Sha256, 128>, WBMap<g1::Config>>::new(
+        DST_MIN_SIG_POP,
+    )
+    .unwrap()
+    .hash(msg)
+    .unwrap()
+}
+
+fn hash_to_g2(msg: &[u8]) -> BlsG2Affine {
+    MapToCurveBasedHasher::<G2Projective, DefaultFieldHasher<Sha256, 128>, WBMap<g2::Config>>::new(
+        DST_MIN_PK_POP,
+    )
+    .unwrap()
+    .hash(msg)
+    .unwrap()
+}
+
+fn fp_bytes<F: CanonicalSerialize>(x: &F) -> [M31; 48] {
+    let mut bytes = [0u8; 48];
+    x.serialize_compressed(bytes.as_mut()).unwrap();
+    bytes.map(|b| M31::from(b as u32))
+}
+
+fn g1_assignment(p: &BlsG1Affine) -> G1Vars<M31> {
+    [fp_bytes(&p.x), fp_bytes(&p.y)]
+}
+
+fn g2_assignment(p: &BlsG2Affine) -> G2Vars<M31> {
+    [
+        [fp_bytes(&p.x.c0), fp_bytes(&p.x.c1)],
+        [fp_bytes(&p.y.c0), fp_bytes(&p.y.c1)],
+    ]
+}
+
+fn msg_assignment(msg: &[u8; MSG_LEN]) -> [M31; MSG_LEN] {
+    msg.map(|b| M31::from(b as u32))
+}
+
+fn secret_keys(n: usize) -> Vec<Fr> {
+    let mut rng = StdRng::seed_from_u64(n as u64);
+    (0..n).map(|_| Fr::rand(&mut rng)).collect()
+}
+
+fn g1_public_key(sk: &Fr) -> BlsG1Affine {
+    (BlsG1Affine::generator() * sk).into_affine()
+}
+
+fn g2_public_key(sk: &Fr) -> BlsG2Affine {
+    (BlsG2Affine::generator() * sk).into_affine()
+}
+
+fn hints() -> HintRegistry<M31> {
+    let mut hint_registry = HintRegistry::<M31>::new();
+    register_hint(&mut hint_registry);
+    hint_registry
+}
+
+fn min_pk_verify_assignment(msg: &[u8; MSG_LEN]) -> BlsMinPkVerifyCircuit<M31> {
+    let sk = secret_keys(1)[0];
+    let sig = (hash_to_g2(msg) * sk).into_affine();
+    BlsMinPkVerifyCircuit {
+        pk: g1_assignment(&g1_public_key(&sk)),
+        msg: msg_assignment(msg),
+        sig: g2_assignment(&sig),
+    }
+}
+
+#[test]
+fn test_native_bls_min_pk() {
+    use ark_ec::pairing::Pairing;
+    type E = ark_bls12_381::Bls12_381;
+    let sk = secret_keys(1)[0];
+    let h = hash_to_g2(b"native message");
+    let sig = (h * sk).into_affine();
+    assert_eq!(
+        E::pairing(g1_public_key(&sk), h),
+        E::pairing(BlsG1Affine::generator(), sig)
+    );
+}
+
+#[test]
+fn test_bls_min_pk_verify() {
+    let assignment = min_pk_verify_assignment(&[7; MSG_LEN]);
+    debug_eval(&BlsMinPkVerifyCircuit::default(), &assignment, hints());
+}
+
+#[test]
+#[should_panic]
+fn test_bls_min_pk_verify_wrong_message() {
+    let mut assignment = min_pk_verify_assignment(&[7; MSG_LEN]);
+    assignment.msg[0] = M31::from(8);
+    debug_eval(&BlsMinPkVerifyCircuit::default(), &assignment, hints());
+}
+
+// sig is the sum of the signatures of msg under each of signers
+fn min_pk_fast_aggregate_assignment(
+    sks: [Fr; 2],
+    signers: &[Fr],
+) -> BlsMinPkFastAggregateCircuit<M31> {
+    let msg = [3; MSG_LEN];
+    let h = hash_to_g2(&msg);
+    let sig = signers
+        .iter()
+        .fold(G2Projective::default(), |acc, sk| acc + h * sk)
+        .into_affine();
+    BlsMinPkFastAggregateCircuit::<M31> {
+        pks: sks.map(|sk| g1_assignment(&g1_public_key(&sk))),
+        msg: msg_assignment(&msg),
+        sig: g2_assignment(&sig),
+    }
+}
+
+#[test]
+fn test_bls_min_pk_fast_aggregate_verify() {
+    let sks = secret_keys(2);
+    let assignment = min_pk_fast_aggregate_assignment([sks[0], sks[1]], &sks);
+    debug_eval(
+        &BlsMinPkFastAggregateCircuit::default(),
+        &assignment,
+        hints(),
+    );
+}
+
+// the same key twice sums to its double
+#[test]
+fn test_bls_min_pk_fast_aggregate_verify_same_key() {
+    let sk = secret_keys(1)[0];
+    let assignment = min_pk_fast_aggregate_assignment([sk, sk], &[sk, sk]);
+    debug_eval(
+        &BlsMinPkFastAggregateCircuit::default(),
+        &assignment,
+        hints(),
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_bls_min_pk_fast_aggregate_verify_same_key_single_signature() {
+    let sk = secret_keys(1)[0];
+    let assignment = min_pk_fast_aggregate_assignment([sk, sk], &[sk]);
+    debug_eval(
+        &BlsMinPkFastAggregateCircuit::default(),
+        &assignment,
+        hints(),
+    );
+}
+
+#[test]
+fn test_bls_min_sig_verify() {
+    let msg = [5; MSG_LEN];
+    let sk = secret_keys(1)[0];
+    let sig = (hash_to_g1(&msg) * sk).into_affine();
+    let assignment = BlsMinSigVerifyCircuit::<M31> {
+        pk: g2_assignment(&g2_public_key(&sk)),
+        msg: msg_assignment(&msg),
+        sig: g1_assignment(&sig),
+    };
+    debug_eval(&BlsMinSigVerifyCircuit::default(), &assignment, hints());
+}
+
+#[test]
+fn test_bls_min_sig_aggregate_verify() {
+    let msgs = [[1; MSG_LEN], [2; MSG_LEN]];
+    let sks = secret_keys(2);
+    let sig = (hash_to_g1(&msgs[0]) * sks[0] + hash_to_g1(&msgs[1]) * sks[1]).into_affine();
+    let assignment = BlsMinSigAggregateCircuit::<M31> {
+        pks: [
+            g2_assignment(&g2_public_key(&sks[0])),
+            g2_assignment(&g2_public_key(&sks[1])),
+        ],
+        msgs: msgs.map(|m| msg_assignment(&m)),
+        sig: g1_assignment(&sig),
+    };
+    debug_eval(&BlsMinSigAggregateCircuit::default(), &assignment, hints());
+}
+
+// A different message in every SIMD lane of a single compiled circuit, the hash of one lane
+// is wrong and only that lane fails
+#[test]
+fn test_bls_min_sig_hash_simd() {
+    let compile_result =
+        compile(&BlsMinSigHashCircuit::default(), CompileOptions::default()).unwrap();
+    let assignments = (0..4u8)
+        .map(|i| {
+            let msg = [i; MSG_LEN];
+            let h = hash_to_g1(if i == 2 { &[0; MSG_LEN] } else { &msg });
+            BlsMinSigHashCircuit::<M31> {
+                msg: msg_assignment(&msg),
+                h: g1_assignment(&h),
+            }
+        })
+        .collect::<Vec<_>>();
+    let witness = compile_result
+        .witness_solver
+        .solve_witnesses_with_hints(&assignments, &hints())
+        .unwrap();
+    let output = compile_result.layered_circuit.run(&witness);
+    assert_eq!(output, vec![true, true, false, true]);
+}
+
+// one signature per SIMD lane, the full verification is too slow to compile by default
+#[test]
+#[ignore]
+fn test_bls_min_pk_verify_simd() {
+    let compile_result =
+        compile(&BlsMinPkVerifyCircuit::default(), CompileOptions::default()).unwrap();
+    let assignments = (0..4u8)
+        .map(|i| min_pk_verify_assignment(&[i; MSG_LEN]))
+        .collect::<Vec<_>>();
+    let witness = compile_result
+        .witness_solver
+        .solve_witnesses_with_hints(&assignments, &hints())
+        .unwrap();
+    let output = compile_result.layered_circuit.run(&witness);
+    assert_eq!(output, vec![true; assignments.len()]);
+}
@@ -0,0 +1,306 @@
+use circuit_std_rs::{
+    ecdsa::{element_from_be_bytes, EmulatedCurve, NativeCurve, NativePoint, Secp256k1, P256},
+    gnark::{emparam::FieldParams, emulated::sw_bls12381::point::AffinePoint},
+    utils::register_hint,
+};
+use expander_compiler::frontend::{extra::debug_eval, *};
+use num_bigint::{BigInt, Sign};
+use num_traits::Signed;
+use sha2::{Digest, Sha256};
+
+// msg_hash, r, s and the public key coordinates, as big endian bytes
+type SignatureVars<T> = [[T; 32]; 5];
+
+fn verify_signature<C: Config, B: RootAPI<C>, Base, Scalar>(
+    builder: &mut B,
+    curve: &mut EmulatedCurve<Base, Scalar>,
+    sig: &SignatureVars<Variable>,
+) where
+    Base: FieldParams + Default + Clone,
+    Scalar: FieldParams + Default,
+{
+    let [msg_hash, r, s, pubkey_x, pubkey_y] = sig;
+    let pubkey = AffinePoint {
+        x: element_from_be_bytes(pubkey_x),
+        y: element_from_be_bytes(pubkey_y),
+    };
+    curve.verify(
+        builder,
+        &element_from_be_bytes(msg_hash),
+        &element_from_be_bytes(r),
+        &element_from_be_bytes(s),
+        &pubkey,
+    );
+    curve.finalize(builder);
+}
+
+fn recover_public_key<C: Config, B: RootAPI<C>, Base, Scalar>(
+    builder: &mut B,
+    curve: &mut EmulatedCurve<Base, Scalar>,
+    sig: &SignatureVars<Variable>,
+    v: Variable,
+) where
+    Base: FieldParams + Default + Clone,
+    Scalar: FieldParams + Default,
+{
+    let [msg_hash, r, s, pubkey_x, pubkey_y] = sig;
+    let pubkey = curve.recover_public_key(
+        builder,
+        &element_from_be_bytes(msg_hash),
+        &element_from_be_bytes(r),
+        &element_from_be_bytes(s),
+        v,
+    );
+    let expected_x = element_from_be_bytes(pubkey_x);
+    let expected_y = element_from_be_bytes(pubkey_y);
+    curve
+        .base_api
+        .assert_is_equal(builder, &pubkey.x, &expected_x);
+    curve
+        .base_api
+        .assert_is_equal(builder, &pubkey.y, &expected_y);
+    curve.finalize(builder);
+}
+
+declare_circuit!(Secp256k1VerifyCircuit {
+    sig: [[Variable; 32]; 5],
+});
+
+impl<C: Config> Define<C> for Secp256k1VerifyCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        let mut curve = Secp256k1::new_secp256k1(builder);
+        verify_signature(builder, &mut curve, &self.sig);
+    }
+}
+
+declare_circuit!(P256VerifyCircuit {
+    sig: [[Variable; 32]; 5],
+});
+
+impl<C: Config> Define<C> for P256VerifyCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        let mut curve = P256::new_p256(builder);
+        verify_signature(builder, &mut curve, &self.sig);
+    }
+}
+
+declare_circuit!(Secp256k1RecoverCircuit {
+    sig: [[Variable; 32]; 5],
+    v: Variable,
+});
+
+impl<C: Config> Define<C> for Secp256k1RecoverCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        let mut curve = Secp256k1::new_secp256k1(builder);
+        recover_public_key(builder, &mut curve, &self.sig, self.v);
+    }
+}
+
+declare_circuit!(P256RecoverCircuit {
+    sig: [[Variable; 32]; 5],
+    v: Variable,
+});
+
+impl<C: Config> Define<C> for P256RecoverCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        let mut curve = P256::new_p256(builder);
+        recover_public_key(builder, &mut curve, &self.sig, self.v);
+    }
+}
+
+fn to_be_bytes<F: From<u32>>(x: &BigInt) -> [F; 32] {
+    let (_, bytes) = x.to_bytes_be();
+    let offset = 32 - bytes.len();
+    std::array::from_fn(|i| {
+        F::from(if i < offset {
+            0
+        } else {
+            bytes[i - offset] as u32
+        })
+    })
+}
+
+fn hash_message(msg: &[u8]) -> BigInt {
+    BigInt::from_bytes_be(Sign::Plus, &Sha256::digest(msg))
+}
+
+struct TestSignature {
+    msg_hash: BigInt,
+    r: BigInt,
+    s: BigInt,
+    v: bool,
+    pubkey: NativePoint,
+}
+
+fn sign(curve: &NativeCurve, msg: &[u8]) -> TestSignature {
+    let private_key = hash_message(b"private key") % &curve.n;
+    let nonce = hash_message(msg) * 7 % &curve.n;
+    let msg_hash = hash_message(msg);
+    let (r, s, v) = curve.sign(&private_key, &msg_hash, &nonce);
+    let pubkey = curve.public_key(&private_key);
+    TestSignature {
+        msg_hash,
+        r,
+        s,
+        v,
+        pubkey,
+    }
+}
+
+impl TestSignature {
+    fn assignment<F: From<u32>>(&self) -> SignatureVars<F> {
+        [
+            to_be_bytes(&self.msg_hash),
+            to_be_bytes(&self.r),
+            to_be_bytes(&self.s),
+            to_be_bytes(&self.pubkey.x),
+            to_be_bytes(&self.pubkey.y),
+        ]
+    }
+}
+
+fn hints<C: Config>() -> HintRegistry<CircuitField<C>> {
+    let mut hint_registry = HintRegistry::<CircuitField<C>>::new();
+    register_hint(&mut hint_registry);
+    hint_registry
+}
+
+#[test]
+fn test_native_sign_verify_recover() {
+    for curve in [NativeCurve::secp256k1(), NativeCurve::p256()] {
+        let sig = sign(&curve, b"hello");
+        assert!(curve.verify(&sig.msg_hash, &sig.r, &sig.s, &sig.pubkey));
+        assert_eq!(
+            curve.recover(&sig.msg_hash, &sig.r, &sig.s, sig.v),
+            Some(sig.pubkey.clone())
+        );
+        let wrong_hash = &sig.msg_hash + 1;
+        assert!(!curve.verify(&wrong_hash, &sig.r, &sig.s, &sig.pubkey));
+        assert!(!curve.verify(&sig.msg_hash, &sig.r, &curve.n, &sig.pubkey));
+    }
+}
+
+#[test]
+fn test_native_glv_decompose() {
+    let curve = NativeCurve::secp256k1();
+    let lambda = curve.params.eigenvalue.clone().unwrap();
+    let beta = curve.params.third_root_one.clone().unwrap();
+    let g = curve.generator();
+    let endo_g = NativePoint {
+        x: &g.x * &beta % &curve.p,
+        y: g.y.clone(),
+    };
+    assert_eq!(curve.mul(&lambda, &Some(g.clone())), Some(endo_g));
+    for msg in [b"a".as_slice(), b"b", b"c"] {
+        let k = hash_message(msg) % &curve.n;
+        let (k1, k2) = curve.glv_decompose(&k);
+        assert!(k1.abs().bits() <= 129 && k2.abs().bits() <= 129);
+        let recomposed = (k1 + k2 * &lambda - &k) % &curve.n;
+        assert_eq!(recomposed, BigInt::ZERO);
+    }
+}
+
+fn test_secp256k1_verify<C: Config>()
+where
+    CircuitField<C>: From<u32>,
+{
+    let sig = sign(&NativeCurve::secp256k1(), b"secp256k1 message");
+    let assignment = Secp256k1VerifyCircuit::<CircuitField<C>> {
+        sig: sig.assignment(),
+    };
+    debug_eval::<C, _, _, _>(
+        &Secp256k1VerifyCircuit::default(),
+        &assignment,
+        hints::<C>(),
+    );
+}
+
+#[test]
+fn test_secp256k1_verify_m31() {
+    test_secp256k1_verify::<M31Config>();
+}
+
+#[test]
+fn test_secp256k1_verify_bn254() {
+    test_secp256k1_verify::<BN254Config>();
+}
+
+fn test_p256_verify<C: Config>()
+where
+    CircuitField<C>: From<u32>,
+{
+    let sig = sign(&NativeCurve::p256(), b"p256 message");
+    let assignment = P256VerifyCircuit::<CircuitField<C>> {
+        sig: sig.assignment(),
+    };
+    debug_eval::<C, _, _, _>(&P256VerifyCircuit::default(), &assignment, hints::<C>());
+}
+
+#[test]
+fn test_p256_verify_m31() {
+    test_p256_verify::<M31Config>();
+}
+
+#[test]
+fn test_p256_verify_bn254() {
+    test_p256_verify::<BN254Config>();
+}
+
+#[test]
+#[should_panic]
+fn test_secp256k1_verify_wrong_message() {
+    let sig = sign(&NativeCurve::secp256k1(), b"secp256k1 message");
+    let mut assignment = Secp256k1VerifyCircuit::<M31> {
+        sig: sig.assignment(),
+    };
+    assignment.sig[0] = to_be_bytes(&hash_message(b"another message"));
+    debug_eval::<M31Config, _, _, _>(
+        &Secp256k1VerifyCircuit::default(),
+        &assignment,
+        hints::<M31Config>(),
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_p256_verify_zero_s() {
+    let sig = sign(&NativeCurve::p256(), b"p256 message");
+    let mut assignment = P256VerifyCircuit::<M31> {
+        sig: sig.assignment(),
+    };
+    assignment.sig[2] = to_be_bytes(&BigInt::ZERO);
+    debug_eval::<M31Config, _, _, _>(
+        &P256VerifyCircuit::default(),
+        &assignment,
+        hints::<M31Config>(),
+    );
+}
+
+#[test]
+fn test_secp256k1_recover_m31() {
+    let sig = sign(&NativeCurve::secp256k1(), b"recover me");
+    let assignment = Secp256k1RecoverCircuit::<M31> {
+        sig: sig.assignment(),
+        v: M31::from(sig.v as u32),
+    };
+    debug_eval::<M31Config, _, _, _>(
+        &Secp256k1RecoverCircuit::default(),
+        &assignment,
+        hints::<M31Config>(),
+    );
+}
+
+#[test]
+fn test_p256_recover_bn254() {
+    type F = CircuitField<BN254Config>;
+    let sig = sign(&NativeCurve::p256(), b"recover me");
+    let assignment = P256RecoverCircuit::<F> {
+        sig: sig.assignment(),
+        v: F::from(sig.v as u32),
+    };
+    debug_eval::<BN254Config, _, _, _>(
+        &P256RecoverCircuit::default(),
+        &assignment,
+        hints::<BN254Config>(),
+    );
+}
@@ -0,0 +1,138 @@
+use circuit_std_rs::{
+    gnark::{
+        element::new_internal_element,
+        emparam::{FieldParams, RuntimeFieldParams, Secp256k1Fp},
+        field::GField,
+    },
+    utils::register_hint,
+};
+use expander_compiler::frontend::{extra::debug_eval, *};
+use num_bigint::BigInt;
+use num_traits::Num;
+
+declare_circuit!(FieldOpsCircuit {
+    a: [Variable; 32],
+    b: [Variable; 32],
+    prod: [Variable; 32],
+    quot: [Variable; 32],
+    inv: [Variable; 32],
+    square: [Variable; 32],
+});
+
+fn check_field_ops<C: Config, B: RootAPI<C>, T: FieldParams + Default>(
+    builder: &mut B,
+    circuit: &FieldOpsCircuit<Variable>,
+) {
+    let mut fp = GField::new(builder, T::default());
+    let element = |limbs: &[Variable; 32]| new_internal_element::<T>(limbs.to_vec(), 0);
+    let (a, b) = (element(&circuit.a), element(&circuit.b));
+
+    let prod = fp.mul(builder, &a, &b);
+    fp.assert_is_equal(builder, &prod, &element(&circuit.prod));
+    let quot = fp.div(builder, &a, &b);
+    fp.assert_is_equal(builder, &quot, &element(&circuit.quot));
+    let inv = fp.inverse(builder, &b);
+    fp.assert_is_equal(builder, &inv, &element(&circuit.inv));
+    // the root is only checked to square back to its input
+    fp.sqrt(builder, &element(&circuit.square));
+
+    fp.check_mul(builder);
+    fp.table.final_check(builder);
+}
+
+impl<C: Config> Define<C> for FieldOpsCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        check_field_ops::<C, Builder, Secp256k1Fp>(builder, self);
+    }
+}
+
+declare_circuit!(RuntimeFieldOpsCircuit {
+    ops: [[Variable; 32]; 6],
+});
+
+impl<C: Config> Define<C> for RuntimeFieldOpsCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        let ops = FieldOpsCircuit {
+            a: self.ops[0],
+            b: self.ops[1],
+            prod: self.ops[2],
+            quot: self.ops[3],
+            inv: self.ops[4],
+            square: self.ops[5],
+        };
+        check_field_ops::<C, Builder, RuntimeFieldParams<0>>(builder, &ops);
+    }
+}
+
+fn to_limbs<F: From<u32>>(x: &BigInt) -> [F; 32] {
+    let (_, bytes) = x.to_bytes_le();
+    std::array::from_fn(|i| F::from(bytes.get(i).copied().unwrap_or(0) as u32))
+}
+
+fn field_ops<F: From<u32>>(p: &BigInt, a: &BigInt, b: &BigInt) -> [[F; 32]; 6] {
+    let inv = b.modinv(p).unwrap();
+    [
+        to_limbs(a),
+        to_limbs(b),
+        to_limbs(&(a * b % p)),
+        to_limbs(&(a * &inv % p)),
+        to_limbs(&inv),
+        to_limbs(&(a * a % p)),
+    ]
+}
+
+fn test_secp256k1_ops<C: Config>()
+where
+    CircuitField<C>: From<u32>,
+{
+    let p = Secp256k1Fp::modulus();
+    let a = BigInt::from_str_radix(
+        "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        16,
+    )
+    .unwrap();
+    let b = BigInt::from_str_radix(
+        "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+        16,
+    )
+    .unwrap();
+    let [a, b, prod, quot, inv, square] = field_ops(&p, &a, &b);
+    let assignment = FieldOpsCircuit::<CircuitField<C>> {
+        a,
+        b,
+        prod,
+        quot,
+        inv,
+        square,
+    };
+    let mut hint_registry = HintRegistry::<CircuitField<C>>::new();
+    register_hint(&mut hint_registry);
+    debug_eval::<C, _, _, _>(&FieldOpsCircuit::default(), &assignment, hint_registry);
+}
+
+#[test]
+fn test_secp256k1_ops_m31() {
+    test_secp256k1_ops::<M31Config>();
+}
+
+#[test]
+fn test_secp256k1_ops_bn254() {
+    test_secp256k1_ops::<BN254Config>();
+}
+
+#[test]
+fn test_runtime_field_ops() {
+    // the ed25519 base field, 2^255 - 19
+    let p = (BigInt::from(1) << 255) - 19;
+    RuntimeFieldParams::<0>::set(p.clone(), 8);
+    let assignment = RuntimeFieldOpsCircuit::<M31> {
+        ops: field_ops(&p, &BigInt::from(9), &(&p - 12345)),
+    };
+    let mut hint_registry = HintRegistry::<M31>::new();
+    register_hint(&mut hint_registry);
+    debug_eval::<M31Config, _, _, _>(
+        &RuntimeFieldOpsCircuit::default(),
+        &assignment,
+        hint_registry,
+    );
+}
@@ -0,0 +1,408 @@
+use circuit_std_rs::{
+    ecdsa::{NativeCurve, NativePoint},
+    gnark::{
+        element::Element,
+        emparam::{BN254Fr, FieldParams},
+        emulated::sw_bn254::{g1::G1Affine, g2::G2Affine, pairing::Pairing},
+    },
+    kzg::{
+        expander,
+        native::{self, g2_generator, g2_mul, NativeG2, NativeHyperKzgChallenges, Srs},
+        HyperKzgChallenges, HyperKzgOpening, KzgVerifier,
+    },
+    utils::register_hint,
+};
+use expander_compiler::frontend::{extra::debug_eval, *};
+use expander_compiler::zkcuda::proving_system::expander::{
+    commit_impl::local_commit_impl,
+    prove_impl::pcs_local_open_impl,
+    structs::{ExpanderProverSetup, ExpanderVerifierSetup},
+    utils::pcs_testing_setup_fixed_seed,
+    verify_impl::verify_pcs,
+};
+use expander_compiler::zkcuda::proving_system::expander_pcs_defered::BN254ConfigSha2UniKZG;
+use gkr_engine::{
+    ExpanderPCS, ExpanderSingleVarChallenge, GKREngine, MPIConfig, StructuredReferenceString,
+    Transcript,
+};
+use halo2curves::bn256::Fr;
+use num_bigint::BigInt;
+use serdes::ExpSerde;
+use std::collections::HashMap;
+use std::io::Cursor;
+
+const DEGREE: usize = 4;
+// variables of the multilinear polynomials opened with HyperKZG
+const NUM_VARS: usize = 3;
+
+type ZkCudaFieldConfig = <BN254ConfigSha2UniKZG as GKREngine>::FieldConfig;
+type ZkCudaTranscript = <BN254ConfigSha2UniKZG as GKREngine>::TranscriptConfig;
+type ZkCudaPcs = <BN254ConfigSha2UniKZG as GKREngine>::PCSConfig;
+type ZkCudaSrs = <ZkCudaPcs as ExpanderPCS<ZkCudaFieldConfig>>::SRS;
+type ZkCudaPKey = <ZkCudaSrs as StructuredReferenceString>::PKey;
+type ZkCudaVKey = <ZkCudaSrs as StructuredReferenceString>::VKey;
+
+type G1Vars<T> = [[T; 32]; 2];
+type G2Vars<T> = [[[T; 32]; 2]; 2];
+
+fn g1_from_vars(p: &G1Vars<Variable>) -> G1Affine {
+    G1Affine::from_vars(p[0].to_vec(), p[1].to_vec())
+}
+
+fn g2_from_vars(p: &G2Vars<Variable>) -> G2Affine {
+    G2Affine::from_vars(
+        p[0][0].to_vec(),
+        p[0][1].to_vec(),
+        p[1][0].to_vec(),
+        p[1][1].to_vec(),
+    )
+}
+
+fn fr_from_vars(x: &[Variable; 32]) -> Element<BN254Fr> {
+    Element::new(x.to_vec(), 0, false, false, false, Variable::default())
+}
+
+// the trapdoor is public, the setup is only fit for tests
+fn test_setup() -> (BigInt, Srs) {
+    let tau = BigInt::parse_bytes(b"1234567890123456789012345678901234567890", 10).unwrap();
+    let srs = native::setup(&tau, DEGREE);
+    (tau, srs)
+}
+
+declare_circuit!(KzgVerifyCircuit {
+    commitment: [[Variable; 32]; 2],
+    point: [Variable; 32],
+    value: [Variable; 32],
+    proof: [[Variable; 32]; 2],
+});
+
+impl Define<M31Config> for KzgVerifyCircuit<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, builder: &mut Builder) {
+        let (_, srs) = test_setup();
+        let mut verifier = KzgVerifier::new(builder, &srs.vk);
+        verifier.verify(
+            builder,
+            &g1_from_vars(&self.commitment),
+            &fr_from_vars(&self.point),
+            &fr_from_vars(&self.value),
+            &g1_from_vars(&self.proof),
+        );
+        verifier.finalize(builder);
+    }
+}
+
+declare_circuit!(HyperKzgVerifyCircuit {
+    commitment: [[Variable; 32]; 2],
+    point: [[Variable; 32]; NUM_VARS],
+    value: [Variable; 32],
+    folded_oracle_commitments: [[[Variable; 32]; 2]; NUM_VARS - 1],
+    beta2_evals: [[Variable; 32]; NUM_VARS],
+    pos_beta_evals: [[Variable; 32]; NUM_VARS],
+    neg_beta_evals: [[Variable; 32]; NUM_VARS],
+    beta_x_commitment: [[Variable; 32]; 2],
+    quotient_delta_x_commitment: [[Variable; 32]; 2],
+    challenges: [[Variable; 32]; 3],
+});
+
+impl Define<M31Config> for HyperKzgVerifyCircuit<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, builder: &mut Builder) {
+        let (_, v_key) = zkcuda_setup();
+        let mut verifier = KzgVerifier::new(builder, &expander::verifying_key_from(&v_key));
+        let frs = |xs: &[[Variable; 32]]| xs.iter().map(fr_from_vars).collect::<Vec<_>>();
+        let opening = HyperKzgOpening {
+            folded_oracle_commitments: self
+                .folded_oracle_commitments
+                .iter()
+                .map(g1_from_vars)
+                .collect(),
+            beta2_evals: frs(&self.beta2_evals),
+            pos_beta_evals: frs(&self.pos_beta_evals),
+            neg_beta_evals: frs(&self.neg_beta_evals),
+            beta_x_commitment: g1_from_vars(&self.beta_x_commitment),
+            quotient_delta_x_commitment: g1_from_vars(&self.quotient_delta_x_commitment),
+        };
+        let challenges = HyperKzgChallenges {
+            beta: fr_from_vars(&self.challenges[0]),
+            gamma: fr_from_vars(&self.challenges[1]),
+            delta: fr_from_vars(&self.challenges[2]),
+        };
+        verifier.verify_multilinear(
+            builder,
+            &g1_from_vars(&self.commitment),
+            &frs(&self.point),
+            &fr_from_vars(&self.value),
+            &opening,
+            &challenges,
+        );
+        verifier.finalize(builder);
+    }
+}
+
+declare_circuit!(PairingCheckCircuit {
+    p: [[[Variable; 32]; 2]; 2],
+    q: [[[[Variable; 32]; 2]; 2]; 2],
+});
+
+impl Define<M31Config> for PairingCheckCircuit<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, builder: &mut Builder) {
+        let mut pairing = Pairing::new(builder);
+        let p = self.p.iter().map(g1_from_vars).collect::<Vec<_>>();
+        let q = self.q.iter().map(g2_from_vars).collect::<Vec<_>>();
+        for q in &q {
+            pairing.g2.assert_is_on_curve(builder, q);
+        }
+        pairing.pairing_check(builder, &p, &q).unwrap();
+        pairing.finalize(builder);
+    }
+}
+
+fn bytes(x: &BigInt) -> [M31; 32] {
+    let (_, le) = x.to_bytes_le();
+    std::array::from_fn(|i| M31::from(le.get(i).copied().unwrap_or(0) as u32))
+}
+
+fn g1_assignment(p: &NativePoint) -> G1Vars<M31> {
+    [bytes(&p.x), bytes(&p.y)]
+}
+
+fn g2_assignment(p: &NativeG2) -> G2Vars<M31> {
+    [
+        [bytes(&p.x[0]), bytes(&p.x[1])],
+        [bytes(&p.y[0]), bytes(&p.y[1])],
+    ]
+}
+
+fn hints() -> HintRegistry<M31> {
+    let mut hint_registry = HintRegistry::<M31>::new();
+    register_hint(&mut hint_registry);
+    hint_registry
+}
+
+fn polynomial() -> Vec<BigInt> {
+    [3u64, 141, 59, 26, 5358].map(BigInt::from).to_vec()
+}
+
+fn kzg_assignment(point: &BigInt) -> KzgVerifyCircuit<M31> {
+    let (_, srs) = test_setup();
+    let coeffs = polynomial();
+    let commitment = native::commit(&srs, &coeffs).unwrap();
+    let (value, proof) = native::open(&srs, &coeffs, point);
+    KzgVerifyCircuit {
+        commitment: g1_assignment(&commitment),
+        point: bytes(point),
+        value: bytes(&value),
+        proof: g1_assignment(&proof.unwrap()),
+    }
+}
+
+// the keys ZKCuda derives from its fixed seed for polynomials with NUM_VARS variables
+fn zkcuda_setup() -> (ZkCudaPKey, ZkCudaVKey) {
+    let (_, p_key, v_key, _) = pcs_testing_setup_fixed_seed::<
+        ZkCudaFieldConfig,
+        ZkCudaTranscript,
+        ZkCudaPcs,
+    >(1 << NUM_VARS, &MPIConfig::prover_new());
+    (p_key, v_key)
+}
+
+// Commits to vals and opens them at point the way a ZKCuda BN254 KZG proof does, checks the
+// opening with the PCS verifier and converts it into a circuit assignment
+fn zkcuda_hyper_kzg_assignment(vals: &[Fr], point: &[Fr]) -> HyperKzgVerifyCircuit<M31> {
+    let (p_key, v_key) = zkcuda_setup();
+    let (commitment, _) = local_commit_impl::<BN254ConfigSha2UniKZG, BN254Config>(&p_key, vals);
+    let challenge = ExpanderSingleVarChallenge::<ZkCudaFieldConfig> {
+        rz: point.to_vec(),
+        r_simd: vec![],
+        r_mpi: vec![],
+    };
+    let p_keys = ExpanderProverSetup {
+        p_keys: HashMap::from([(vals.len(), p_key)]),
+    };
+    let mut transcript = ZkCudaTranscript::new();
+    pcs_local_open_impl::<BN254ConfigSha2UniKZG>(vals, &challenge, &p_keys, &mut transcript);
+    let proof = transcript.finalize_and_get_proof();
+
+    // the claimed evaluation, then the opening
+    let mut reader = Cursor::new(&proof.bytes);
+    let value = Fr::deserialize_from(&mut reader).unwrap();
+    let opening_start = reader.position() as usize;
+    let opening =
+        <ZkCudaPcs as ExpanderPCS<ZkCudaFieldConfig>>::Opening::deserialize_from(&mut reader)
+            .unwrap();
+    let mut transcript = ZkCudaTranscript::new();
+    transcript.append_field_element(&value);
+    let challenges = expander::hyper_kzg_challenges(&opening, &mut transcript.clone());
+    let v_keys = ExpanderVerifierSetup {
+        v_keys: HashMap::from([(vals.len(), v_key)]),
+    };
+    assert!(verify_pcs::<BN254ConfigSha2UniKZG, BN254Config>(
+        &proof.bytes[opening_start..],
+        &commitment,
+        &challenge,
+        &value,
+        &v_keys,
+        &mut transcript,
+    ));
+
+    let commitment = expander::commitment_to_native(&commitment.commitment).unwrap();
+    let opening = expander::hyper_opening_from(&opening).unwrap();
+    let frs = |xs: &[BigInt]| std::array::from_fn(|i| bytes(&xs[i]));
+    let NativeHyperKzgChallenges { beta, gamma, delta } = challenges;
+    HyperKzgVerifyCircuit {
+        commitment: g1_assignment(&commitment),
+        point: std::array::from_fn(|i| bytes(&expander::fr_to_bigint(&point[i]))),
+        value: bytes(&expander::fr_to_bigint(&value)),
+        folded_oracle_commitments: std::array::from_fn(|i| {
+            g1_assignment(&opening.folded_oracle_commitments[i])
+        }),
+        beta2_evals: frs(&opening.beta2_evals),
+        pos_beta_evals: frs(&opening.pos_beta_evals),
+        neg_beta_evals: frs(&opening.neg_beta_evals),
+        beta_x_commitment: g1_assignment(&opening.beta_x_commitment),
+        quotient_delta_x_commitment: g1_assignment(&opening.quotient_delta_x_commitment),
+        challenges: [bytes(&beta), bytes(&gamma), bytes(&delta)],
+    }
+}
+
+fn zkcuda_hyper_kzg_test_assignment() -> HyperKzgVerifyCircuit<M31> {
+    let vals = (0..1u64 << NUM_VARS)
+        .map(|i| Fr::from(i * i + 7))
+        .collect::<Vec<_>>();
+    let point = [
+        Fr::from(123456789u64),
+        Fr::from(42u64),
+        Fr::from(987654321u64),
+    ];
+    zkcuda_hyper_kzg_assignment(&vals, &point)
+}
+
+#[test]
+fn test_native_kzg_open() {
+    let (tau, srs) = test_setup();
+    let coeffs = polynomial();
+    let commitment = native::commit(&srs, &coeffs);
+    let z = BigInt::from(987654321);
+    let (y, proof) = native::open(&srs, &coeffs, &z);
+    let expected = coeffs
+        .iter()
+        .rev()
+        .fold(BigInt::from(0), |acc, c| acc * &z + c);
+    assert_eq!(y, expected);
+    assert!(native::verify_with_trapdoor(
+        &srs,
+        &tau,
+        &commitment,
+        &z,
+        &y,
+        &proof
+    ));
+    assert!(!native::verify_with_trapdoor(
+        &srs,
+        &tau,
+        &commitment,
+        &z,
+        &(y + 1),
+        &proof
+    ));
+}
+
+// e(aP, bQ)·e(-abP, Q) == 1
+#[test]
+fn test_bn254_pairing_check() {
+    let curve = NativeCurve::bn254();
+    let (a, b) = (BigInt::from(1234567), BigInt::from(7654321));
+    let g1 = Some(curve.generator());
+    let g2 = Some(g2_generator());
+    let assignment = PairingCheckCircuit::<M31> {
+        p: [
+            g1_assignment(&curve.mul(&a, &g1).unwrap()),
+            g1_assignment(&curve.neg(&curve.mul(&(&a * &b), &g1)).unwrap()),
+        ],
+        q: [
+            g2_assignment(&g2_mul(&b, &g2).unwrap()),
+            g2_assignment(g2.as_ref().unwrap()),
+        ],
+    };
+    debug_eval(&PairingCheckCircuit::default(), &assignment, hints());
+}
+
+#[test]
+#[should_panic]
+fn test_bn254_pairing_check_fails() {
+    let curve = NativeCurve::bn254();
+    let (a, b) = (BigInt::from(1234567), BigInt::from(7654321));
+    let g1 = Some(curve.generator());
+    let g2 = Some(g2_generator());
+    let assignment = PairingCheckCircuit::<M31> {
+        p: [
+            g1_assignment(&curve.mul(&a, &g1).unwrap()),
+            g1_assignment(&curve.neg(&curve.mul(&(&a * &b + 1), &g1)).unwrap()),
+        ],
+        q: [
+            g2_assignment(&g2_mul(&b, &g2).unwrap()),
+            g2_assignment(g2.as_ref().unwrap()),
+        ],
+    };
+    debug_eval(&PairingCheckCircuit::default(), &assignment, hints());
+}
+
+#[test]
+fn test_kzg_verify() {
+    let assignment = kzg_assignment(&BigInt::from(987654321));
+    debug_eval(&KzgVerifyCircuit::default(), &assignment, hints());
+}
+
+#[test]
+#[should_panic]
+fn test_kzg_verify_wrong_value() {
+    let mut assignment = kzg_assignment(&BigInt::from(987654321));
+    assignment.value[0] += M31::from(1);
+    debug_eval(&KzgVerifyCircuit::default(), &assignment, hints());
+}
+
+#[test]
+fn test_native_hyper_kzg_open() {
+    let (tau, _) = test_setup();
+    let srs = native::setup(&tau, (1 << NUM_VARS) - 1);
+    let evals = (0..1u64 << NUM_VARS)
+        .map(|i| BigInt::from(i * i + 7))
+        .collect::<Vec<_>>();
+    let point = [123456789u64, 42, 987654321].map(BigInt::from);
+    let challenges = NativeHyperKzgChallenges {
+        beta: BigInt::from(1111),
+        gamma: BigInt::from(2222),
+        delta: BigInt::from(3333),
+    };
+    let (value, opening) = native::hyper_open(&srs, &evals, &point, &challenges);
+    // the multilinear extension with the first coordinate folding adjacent evaluations
+    let expected = point.iter().fold(evals.clone(), |f, x| {
+        f.chunks(2).map(|c| &c[0] + x * (&c[1] - &c[0])).collect()
+    })[0]
+        .clone();
+    assert_eq!(value, expected % BN254Fr::modulus());
+
+    let commitment = native::commit(&srs, &evals).unwrap();
+    let (reduced, z, y) =
+        native::hyper_reduce(&commitment, &point, &value, &opening, &challenges).unwrap();
+    let proof = Some(opening.quotient_delta_x_commitment.clone());
+    assert!(native::verify_with_trapdoor(
+        &srs, &tau, &reduced, &z, &y, &proof
+    ));
+    assert!(
+        native::hyper_reduce(&commitment, &point, &(value + 1), &opening, &challenges).is_none()
+    );
+}
+
+#[test]
+fn test_zkcuda_hyper_kzg_verify() {
+    let assignment = zkcuda_hyper_kzg_test_assignment();
+    debug_eval(&HyperKzgVerifyCircuit::default(), &assignment, hints());
+}
+
+#[test]
+#[should_panic]
+fn test_zkcuda_hyper_kzg_verify_wrong_value() {
+    let mut assignment = zkcuda_hyper_kzg_test_assignment();
+    assignment.value[0] += M31::from(1);
+    debug_eval(&HyperKzgVerifyCircuit::default(), &assignment, hints());
+}
@@ -1,7 +1,10 @@
+pub mod bls;
+pub mod ecdsa;
 pub mod element;
 // pub mod emparam;
 pub mod emulated;
-// pub mod field;
+pub mod field;
+pub mod kzg;
 // pub mod hints;
 // pub mod limbs;
 // pub mod utils;
@@ -0,0 +1,232 @@
+use circuit_std_rs::keccak::{
+    keccak256,
+    kernel::{compile_keccak256_kernel, KERNEL_INPUT_BITS},
+    sha3_256, KeccakSponge,
+};
+use expander_compiler::{
+    frontend::{extra::debug_eval, *},
+    zkcuda::{context::*, kernel::*, proving_system::*},
+};
+use rand::{Rng, SeedableRng};
+use tiny_keccak::{Hasher, Keccak, Sha3};
+
+const MSG_LEN: usize = 300;
+// absorbed in pieces that end before, on and after block boundaries
+const CHUNKS: [usize; 4] = [7, 129, 0, 164];
+const LONG_OUTPUT_LEN: usize = 200;
+
+fn to_bits(bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .iter()
+        .flat_map(|b| (0..8).map(move |i| (*b >> i) as u32 & 1))
+        .collect()
+}
+
+fn random_bytes(seed: u64, len: usize) -> Vec<u8> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    (0..len).map(|_| rng.gen()).collect()
+}
+
+declare_circuit!(KeccakChunksCircuit {
+    input: [Variable; MSG_LEN * 8],
+    output: [Variable; 256],
+});
+
+impl<C: Config> Define<C> for KeccakChunksCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, api: &mut Builder) {
+        let mut sponge = KeccakSponge::keccak256(api);
+        let mut start = 0;
+        for len in CHUNKS {
+            sponge.absorb(api, &self.input[start * 8..(start + len) * 8]);
+            start += len;
+        }
+        let digest = sponge.squeeze(api, 32);
+        for i in 0..256 {
+            api.assert_is_equal(digest[i], self.output[i]);
+        }
+    }
+}
+
+declare_circuit!(Sha3BlockCircuit {
+    input: [Variable; 136 * 8],
+    output: [Variable; 256],
+});
+
+impl<C: Config> Define<C> for Sha3BlockCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, api: &mut Builder) {
+        let digest = sha3_256(api, &self.input);
+        for i in 0..256 {
+            api.assert_is_equal(digest[i], self.output[i]);
+        }
+    }
+}
+
+declare_circuit!(KeccakLongOutputCircuit {
+    input: [Variable; 64 * 8],
+    output: [Variable; LONG_OUTPUT_LEN * 8],
+});
+
+impl<C: Config> Define<C> for KeccakLongOutputCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, api: &mut Builder) {
+        let mut sponge = KeccakSponge::keccak256(api);
+        sponge.absorb(api, &self.input);
+        // squeezed in two calls, the second one crossing into the next block
+        let mut digest = sponge.squeeze(api, 100);
+        digest.extend(sponge.squeeze(api, LONG_OUTPUT_LEN - 100));
+        for i in 0..LONG_OUTPUT_LEN * 8 {
+            api.assert_is_equal(digest[i], self.output[i]);
+        }
+    }
+}
+
+fn keccak_chunks_helper<C: Config>(seed: u64, corrupt: bool) {
+    let data = random_bytes(seed, MSG_LEN);
+    let mut hasher = Keccak::v256();
+    hasher.update(&data);
+    let mut digest = [0u8; 32];
+    hasher.finalize(&mut digest);
+    if corrupt {
+        digest[5] ^= 0x10;
+    }
+    let mut assignment = KeccakChunksCircuit::<CircuitField<C>>::default();
+    for (i, bit) in to_bits(&data).into_iter().enumerate() {
+        assignment.input[i] = CircuitField::<C>::from(bit);
+    }
+    for (i, bit) in to_bits(&digest).into_iter().enumerate() {
+        assignment.output[i] = CircuitField::<C>::from(bit);
+    }
+    debug_eval::<C, _, _, _>(
+        &KeccakChunksCircuit::default(),
+        &assignment,
+        EmptyHintCaller,
+    );
+}
+
+#[test]
+fn test_keccak256_chunks() {
+    keccak_chunks_helper::<GF2Config>(1, false);
+    keccak_chunks_helper::<M31Config>(2, false);
+    keccak_chunks_helper::<BN254Config>(3, false);
+}
+
+#[test]
+#[should_panic]
+fn test_keccak256_wrong_digest_gf2() {
+    keccak_chunks_helper::<GF2Config>(4, true);
+}
+
+#[test]
+#[should_panic]
+fn test_keccak256_wrong_digest_m31() {
+    keccak_chunks_helper::<M31Config>(5, true);
+}
+
+fn sha3_block_helper<C: Config>(seed: u64) {
+    let data = random_bytes(seed, 136);
+    let mut hasher = Sha3::v256();
+    hasher.update(&data);
+    let mut digest = [0u8; 32];
+    hasher.finalize(&mut digest);
+    let mut assignment = Sha3BlockCircuit::<CircuitField<C>>::default();
+    for (i, bit) in to_bits(&data).into_iter().enumerate() {
+        assignment.input[i] = CircuitField::<C>::from(bit);
+    }
+    for (i, bit) in to_bits(&digest).into_iter().enumerate() {
+        assignment.output[i] = CircuitField::<C>::from(bit);
+    }
+    debug_eval::<C, _, _, _>(&Sha3BlockCircuit::default(), &assignment, EmptyHintCaller);
+}
+
+#[test]
+fn test_sha3_256_full_block() {
+    sha3_block_helper::<GF2Config>(6);
+    sha3_block_helper::<M31Config>(7);
+}
+
+#[test]
+fn test_keccak_long_output() {
+    let data = random_bytes(8, 64);
+    let mut hasher = Keccak::v256();
+    hasher.update(&data);
+    let mut output = [0u8; LONG_OUTPUT_LEN];
+    hasher.finalize(&mut output);
+    let mut assignment = KeccakLongOutputCircuit::<GF2>::default();
+    for (i, bit) in to_bits(&data).into_iter().enumerate() {
+        assignment.input[i] = GF2::from(bit);
+    }
+    for (i, bit) in to_bits(&output).into_iter().enumerate() {
+        assignment.output[i] = GF2::from(bit);
+    }
+    debug_eval::<GF2Config, _, _, _>(
+        &KeccakLongOutputCircuit::default(),
+        &assignment,
+        EmptyHintCaller,
+    );
+}
+
+declare_circuit!(Keccak256Circuit {
+    input: [Variable; 64 * 8],
+    output: [Variable; 256],
+});
+
+impl<C: Config> Define<C> for Keccak256Circuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, api: &mut Builder) {
+        let digest = keccak256(api, &self.input);
+        for i in 0..256 {
+            api.assert_is_equal(digest[i], self.output[i]);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_keccak256_non_boolean_input() {
+    let mut assignment = Keccak256Circuit::<M31>::default();
+    assignment.input[0] = M31::from(2);
+    debug_eval::<M31Config, _, _, _>(&Keccak256Circuit::default(), &assignment, EmptyHintCaller);
+}
+
+#[test]
+fn test_keccak256_kernel() {
+    const N: usize = 2;
+    let kernel: KernelPrimitive<M31Config> = compile_keccak256_kernel().unwrap();
+    let mut ctx: Context<M31Config> = Context::default();
+
+    let mut input = vec![];
+    let mut expected = vec![];
+    for i in 0..N {
+        let data = random_bytes(10 + i as u64, KERNEL_INPUT_BITS / 8);
+        let mut hasher = Keccak::v256();
+        hasher.update(&data);
+        let mut digest = [0u8; 32];
+        hasher.finalize(&mut digest);
+        input.push(
+            to_bits(&data)
+                .into_iter()
+                .map(M31::from)
+                .collect::<Vec<_>>(),
+        );
+        expected.push(
+            to_bits(&digest)
+                .into_iter()
+                .map(M31::from)
+                .collect::<Vec<_>>(),
+        );
+    }
+    let input = ctx.copy_to_device(&input);
+    let mut output = None;
+    call_kernel!(ctx, kernel, N, input, mut output).unwrap();
+    let output: Vec<Vec<M31>> = ctx.copy_to_host(output);
+    assert_eq!(output, expected);
+
+    type P = Expander<M31Config>;
+    let computation_graph = ctx.compile_computation_graph().unwrap();
+    ctx.solve_witness().unwrap();
+    let (prover_setup, verifier_setup) = <P as ProvingSystem<M31Config>>::setup(&computation_graph);
+    let proof = P::prove(
+        &prover_setup,
+        &computation_graph,
+        ctx.export_device_memories(),
+    );
+    assert!(P::verify(&verifier_setup, &computation_graph, &proof));
+}
@@ -0,0 +1,264 @@
+use circuit_std_rs::{
+    merkle::{
+        update_leaf, verify_multi_proof, verify_path, KeccakHasher, MerkleHasher, MerkleTree,
+        NativeKeccakHasher, NativeMerkleHasher, NativePoseidonM31Hasher, NativeSha256Hasher,
+        PoseidonM31Hasher, Sha256Hasher,
+    },
+    utils::register_hint,
+};
+use expander_compiler::frontend::{extra::debug_eval, *};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const DEPTH: usize = 4;
+// shared paths, a sibling pair and a lone leaf
+const MULTI_INDICES: [usize; 5] = [1, 2, 3, 9, 14];
+
+// M31 hashes with Poseidon, GF2 with Keccak and BN254 with SHA-256
+trait TestHasher<C: Config>: MerkleHasher<C> + Sized {
+    // leaf length and the bound of its values
+    const LEAF_SHAPE: (usize, u32);
+    type Native: NativeMerkleHasher + Default;
+    fn new<B: RootAPI<C>>(api: &mut B) -> Self;
+}
+
+impl TestHasher<M31Config> for PoseidonM31Hasher {
+    const LEAF_SHAPE: (usize, u32) = (3, 2147483647);
+    type Native = NativePoseidonM31Hasher;
+    fn new<B: RootAPI<M31Config>>(api: &mut B) -> Self {
+        PoseidonM31Hasher::new(api)
+    }
+}
+
+impl TestHasher<GF2Config> for KeccakHasher {
+    const LEAF_SHAPE: (usize, u32) = (64, 2);
+    type Native = NativeKeccakHasher;
+    fn new<B: RootAPI<GF2Config>>(_api: &mut B) -> Self {
+        KeccakHasher
+    }
+}
+
+impl TestHasher<BN254Config> for Sha256Hasher {
+    const LEAF_SHAPE: (usize, u32) = (64, 2);
+    type Native = NativeSha256Hasher;
+    fn new<B: RootAPI<BN254Config>>(api: &mut B) -> Self {
+        Sha256Hasher::new(api)
+    }
+}
+
+fn random_leaf(rng: &mut StdRng, (len, bound): (usize, u32)) -> Vec<u32> {
+    (0..len).map(|_| rng.gen_range(0..bound)).collect()
+}
+
+fn to_field<C: Config>(values: &[u32]) -> Vec<CircuitField<C>> {
+    values.iter().map(|v| CircuitField::<C>::from(*v)).collect()
+}
+
+fn variables(len: usize) -> Vec<Variable> {
+    vec![Variable::default(); len]
+}
+
+fn hints<C: Config>() -> HintRegistry<CircuitField<C>> {
+    let mut hint_registry = HintRegistry::<CircuitField<C>>::new();
+    register_hint(&mut hint_registry);
+    hint_registry
+}
+
+declare_circuit!(UpdateCircuit {
+    old_root: [Variable],
+    new_root: [Variable],
+    old_leaf: [Variable],
+    new_leaf: [Variable],
+    index_bits: [Variable],
+    siblings: [[Variable]],
+});
+
+fn define_update<C: Config, B: RootAPI<C>, H: TestHasher<C>>(
+    circuit: &UpdateCircuit<Variable>,
+    api: &mut B,
+) {
+    let mut hasher = H::new(api);
+    let new_root = update_leaf(
+        api,
+        &mut hasher,
+        &circuit.old_root,
+        &circuit.old_leaf,
+        &circuit.new_leaf,
+        &circuit.index_bits,
+        &circuit.siblings,
+    );
+    verify_path(
+        api,
+        &mut hasher,
+        &new_root,
+        &circuit.new_leaf,
+        &circuit.index_bits,
+        &circuit.siblings,
+    );
+    for (x, y) in new_root.iter().zip(&circuit.new_root) {
+        api.assert_is_equal(*x, *y);
+    }
+    hasher.final_check(api);
+}
+
+impl Define<M31Config> for UpdateCircuit<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, api: &mut Builder) {
+        define_update::<_, _, PoseidonM31Hasher>(self, api);
+    }
+}
+
+impl Define<GF2Config> for UpdateCircuit<Variable> {
+    fn define<Builder: RootAPI<GF2Config>>(&self, api: &mut Builder) {
+        define_update::<_, _, KeccakHasher>(self, api);
+    }
+}
+
+impl Define<BN254Config> for UpdateCircuit<Variable> {
+    fn define<Builder: RootAPI<BN254Config>>(&self, api: &mut Builder) {
+        define_update::<_, _, Sha256Hasher>(self, api);
+    }
+}
+
+fn update_helper<C: Config, H: TestHasher<C>>(seed: u64, index: usize, corrupt: bool)
+where
+    UpdateCircuit<Variable>: Define<C>,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let leaves = (0..1 << DEPTH)
+        .map(|_| random_leaf(&mut rng, H::LEAF_SHAPE))
+        .collect();
+    let mut tree = MerkleTree::new(H::Native::default(), DEPTH, leaves);
+    let old_root = tree.root();
+    let old_leaf = tree.leaf(index).to_vec();
+    let mut path = tree.path(index);
+    let new_leaf = random_leaf(&mut rng, H::LEAF_SHAPE);
+    tree.update(index, new_leaf.clone());
+    // the path of the other leaves changes, not the one of the updated leaf
+    assert_eq!(tree.path(index), path);
+    if corrupt {
+        path.siblings[DEPTH - 1][0] ^= 1;
+    }
+
+    let digest_len = path.siblings[0].len();
+    let circuit = UpdateCircuit::<Variable> {
+        old_root: variables(digest_len),
+        new_root: variables(digest_len),
+        old_leaf: variables(old_leaf.len()),
+        new_leaf: variables(new_leaf.len()),
+        index_bits: variables(DEPTH),
+        siblings: vec![variables(digest_len); DEPTH],
+    };
+    let assignment = UpdateCircuit::<CircuitField<C>> {
+        old_root: to_field::<C>(&old_root),
+        new_root: to_field::<C>(&tree.root()),
+        old_leaf: to_field::<C>(&old_leaf),
+        new_leaf: to_field::<C>(&new_leaf),
+        index_bits: to_field::<C>(&path.index_bits),
+        siblings: path.siblings.iter().map(|s| to_field::<C>(s)).collect(),
+    };
+    debug_eval::<C, _, _, _>(&circuit, &assignment, hints::<C>());
+}
+
+#[test]
+fn test_merkle_update_leaf() {
+    update_helper::<M31Config, PoseidonM31Hasher>(1, 5, false);
+    update_helper::<GF2Config, KeccakHasher>(2, 0, false);
+    update_helper::<BN254Config, Sha256Hasher>(3, 15, false);
+}
+
+#[test]
+#[should_panic]
+fn test_merkle_update_wrong_sibling() {
+    update_helper::<M31Config, PoseidonM31Hasher>(4, 6, true);
+}
+
+#[test]
+#[should_panic]
+fn test_merkle_update_wrong_sibling_keccak() {
+    update_helper::<GF2Config, KeccakHasher>(5, 11, true);
+}
+
+declare_circuit!(MultiProofCircuit {
+    root: [Variable],
+    leaves: [[Variable]],
+    proof: [[Variable]],
+});
+
+fn define_multi_proof<C: Config, B: RootAPI<C>, H: TestHasher<C>>(
+    circuit: &MultiProofCircuit<Variable>,
+    api: &mut B,
+) {
+    let mut hasher = H::new(api);
+    verify_multi_proof(
+        api,
+        &mut hasher,
+        &circuit.root,
+        DEPTH,
+        &MULTI_INDICES,
+        &circuit.leaves,
+        &circuit.proof,
+    );
+    hasher.final_check(api);
+}
+
+impl Define<M31Config> for MultiProofCircuit<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, api: &mut Builder) {
+        define_multi_proof::<_, _, PoseidonM31Hasher>(self, api);
+    }
+}
+
+impl Define<GF2Config> for MultiProofCircuit<Variable> {
+    fn define<Builder: RootAPI<GF2Config>>(&self, api: &mut Builder) {
+        define_multi_proof::<_, _, KeccakHasher>(self, api);
+    }
+}
+
+impl Define<BN254Config> for MultiProofCircuit<Variable> {
+    fn define<Builder: RootAPI<BN254Config>>(&self, api: &mut Builder) {
+        define_multi_proof::<_, _, Sha256Hasher>(self, api);
+    }
+}
+
+fn multi_proof_helper<C: Config, H: TestHasher<C>>(seed: u64, wrong_leaf: bool)
+where
+    MultiProofCircuit<Variable>: Define<C>,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let leaves = (0..1 << DEPTH)
+        .map(|_| random_leaf(&mut rng, H::LEAF_SHAPE))
+        .collect::<Vec<_>>();
+    let tree = MerkleTree::new(H::Native::default(), DEPTH, leaves.clone());
+    let proof = tree.multi_proof(&MULTI_INDICES);
+    // siblings of 1, 9 and 14 on the leaf level, of 9 and 14 on the next one and of 1, 2, 3 on
+    // the one after, the two halves only meet below the root
+    assert_eq!(proof.len(), 6);
+    let mut opened = MULTI_INDICES.map(|i| leaves[i].clone()).to_vec();
+    if wrong_leaf {
+        opened[3] = leaves[8].clone();
+    }
+
+    let digest_len = tree.root().len();
+    let circuit = MultiProofCircuit::<Variable> {
+        root: variables(digest_len),
+        leaves: vec![variables(H::LEAF_SHAPE.0); MULTI_INDICES.len()],
+        proof: vec![variables(digest_len); proof.len()],
+    };
+    let assignment = MultiProofCircuit::<CircuitField<C>> {
+        root: to_field::<C>(&tree.root()),
+        leaves: opened.iter().map(|l| to_field::<C>(l)).collect(),
+        proof: proof.iter().map(|p| to_field::<C>(p)).collect(),
+    };
+    debug_eval::<C, _, _, _>(&circuit, &assignment, hints::<C>());
+}
+
+#[test]
+fn test_merkle_multi_proof() {
+    multi_proof_helper::<M31Config, PoseidonM31Hasher>(10, false);
+    multi_proof_helper::<GF2Config, KeccakHasher>(11, false);
+    multi_proof_helper::<BN254Config, Sha256Hasher>(12, false);
+}
+
+#[test]
+#[should_panic]
+fn test_merkle_multi_proof_wrong_leaf() {
+    multi_proof_helper::<M31Config, PoseidonM31Hasher>(13, true);
+}
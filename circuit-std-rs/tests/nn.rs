@@ -0,0 +1,449 @@
+use circuit_std_rs::{
+    nn::{
+        self,
+        kernel::{conv2d_kernel, layer_norm_kernel, relu_kernel, softmax_kernel},
+        native::{self, to_field},
+        Activation, ActivationTable, FixedPoint, Tensor,
+    },
+    utils::register_hint,
+};
+use expander_compiler::frontend::{extra::debug_eval, *};
+use expander_compiler::zkcuda::{context::*, kernel::*, proving_system::*, shape::Reshape};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const Q: FixedPoint = FixedPoint {
+    bits: 8,
+    frac_bits: 4,
+};
+const RSQRT_DOMAIN_BITS: usize = 12;
+const EPS: u64 = 1;
+// values of RESCALE_BITS bits, divided by 2^RESCALE_SHIFT and by DIVISOR
+const RESCALE_BITS: usize = 20;
+const RESCALE_SHIFT: usize = 5;
+const DIVISOR: u64 = 7;
+
+fn random_values(rng: &mut StdRng, len: usize, range: std::ops::Range<i64>) -> Vec<i64> {
+    (0..len).map(|_| rng.gen_range(range.clone())).collect()
+}
+
+fn to_fields<C: Config>(values: &[i64]) -> Vec<CircuitField<C>> {
+    values.iter().map(|v| to_field(*v)).collect()
+}
+
+fn variables(len: usize) -> Vec<Variable> {
+    vec![Variable::default(); len]
+}
+
+fn hints<C: Config>() -> HintRegistry<CircuitField<C>> {
+    let mut hint_registry = HintRegistry::<CircuitField<C>>::new();
+    register_hint(&mut hint_registry);
+    hint_registry
+}
+
+fn assert_all_equal<C: Config, B: RootAPI<C>>(api: &mut B, a: &[Variable], b: &[Variable]) {
+    assert_eq!(a.len(), b.len());
+    for (x, y) in a.iter().zip(b) {
+        api.assert_is_equal(*x, *y);
+    }
+}
+
+declare_circuit!(RescaleCircuit {
+    x: [Variable],
+    rescaled: [Variable],
+    divided: [Variable],
+});
+
+impl<C: Config> Define<C> for RescaleCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, api: &mut Builder) {
+        for i in 0..self.x.len() {
+            let y = nn::rescale(api, self.x[i], RESCALE_BITS, RESCALE_SHIFT);
+            api.assert_is_equal(y, self.rescaled[i]);
+            let y = nn::div_round(api, self.x[i], RESCALE_BITS, DIVISOR);
+            api.assert_is_equal(y, self.divided[i]);
+        }
+    }
+}
+
+fn rescale_helper<C: Config>(seed: u64, corrupt: bool) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let bound = 1 << (RESCALE_BITS - 1);
+    let mut x = random_values(&mut rng, 16, -bound..bound);
+    // the extremes, and halves that round up on both signs
+    x.extend([-bound, bound - 1, 16, -16, 48, -48]);
+    let rescaled = x
+        .iter()
+        .map(|v| native::rescale(*v, RESCALE_SHIFT))
+        .collect::<Vec<_>>();
+    let mut divided = x
+        .iter()
+        .map(|v| native::div_round(*v, DIVISOR as i64))
+        .collect::<Vec<_>>();
+    assert_eq!(rescaled[rescaled.len() - 4..], [1, 0, 2, -1]);
+    if corrupt {
+        divided[3] += 1;
+    }
+
+    let circuit = RescaleCircuit::<Variable> {
+        x: variables(x.len()),
+        rescaled: variables(x.len()),
+        divided: variables(x.len()),
+    };
+    let assignment = RescaleCircuit::<CircuitField<C>> {
+        x: to_fields::<C>(&x),
+        rescaled: to_fields::<C>(&rescaled),
+        divided: to_fields::<C>(&divided),
+    };
+    debug_eval::<C, _, _, _>(&circuit, &assignment, hints::<C>());
+}
+
+#[test]
+fn test_nn_rescale() {
+    rescale_helper::<M31Config>(1, false);
+    rescale_helper::<BN254Config>(2, false);
+}
+
+#[test]
+#[should_panic]
+fn test_nn_rescale_wrong_rounding() {
+    rescale_helper::<M31Config>(3, true);
+}
+
+// a 6x6 image through conv2d, relu, both poolings and argmax
+declare_circuit!(ConvCircuit {
+    x: [Variable],
+    weight: [Variable],
+    bias: [Variable],
+    conv: [Variable],
+    relu: [Variable],
+    max_pool: [Variable],
+    avg_pool: [Variable],
+    argmax: Variable,
+});
+
+impl<C: Config> Define<C> for ConvCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, api: &mut Builder) {
+        let x = Tensor::new(vec![1, 6, 6], self.x.clone());
+        let weight = Tensor::new(vec![2, 1, 3, 3], self.weight.clone());
+        let conv = nn::conv2d(api, Q, &x, &weight, &self.bias, 1, 0);
+        assert_all_equal(api, &conv.data, &self.conv);
+        let relu = nn::relu(api, Q, &conv);
+        assert_all_equal(api, &relu.data, &self.relu);
+        let max_pool = nn::max_pool2d(api, Q, &relu, 2, 2);
+        assert_all_equal(api, &max_pool.data, &self.max_pool);
+        let avg_pool = nn::avg_pool2d(api, Q, &conv, 2, 2);
+        assert_all_equal(api, &avg_pool.data, &self.avg_pool);
+        let argmax = nn::argmax(api, Q, &conv.data);
+        api.assert_is_equal(argmax, self.argmax);
+    }
+}
+
+fn conv_helper<C: Config>(seed: u64, corrupt: bool) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let x = Tensor::new(vec![1, 6, 6], random_values(&mut rng, 36, -32..32));
+    let weight = Tensor::new(vec![2, 1, 3, 3], random_values(&mut rng, 18, -6..6));
+    let bias = random_values(&mut rng, 2, -64..64);
+    let conv = native::conv2d(Q, &x, &weight, &bias, 1, 0);
+    let relu = native::relu(&conv);
+    let max_pool = native::max_pool2d(&relu, 2, 2);
+    let avg_pool = native::avg_pool2d(&conv, 2, 2);
+    let mut argmax = native::argmax(&conv.data) as i64;
+    if corrupt {
+        argmax = (argmax + 1) % conv.data.len() as i64;
+    }
+
+    let circuit = ConvCircuit::<Variable> {
+        x: variables(36),
+        weight: variables(18),
+        bias: variables(2),
+        conv: variables(32),
+        relu: variables(32),
+        max_pool: variables(8),
+        avg_pool: variables(8),
+        argmax: Variable::default(),
+    };
+    let assignment = ConvCircuit::<CircuitField<C>> {
+        x: to_fields::<C>(&x.data),
+        weight: to_fields::<C>(&weight.data),
+        bias: to_fields::<C>(&bias),
+        conv: to_fields::<C>(&conv.data),
+        relu: to_fields::<C>(&relu.data),
+        max_pool: to_fields::<C>(&max_pool.data),
+        avg_pool: to_fields::<C>(&avg_pool.data),
+        argmax: to_field(argmax),
+    };
+    debug_eval::<C, _, _, _>(&circuit, &assignment, hints::<C>());
+}
+
+#[test]
+fn test_nn_conv_pool() {
+    conv_helper::<M31Config>(10, false);
+    conv_helper::<BN254Config>(11, false);
+}
+
+#[test]
+#[should_panic]
+fn test_nn_wrong_argmax() {
+    conv_helper::<M31Config>(12, true);
+}
+
+declare_circuit!(ReluRangeCircuit { x: Variable });
+
+impl<C: Config> Define<C> for ReluRangeCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, api: &mut Builder) {
+        nn::relu(api, Q, &Tensor::new(vec![1], vec![self.x]));
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_nn_relu_out_of_range() {
+    let assignment = ReluRangeCircuit::<M31> {
+        x: to_field(1 << (Q.bits - 1)),
+    };
+    debug_eval::<M31Config, _, _, _>(
+        &ReluRangeCircuit::default(),
+        &assignment,
+        hints::<M31Config>(),
+    );
+}
+
+// the lookup based functions on a vector of 16 values
+declare_circuit!(LookupCircuit {
+    x: [Variable],
+    gamma: [Variable],
+    beta: [Variable],
+    gelu: [Variable],
+    sigmoid: [Variable],
+    softmax: [Variable],
+    layer_norm: [Variable],
+});
+
+impl<C: Config> Define<C> for LookupCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, api: &mut Builder) {
+        let x = Tensor::new(vec![self.x.len()], self.x.clone());
+        let mut gelu = ActivationTable::new(api, Activation::Gelu, Q.bits, Q.frac_bits);
+        let y = gelu.apply_tensor(api, &x);
+        assert_all_equal(api, &y.data, &self.gelu);
+        gelu.final_check(api);
+        let mut sigmoid = ActivationTable::new(api, Activation::Sigmoid, Q.bits, Q.frac_bits);
+        let y = sigmoid.apply_tensor(api, &x);
+        assert_all_equal(api, &y.data, &self.sigmoid);
+        sigmoid.final_check(api);
+
+        let mut exp = ActivationTable::new(api, Activation::Exp, Q.bits + 1, Q.frac_bits);
+        let y = nn::softmax(api, Q, &self.x, &mut exp);
+        assert_all_equal(api, &y, &self.softmax);
+        exp.final_check(api);
+
+        let mut rsqrt =
+            ActivationTable::new(api, Activation::Rsqrt, RSQRT_DOMAIN_BITS, Q.frac_bits);
+        let y = nn::layer_norm(api, Q, &self.x, &self.gamma, &self.beta, EPS, &mut rsqrt);
+        assert_all_equal(api, &y, &self.layer_norm);
+        rsqrt.final_check(api);
+    }
+}
+
+fn lookup_helper<C: Config>(seed: u64, corrupt: bool) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let x = random_values(&mut rng, 16, -128..128);
+    let gamma = random_values(&mut rng, 16, 8..24);
+    let beta = random_values(&mut rng, 16, -16..16);
+    let t = Tensor::new(vec![16], x.clone());
+    let gelu = native::activation(Activation::Gelu, Q, &t).data;
+    let sigmoid = native::activation(Activation::Sigmoid, Q, &t).data;
+    let mut softmax = native::softmax(Q, &x);
+    let layer_norm = native::layer_norm(Q, &x, &gamma, &beta, EPS as i64);
+    // the probabilities add up to about one
+    let total = softmax.iter().sum::<i64>();
+    assert!((total - (1 << Q.frac_bits)).abs() <= 8);
+    if corrupt {
+        softmax[0] += 1;
+    }
+
+    let circuit = LookupCircuit::<Variable> {
+        x: variables(16),
+        gamma: variables(16),
+        beta: variables(16),
+        gelu: variables(16),
+        sigmoid: variables(16),
+        softmax: variables(16),
+        layer_norm: variables(16),
+    };
+    let assignment = LookupCircuit::<CircuitField<C>> {
+        x: to_fields::<C>(&x),
+        gamma: to_fields::<C>(&gamma),
+        beta: to_fields::<C>(&beta),
+        gelu: to_fields::<C>(&gelu),
+        sigmoid: to_fields::<C>(&sigmoid),
+        softmax: to_fields::<C>(&softmax),
+        layer_norm: to_fields::<C>(&layer_norm),
+    };
+    debug_eval::<C, _, _, _>(&circuit, &assignment, hints::<C>());
+}
+
+#[test]
+fn test_nn_lookup_functions() {
+    lookup_helper::<M31Config>(20, false);
+    lookup_helper::<BN254Config>(21, false);
+}
+
+#[test]
+#[should_panic]
+fn test_nn_wrong_softmax() {
+    lookup_helper::<M31Config>(22, true);
+}
+
+#[test]
+fn test_nn_native_quantize() {
+    assert_eq!(native::quantize(1.5, Q), 24);
+    assert_eq!(native::quantize(-100.0, Q), -128);
+    assert_eq!(native::dequantize(-24, Q), -1.5);
+    assert_eq!(
+        native::activation(Activation::Exp, Q, &Tensor::new(vec![1], vec![0])).data,
+        [16]
+    );
+    // the tables are computed in fixed point: e^-1, sigmoid(1), gelu(1) and 1 / sqrt(4)
+    let x = Tensor::new(vec![4], vec![-16, 16, 16, 64]);
+    assert_eq!(native::activation(Activation::Exp, Q, &x).data[0], 6);
+    assert_eq!(native::activation(Activation::Sigmoid, Q, &x).data[1], 12);
+    assert_eq!(native::activation(Activation::Gelu, Q, &x).data[2], 13);
+    assert_eq!(native::activation(Activation::Rsqrt, Q, &x).data[3], 8);
+}
+
+fn kernel_inputs(
+    rng: &mut StdRng,
+    n: usize,
+    len: usize,
+    range: std::ops::Range<i64>,
+) -> Vec<Vec<i64>> {
+    (0..n)
+        .map(|_| random_values(rng, len, range.clone()))
+        .collect()
+}
+
+fn kernel_fields(values: &[Vec<i64>]) -> Vec<Vec<M31>> {
+    values.iter().map(|v| to_fields::<M31Config>(v)).collect()
+}
+
+#[test]
+fn test_nn_kernels() {
+    const N: usize = 2;
+    const LEN: usize = 16;
+    // the convolution takes 2 channels of 6x6 to 3 channels of 4x4, with another quantization
+    const CONV_Q: FixedPoint = FixedPoint {
+        bits: 10,
+        frac_bits: 6,
+    };
+    const IMAGE: [usize; 3] = [2, 6, 6];
+    const WEIGHT: [usize; 4] = [3, 2, 3, 3];
+    let mut rng = StdRng::seed_from_u64(30);
+    let x = kernel_inputs(&mut rng, N, LEN, -128..128);
+    let gamma = kernel_inputs(&mut rng, N, LEN, 8..24);
+    let beta = kernel_inputs(&mut rng, N, LEN, -16..16);
+    let image = kernel_inputs(&mut rng, N, 72, -64..64);
+    let weight = kernel_inputs(&mut rng, N, 54, -16..16);
+    let bias = kernel_inputs(&mut rng, N, 3, -256..256);
+
+    let relu_kernel: KernelPrimitive<M31Config> = relu_kernel(Q, &[LEN]).unwrap();
+    let softmax_kernel: KernelPrimitive<M31Config> = softmax_kernel(Q, LEN).unwrap();
+    let layer_norm_kernel: KernelPrimitive<M31Config> =
+        layer_norm_kernel(Q, LEN, EPS, RSQRT_DOMAIN_BITS).unwrap();
+    let conv_kernel: KernelPrimitive<M31Config> =
+        conv2d_kernel(CONV_Q, &IMAGE, &WEIGHT, 1, 0).unwrap();
+    let mut ctx: Context<M31Config, _> = Context::new(hints::<M31Config>());
+
+    let x_device = ctx.copy_to_device(&kernel_fields(&x));
+    let mut relu = None;
+    call_kernel!(ctx, relu_kernel, N, x_device, mut relu).unwrap();
+    let x_device = ctx.copy_to_device(&kernel_fields(&x));
+    let mut softmax = None;
+    call_kernel!(ctx, softmax_kernel, N, x_device, mut softmax).unwrap();
+    let x_device = ctx.copy_to_device(&kernel_fields(&x));
+    let gamma_device = ctx.copy_to_device(&kernel_fields(&gamma));
+    let beta_device = ctx.copy_to_device(&kernel_fields(&beta));
+    let mut layer_norm = None;
+    call_kernel!(
+        ctx,
+        layer_norm_kernel,
+        N,
+        x_device,
+        gamma_device,
+        beta_device,
+        mut layer_norm
+    )
+    .unwrap();
+    let image_device = ctx
+        .copy_to_device(&kernel_fields(&image))
+        .reshape(&[N, 2, 6, 6]);
+    let weight_device = ctx
+        .copy_to_device(&kernel_fields(&weight))
+        .reshape(&[N, 3, 2, 3, 3]);
+    let bias_device = ctx.copy_to_device(&kernel_fields(&bias));
+    let mut conv = None;
+    call_kernel!(
+        ctx,
+        conv_kernel,
+        N,
+        image_device,
+        weight_device,
+        bias_device,
+        mut conv
+    )
+    .unwrap();
+
+    let relu: Vec<Vec<M31>> = ctx.copy_to_host(relu);
+    let softmax: Vec<Vec<M31>> = ctx.copy_to_host(softmax);
+    let layer_norm: Vec<Vec<M31>> = ctx.copy_to_host(layer_norm);
+    let conv: Vec<Vec<M31>> = ctx.copy_to_host(conv.reshape(&[N, 48]));
+    for i in 0..N {
+        let t = Tensor::new(vec![LEN], x[i].clone());
+        assert_eq!(relu[i], to_fields::<M31Config>(&native::relu(&t).data));
+        assert_eq!(
+            softmax[i],
+            to_fields::<M31Config>(&native::softmax(Q, &x[i]))
+        );
+        assert_eq!(
+            layer_norm[i],
+            to_fields::<M31Config>(&native::layer_norm(
+                Q, &x[i], &gamma[i], &beta[i], EPS as i64
+            ))
+        );
+        let expected = native::conv2d(
+            CONV_Q,
+            &Tensor::new(IMAGE.to_vec(), image[i].clone()),
+            &Tensor::new(WEIGHT.to_vec(), weight[i].clone()),
+            &bias[i],
+            1,
+            0,
+        );
+        assert_eq!(conv[i], to_fields::<M31Config>(&expected.data));
+    }
+
+    type P = Expander<M31Config>;
+    let computation_graph = ctx.compile_computation_graph().unwrap();
+    ctx.solve_witness().unwrap();
+    let (prover_setup, verifier_setup) = <P as ProvingSystem<M31Config>>::setup(&computation_graph);
+    let proof = P::prove(
+        &prover_setup,
+        &computation_graph,
+        ctx.export_device_memories(),
+    );
+    assert!(P::verify(&verifier_setup, &computation_graph, &proof));
+}
+
+#[test]
+fn test_nn_kernel_shapes() {
+    type K = Result<KernelPrimitive<M31Config>, Error>;
+    let k: K = conv2d_kernel(Q, &[2, 6, 6], &[3, 1, 3, 3], 1, 0);
+    assert!(k.is_err());
+    let k: K = conv2d_kernel(Q, &[1, 2, 2], &[1, 1, 3, 3], 1, 0);
+    assert!(k.is_err());
+    let k: K = nn::kernel::max_pool_kernel(Q, &[4, 4], 2, 2);
+    assert!(k.is_err());
+    let k: K = relu_kernel(Q, &[4, 0]);
+    assert!(k.is_err());
+    let k: K = layer_norm_kernel(Q, 16, EPS, 8);
+    assert!(k.is_err());
+    let k: K = layer_norm_kernel(Q, 16, 0, RSQRT_DOMAIN_BITS);
+    assert!(k.is_err());
+}
@@ -0,0 +1,154 @@
+use circuit_std_rs::poseidon::{poseidon2::Poseidon2, poseidon2_utils::Poseidon2Params};
+use ethnum::U256;
+use expander_compiler::frontend::{extra::debug_eval, *};
+use rand::SeedableRng;
+
+const HASH_INPUT_LEN: usize = 11;
+
+declare_circuit!(Poseidon2Circuit {
+    state: [Variable],
+    permuted: [Variable],
+    inputs: [Variable],
+    digest: [Variable],
+    left: [Variable],
+    right: [Variable],
+    compressed: [Variable],
+});
+
+impl<C: Config> Define<C> for Poseidon2Circuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, api: &mut Builder) {
+        let poseidon2 = Poseidon2::<C>::new();
+
+        let mut state = self.state.clone();
+        poseidon2.permute(api, &mut state);
+        (0..state.len()).for_each(|i| api.assert_is_equal(state[i], self.permuted[i]));
+
+        let digest = poseidon2.hash(api, &self.inputs);
+        (0..digest.len()).for_each(|i| api.assert_is_equal(digest[i], self.digest[i]));
+
+        let compressed = poseidon2.compress(api, &self.left, &self.right);
+        (0..compressed.len()).for_each(|i| api.assert_is_equal(compressed[i], self.compressed[i]));
+    }
+}
+
+// Checks the circuit against the native implementation on random inputs
+fn poseidon2_helper<C: Config>(seed: u64, corrupt: bool) {
+    let params = Poseidon2Params::<CircuitField<C>>::new();
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let mut random = |n: usize| {
+        (0..n)
+            .map(|_| CircuitField::<C>::random_unsafe(&mut rng))
+            .collect::<Vec<_>>()
+    };
+
+    let state = random(params.width);
+    let inputs = random(HASH_INPUT_LEN);
+    let left = random(params.digest_len);
+    let right = random(params.digest_len);
+    let mut permuted = state.clone();
+    params.permute(&mut permuted);
+    let digest = params.hash(&inputs);
+    let mut compressed = params.compress(&left, &right);
+    if corrupt {
+        compressed[0] += CircuitField::<C>::one();
+    }
+
+    let circuit = Poseidon2Circuit::<Variable> {
+        state: vec![Variable::default(); params.width],
+        permuted: vec![Variable::default(); params.width],
+        inputs: vec![Variable::default(); HASH_INPUT_LEN],
+        digest: vec![Variable::default(); params.digest_len],
+        left: vec![Variable::default(); params.digest_len],
+        right: vec![Variable::default(); params.digest_len],
+        compressed: vec![Variable::default(); params.digest_len],
+    };
+    let assignment = Poseidon2Circuit::<CircuitField<C>> {
+        state,
+        permuted,
+        inputs,
+        digest,
+        left,
+        right,
+        compressed,
+    };
+    debug_eval::<C, _, _, _>(&circuit, &assignment, EmptyHintCaller);
+}
+
+#[test]
+fn test_poseidon2_circuit_matches_native() {
+    poseidon2_helper::<M31Config>(1, false);
+    poseidon2_helper::<BabyBearConfig>(2, false);
+    poseidon2_helper::<GoldilocksConfig>(3, false);
+    poseidon2_helper::<BN254Config>(4, false);
+}
+
+#[test]
+#[should_panic]
+fn test_poseidon2_wrong_compression() {
+    poseidon2_helper::<GoldilocksConfig>(5, true);
+}
+
+// The permutation of [0, 1, ..., width - 1]
+fn reference_vector_helper<C: Config>(expected: &[&str]) {
+    let params = Poseidon2Params::<CircuitField<C>>::new();
+    let mut state = (0..params.width as u32)
+        .map(CircuitField::<C>::from)
+        .collect::<Vec<_>>();
+    params.permute(&mut state);
+    let expected = expected
+        .iter()
+        .map(|x| CircuitField::<C>::from_u256(U256::from_str_radix(x, 16).unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(state, expected);
+}
+
+// The test vector of Plonky3 for its Mersenne31 instance with width 16
+#[test]
+fn test_poseidon2_m31_plonky3_vector() {
+    let params = Poseidon2Params::<M31>::new();
+    let input: [u32; 16] = [
+        894848333, 1437655012, 1200606629, 1690012884, 71131202, 1749206695, 1717947831, 120589055,
+        19776022, 42382981, 1831865506, 724844064, 171220207, 1299207443, 227047920, 1783754913,
+    ];
+    let expected: [u32; 16] = [
+        1124552602, 2127602268, 1834113265, 1207687593, 1891161485, 245915620, 981277919,
+        627265710, 1534924153, 1580826924, 887997842, 1526280482, 547791593, 1028672510,
+        1803086471, 323071277,
+    ];
+    let mut state = input.map(M31::from);
+    params.permute(&mut state);
+    assert_eq!(state, expected.map(M31::from));
+}
+
+#[test]
+fn test_poseidon2_reference_vectors() {
+    // the test vector of the reference implementation for BN254 with width 3
+    reference_vector_helper::<BN254Config>(&[
+        "0bb61d24daca55eebcb1929a82650f328134334da98ea4f847f760054f4a3033",
+        "303b6f7c86d043bfcbcc80214f26a30277a15d3f74ca654992defe7ff8d03570",
+        "1ed25194542b12eef8617361c3ba7c52e660b145994427cc86296242cf766ec8",
+    ]);
+    reference_vector_helper::<BabyBearConfig>(&[
+        "35706d52", "2dfede1f", "07a2d988", "523182b3", "0989b500", "569707d2", "28b377e1",
+        "0d525885", "1de385cb", "6b9151dd", "639f88a9", "6a9e3d13", "6786af9b", "5098a430",
+        "72f26ae2", "43482aa1",
+    ]);
+    reference_vector_helper::<GoldilocksConfig>(&[
+        "c5fb1cfe0b4697bb",
+        "4a4a32ff849af473",
+        "d2fd266077f8efba",
+        "f4ad9b74e833916d",
+        "e6648eb0acc11463",
+        "8d5529a930d75194",
+        "e8c993aa10da6c90",
+        "a73104a95b68031c",
+    ]);
+}
+
+#[test]
+fn test_poseidon2_hash_separates_lengths() {
+    let params = Poseidon2Params::<CircuitField<M31Config>>::new();
+    let inputs = vec![M31::from(7); params.rate];
+    assert_ne!(params.hash(&inputs[..1]), params.hash(&inputs[..2]));
+    assert_ne!(params.hash(&[]), params.hash(&[M31::from(0)]));
+}
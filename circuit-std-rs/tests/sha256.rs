@@ -0,0 +1,222 @@
+use circuit_std_rs::{
+    sha256::{HmacSha256, Sha256},
+    utils::register_hint,
+};
+use expander_compiler::frontend::{extra::debug_eval, *};
+use rand::{Rng, SeedableRng};
+use sha2::Digest;
+
+const MSG_LEN: usize = 100;
+const MAX_LEN: usize = 150;
+const LEN_BITS: usize = 8;
+const KEY_LEN: usize = 20;
+const LONG_KEY_LEN: usize = 100;
+const HMAC_MAX_LEN: usize = 80;
+const HMAC_LEN_BITS: usize = 7;
+
+fn hints<C: Config>() -> HintRegistry<CircuitField<C>> {
+    let mut hint_registry = HintRegistry::<CircuitField<C>>::new();
+    register_hint(&mut hint_registry);
+    hint_registry
+}
+
+fn random_bytes(seed: u64, len: usize) -> Vec<u8> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    (0..len).map(|_| rng.gen()).collect()
+}
+
+// most significant bit of each byte first
+fn to_bits<C: Config>(bytes: &[u8]) -> Vec<CircuitField<C>> {
+    bytes
+        .iter()
+        .flat_map(|b| (0..8).map(move |i| CircuitField::<C>::from((*b >> (7 - i)) as u32 & 1)))
+        .collect()
+}
+
+fn len_bits<C: Config>(len: usize, n: usize) -> Vec<CircuitField<C>> {
+    (0..n)
+        .map(|i| CircuitField::<C>::from((len >> i) as u32 & 1))
+        .collect()
+}
+
+fn hmac_sha256(key: &[u8], msg: &[u8]) -> Vec<u8> {
+    let mut key = if key.len() > 64 {
+        sha2::Sha256::digest(key).to_vec()
+    } else {
+        key.to_vec()
+    };
+    key.resize(64, 0);
+    let inner_key = key.iter().map(|b| b ^ 0x36).collect::<Vec<_>>();
+    let outer_key = key.iter().map(|b| b ^ 0x5c).collect::<Vec<_>>();
+    let inner = sha2::Sha256::digest([inner_key, msg.to_vec()].concat());
+    sha2::Sha256::digest([outer_key, inner.to_vec()].concat()).to_vec()
+}
+
+declare_circuit!(Sha256FixedCircuit {
+    input: [Variable; MSG_LEN * 8],
+    output: [Variable; 256],
+});
+
+impl<C: Config> Define<C> for Sha256FixedCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, api: &mut Builder) {
+        let mut hasher = Sha256::new(api);
+        hasher.update(api, &self.input[..30 * 8]);
+        hasher.update(api, &self.input[30 * 8..]);
+        let digest = hasher.finalize(api);
+        for i in 0..256 {
+            api.assert_is_equal(digest[i], self.output[i]);
+        }
+        hasher.final_check(api);
+    }
+}
+
+fn sha256_fixed_helper<C: Config>(seed: u64, corrupt: bool) {
+    let data = random_bytes(seed, MSG_LEN);
+    let mut digest = sha2::Sha256::digest(&data).to_vec();
+    if corrupt {
+        digest[31] ^= 1;
+    }
+    let mut assignment = Sha256FixedCircuit::<CircuitField<C>>::default();
+    assignment.input.copy_from_slice(&to_bits::<C>(&data));
+    assignment.output.copy_from_slice(&to_bits::<C>(&digest));
+    debug_eval::<C, _, _, _>(&Sha256FixedCircuit::default(), &assignment, hints::<C>());
+}
+
+#[test]
+fn test_sha256_fixed_len() {
+    sha256_fixed_helper::<GF2Config>(1, false);
+    sha256_fixed_helper::<M31Config>(2, false);
+    sha256_fixed_helper::<BN254Config>(3, false);
+}
+
+#[test]
+#[should_panic]
+fn test_sha256_wrong_digest() {
+    sha256_fixed_helper::<M31Config>(4, true);
+}
+
+declare_circuit!(Sha256VarLenCircuit {
+    input: [Variable; MAX_LEN * 8],
+    len: [Variable; LEN_BITS],
+    output: [Variable; 256],
+});
+
+impl<C: Config> Define<C> for Sha256VarLenCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, api: &mut Builder) {
+        let mut hasher = Sha256::new(api);
+        hasher.update(api, &self.input);
+        let digest = hasher.finalize_var_len(api, &self.len);
+        for i in 0..256 {
+            api.assert_is_equal(digest[i], self.output[i]);
+        }
+        hasher.final_check(api);
+    }
+}
+
+fn sha256_var_len_helper<C: Config>(seed: u64, len: usize, claimed_len: usize) {
+    let data = random_bytes(seed, MAX_LEN);
+    let digest = sha2::Sha256::digest(&data[..len.min(MAX_LEN)]);
+    let mut assignment = Sha256VarLenCircuit::<CircuitField<C>>::default();
+    assignment.input.copy_from_slice(&to_bits::<C>(&data));
+    assignment
+        .len
+        .copy_from_slice(&len_bits::<C>(claimed_len, LEN_BITS));
+    assignment.output.copy_from_slice(&to_bits::<C>(&digest));
+    debug_eval::<C, _, _, _>(&Sha256VarLenCircuit::default(), &assignment, hints::<C>());
+}
+
+#[test]
+fn test_sha256_var_len() {
+    // around the lengths where the padding needs one more block
+    for (i, len) in [0, 55, 56, 64, 119, 120, MAX_LEN].into_iter().enumerate() {
+        sha256_var_len_helper::<M31Config>(10 + i as u64, len, len);
+    }
+    sha256_var_len_helper::<GF2Config>(20, 77, 77);
+    sha256_var_len_helper::<BN254Config>(21, 130, 130);
+}
+
+#[test]
+#[should_panic]
+fn test_sha256_var_len_wrong_len() {
+    sha256_var_len_helper::<M31Config>(22, 60, 61);
+}
+
+#[test]
+#[should_panic]
+fn test_sha256_var_len_too_long() {
+    sha256_var_len_helper::<GF2Config>(23, MAX_LEN, MAX_LEN + 1);
+}
+
+declare_circuit!(HmacCircuit {
+    key: [Variable; KEY_LEN * 8],
+    msg: [Variable; MSG_LEN * 8],
+    tag: [Variable; 256],
+});
+
+impl<C: Config> Define<C> for HmacCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, api: &mut Builder) {
+        let mut hmac = HmacSha256::new(api, &self.key);
+        hmac.update(api, &self.msg);
+        let tag = hmac.finalize(api);
+        for i in 0..256 {
+            api.assert_is_equal(tag[i], self.tag[i]);
+        }
+        hmac.final_check(api);
+    }
+}
+
+#[test]
+fn test_hmac_sha256() {
+    fn helper<C: Config>(seed: u64) {
+        let key = random_bytes(seed, KEY_LEN);
+        let msg = random_bytes(seed + 1, MSG_LEN);
+        let tag = hmac_sha256(&key, &msg);
+        let mut assignment = HmacCircuit::<CircuitField<C>>::default();
+        assignment.key.copy_from_slice(&to_bits::<C>(&key));
+        assignment.msg.copy_from_slice(&to_bits::<C>(&msg));
+        assignment.tag.copy_from_slice(&to_bits::<C>(&tag));
+        debug_eval::<C, _, _, _>(&HmacCircuit::default(), &assignment, hints::<C>());
+    }
+    helper::<GF2Config>(30);
+    helper::<M31Config>(32);
+}
+
+declare_circuit!(HmacVarLenCircuit {
+    key: [Variable; LONG_KEY_LEN * 8],
+    msg: [Variable; HMAC_MAX_LEN * 8],
+    len: [Variable; HMAC_LEN_BITS],
+    tag: [Variable; 256],
+});
+
+impl<C: Config> Define<C> for HmacVarLenCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, api: &mut Builder) {
+        let mut hmac = HmacSha256::new(api, &self.key);
+        hmac.update(api, &self.msg);
+        let tag = hmac.finalize_var_len(api, &self.len);
+        for i in 0..256 {
+            api.assert_is_equal(tag[i], self.tag[i]);
+        }
+        hmac.final_check(api);
+    }
+}
+
+// the key is longer than a block and gets hashed first
+#[test]
+fn test_hmac_sha256_long_key_var_len() {
+    fn helper<C: Config>(seed: u64, len: usize) {
+        let key = random_bytes(seed, LONG_KEY_LEN);
+        let msg = random_bytes(seed + 1, HMAC_MAX_LEN);
+        let tag = hmac_sha256(&key, &msg[..len]);
+        let mut assignment = HmacVarLenCircuit::<CircuitField<C>>::default();
+        assignment.key.copy_from_slice(&to_bits::<C>(&key));
+        assignment.msg.copy_from_slice(&to_bits::<C>(&msg));
+        assignment
+            .len
+            .copy_from_slice(&len_bits::<C>(len, HMAC_LEN_BITS));
+        assignment.tag.copy_from_slice(&to_bits::<C>(&tag));
+        debug_eval::<C, _, _, _>(&HmacVarLenCircuit::default(), &assignment, hints::<C>());
+    }
+    helper::<M31Config>(40, 0);
+    helper::<M31Config>(42, 70);
+    helper::<GF2Config>(44, 33);
+}
@@ -0,0 +1,61 @@
+use circuit_std_rs::sha512::sha512;
+use expander_compiler::frontend::{extra::debug_eval, *};
+use sha2::{Digest, Sha512};
+
+// one block, the padding boundary and two blocks
+const LENGTHS: [usize; 3] = [3, 112, 200];
+
+declare_circuit!(Sha512Circuit {
+    input: [Variable],
+    output: [Variable; 64],
+});
+
+impl<C: Config> Define<C> for Sha512Circuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        let digest = sha512(builder, &self.input);
+        for i in 0..64 {
+            builder.assert_is_equal(digest[i], self.output[i]);
+        }
+    }
+}
+
+fn test_sha512<C: Config>(len: usize, tamper: bool)
+where
+    CircuitField<C>: From<u32>,
+{
+    let data = (0..len).map(|i| (i * 7 + 3) as u8).collect::<Vec<_>>();
+    let mut digest = Sha512::digest(&data);
+    if tamper {
+        digest[0] ^= 1;
+    }
+    let mut circuit = Sha512Circuit::<Variable>::default();
+    circuit.input.resize(len, Variable::default());
+    let assignment = Sha512Circuit::<CircuitField<C>> {
+        input: data
+            .iter()
+            .map(|&b| CircuitField::<C>::from(b as u32))
+            .collect(),
+        output: std::array::from_fn(|i| CircuitField::<C>::from(digest[i] as u32)),
+    };
+    debug_eval::<C, _, _, _>(&circuit, &assignment, EmptyHintCaller);
+}
+
+#[test]
+fn test_sha512_m31() {
+    for len in LENGTHS {
+        test_sha512::<M31Config>(len, false);
+    }
+}
+
+#[test]
+fn test_sha512_bn254() {
+    for len in LENGTHS {
+        test_sha512::<BN254Config>(len, false);
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_sha512_wrong_digest() {
+    test_sha512::<M31Config>(3, true);
+}
@@ -51,6 +51,7 @@ fn build<C: Config>() {
     let CompileResult {
         witness_solver,
         layered_circuit,
+        ..
     } = compile_result;
 
     let witness = witness_solver.solve_witness(&assignment).unwrap();
@@ -0,0 +1,480 @@
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{format_ident, quote};
+use syn::{
+    parse_quote, spanned::Spanned, Data, DeriveInput, Error, Fields, GenericArgument, GenericParam,
+    Ident, Index, Member, PathArguments, Result, Type,
+};
+
+// What to generate for every leaf reachable from a field
+#[derive(Clone, Copy)]
+enum Op {
+    Dump,
+    Load,
+    Count,
+}
+
+// Walks the syntax of field types, leaves are the type parameter of the circuit
+// and anything else that mentions it is expected to implement DumpLoadTwoVariables itself
+struct Walker<'a> {
+    leaf: &'a Ident,
+    op: Op,
+    depth: usize,
+}
+
+fn mentions(tokens: TokenStream, ident: &Ident) -> bool {
+    tokens.into_iter().any(|t| match t {
+        TokenTree::Ident(i) => i == *ident,
+        TokenTree::Group(g) => mentions(g.stream(), ident),
+        _ => false,
+    })
+}
+
+fn vec_element(ty: &syn::TypePath) -> Option<&Type> {
+    let seg = ty.path.segments.last()?;
+    if seg.ident != "Vec" {
+        return None;
+    }
+    match &seg.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(t) => Some(t),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl Walker<'_> {
+    // `r` is a reference to the value, mutable when loading
+    fn walk(&mut self, ty: &Type, r: TokenStream, public: bool) -> Result<TokenStream> {
+        if !mentions(quote!(#ty), self.leaf) {
+            return Ok(quote! {});
+        }
+        match ty {
+            Type::Path(p) if p.qself.is_none() && p.path.is_ident(self.leaf) => {
+                Ok(self.leaf_code(r, public))
+            }
+            Type::Path(p) if p.qself.is_none() && vec_element(p).is_some() => {
+                self.walk_iter(vec_element(p).unwrap(), r, public)
+            }
+            Type::Path(_) => Ok(self.nested_code(r, public)),
+            Type::Array(a) => self.walk_iter(&a.elem, r, public),
+            Type::Tuple(t) => {
+                let tuple = format_ident!("_t{}", self.depth);
+                self.depth += 1;
+                let mut body = Vec::with_capacity(t.elems.len());
+                for (i, elem) in t.elems.iter().enumerate() {
+                    let index = Index::from(i);
+                    let r = match self.op {
+                        Op::Load => quote! { &mut #tuple.#index },
+                        _ => quote! { &#tuple.#index },
+                    };
+                    body.push(self.walk(elem, r, public)?);
+                }
+                self.depth -= 1;
+                Ok(quote! {
+                    let #tuple = #r;
+                    #(#body)*
+                })
+            }
+            Type::Paren(p) => self.walk(&p.elem, r, public),
+            Type::Group(g) => self.walk(&g.elem, r, public),
+            _ => Err(Error::new(
+                ty.span(),
+                "Circuit fields must be built from the variable type, arrays, tuples, Vecs and other circuit structs",
+            )),
+        }
+    }
+
+    fn walk_iter(&mut self, elem: &Type, r: TokenStream, public: bool) -> Result<TokenStream> {
+        let x = format_ident!("_x{}", self.depth);
+        self.depth += 1;
+        let body = self.walk(elem, quote! { #x }, public)?;
+        self.depth -= 1;
+        Ok(match self.op {
+            Op::Load => quote! {
+                for #x in (#r).iter_mut() {
+                    #body
+                }
+            },
+            _ => quote! {
+                for #x in (#r).iter() {
+                    #body
+                }
+            },
+        })
+    }
+
+    fn leaf_code(&self, r: TokenStream, public: bool) -> TokenStream {
+        let leaf = self.leaf;
+        let target = if public {
+            quote! { public_vars }
+        } else {
+            quote! { vars }
+        };
+        match self.op {
+            Op::Dump => quote! {
+                ::expander_compiler::frontend::internal::DumpLoadVariables::<#leaf>::dump_into(#r, #target);
+            },
+            Op::Load => quote! {
+                ::expander_compiler::frontend::internal::DumpLoadVariables::<#leaf>::load_from(#r, #target);
+            },
+            Op::Count if public => quote! { cnt_pub += 1; },
+            Op::Count => quote! { cnt_sec += 1; },
+        }
+    }
+
+    // a public nested struct puts its secret variables first and then its public ones,
+    // both into the public variables of the outer circuit
+    fn nested_code(&self, r: TokenStream, public: bool) -> TokenStream {
+        let leaf = self.leaf;
+        let tr = quote! { ::expander_compiler::frontend::internal::DumpLoadTwoVariables::<#leaf> };
+        match (self.op, public) {
+            (Op::Dump, false) => quote! { #tr::dump_into(#r, vars, public_vars); },
+            (Op::Dump, true) => quote! {
+                let mut _p = Vec::new();
+                #tr::dump_into(#r, public_vars, &mut _p);
+                public_vars.append(&mut _p);
+            },
+            (Op::Load, false) => quote! { #tr::load_from(#r, vars, public_vars); },
+            (Op::Load, true) => quote! {
+                let _r = #r;
+                let (_s, _p) = #tr::num_vars(_r);
+                let _all: &[#leaf] = *public_vars;
+                let (mut _sv, _rest) = _all.split_at(_s);
+                let (mut _pv, _rest) = _rest.split_at(_p);
+                #tr::load_from(_r, &mut _sv, &mut _pv);
+                *public_vars = _rest;
+            },
+            (Op::Count, false) => quote! {
+                let (_s, _p) = #tr::num_vars(#r);
+                cnt_sec += _s;
+                cnt_pub += _p;
+            },
+            (Op::Count, true) => quote! {
+                let (_s, _p) = #tr::num_vars(#r);
+                cnt_pub += _s + _p;
+            },
+        }
+    }
+}
+
+// Assignments as serde documents, only called on types already accepted by the Walker
+struct DocumentWalker<'a> {
+    leaf: &'a Ident,
+    depth: usize,
+}
+
+impl DocumentWalker<'_> {
+    // `r` is a reference to the value
+    fn to_document(&mut self, ty: &Type, r: TokenStream) -> TokenStream {
+        let internal = quote! { ::expander_compiler::frontend::internal };
+        if !mentions(quote!(#ty), self.leaf) {
+            return quote! { #internal::primitive_to_document(#r) };
+        }
+        match ty {
+            Type::Path(p) if p.qself.is_none() && p.path.is_ident(self.leaf) => {
+                quote! { #internal::element_to_document(#r) }
+            }
+            Type::Path(p) if p.qself.is_none() && vec_element(p).is_some() => {
+                self.iter_to_document(vec_element(p).unwrap(), r)
+            }
+            Type::Array(a) => self.iter_to_document(&a.elem, r),
+            Type::Tuple(t) => {
+                let tuple = format_ident!("_t{}", self.depth);
+                self.depth += 1;
+                let elems: Vec<TokenStream> = (0..t.elems.len())
+                    .map(|i| {
+                        let index = Index::from(i);
+                        self.to_document(&t.elems[i], quote! { &#tuple.#index })
+                    })
+                    .collect();
+                self.depth -= 1;
+                quote! {{
+                    let #tuple = #r;
+                    #internal::serde_json::Value::Array(vec![#(#elems),*])
+                }}
+            }
+            Type::Paren(p) => self.to_document(&p.elem, r),
+            Type::Group(g) => self.to_document(&g.elem, r),
+            _ => quote! { ::expander_compiler::frontend::SerdeAssignment::to_document(#r) },
+        }
+    }
+
+    fn iter_to_document(&mut self, elem: &Type, r: TokenStream) -> TokenStream {
+        let x = format_ident!("_x{}", self.depth);
+        self.depth += 1;
+        let body = self.to_document(elem, quote! { #x });
+        self.depth -= 1;
+        quote! {
+            ::expander_compiler::frontend::internal::serde_json::Value::Array(
+                (#r).iter().map(|#x| #body).collect(),
+            )
+        }
+    }
+
+    // `doc` is a reference to the document and `path` a &str locating it
+    fn from_document(&mut self, ty: &Type, doc: TokenStream, path: TokenStream) -> TokenStream {
+        let internal = quote! { ::expander_compiler::frontend::internal };
+        if !mentions(quote!(#ty), self.leaf) {
+            return quote! { #internal::primitive_from_document(#doc, #path)? };
+        }
+        match ty {
+            Type::Path(p) if p.qself.is_none() && p.path.is_ident(self.leaf) => {
+                quote! { #internal::element_from_document(#doc, #path)? }
+            }
+            Type::Path(p) if p.qself.is_none() && vec_element(p).is_some() => {
+                let elem = vec_element(p).unwrap();
+                let res = self.iter_from_document(elem, doc, path, quote! { None });
+                quote! {{ #res }}
+            }
+            Type::Array(a) => {
+                let (elem, len) = (&a.elem, &a.len);
+                let res = self.iter_from_document(elem, doc, path, quote! { Some(#len) });
+                quote! {
+                    match <[#elem; #len]>::try_from({ #res }) {
+                        Ok(_x) => _x,
+                        Err(_) => unreachable!(),
+                    }
+                }
+            }
+            Type::Tuple(t) => {
+                let items = format_ident!("_items{}", self.depth);
+                let p = format_ident!("_p{}", self.depth);
+                let len = t.elems.len();
+                self.depth += 1;
+                let elems: Vec<TokenStream> = (0..len)
+                    .map(|i| {
+                        let body =
+                            self.from_document(&t.elems[i], quote! { &#items[#i] }, quote! { &#p });
+                        quote! {{
+                            let #p = format!("{}[{}]", #path, #i);
+                            #body
+                        }}
+                    })
+                    .collect();
+                self.depth -= 1;
+                quote! {{
+                    let #items = #internal::array_from_document(#doc, #path, Some(#len))?;
+                    (#(#elems,)*)
+                }}
+            }
+            Type::Paren(p) => self.from_document(&p.elem, doc, path),
+            Type::Group(g) => self.from_document(&g.elem, doc, path),
+            _ => quote! {
+                <#ty as ::expander_compiler::frontend::SerdeAssignment>::from_document(#doc, #path)?
+            },
+        }
+    }
+
+    // Collects the elements of a document array into a Vec
+    fn iter_from_document(
+        &mut self,
+        elem: &Type,
+        doc: TokenStream,
+        path: TokenStream,
+        len: TokenStream,
+    ) -> TokenStream {
+        let items = format_ident!("_items{}", self.depth);
+        let res = format_ident!("_res{}", self.depth);
+        let i = format_ident!("_i{}", self.depth);
+        let item = format_ident!("_item{}", self.depth);
+        let p = format_ident!("_p{}", self.depth);
+        self.depth += 1;
+        let body = self.from_document(elem, quote! { #item }, quote! { &#p });
+        self.depth -= 1;
+        quote! {
+            let #items = ::expander_compiler::frontend::internal::array_from_document(#doc, #path, #len)?;
+            let mut #res: Vec<#elem> = Vec::with_capacity(#items.len());
+            for (#i, #item) in #items.iter().enumerate() {
+                let #p = format!("{}[{}]", #path, #i);
+                #res.push(#body);
+            }
+            #res
+        }
+    }
+}
+
+pub fn derive_circuit(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let data = match &input.data {
+        Data::Struct(data) => data,
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "Circuit can only be derived for structs",
+            ))
+        }
+    };
+    let type_params: Vec<&Ident> = input
+        .generics
+        .params
+        .iter()
+        .filter_map(|p| match p {
+            GenericParam::Type(t) => Some(&t.ident),
+            _ => None,
+        })
+        .collect();
+    if type_params.len() != 1 {
+        return Err(Error::new(
+            input.generics.span(),
+            "Circuit structs must have exactly one type parameter, the type of their variables",
+        ));
+    }
+    let leaf = type_params[0];
+
+    let fields: Vec<(Member, &Type, bool)> = match &data.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|f| (Member::Named(f.ident.clone().unwrap()), f))
+            .collect::<Vec<_>>(),
+        Fields::Unnamed(fields) => fields
+            .unnamed
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (Member::Unnamed(Index::from(i)), f))
+            .collect(),
+        Fields::Unit => Vec::new(),
+    }
+    .into_iter()
+    .map(|(member, f)| {
+        let public = f.attrs.iter().any(|a| a.path().is_ident("public"));
+        (member, &f.ty, public)
+    })
+    .collect();
+
+    let code = |op: Op| -> Result<Vec<TokenStream>> {
+        let mut walker = Walker { leaf, op, depth: 0 };
+        fields
+            .iter()
+            .map(|(member, ty, public)| {
+                let r = match op {
+                    Op::Load => quote! { &mut self.#member },
+                    _ => quote! { &self.#member },
+                };
+                walker.walk(ty, r, *public).map(|body| quote! { { #body } })
+            })
+            .collect()
+    };
+    let dump = code(Op::Dump)?;
+    let load = code(Op::Load)?;
+    let count = code(Op::Count)?;
+
+    // named fields are object keys, unnamed ones array elements
+    let mut doc_walker = DocumentWalker { leaf, depth: 0 };
+    let named = matches!(data.fields, Fields::Named(_));
+    let names: Vec<String> = fields
+        .iter()
+        .map(|(member, _, _)| match member {
+            Member::Named(ident) => ident.to_string(),
+            Member::Unnamed(index) => index.index.to_string(),
+        })
+        .collect();
+    let to_document: Vec<TokenStream> = fields
+        .iter()
+        .map(|(member, ty, _)| doc_walker.to_document(ty, quote! { &self.#member }))
+        .collect();
+    let from_document: Vec<TokenStream> = fields
+        .iter()
+        .zip(names.iter())
+        .enumerate()
+        .map(|(i, ((member, ty, _), name))| {
+            let doc = if named {
+                quote! { &doc[#name] }
+            } else {
+                quote! { &_items[#i] }
+            };
+            let body = doc_walker.from_document(ty, doc, quote! { &_p });
+            let path = if named {
+                quote! { ::expander_compiler::frontend::internal::join_path(path, #name) }
+            } else {
+                quote! { format!("{}[{}]", path, #i) }
+            };
+            quote! {
+                #member: {
+                    let _p = #path;
+                    #body
+                }
+            }
+        })
+        .collect();
+    let num_fields = fields.len();
+    let (to_document, check_document) = if named {
+        (
+            quote! {
+                let mut _map = ::expander_compiler::frontend::internal::serde_json::Map::new();
+                #(_map.insert(#names.to_string(), #to_document);)*
+                ::expander_compiler::frontend::internal::serde_json::Value::Object(_map)
+            },
+            quote! {
+                ::expander_compiler::frontend::internal::check_fields(doc, path, &[#(#names),*])?;
+            },
+        )
+    } else {
+        (
+            quote! {
+                ::expander_compiler::frontend::internal::serde_json::Value::Array(vec![#(#to_document),*])
+            },
+            quote! {
+                let _items = ::expander_compiler::frontend::internal::array_from_document(
+                    doc,
+                    path,
+                    Some(#num_fields),
+                )?;
+            },
+        )
+    };
+
+    let mut doc_generics = input.generics.clone();
+    doc_generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote! { #leaf: ::expander_compiler::field::Field });
+    let (doc_impl_generics, doc_ty_generics, doc_where_clause) = doc_generics.split_for_impl();
+
+    let mut generics = input.generics.clone();
+    generics.make_where_clause().predicates.push(parse_quote! {
+        #leaf: Clone + ::expander_compiler::frontend::internal::DumpLoadVariables<#leaf>
+    });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::expander_compiler::frontend::internal::DumpLoadTwoVariables<#leaf>
+            for #name #ty_generics #where_clause
+        {
+            #[allow(unused_variables)]
+            fn dump_into(&self, vars: &mut Vec<#leaf>, public_vars: &mut Vec<#leaf>) {
+                #(#dump)*
+            }
+            #[allow(unused_variables)]
+            fn load_from(&mut self, vars: &mut &[#leaf], public_vars: &mut &[#leaf]) {
+                #(#load)*
+            }
+            #[allow(unused_mut)]
+            fn num_vars(&self) -> (usize, usize) {
+                let mut cnt_sec = 0;
+                let mut cnt_pub = 0;
+                #(#count)*
+                (cnt_sec, cnt_pub)
+            }
+        }
+
+        impl #doc_impl_generics ::expander_compiler::frontend::SerdeAssignment
+            for #name #doc_ty_generics #doc_where_clause
+        {
+            fn to_document(&self) -> ::expander_compiler::frontend::internal::serde_json::Value {
+                #to_document
+            }
+            #[allow(unused_variables)]
+            fn from_document(
+                doc: &::expander_compiler::frontend::internal::serde_json::Value,
+                path: &str,
+            ) -> Result<Self, ::expander_compiler::frontend::Error> {
+                #check_document
+                Ok(Self {
+                    #(#from_document),*
+                })
+            }
+        }
+    })
+}
@@ -4,9 +4,11 @@ use syn::{
     parse::{Parse, ParseStream},
     parse_macro_input,
     punctuated::Punctuated,
-    Expr, FnArg, Ident, ItemFn, PatType, Result, ReturnType, Token, Type,
+    DeriveInput, Expr, FnArg, Ident, ItemFn, PatType, Result, ReturnType, Token, Type,
 };
 
+mod derive_circuit;
+
 #[derive(Debug)]
 enum ParamKind {
     Variable,
@@ -79,6 +81,17 @@ fn analyze_type_structure(ty: &Type, allow_primitive: bool) -> Option<(ParamKind
     None
 }
 
+/// Implements `DumpLoadTwoVariables` for a circuit struct with a single type parameter, the
+/// type of its variables. Fields may nest arrays, tuples, `Vec`s and other circuit structs,
+/// fields marked `#[public]` become public inputs.
+#[proc_macro_derive(Circuit, attributes(public))]
+pub fn derive_circuit(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive_circuit::derive_circuit(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
 #[proc_macro_attribute]
 pub fn memorized(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let input_fn = parse_macro_input!(item as ItemFn);
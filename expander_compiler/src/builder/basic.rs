@@ -1,11 +1,14 @@
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
 use crate::{
     circuit::{
         config::Config,
         ir::{
             self,
-            common::{Constraint, Instruction as _, IrConfig},
+            common::{
+                origin::{CircuitOrigins, Origin, Origins},
+                Constraint, Instruction as _, IrConfig,
+            },
             expr::{Expression, LinComb, Term, VarSpec},
         },
     },
@@ -60,6 +63,15 @@ pub struct Builder<'a, C: Config, IrcIn: IrConfig<Config = C>, IrcOut: IrConfig<
 
     // output instructions
     pub out_insns: Vec<IrcOut::Instruction>,
+
+    // in_circuit instruction or constraint currently being processed
+    pub cur_origin: Origin,
+    // origin of each output instruction
+    pub out_insn_origins: Vec<Origin>,
+    // origins of each asserted constraint, and of each output constraint once built
+    pub constraint_origins:
+        HashMap<<IrcOut::Constraint as Constraint<C>>::Type, HashMap<Expression<C>, Vec<Origin>>>,
+    pub out_constraint_origins: Vec<Vec<Origin>>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -144,6 +156,10 @@ impl<'a, C: Config, IrcIn: IrConfig<Config = C>, IrcOut: IrConfig<Config = C>>
             mid_expr_to_out: HashMap::new(),
             in_to_out: vec![0],
             out_insns: Vec::new(),
+            cur_origin: Origin::Unknown,
+            out_insn_origins: Vec::new(),
+            constraint_origins: HashMap::new(),
+            out_constraint_origins: Vec::new(),
         };
         res.mid_vars.add(&Expression::invalid());
         res.mid_to_out.push(None);
@@ -225,6 +241,15 @@ impl<'a, C: Config, IrcIn: IrConfig<Config = C>, IrcOut: IrConfig<Config = C>>
         out_var_id: usize,
     ) {
         let expr = self.out_var_exprs[out_var_id].clone();
+        let origins = self
+            .constraint_origins
+            .entry(constraint_type)
+            .or_default()
+            .entry(expr.clone())
+            .or_default();
+        if !origins.contains(&self.cur_origin) {
+            origins.push(self.cur_origin);
+        }
         self.constraints
             .entry(constraint_type)
             .or_default()
@@ -321,6 +346,7 @@ where
     {
         let num_out = out_insn.num_outputs();
         self.out_insns.push(out_insn.clone());
+        self.out_insn_origins.push(self.cur_origin);
         self.execute_out(&out_insn, root);
         if num_out == 1 {
             Some(self.out_var_exprs.len() - 1)
@@ -334,6 +360,7 @@ where
     pub fn push_insn_multi_out(&mut self, out_insn: IrcOut::Instruction) -> Vec<usize> {
         let num_out = out_insn.num_outputs();
         self.out_insns.push(out_insn.clone());
+        self.out_insn_origins.push(self.cur_origin);
         self.execute_out(&out_insn, None);
         let mut out_var_ids = Vec::new();
         for i in 0..num_out {
@@ -606,13 +633,17 @@ where
     //let circuit = root.rc.circuits.get(&circuit_id).unwrap();
     let mut builder = Builder::new(circuit_id, circuit);
     builder.add_input();
-    for insn in circuit.instructions.iter() {
+    for (i, insn) in circuit.instructions.iter().enumerate() {
+        builder.cur_origin = Origin::Instruction(i);
         builder.process_insn(insn, root)?;
     }
-    for con in circuit.constraints.iter() {
+    for (i, con) in circuit.constraints.iter().enumerate() {
+        builder.cur_origin = Origin::Constraint(i);
         builder.process_con(con)?;
     }
+    builder.cur_origin = Origin::Unknown;
     let mut constraints = Vec::new();
+    let mut constraint_origins = Vec::new();
     for (typ, cons) in builder.constraints.iter() {
         let mut cons_keys = cons.keys().collect::<Vec<_>>();
         cons_keys.sort();
@@ -622,10 +653,19 @@ where
                 ConstraintStatus::Marked => {}
                 ConstraintStatus::Asserted => {
                     constraints.push(Constraint::new(builder.mid_expr_to_out[expr], *typ));
+                    constraint_origins.push(
+                        builder
+                            .constraint_origins
+                            .get(typ)
+                            .and_then(|x| x.get(expr))
+                            .cloned()
+                            .unwrap_or_default(),
+                    );
                 }
             }
         }
     }
+    builder.out_constraint_origins = constraint_origins;
     let new_circuit = ir::common::Circuit {
         instructions: builder.out_insns.clone(),
         constraints,
@@ -647,6 +687,38 @@ pub fn process_root_circuit<
 >(
     rc: &'a ir::common::RootCircuit<IrcIn>,
 ) -> Result<ir::common::RootCircuit<IrcOut>, Error>
+where
+    Builder<'a, C, IrcIn, IrcOut>: InsnTransformAndExecute<'a, C, IrcIn, IrcOut>,
+{
+    let (res, _) = process_root_circuit_with_origins(rc)?;
+    Ok(res)
+}
+
+pub fn process_root_circuit_with_origins<
+    'a,
+    C: Config + 'a,
+    IrcIn: IrConfig<Config = C> + 'a,
+    IrcOut: IrConfig<Config = C> + 'a,
+>(
+    rc: &'a ir::common::RootCircuit<IrcIn>,
+) -> Result<(ir::common::RootCircuit<IrcOut>, Origins), Error>
+where
+    Builder<'a, C, IrcIn, IrcOut>: InsnTransformAndExecute<'a, C, IrcIn, IrcOut>,
+{
+    process_root_circuit_skipping(rc, &HashSet::new())
+}
+
+// Circuits in skip are left out of the result, for callers that already have them.
+// This only works for transformations that never look into their sub circuits.
+pub fn process_root_circuit_skipping<
+    'a,
+    C: Config + 'a,
+    IrcIn: IrConfig<Config = C> + 'a,
+    IrcOut: IrConfig<Config = C> + 'a,
+>(
+    rc: &'a ir::common::RootCircuit<IrcIn>,
+    skip: &HashSet<usize>,
+) -> Result<(ir::common::RootCircuit<IrcOut>, Origins), Error>
 where
     Builder<'a, C, IrcIn, IrcOut>: InsnTransformAndExecute<'a, C, IrcIn, IrcOut>,
 {
@@ -657,14 +729,30 @@ where
     };
     let order = rc.topo_order();
     for &circuit_id in order.iter().rev() {
+        if skip.contains(&circuit_id) {
+            continue;
+        }
         let (new_circuit, final_builder) =
             process_circuit(&mut root, circuit_id, rc.circuits.get(&circuit_id).unwrap())?;
         root.out_circuits.insert(circuit_id, new_circuit);
         root.builders.insert(circuit_id, final_builder);
     }
-    Ok(ir::common::RootCircuit {
-        num_public_inputs: rc.num_public_inputs,
-        expected_num_output_zeroes: rc.expected_num_output_zeroes,
-        circuits: root.out_circuits,
-    })
+    let mut origins = Origins::default();
+    for (circuit_id, builder) in root.builders.iter_mut() {
+        origins.circuits.insert(
+            *circuit_id,
+            CircuitOrigins {
+                instructions: std::mem::take(&mut builder.out_insn_origins),
+                constraints: std::mem::take(&mut builder.out_constraint_origins),
+            },
+        );
+    }
+    Ok((
+        ir::common::RootCircuit {
+            num_public_inputs: rc.num_public_inputs,
+            expected_num_output_zeroes: rc.expected_num_output_zeroes,
+            circuits: root.out_circuits,
+        },
+        origins,
+    ))
 }
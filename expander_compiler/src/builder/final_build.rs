@@ -353,7 +353,7 @@ mod tests {
                     }
                 }
                 Err(e) => match e {
-                    Error::UserError(_) => {}
+                    Error::UserError(_) | Error::HintError(_) => {}
                     Error::InternalError(e) => {
                         panic!("{:?}", e);
                     }
@@ -394,7 +394,7 @@ mod tests {
                     }
                 }
                 Err(e) => match e {
-                    Error::UserError(_) => {}
+                    Error::UserError(_) | Error::HintError(_) => {}
                     Error::InternalError(e) => {
                         panic!("{:?}", e);
                     }
@@ -856,7 +856,7 @@ mod tests {
                     }
                 }
                 Err(e) => match e {
-                    Error::UserError(_) => {}
+                    Error::UserError(_) | Error::HintError(_) => {}
                     Error::InternalError(e) => {
                         panic!("{:?}", e);
                     }
@@ -897,7 +897,7 @@ mod tests {
                     }
                 }
                 Err(e) => match e {
-                    Error::UserError(_) => {}
+                    Error::UserError(_) | Error::HintError(_) => {}
                     Error::InternalError(e) => {
                         panic!("{:?}", e);
                     }
@@ -1,4 +1,8 @@
-use crate::circuit::ir::common::RawConstraint;
+use std::collections::HashSet;
+
+use ethnum::U256;
+
+use crate::circuit::ir::common::{origin::Origins, RawConstraint};
 use crate::circuit::ir::expr;
 use crate::field::FieldArith;
 use crate::frontend::CircuitField;
@@ -16,7 +20,8 @@ use crate::{
 };
 
 use super::basic::{
-    process_root_circuit, InsnTransformAndExecute, InsnTransformResult, RootBuilder,
+    process_root_circuit, process_root_circuit_skipping, process_root_circuit_with_origins,
+    InsnTransformAndExecute, InsnTransformResult, RootBuilder,
 };
 
 type IrcIn<C> = ir::source::Irc<C>;
@@ -84,6 +89,31 @@ impl<'a, C: Config> Builder<'a, C> {
         let t = self.bool_cond(a);
         self.mark((), t);
     }
+    fn push_is_zero(&mut self, x: usize) -> usize {
+        let one = self.push_const(CircuitField::<C>::one());
+        let inv = self
+            .push_insn(InsnOut::Hint {
+                hint_id: BuiltinHintIds::Div as u64 as usize,
+                inputs: vec![one, x],
+                num_outputs: 1,
+            })
+            .unwrap();
+        let prod = self.push_mul(x, inv);
+        let m = self.push_sub(one, prod);
+        let xm = self.push_mul(x, m);
+        self.assert((), xm);
+        self.mark_bool(m);
+        m
+    }
+    // if_false + cond * (if_true - if_false), cond is assumed to be boolean
+    fn push_select(&mut self, cond: usize, if_true: usize, if_false: usize) -> usize {
+        if if_true == if_false {
+            return if_true;
+        }
+        let diff = self.push_sub(if_true, if_false);
+        let t = self.push_mul(cond, diff);
+        self.push_add(if_false, t)
+    }
 }
 
 impl<'a, C: Config> InsnTransformAndExecute<'a, C, IrcIn<C>, IrcOut<C>> for Builder<'a, C> {
@@ -166,19 +196,7 @@ impl<'a, C: Config> InsnTransformAndExecute<'a, C, IrcIn<C>, IrcOut<C>> for Buil
                         CircuitField::<C>::zero()
                     }))
                 } else {
-                    let one = self.push_const(CircuitField::<C>::one());
-                    let inv = self
-                        .push_insn(InsnOut::Hint {
-                            hint_id: BuiltinHintIds::Div as u64 as usize,
-                            inputs: vec![one, *x],
-                            num_outputs: 1,
-                        })
-                        .unwrap();
-                    let prod = self.push_mul(*x, inv);
-                    let m = self.push_sub(one, prod);
-                    let xm = self.push_mul(*x, m);
-                    self.assert((), xm);
-                    self.mark_bool(m);
+                    let m = self.push_is_zero(*x);
                     return self.copy(m);
                 }
             }
@@ -301,6 +319,67 @@ impl<'a, C: Config> InsnTransformAndExecute<'a, C, IrcIn<C>, IrcOut<C>> for Buil
                 self.assert((), sum);
                 return self.copys(&bits);
             }
+            Mux { selectors, values } => {
+                // halve the candidates once per selector, constant selectors pick a half
+                let mut values = values.clone();
+                for &s in selectors.iter() {
+                    match self.constant_value(s) {
+                        Some(sv) if sv.is_zero() => {
+                            values = values.iter().step_by(2).cloned().collect();
+                        }
+                        Some(sv) if sv == CircuitField::<C>::one() => {
+                            values = values.iter().skip(1).step_by(2).cloned().collect();
+                        }
+                        Some(_) => {
+                            return InsnTransformResult::Err(Error::UserError(
+                                "mux selector is not boolean".to_string(),
+                            ));
+                        }
+                        None => {
+                            self.assert_bool(s);
+                            let mut next = Vec::with_capacity(values.len() / 2);
+                            for pair in values.chunks(2) {
+                                next.push(self.push_select(s, pair[1], pair[0]));
+                            }
+                            values = next;
+                        }
+                    }
+                }
+                return self.copy(values[0]);
+            }
+            Lookup { index, values } => {
+                if let Some(iv) = self.constant_value(*index) {
+                    let iv = iv.to_u256();
+                    if iv >= U256::from(values.len() as u64) {
+                        return InsnTransformResult::Err(Error::UserError(
+                            "lookup index out of range".to_string(),
+                        ));
+                    }
+                    return self.copy(values[iv.as_usize()]);
+                }
+                // e_i = is_zero(index - i) is one-hot iff the index is in range
+                let mut sum_e = self.push_const(CircuitField::<C>::zero());
+                let mut res = self.push_const(CircuitField::<C>::zero());
+                for (i, &v) in values.iter().enumerate() {
+                    let d = self
+                        .push_insn(InsnOut::LinComb(expr::LinComb {
+                            terms: vec![LinCombTerm {
+                                coef: CircuitField::<C>::one(),
+                                var: *index,
+                            }],
+                            constant: -CircuitField::<C>::from(i as u32),
+                        }))
+                        .unwrap();
+                    let e = self.push_is_zero(d);
+                    sum_e = self.push_add(sum_e, e);
+                    let ev = self.push_mul(e, v);
+                    res = self.push_add(res, ev);
+                }
+                let one = self.push_const(CircuitField::<C>::one());
+                let t = self.push_sub(sum_e, one);
+                self.assert((), t);
+                return self.copy(res);
+            }
         })
     }
 
@@ -375,6 +454,21 @@ pub fn process<C: Config>(
     process_root_circuit(rc)
 }
 
+pub fn process_with_origins<C: Config>(
+    rc: &ir::common::RootCircuit<IrcIn<C>>,
+) -> Result<(ir::common::RootCircuit<IrcOut<C>>, Origins), Error> {
+    process_root_circuit_with_origins(rc)
+}
+
+// hint normalization of a circuit does not depend on its sub circuits, so circuits that
+// were normalized before can be skipped
+pub fn process_skipping<C: Config>(
+    rc: &ir::common::RootCircuit<IrcIn<C>>,
+    skip: &HashSet<usize>,
+) -> Result<(ir::common::RootCircuit<IrcOut<C>>, Origins), Error> {
+    process_root_circuit_skipping(rc, skip)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::field::FieldArith;
@@ -461,7 +555,7 @@ mod tests {
                     }
                 }
                 Err(e) => match e {
-                    Error::UserError(_) => {}
+                    Error::UserError(_) | Error::HintError(_) => {}
                     Error::InternalError(e) => {
                         panic!("{:?}", e);
                     }
@@ -504,7 +598,7 @@ mod tests {
                     }
                 }
                 Err(e) => match e {
-                    Error::UserError(_) => {}
+                    Error::UserError(_) | Error::HintError(_) => {}
                     Error::InternalError(e) => {
                         panic!("{:?}", e);
                     }
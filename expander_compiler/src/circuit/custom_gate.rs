@@ -0,0 +1,264 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use once_cell::sync::Lazy;
+
+use crate::{
+    circuit::{
+        config::{CircuitField, Config},
+        ir::hint_normalized::{Instruction, RootCircuit},
+        layered::{self, InputType},
+    },
+    field::Field,
+    utils::error::Error,
+};
+
+// Uni gates built into Expander, they need no registration
+pub const EXPANDER_POW5_GATE_TYPE: usize = 12345;
+pub const EXPANDER_POW1_GATE_TYPE: usize = 12346;
+
+// Highest degree of a custom gate, that of the pow5 gate. The sumcheck of a layer is run over
+// polynomials of this degree in Expander.
+pub const MAX_CUSTOM_GATE_DEGREE: usize = 5;
+
+/// Semantics of a user defined gate.
+///
+/// A custom gate computes a single output from `arity()` inputs. Every stage of the compiler,
+/// the witness solver and the layered circuit evaluate it through `CustomGateRegistry`.
+pub trait CustomGate<F: Field>: Send + Sync + 'static {
+    fn arity(&self) -> usize;
+
+    /// Degree of the gate as a polynomial in its inputs, at most `MAX_CUSTOM_GATE_DEGREE`.
+    fn degree(&self) -> usize;
+
+    fn eval(&self, inputs: &[F]) -> F;
+
+    /// Evaluates all lanes of a SIMD field at once, `inputs[i][lane]` is input `i`.
+    fn eval_simd(&self, inputs: &[Vec<F>], outputs: &mut [F]) {
+        let mut args = Vec::with_capacity(inputs.len());
+        for (lane, output) in outputs.iter_mut().enumerate() {
+            args.clear();
+            args.extend(inputs.iter().map(|x| x[lane]));
+            *output = self.eval(&args);
+        }
+    }
+
+    /// Gate type of the equivalent uni gate in Expander, if there is one.
+    /// Circuits using a gate without it can be evaluated but not exported.
+    fn expander_gate_type(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// x^5, the S-box of Poseidon over M31 and friends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Pow5Gate;
+
+impl<F: Field> CustomGate<F> for Pow5Gate {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn degree(&self) -> usize {
+        5
+    }
+
+    fn eval(&self, inputs: &[F]) -> F {
+        let x2 = inputs[0] * inputs[0];
+        x2 * x2 * inputs[0]
+    }
+
+    fn expander_gate_type(&self) -> Option<usize> {
+        Some(EXPANDER_POW5_GATE_TYPE)
+    }
+}
+
+/// The identity, a relay through a uni gate
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Pow1Gate;
+
+impl<F: Field> CustomGate<F> for Pow1Gate {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn degree(&self) -> usize {
+        1
+    }
+
+    fn eval(&self, inputs: &[F]) -> F {
+        inputs[0]
+    }
+
+    fn expander_gate_type(&self) -> Option<usize> {
+        Some(EXPANDER_POW1_GATE_TYPE)
+    }
+}
+
+type GateEntry = Arc<dyn Any + Send + Sync>;
+
+// Gates are keyed by field and gate type, each entry holds an Arc<dyn CustomGate<F>>
+static CUSTOM_GATES: Lazy<RwLock<HashMap<(TypeId, usize), GateEntry>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Process wide registry of custom gates.
+///
+/// The registry is global because layered circuits and IRs are evaluated without any context,
+/// e.g. in `layered::Circuit::eval_with_public_inputs_simd`. A gate must be registered for
+/// the circuit field before a circuit using it is evaluated, solved or exported.
+pub struct CustomGateRegistry;
+
+impl CustomGateRegistry {
+    pub fn is_builtin(gate_type: usize) -> bool {
+        gate_type == EXPANDER_POW5_GATE_TYPE || gate_type == EXPANDER_POW1_GATE_TYPE
+    }
+
+    pub fn register<F: Field + 'static, G: CustomGate<F>>(gate_type: usize, gate: G) {
+        Self::try_register(gate_type, gate).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Same as `register`, but returns an error instead of panicking when the gate type is
+    /// builtin or already registered for this field.
+    pub fn try_register<F: Field + 'static, G: CustomGate<F>>(
+        gate_type: usize,
+        gate: G,
+    ) -> Result<(), Error> {
+        if Self::is_builtin(gate_type) {
+            return Err(Error::UserError(format!(
+                "custom gate type {gate_type} is reserved for a builtin gate"
+            )));
+        }
+        if gate.arity() == 0 {
+            return Err(Error::UserError(format!(
+                "custom gate type {gate_type} must have at least 1 input"
+            )));
+        }
+        if gate.degree() > MAX_CUSTOM_GATE_DEGREE {
+            return Err(Error::UserError(format!(
+                "custom gate type {gate_type} has degree {}, over {MAX_CUSTOM_GATE_DEGREE}",
+                gate.degree()
+            )));
+        }
+        let mut gates = CUSTOM_GATES.write().unwrap();
+        let key = (TypeId::of::<F>(), gate_type);
+        if gates.contains_key(&key) {
+            return Err(Error::UserError(format!(
+                "custom gate type {gate_type} is already registered"
+            )));
+        }
+        let gate: Arc<dyn CustomGate<F>> = Arc::new(gate);
+        gates.insert(key, Arc::new(gate));
+        Ok(())
+    }
+
+    pub fn get<F: Field + 'static>(gate_type: usize) -> Option<Arc<dyn CustomGate<F>>> {
+        match gate_type {
+            EXPANDER_POW5_GATE_TYPE => return Some(Arc::new(Pow5Gate)),
+            EXPANDER_POW1_GATE_TYPE => return Some(Arc::new(Pow1Gate)),
+            _ => {}
+        }
+        CUSTOM_GATES
+            .read()
+            .unwrap()
+            .get(&(TypeId::of::<F>(), gate_type))
+            .and_then(|x| x.downcast_ref::<Arc<dyn CustomGate<F>>>())
+            .cloned()
+    }
+
+    pub fn contains<F: Field + 'static>(gate_type: usize) -> bool {
+        Self::get::<F>(gate_type).is_some()
+    }
+
+    pub fn eval<F: Field + 'static>(gate_type: usize, inputs: &[F]) -> Result<F, Error> {
+        let gate = Self::get_checked::<F>(gate_type, inputs.len())?;
+        Ok(gate.eval(inputs))
+    }
+
+    pub fn eval_simd<F: Field + 'static>(
+        gate_type: usize,
+        inputs: &[Vec<F>],
+        outputs: &mut [F],
+    ) -> Result<(), Error> {
+        let gate = Self::get_checked::<F>(gate_type, inputs.len())?;
+        gate.eval_simd(inputs, outputs);
+        Ok(())
+    }
+
+    /// Gate type of the uni gate exported to Expander for this custom gate.
+    pub fn expander_gate_type<F: Field + 'static>(gate_type: usize) -> Result<usize, Error> {
+        let gate = Self::get::<F>(gate_type).ok_or_else(|| not_registered(gate_type))?;
+        if gate.arity() != 1 {
+            return Err(Error::UserError(format!(
+                "custom gate type {gate_type} has {} inputs, Expander only supports uni gates",
+                gate.arity()
+            )));
+        }
+        gate.expander_gate_type().ok_or_else(|| {
+            Error::UserError(format!(
+                "custom gate type {gate_type} has no Expander equivalent"
+            ))
+        })
+    }
+
+    /// Gate type written to Expander for a gate of the layered circuit: registered gates are
+    /// mapped to their Expander equivalent, other types are kept as they are.
+    pub fn export_gate_type<F: Field + 'static>(gate_type: usize) -> Result<usize, Error> {
+        if Self::contains::<F>(gate_type) {
+            Self::expander_gate_type::<F>(gate_type)
+        } else {
+            Ok(gate_type)
+        }
+    }
+
+    /// Checks that every registered custom gate of a layered circuit has an Expander
+    /// equivalent. `export_to_expander` runs it first, gate types that are not registered are
+    /// exported unchanged.
+    pub fn validate_export<C: Config, I: InputType>(
+        circuit: &layered::Circuit<C, I>,
+    ) -> Result<(), Error> {
+        for (i, seg) in circuit.segments.iter().enumerate() {
+            for gate in seg.gate_customs.iter() {
+                Self::export_gate_type::<CircuitField<C>>(gate.gate_type)
+                    .map_err(|e| e.prepend(&format!("segment {i}")))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that every custom gate used by the circuit is registered for its field with the
+    /// right number of inputs, so that missing gates are reported before witness solving.
+    pub fn validate_against<C: Config>(root: &RootCircuit<C>) -> Result<(), Error> {
+        let mut circuit_ids: Vec<usize> = root.circuits.keys().cloned().collect();
+        circuit_ids.sort();
+        for circuit_id in circuit_ids {
+            for (i, insn) in root.circuits[&circuit_id].instructions.iter().enumerate() {
+                if let Instruction::CustomGate { gate_type, inputs } = insn {
+                    Self::get_checked::<CircuitField<C>>(*gate_type, inputs.len())
+                        .map_err(|e| e.prepend(&format!("circuit {circuit_id} instruction {i}")))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn get_checked<F: Field + 'static>(
+        gate_type: usize,
+        num_inputs: usize,
+    ) -> Result<Arc<dyn CustomGate<F>>, Error> {
+        let gate = Self::get::<F>(gate_type).ok_or_else(|| not_registered(gate_type))?;
+        if gate.arity() != num_inputs {
+            return Err(Error::UserError(format!(
+                "custom gate type {gate_type} expects {} inputs, got {num_inputs}",
+                gate.arity()
+            )));
+        }
+        Ok(gate)
+    }
+}
+
+fn not_registered(gate_type: usize) -> Error {
+    Error::UserError(format!("custom gate type {gate_type} is not registered"))
+}
@@ -0,0 +1,78 @@
+use serdes::ExpSerde;
+use tiny_keccak::Hasher;
+
+use super::{Circuit, HashMap, Instruction, IrConfig, RootCircuit};
+use crate::circuit::config::Config;
+
+impl<Irc: IrConfig> Circuit<Irc> {
+    // distinct sub circuits called by this circuit, in order of their first call
+    pub fn callees(&self) -> Vec<usize> {
+        let mut res = Vec::new();
+        for insn in self.instructions.iter() {
+            if let Some((sub_circuit_id, _, _)) = insn.as_sub_circuit_call() {
+                if !res.contains(&sub_circuit_id) {
+                    res.push(sub_circuit_id);
+                }
+            }
+        }
+        res
+    }
+
+    pub fn replace_sub_circuit_ids<F: Fn(usize) -> usize>(&self, f: F) -> Self {
+        Circuit {
+            instructions: self
+                .instructions
+                .iter()
+                .map(|insn| match insn.as_sub_circuit_call() {
+                    Some((sub_circuit_id, inputs, num_outputs)) => {
+                        Irc::Instruction::sub_circuit_call(
+                            f(sub_circuit_id),
+                            inputs.clone(),
+                            num_outputs,
+                        )
+                    }
+                    None => insn.clone(),
+                })
+                .collect(),
+            constraints: self.constraints.clone(),
+            outputs: self.outputs.clone(),
+            num_inputs: self.num_inputs,
+        }
+    }
+
+    // sub circuit ids replaced by their index in callees()
+    pub fn with_local_sub_circuit_ids(&self) -> Self {
+        let callees = self.callees();
+        self.replace_sub_circuit_ids(|id| callees.iter().position(|x| *x == id).unwrap())
+    }
+}
+
+impl<Irc: IrConfig> RootCircuit<Irc>
+where
+    Irc::Instruction: ExpSerde,
+    Irc::Constraint: ExpSerde,
+{
+    // A hash of each circuit that does not depend on circuit ids: sub circuits are identified
+    // by their own content hash, so the same gadget hashes the same way in every build.
+    pub fn content_hashes(&self) -> HashMap<usize, [u8; 32]> {
+        let mut res: HashMap<usize, [u8; 32]> = HashMap::new();
+        for id in self.topo_order().iter().rev() {
+            let circuit = &self.circuits[id];
+            let mut buf = Vec::new();
+            circuit
+                .with_local_sub_circuit_ids()
+                .serialize_into(&mut buf)
+                .unwrap();
+            let mut hasher = tiny_keccak::Keccak::v256();
+            hasher.update(&Irc::Config::CONFIG_ID.to_le_bytes());
+            hasher.update(&buf);
+            for callee in circuit.callees() {
+                hasher.update(&res[&callee]);
+            }
+            let mut hash = [0u8; 32];
+            hasher.finalize(&mut hash);
+            res.insert(*id, hash);
+        }
+        res
+    }
+}
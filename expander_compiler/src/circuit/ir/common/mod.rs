@@ -14,8 +14,10 @@ use crate::{
     },
 };
 
+pub mod content_hash;
 pub mod display;
 pub mod opt;
+pub mod origin;
 pub mod serde;
 pub mod stats;
 
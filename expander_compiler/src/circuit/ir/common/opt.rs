@@ -5,8 +5,8 @@ use crate::{
 };
 
 use super::{
-    Circuit, Constraint, Debug, FieldArith, Hash, HashMap, HashSet, Instruction, IrConfig,
-    RootCircuit,
+    origin::Origins, Circuit, Constraint, Debug, FieldArith, Hash, HashMap, HashSet, Instruction,
+    IrConfig, RootCircuit,
 };
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -27,6 +27,18 @@ use ElementType::*;
 
 impl<Irc: IrConfig> RootCircuit<Irc> {
     pub fn remove_unreachable(&self) -> (Self, InputMapping) {
+        let (res, im, _) = self.remove_unreachable_inner();
+        (res, im)
+    }
+
+    // same as remove_unreachable, but also reports where the remaining instructions come from
+    pub fn remove_unreachable_with_origins(&self) -> (Self, InputMapping, Origins) {
+        let (res, im, kept) = self.remove_unreachable_inner();
+        let origins = Origins::from_kept_instructions(&res, &kept);
+        (res, im, origins)
+    }
+
+    fn remove_unreachable_inner(&self) -> (Self, InputMapping, HashMap<usize, Vec<usize>>) {
         let order = self.topo_order();
         // first, remove unused sub circuits based on constraints
         let mut circuit_used: HashSet<usize> = HashSet::new();
@@ -268,10 +280,12 @@ impl<Irc: IrConfig> RootCircuit<Irc> {
         let is_visited = |x: &Element| visited[vertice_id(x)];
         // now we can process each circuit
         let mut new_circuits: HashMap<usize, Circuit<Irc>> = HashMap::new();
+        let mut kept_instructions: HashMap<usize, Vec<usize>> = HashMap::new();
         for circuit_id in order.iter().rev() {
             let circuit = self.circuits.get(circuit_id).unwrap();
             let mut var_map: Vec<usize> = vec![EMPTY];
             let mut new_instructions: Vec<Irc::Instruction> = Vec::new();
+            let mut kept: Vec<usize> = Vec::new();
             let mut mapped_var_max: usize = 0;
             for i in 1..=circuit.get_num_inputs_all() {
                 if is_visited(&Element {
@@ -294,6 +308,7 @@ impl<Irc: IrConfig> RootCircuit<Irc> {
                     var_map.resize(var_map.len() + insn.num_outputs(), EMPTY);
                     continue;
                 }
+                kept.push(i);
                 if let Some((sub_circuit_id, inputs, num_outputs)) = insn.as_sub_circuit_call() {
                     let mut new_inputs: Vec<usize> = Vec::new();
                     let mut new_num_outputs: usize = 0;
@@ -359,6 +374,7 @@ impl<Irc: IrConfig> RootCircuit<Irc> {
                         .count(),
                 },
             );
+            kept_instructions.insert(*circuit_id, kept);
         }
         let mut root_input_mask: Vec<bool> = Vec::new();
         for i in 1..=self.circuits[&0].num_inputs {
@@ -385,6 +401,7 @@ impl<Irc: IrConfig> RootCircuit<Irc> {
                 circuits: new_circuits,
             },
             InputMapping::new(new_input_size, input_mapping_vec),
+            kept_instructions,
         )
     }
 
@@ -0,0 +1,126 @@
+use super::{HashMap, IrConfig, RootCircuit};
+
+// Where an instruction or constraint of a transformed circuit comes from, as an index
+// into the circuit with the same id before the transformation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Origin {
+    Unknown,
+    Instruction(usize),
+    Constraint(usize),
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct CircuitOrigins {
+    pub instructions: Vec<Origin>,
+    // a constraint may be merged from several ones
+    pub constraints: Vec<Vec<Origin>>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Origins {
+    pub circuits: HashMap<usize, CircuitOrigins>,
+}
+
+impl Origins {
+    pub fn identity<Irc: IrConfig>(rc: &RootCircuit<Irc>) -> Self {
+        let mut circuits = HashMap::new();
+        for (id, circuit) in rc.circuits.iter() {
+            circuits.insert(
+                *id,
+                CircuitOrigins {
+                    instructions: (0..circuit.instructions.len())
+                        .map(Origin::Instruction)
+                        .collect(),
+                    constraints: (0..circuit.constraints.len())
+                        .map(|i| vec![Origin::Constraint(i)])
+                        .collect(),
+                },
+            );
+        }
+        Origins { circuits }
+    }
+
+    // for passes that keep constraints and a subsequence of the instructions of each circuit
+    pub fn from_kept_instructions<Irc: IrConfig>(
+        rc: &RootCircuit<Irc>,
+        kept: &HashMap<usize, Vec<usize>>,
+    ) -> Self {
+        let mut circuits = HashMap::new();
+        for (id, circuit) in rc.circuits.iter() {
+            circuits.insert(
+                *id,
+                CircuitOrigins {
+                    instructions: kept[id].iter().map(|i| Origin::Instruction(*i)).collect(),
+                    constraints: (0..circuit.constraints.len())
+                        .map(|i| vec![Origin::Constraint(i)])
+                        .collect(),
+                },
+            );
+        }
+        Origins { circuits }
+    }
+
+    // instructions appended by a pass (e.g. reassign_duplicate_sub_circuit_outputs) have no origin
+    pub fn extend_to<Irc: IrConfig>(&mut self, rc: &RootCircuit<Irc>) {
+        for (id, circuit) in rc.circuits.iter() {
+            if let Some(co) = self.circuits.get_mut(id) {
+                co.instructions
+                    .resize(circuit.instructions.len(), Origin::Unknown);
+            }
+        }
+    }
+
+    // self maps the current circuit to prev's circuit, prev maps that one further back
+    pub fn compose(&self, prev: &Origins) -> Origins {
+        let mut circuits = HashMap::new();
+        for (id, co) in self.circuits.iter() {
+            let prev_co = match prev.circuits.get(id) {
+                Some(x) => x,
+                None => {
+                    circuits.insert(*id, co.clone());
+                    continue;
+                }
+            };
+            let map_one = |o: &Origin| -> Vec<Origin> {
+                match o {
+                    Origin::Unknown => vec![],
+                    Origin::Instruction(i) => match prev_co.instructions.get(*i) {
+                        Some(Origin::Unknown) | None => vec![],
+                        Some(x) => vec![*x],
+                    },
+                    Origin::Constraint(i) => {
+                        prev_co.constraints.get(*i).cloned().unwrap_or_default()
+                    }
+                }
+            };
+            let instructions = co
+                .instructions
+                .iter()
+                .map(|o| map_one(o).first().copied().unwrap_or(Origin::Unknown))
+                .collect();
+            let constraints = co
+                .constraints
+                .iter()
+                .map(|os| {
+                    let mut res: Vec<Origin> = Vec::new();
+                    for o in os.iter() {
+                        for x in map_one(o) {
+                            if !res.contains(&x) {
+                                res.push(x);
+                            }
+                        }
+                    }
+                    res
+                })
+                .collect();
+            circuits.insert(
+                *id,
+                CircuitOrigins {
+                    instructions,
+                    constraints,
+                },
+            );
+        }
+        Origins { circuits }
+    }
+}
@@ -1,9 +1,8 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::circuit::{config::Config, layered::Coef};
+use crate::circuit::{config::Config, custom_gate::CustomGateRegistry, layered::Coef};
 use crate::field::FieldArith;
 use crate::frontend::CircuitField;
-use crate::hints;
 use crate::utils::error::Error;
 
 use super::common::EvalResult;
@@ -18,6 +17,7 @@ pub mod tests;
 
 pub mod display;
 pub mod mul_fanout_limit;
+pub mod serde;
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub enum Instruction<C: Config> {
@@ -138,7 +138,10 @@ impl<C: Config> common::Instruction<C> for Instruction<C> {
                         VarSpec::Custom { gate_type, inputs } => {
                             let args: Vec<CircuitField<C>> =
                                 inputs.iter().map(|i| values[*i]).collect();
-                            sum += hints::stub_impl(*gate_type, &args, 1)[0] * term.coef;
+                            match CustomGateRegistry::eval(*gate_type, &args) {
+                                Ok(output) => sum += output * term.coef,
+                                Err(e) => return EvalResult::Error(e),
+                            }
                         }
                         VarSpec::RandomLinear(i) => {
                             sum += values[*i] * Coef::<C>::Random.get_value_unsafe();
@@ -464,7 +464,7 @@ mod tests {
                         panic!("{:?}", e);
                     }
                 }
-                Ok((_, circuit)) => {
+                Ok((_, circuit, _)) => {
                     assert_eq!(circuit.validate(), Ok(()));
                     for segment in circuit.segments.iter() {
                         let mut ref_num = vec![0; segment.num_inputs.get(0)];
@@ -1,6 +1,6 @@
 use std::{
     fmt,
-    io::{Read, Write},
+    io::{Error as IoError, Read, Write},
     ops::{Deref, DerefMut},
 };
 
@@ -497,3 +497,77 @@ impl<C: Config> ExpSerde for LinComb<C> {
         Ok(LinComb { terms, constant })
     }
 }
+
+impl ExpSerde for VarSpec {
+    fn serialize_into<W: Write>(&self, mut writer: W) -> SerdeResult<()> {
+        match self {
+            VarSpec::Const => {
+                1u8.serialize_into(&mut writer)?;
+            }
+            VarSpec::Linear(x) => {
+                2u8.serialize_into(&mut writer)?;
+                x.serialize_into(&mut writer)?;
+            }
+            VarSpec::Quad(x, y) => {
+                3u8.serialize_into(&mut writer)?;
+                x.serialize_into(&mut writer)?;
+                y.serialize_into(&mut writer)?;
+            }
+            VarSpec::Custom { gate_type, inputs } => {
+                4u8.serialize_into(&mut writer)?;
+                gate_type.serialize_into(&mut writer)?;
+                inputs.serialize_into(&mut writer)?;
+            }
+            VarSpec::RandomLinear(x) => {
+                5u8.serialize_into(&mut writer)?;
+                x.serialize_into(&mut writer)?;
+            }
+        };
+        Ok(())
+    }
+
+    fn deserialize_from<R: Read>(mut reader: R) -> SerdeResult<Self> {
+        let var_spec_type = u8::deserialize_from(&mut reader)?;
+        Ok(match var_spec_type {
+            1 => VarSpec::Const,
+            2 => VarSpec::Linear(usize::deserialize_from(&mut reader)?),
+            3 => VarSpec::Quad(
+                usize::deserialize_from(&mut reader)?,
+                usize::deserialize_from(&mut reader)?,
+            ),
+            4 => VarSpec::Custom {
+                gate_type: usize::deserialize_from(&mut reader)?,
+                inputs: Vec::<usize>::deserialize_from(&mut reader)?,
+            },
+            5 => VarSpec::RandomLinear(usize::deserialize_from(&mut reader)?),
+            _ => {
+                return Err(IoError::new(
+                    std::io::ErrorKind::InvalidData,
+                    "invalid VarSpecType",
+                ))?
+            }
+        })
+    }
+}
+
+impl<C: Config> ExpSerde for Expression<C> {
+    fn serialize_into<W: Write>(&self, mut writer: W) -> SerdeResult<()> {
+        self.terms.len().serialize_into(&mut writer)?;
+        for term in self.terms.iter() {
+            term.coef.serialize_into(&mut writer)?;
+            term.vars.serialize_into(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize_from<R: Read>(mut reader: R) -> SerdeResult<Self> {
+        let len = usize::deserialize_from(&mut reader)?;
+        let mut terms = Vec::with_capacity(len);
+        for _ in 0..len {
+            let coef = CircuitField::<C>::deserialize_from(&mut reader)?;
+            let vars = VarSpec::deserialize_from(&mut reader)?;
+            terms.push(Term { coef, vars });
+        }
+        Ok(Expression { terms })
+    }
+}
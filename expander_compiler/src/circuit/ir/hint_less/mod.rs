@@ -1,7 +1,6 @@
-use crate::circuit::{config::Config, layered::Coef};
+use crate::circuit::{config::Config, custom_gate::CustomGateRegistry, layered::Coef};
 use crate::field::FieldArith;
 use crate::frontend::CircuitField;
-use crate::hints;
 use crate::utils::error::Error;
 
 use super::{
@@ -141,9 +140,11 @@ impl<C: Config> common::Instruction<C> for Instruction<C> {
                 ..
             } => EvalResult::SubCircuitCall(*sub_circuit_id, inputs),
             Instruction::CustomGate { gate_type, inputs } => {
-                let outputs =
-                    hints::stub_impl(*gate_type, &inputs.iter().map(|i| values[*i]).collect(), 1);
-                EvalResult::Values(outputs)
+                let inputs: Vec<CircuitField<C>> = inputs.iter().map(|i| values[*i]).collect();
+                match CustomGateRegistry::eval(*gate_type, &inputs) {
+                    Ok(output) => EvalResult::Value(output),
+                    Err(e) => EvalResult::Error(e),
+                }
             }
         }
     }
@@ -7,6 +7,7 @@ use crate::utils::error::Error;
 use crate::{
     circuit::{
         config::Config,
+        custom_gate::CustomGateRegistry,
         input_mapping::{InputMapping, EMPTY},
         layered::Coef,
     },
@@ -14,7 +15,7 @@ use crate::{
 };
 
 use super::{
-    common::{self, EvalResult, Instruction as _, IrConfig, RawConstraint},
+    common::{self, origin::Origins, EvalResult, Instruction as _, IrConfig, RawConstraint},
     expr,
 };
 
@@ -181,12 +182,14 @@ impl<C: Config> common::Instruction<C> for Instruction<C> {
                 inputs,
                 num_outputs,
             } => {
-                let outputs = hints::stub_impl(
+                match hints::stub_impl(
                     *hint_id,
                     &inputs.iter().map(|i| values[*i]).collect(),
                     *num_outputs,
-                );
-                EvalResult::Values(outputs)
+                ) {
+                    Ok(outputs) => EvalResult::Values(outputs),
+                    Err(e) => EvalResult::Error(e),
+                }
             }
             Instruction::ConstantLike(coef) => EvalResult::Value(coef.get_value_unsafe()),
             Instruction::SubCircuitCall {
@@ -195,9 +198,11 @@ impl<C: Config> common::Instruction<C> for Instruction<C> {
                 ..
             } => EvalResult::SubCircuitCall(*sub_circuit_id, inputs),
             Instruction::CustomGate { gate_type, inputs } => {
-                let outputs =
-                    hints::stub_impl(*gate_type, &inputs.iter().map(|i| values[*i]).collect(), 1);
-                EvalResult::Values(outputs)
+                let inputs: Vec<CircuitField<C>> = inputs.iter().map(|i| values[*i]).collect();
+                match CustomGateRegistry::eval(*gate_type, &inputs) {
+                    Ok(output) => EvalResult::Value(output),
+                    Err(e) => EvalResult::Error(e),
+                }
             }
         }
     }
@@ -231,11 +236,6 @@ impl<C: Config> Instruction<C> {
                 Err(e) => EvalResult::Error(e),
             };
         }
-        if let Instruction::CustomGate { .. } = self {
-            return EvalResult::Error(Error::UserError(
-                "CustomGate currently unsupported".to_string(),
-            ));
-        }
         self.eval_unsafe(values)
     }
 }
@@ -477,6 +477,24 @@ impl<C: Config> RootCircuit<C> {
         )
     }
 
+    // origins of the hint-less circuit produced by remove_and_export_hints
+    pub fn hint_less_origins(&self) -> Origins {
+        let mut kept = HashMap::new();
+        for (id, circuit) in self.circuits.iter() {
+            kept.insert(
+                *id,
+                circuit
+                    .instructions
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, insn)| !matches!(insn, Instruction::Hint { .. }))
+                    .map(|(i, _)| i)
+                    .collect(),
+            );
+        }
+        Origins::from_kept_instructions(self, &kept)
+    }
+
     pub fn add_back_removed_inputs(&mut self, im: &InputMapping) {
         let c0 = self.circuits.get(&0).unwrap().add_back_removed_inputs(im);
         self.circuits.insert(0, c0);
@@ -489,19 +507,20 @@ impl<C: Config> RootCircuit<C> {
         hint_caller: &impl HintCaller<CircuitField<C>>,
     ) -> Result<Vec<CircuitField<C>>, Error> {
         assert_eq!(inputs.len(), self.input_size());
-        self.eval_sub_safe(&self.circuits[&0], inputs, public_inputs, hint_caller)
+        self.eval_sub_safe(0, inputs, public_inputs, hint_caller)
     }
 
     fn eval_sub_safe(
         &self,
-        circuit: &Circuit<C>,
+        circuit_id: usize,
         inputs: Vec<CircuitField<C>>,
         public_inputs: &[CircuitField<C>],
         hint_caller: &impl HintCaller<CircuitField<C>>,
     ) -> Result<Vec<CircuitField<C>>, Error> {
+        let circuit = &self.circuits[&circuit_id];
         let mut values = vec![CircuitField::<C>::zero(); 1];
         values.extend(inputs);
-        for insn in circuit.instructions.iter() {
+        for (insn_index, insn) in circuit.instructions.iter().enumerate() {
             match insn.eval_safe(&values, public_inputs, hint_caller) {
                 EvalResult::Value(v) => {
                     values.push(v);
@@ -510,16 +529,18 @@ impl<C: Config> RootCircuit<C> {
                     values.append(&mut vs);
                 }
                 EvalResult::SubCircuitCall(sub_circuit_id, inputs) => {
-                    let res = self.eval_sub_safe(
-                        &self.circuits[&sub_circuit_id],
-                        inputs.iter().map(|&i| values[i]).collect(),
-                        public_inputs,
-                        hint_caller,
-                    )?;
+                    let res = self
+                        .eval_sub_safe(
+                            sub_circuit_id,
+                            inputs.iter().map(|&i| values[i]).collect(),
+                            public_inputs,
+                            hint_caller,
+                        )
+                        .map_err(|e| e.at_location(circuit_id, insn_index))?;
                     values.extend(res);
                 }
                 EvalResult::Error(e) => {
-                    return Err(e);
+                    return Err(e.at_location(circuit_id, insn_index));
                 }
             }
         }
@@ -538,27 +559,22 @@ impl<C: Config> RootCircuit<C> {
     ) -> Result<Vec<SF>, Error> {
         assert_eq!(inputs.len(), self.input_size());
         let mut result_values = Vec::new();
-        self.eval_sub_safe_simd(
-            &self.circuits[&0],
-            inputs,
-            public_inputs,
-            hint_caller,
-            &mut result_values,
-        )?;
+        self.eval_sub_safe_simd(0, inputs, public_inputs, hint_caller, &mut result_values)?;
         Ok(result_values)
     }
 
     fn eval_sub_safe_simd<SF: arith::SimdField<Scalar = CircuitField<C>>>(
         &self,
-        circuit: &Circuit<C>,
+        circuit_id: usize,
         inputs: Vec<SF>,
         public_inputs: &[SF],
         hint_caller: &impl HintCaller<CircuitField<C>>,
         result_values: &mut Vec<SF>,
     ) -> Result<(), Error> {
+        let circuit = &self.circuits[&circuit_id];
         let mut values = vec![SF::zero(); 1];
         values.extend(inputs);
-        for insn in circuit.instructions.iter() {
+        for (insn_index, insn) in circuit.instructions.iter().enumerate() {
             match insn {
                 Instruction::LinComb(lc) => {
                     let res = lc.eval_simd(&values);
@@ -586,8 +602,8 @@ impl<C: Config> RootCircuit<C> {
                     let mut outputs_tmp =
                         vec![CircuitField::<C>::zero(); num_outputs * SF::PACK_SIZE];
                     for (i, inputs) in inputs_scalar.iter().enumerate() {
-                        let outputs =
-                            hints::safe_impl(hint_caller, *hint_id, inputs, *num_outputs)?;
+                        let outputs = hints::safe_impl(hint_caller, *hint_id, inputs, *num_outputs)
+                            .map_err(|e| e.at_location(circuit_id, insn_index))?;
                         for (j, x) in outputs.iter().enumerate() {
                             outputs_tmp[j * SF::PACK_SIZE + i] = *x;
                         }
@@ -616,15 +632,23 @@ impl<C: Config> RootCircuit<C> {
                     ..
                 } => {
                     self.eval_sub_safe_simd(
-                        &self.circuits[sub_circuit_id],
+                        *sub_circuit_id,
                         inputs.iter().map(|&i| values[i]).collect(),
                         public_inputs,
                         hint_caller,
                         &mut values,
-                    )?;
+                    )
+                    .map_err(|e| e.at_location(circuit_id, insn_index))?;
                 }
-                Instruction::CustomGate { .. } => {
-                    panic!("CustomGate currently unsupported");
+                Instruction::CustomGate { gate_type, inputs } => {
+                    let mut inputs_scalar = Vec::with_capacity(inputs.len());
+                    for x in inputs.iter().map(|i| values[*i]) {
+                        inputs_scalar.push(x.unpack());
+                    }
+                    let mut outputs = vec![CircuitField::<C>::zero(); SF::PACK_SIZE];
+                    CustomGateRegistry::eval_simd(*gate_type, &inputs_scalar, &mut outputs)
+                        .map_err(|e| e.at_location(circuit_id, insn_index))?;
+                    values.push(SF::pack(&outputs));
                 }
             }
         }
@@ -1,16 +1,54 @@
+use std::{
+    collections::HashMap,
+    io::{Error as IoError, Read, Write},
+};
+
 use crate::circuit::{
     config::{CircuitField, SIMDField},
     layered::witness::{Witness, WitnessValues},
 };
 
 use arith::SimdField;
-use serdes::ExpSerde;
+use serdes::{ExpSerde, SerdeResult};
 
 use super::{Config, Error, FieldArith, HintCaller, RootCircuit};
 
-#[derive(ExpSerde)]
 pub struct WitnessSolver<C: Config> {
     pub circuit: RootCircuit<C>,
+    // keys the builder was given for the hint ids of the circuit, used to name hints in errors
+    pub hint_keys: HashMap<usize, String>,
+}
+
+impl<C: Config> ExpSerde for WitnessSolver<C> {
+    fn serialize_into<W: Write>(&self, mut writer: W) -> SerdeResult<()> {
+        self.circuit.serialize_into(&mut writer)?;
+        let mut hint_ids: Vec<usize> = self.hint_keys.keys().cloned().collect();
+        hint_ids.sort();
+        hint_ids.len().serialize_into(&mut writer)?;
+        for id in hint_ids {
+            id.serialize_into(&mut writer)?;
+            self.hint_keys[&id]
+                .as_bytes()
+                .to_vec()
+                .serialize_into(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize_from<R: Read>(mut reader: R) -> SerdeResult<Self> {
+        let circuit = RootCircuit::<C>::deserialize_from(&mut reader)?;
+        let num_hint_keys = usize::deserialize_from(&mut reader)?;
+        let mut hint_keys = HashMap::new();
+        for _ in 0..num_hint_keys {
+            let id = usize::deserialize_from(&mut reader)?;
+            let bytes = Vec::<u8>::deserialize_from(&mut reader)?;
+            let key = String::from_utf8(bytes).map_err(|_| {
+                IoError::new(std::io::ErrorKind::InvalidData, "hint key is not utf-8")
+            })?;
+            hint_keys.insert(id, key);
+        }
+        Ok(WitnessSolver { circuit, hint_keys })
+    }
 }
 
 impl<C: Config> WitnessSolver<C> {
@@ -22,7 +60,10 @@ impl<C: Config> WitnessSolver<C> {
     ) -> Result<(Vec<CircuitField<C>>, usize), Error> {
         assert_eq!(vars.len(), self.circuit.input_size());
         assert_eq!(public_vars.len(), self.circuit.num_public_inputs);
-        let mut a = self.circuit.eval_safe(vars, &public_vars, hint_caller)?;
+        let mut a = self
+            .circuit
+            .eval_safe(vars, &public_vars, hint_caller)
+            .map_err(|e| e.with_hint_keys(&self.hint_keys))?;
         let res_len = a.len();
         a.extend(public_vars);
         Ok((a, res_len))
@@ -90,9 +131,10 @@ impl<C: Config> WitnessSolver<C> {
                 }
                 simd_public_inputs.push(SIMDField::<C>::pack(&tmp));
             }
-            let simd_result =
-                self.circuit
-                    .eval_safe_simd(simd_inputs, &simd_public_inputs, hint_caller)?;
+            let simd_result = self
+                .circuit
+                .eval_safe_simd(simd_inputs, &simd_public_inputs, hint_caller)
+                .map_err(|e| e.with_hint_keys(&self.hint_keys))?;
             num_inputs_per_witness = simd_result.len();
             values.extend(simd_result);
             values.extend(simd_public_inputs);
@@ -5,7 +5,40 @@ use crate::{circuit::ir::common::Instruction as _, frontend::CircuitField};
 use super::{expr, Circuit, Config, FieldArith, Instruction, RootCircuit};
 
 impl<C: Config> Circuit<C> {
+    // mux(s, [.., mux(s, [.., x_j, ..]) at j, ..]) picks x_j, so the inner mux is skipped
+    fn simplify_muxes(&mut self) {
+        let mut var_insn_id = vec![self.instructions.len(); self.num_inputs + 1];
+        for (i, insn) in self.instructions.iter().enumerate() {
+            for _ in 0..insn.num_outputs() {
+                var_insn_id.push(i);
+            }
+        }
+        for i in 0..self.instructions.len() {
+            let (selectors, mut values) = match &self.instructions[i] {
+                Instruction::Mux { selectors, values } => (selectors.clone(), values.clone()),
+                _ => continue,
+            };
+            let mut changed = false;
+            for (j, v) in values.iter_mut().enumerate() {
+                if let Some(Instruction::Mux {
+                    selectors: inner_selectors,
+                    values: inner_values,
+                }) = self.instructions.get(var_insn_id[*v])
+                {
+                    if *inner_selectors == selectors {
+                        *v = inner_values[j];
+                        changed = true;
+                    }
+                }
+            }
+            if changed {
+                self.instructions[i] = Instruction::Mux { selectors, values };
+            }
+        }
+    }
+
     pub fn detect_chains(&mut self) {
+        self.simplify_muxes();
         let mut var_insn_id = vec![self.instructions.len(); self.num_inputs + 1];
         let mut is_add = vec![false; self.instructions.len() + 1];
         let mut is_mul = vec![false; self.instructions.len() + 1];
@@ -0,0 +1,64 @@
+use std::{collections::HashMap, fmt, panic::Location};
+
+// A call site in circuit code, captured through #[track_caller] on the builder API
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceLocation(&'static Location<'static>);
+
+impl SourceLocation {
+    pub fn file(&self) -> &'static str {
+        self.0.file()
+    }
+
+    pub fn line(&self) -> u32 {
+        self.0.line()
+    }
+
+    pub fn column(&self) -> u32 {
+        self.0.column()
+    }
+}
+
+impl From<&'static Location<'static>> for SourceLocation {
+    fn from(location: &'static Location<'static>) -> Self {
+        SourceLocation(location)
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file(), self.line(), self.column())
+    }
+}
+
+impl fmt::Debug for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+// Locations of the instructions and constraints of a source circuit, indexed like
+// Circuit::instructions and Circuit::constraints
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct CircuitLocations {
+    pub instructions: Vec<Option<SourceLocation>>,
+    pub constraints: Vec<Option<SourceLocation>>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct LocationTable {
+    pub circuits: HashMap<usize, CircuitLocations>,
+}
+
+impl LocationTable {
+    pub fn instruction(&self, circuit_id: usize, index: usize) -> Option<SourceLocation> {
+        self.circuits
+            .get(&circuit_id)
+            .and_then(|c| c.instructions.get(index).copied().flatten())
+    }
+
+    pub fn constraint(&self, circuit_id: usize, index: usize) -> Option<SourceLocation> {
+        self.circuits
+            .get(&circuit_id)
+            .and_then(|c| c.constraints.get(index).copied().flatten())
+    }
+}
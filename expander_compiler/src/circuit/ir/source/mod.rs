@@ -1,7 +1,7 @@
 use ethnum::U256;
 
 use crate::{
-    circuit::{config::Config, layered::Coef},
+    circuit::{config::Config, custom_gate::CustomGateRegistry, layered::Coef},
     field::{Field, FieldArith},
     frontend::CircuitField,
     hints::{self, circom_shift_l_impl, circom_shift_r_impl, to_binary},
@@ -17,6 +17,7 @@ use super::{
 mod tests;
 
 pub mod chains;
+pub mod location;
 pub mod serde;
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -64,6 +65,17 @@ pub enum Instruction<C: Config> {
         x: usize,
         num_bits: usize,
     },
+    // values[i] where i is given by the little endian selector bits,
+    // values.len() == 1 << selectors.len()
+    Mux {
+        selectors: Vec<usize>,
+        values: Vec<usize>,
+    },
+    // values[index], the index must be less than values.len()
+    Lookup {
+        index: usize,
+        values: Vec<usize>,
+    },
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -169,6 +181,13 @@ impl<C: Config> common::Instruction<C> for Instruction<C> {
             } => vec![*cond, *if_true, *if_false],
             Instruction::CustomGate { inputs, .. } => inputs.clone(),
             Instruction::ToBinary { x, .. } => vec![*x],
+            Instruction::Mux { selectors, values } => {
+                selectors.iter().chain(values.iter()).cloned().collect()
+            }
+            Instruction::Lookup { index, values } => std::iter::once(index)
+                .chain(values.iter())
+                .cloned()
+                .collect(),
         }
     }
     fn num_outputs(&self) -> usize {
@@ -186,6 +205,8 @@ impl<C: Config> common::Instruction<C> for Instruction<C> {
             Instruction::UnconstrainedSelect { .. } => 1,
             Instruction::CustomGate { .. } => 1,
             Instruction::ToBinary { num_bits, .. } => *num_bits,
+            Instruction::Mux { .. } => 1,
+            Instruction::Lookup { .. } => 1,
         }
     }
     fn as_sub_circuit_call(&self) -> Option<(usize, &Vec<usize>, usize)> {
@@ -264,6 +285,14 @@ impl<C: Config> common::Instruction<C> for Instruction<C> {
                 x: f(*x),
                 num_bits: *num_bits,
             },
+            Instruction::Mux { selectors, values } => Instruction::Mux {
+                selectors: selectors.iter().map(|i| f(*i)).collect(),
+                values: values.iter().map(|i| f(*i)).collect(),
+            },
+            Instruction::Lookup { index, values } => Instruction::Lookup {
+                index: f(*index),
+                values: values.iter().map(|i| f(*i)).collect(),
+            },
         }
     }
     fn from_kx_plus_b(x: usize, k: CircuitField<C>, b: CircuitField<C>) -> Self {
@@ -313,6 +342,24 @@ impl<C: Config> common::Instruction<C> for Instruction<C> {
                     ))
                 }
             }
+            Instruction::Mux { selectors, values } => {
+                if selectors.len() < usize::BITS as usize && values.len() == 1 << selectors.len() {
+                    Ok(())
+                } else {
+                    Err(Error::InternalError(
+                        "mux instruction must have 2^n values for n selectors".to_string(),
+                    ))
+                }
+            }
+            Instruction::Lookup { values, .. } => {
+                if !values.is_empty() {
+                    Ok(())
+                } else {
+                    Err(Error::InternalError(
+                        "lookup instruction must have at least 1 value".to_string(),
+                    ))
+                }
+            }
             _ => Ok(()),
         }
     }
@@ -367,12 +414,14 @@ impl<C: Config> common::Instruction<C> for Instruction<C> {
                 inputs,
                 num_outputs,
             } => {
-                let outputs = hints::stub_impl(
+                match hints::stub_impl(
                     *hint_id,
                     &inputs.iter().map(|i| values[*i]).collect(),
                     *num_outputs,
-                );
-                EvalResult::Values(outputs)
+                ) {
+                    Ok(outputs) => EvalResult::Values(outputs),
+                    Err(e) => EvalResult::Error(e),
+                }
             }
             Instruction::ConstantLike(coef) => EvalResult::Value(coef.get_value_unsafe()),
             Instruction::SubCircuitCall {
@@ -398,14 +447,45 @@ impl<C: Config> common::Instruction<C> for Instruction<C> {
                 values[*if_true]
             }),
             Instruction::CustomGate { gate_type, inputs } => {
-                let outputs =
-                    hints::stub_impl(*gate_type, &inputs.iter().map(|i| values[*i]).collect(), 1);
-                EvalResult::Values(outputs)
+                let inputs: Vec<CircuitField<C>> = inputs.iter().map(|i| values[*i]).collect();
+                match CustomGateRegistry::eval(*gate_type, &inputs) {
+                    Ok(output) => EvalResult::Value(output),
+                    Err(e) => EvalResult::Error(e),
+                }
             }
             Instruction::ToBinary { x, num_bits } => match to_binary(values[*x], *num_bits) {
                 Ok(outputs) => EvalResult::Values(outputs),
                 Err(e) => EvalResult::Error(e),
             },
+            Instruction::Mux {
+                selectors,
+                values: mux_values,
+            } => {
+                let mut index = 0;
+                for (i, s) in selectors.iter().enumerate() {
+                    let s = values[*s];
+                    if s == CircuitField::<C>::one() {
+                        index |= 1 << i;
+                    } else if !s.is_zero() {
+                        return EvalResult::Error(Error::UserError(
+                            "invalid bool value".to_string(),
+                        ));
+                    }
+                }
+                EvalResult::Value(values[mux_values[index]])
+            }
+            Instruction::Lookup {
+                index,
+                values: lookup_values,
+            } => {
+                let index = values[*index].to_u256();
+                if index >= U256::from(lookup_values.len() as u64) {
+                    return EvalResult::Error(Error::UserError(
+                        "lookup index out of range".to_string(),
+                    ));
+                }
+                EvalResult::Value(values[lookup_values[index.as_usize()]])
+            }
         }
     }
 }
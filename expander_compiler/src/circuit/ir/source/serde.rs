@@ -87,6 +87,16 @@ impl<C: Config> ExpSerde for Instruction<C> {
                 x.serialize_into(&mut writer)?;
                 num_bits.serialize_into(&mut writer)?;
             }
+            Instruction::Mux { selectors, values } => {
+                14u8.serialize_into(&mut writer)?;
+                selectors.serialize_into(&mut writer)?;
+                values.serialize_into(&mut writer)?;
+            }
+            Instruction::Lookup { index, values } => {
+                15u8.serialize_into(&mut writer)?;
+                index.serialize_into(&mut writer)?;
+                values.serialize_into(&mut writer)?;
+            }
         };
         Ok(())
     }
@@ -174,6 +184,14 @@ impl<C: Config> ExpSerde for Instruction<C> {
                 x: usize::deserialize_from(&mut reader)?,
                 num_bits: usize::deserialize_from(&mut reader)?,
             },
+            14 => Instruction::Mux {
+                selectors: Vec::<usize>::deserialize_from(&mut reader)?,
+                values: Vec::<usize>::deserialize_from(&mut reader)?,
+            },
+            15 => Instruction::Lookup {
+                index: usize::deserialize_from(&mut reader)?,
+                values: Vec::<usize>::deserialize_from(&mut reader)?,
+            },
             _ => {
                 return Err(IoError::new(
                     std::io::ErrorKind::InvalidData,
@@ -10,15 +10,39 @@ use crate::frontend::M31Config as C;
 use crate::{
     circuit::{
         config::Config,
+        custom_gate::{CustomGate, CustomGateRegistry},
         ir::{common::rand_gen::*, expr},
         layered::Coef,
     },
     hints,
 };
-use crate::{field::FieldArith, frontend::CircuitField};
+use crate::{
+    field::{Field, FieldArith},
+    frontend::CircuitField,
+};
 
 type CField = M31;
 
+// Stands in for the custom gates of random circuits, evaluating to the hint stub
+struct StubGate {
+    gate_type: usize,
+    arity: usize,
+}
+
+impl<F: Field> CustomGate<F> for StubGate {
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn degree(&self) -> usize {
+        1
+    }
+
+    fn eval(&self, inputs: &[F]) -> F {
+        hints::stub_impl(self.gate_type, &inputs.to_vec(), 1).unwrap()[0]
+    }
+}
+
 impl<C: Config> RandomInstruction for Instruction<C> {
     fn random_no_sub_circuit(
         mut rnd: impl RngCore,
@@ -62,6 +86,14 @@ impl<C: Config> RandomInstruction for Instruction<C> {
                     num_outputs,
                 }
             } else {
+                // already registered if another random circuit drew the same gate
+                let _ = CustomGateRegistry::try_register::<CircuitField<C>, _>(
+                    hint_id,
+                    StubGate {
+                        gate_type: hint_id,
+                        arity: num_inputs,
+                    },
+                );
                 super::Instruction::CustomGate {
                     gate_type: hint_id,
                     inputs: (0..num_inputs)
@@ -270,3 +302,57 @@ fn test_detect_chains() {
     test_detect_chains_inner(true, 2);
     test_detect_chains_inner(true, 3);
 }
+
+#[test]
+fn test_detect_chains_mux() {
+    // inputs 1..=4: s, a, b, c; 5 = mux(s, [a, b]); 6 = mux(s, [5, c]); 7 = mux(s, [c, 5])
+    let mut root = RootCircuit::<C>::default();
+    root.circuits.insert(
+        0,
+        Circuit::<C> {
+            num_inputs: 4,
+            instructions: vec![
+                Instruction::Mux {
+                    selectors: vec![1],
+                    values: vec![2, 3],
+                },
+                Instruction::Mux {
+                    selectors: vec![1],
+                    values: vec![5, 4],
+                },
+                Instruction::Mux {
+                    selectors: vec![1],
+                    values: vec![4, 5],
+                },
+            ],
+            constraints: vec![],
+            outputs: vec![6, 7],
+        },
+    );
+    assert_eq!(root.validate(), Ok(()));
+    let inputs = vec![
+        CField::one(),
+        CField::from(2),
+        CField::from(3),
+        CField::from(4),
+    ];
+    let (expected, _) = root.eval_unsafe(inputs.clone());
+    root.detect_chains();
+    let (root, _) = root.remove_unreachable();
+    assert_eq!(root.validate(), Ok(()));
+    let c = &root.circuits[&0];
+    assert_eq!(
+        c.instructions,
+        vec![
+            Instruction::Mux {
+                selectors: vec![1],
+                values: vec![2, 4],
+            },
+            Instruction::Mux {
+                selectors: vec![1],
+                values: vec![4, 3],
+            },
+        ]
+    );
+    assert_eq!(root.eval_unsafe(inputs).0, expected);
+}
@@ -0,0 +1,79 @@
+use std::fmt;
+
+use crate::{
+    circuit::config::{CircuitField, Config},
+    field::FieldArith,
+};
+
+use super::{source_map::ConstraintFailure, witness::Witness, Circuit, InputType};
+
+// A non-zero expected-zero output of a layered circuit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintViolation<C: Config> {
+    pub witness_index: usize,
+    // the output layer, the input layer is layer 0
+    pub layer: usize,
+    // index of the output gate in its layer, expected-zero outputs come first
+    pub gate_index: usize,
+    pub value: CircuitField<C>,
+    // Constraints of the hint-less ir exported to this output that fail for this witness.
+    // They are only filled in when the output they were exported to is known exactly, i.e.
+    // when it is the only failing output of the witness. Otherwise failed_constraints lists
+    // them without an output.
+    pub sources: Vec<ConstraintFailure<C>>,
+}
+
+impl<C: Config, I: InputType> Circuit<C, I> {
+    // Evaluates each witness and returns a violation for every non-zero expected-zero output
+    pub fn explain(&self, witness: &Witness<C>) -> Vec<ConstraintViolation<C>> {
+        let layer = self.layer_ids.len();
+        let mut res = Vec::new();
+        for (witness_index, (inputs, public_inputs)) in witness.iter_scalar().enumerate() {
+            let failures = match self.source_map() {
+                Some(source_map) => source_map.failed_constraints(&inputs, &public_inputs),
+                None => vec![],
+            };
+            let outputs = self.eval_last_layer_with_public_inputs(inputs, &public_inputs);
+            let mut violations: Vec<ConstraintViolation<C>> = outputs
+                .iter()
+                .take(self.expected_num_output_zeroes)
+                .enumerate()
+                .filter(|(_, v)| !v.is_zero())
+                .map(|(i, v)| ConstraintViolation {
+                    witness_index,
+                    layer,
+                    gate_index: i,
+                    value: *v,
+                    sources: vec![],
+                })
+                .collect();
+            // a failing constraint makes the output it is exported to non-zero, so with a
+            // single failing output all failing constraints belong to it
+            if let [violation] = &mut violations[..] {
+                violation.sources = failures
+                    .into_iter()
+                    .map(|mut failure| {
+                        failure.witness_index = witness_index;
+                        failure
+                    })
+                    .collect();
+            }
+            res.extend(violations);
+        }
+        res
+    }
+}
+
+impl<C: Config> fmt::Display for ConstraintViolation<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "witness {}: output gate {} of layer {} is {:?}, expected zero",
+            self.witness_index, self.gate_index, self.layer, self.value
+        )?;
+        for source in self.sources.iter() {
+            write!(f, "\n{source}")?;
+        }
+        Ok(())
+    }
+}
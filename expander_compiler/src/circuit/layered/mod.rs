@@ -2,16 +2,21 @@ use std::{fmt, hash::Hash};
 
 use serdes::ExpSerde;
 
-use crate::{field::FieldArith, hints, utils::error::Error};
+use crate::{field::FieldArith, utils::error::Error};
 
-use super::config::{CircuitField, Config};
+use super::{
+    config::{CircuitField, Config},
+    custom_gate::CustomGateRegistry,
+};
 
 #[cfg(test)]
 mod tests;
 
+pub mod explain;
 pub mod export;
 pub mod opt;
 pub mod serde;
+pub mod source_map;
 pub mod stats;
 pub mod witness;
 
@@ -373,6 +378,7 @@ pub struct Circuit<C: Config, I: InputType> {
     pub expected_num_output_zeroes: usize,
     pub segments: Vec<Segment<C, I>>,
     pub layer_ids: Vec<usize>,
+    pub source_map: source_map::SourceMapRef<C>,
 }
 
 impl<C: Config, I: InputType> Circuit<C, I> {
@@ -705,10 +711,10 @@ impl<C: Config, I: InputType> Circuit<C, I> {
             for input in cu.inputs.iter() {
                 inputs.push(cur[input.layer()][input.offset()]);
             }
-            let outputs = hints::stub_impl(cu.gate_type, &inputs, 1);
-            for (i, output) in outputs.iter().enumerate() {
-                nxt[cu.output + i] += *output * cu.coef.get_value_unsafe();
-            }
+            // the gate must be registered, CustomGateRegistry::validate_against checks it
+            let output = CustomGateRegistry::eval(cu.gate_type, &inputs)
+                .unwrap_or_else(|e| panic!("{e}"));
+            nxt[cu.output] += output * cu.coef.get_value_unsafe();
         }
         for (sub_id, allocs) in seg.child_segs.iter() {
             let subc = &self.segments[*sub_id];
@@ -734,6 +740,26 @@ impl<C: Config, I: InputType> Circuit<C, I> {
         inputs: Vec<CircuitField<C>>,
         public_inputs: &[CircuitField<C>],
     ) -> (Vec<CircuitField<C>>, bool) {
+        let cur = self.eval_last_layer_with_public_inputs(inputs, public_inputs);
+        let mut constraints_satisfied = true;
+        for out in cur.iter().take(self.expected_num_output_zeroes) {
+            if !out.is_zero() {
+                constraints_satisfied = false;
+                break;
+            }
+        }
+        (
+            cur[self.expected_num_output_zeroes..self.num_actual_outputs].to_vec(),
+            constraints_satisfied,
+        )
+    }
+
+    // all gates of the last layer, including the expected zeroes and the padding
+    pub fn eval_last_layer_with_public_inputs(
+        &self,
+        inputs: Vec<CircuitField<C>>,
+        public_inputs: &[CircuitField<C>],
+    ) -> Vec<CircuitField<C>> {
         if inputs.len() != self.input_size() {
             panic!("input length mismatch");
         }
@@ -752,18 +778,7 @@ impl<C: Config, I: InputType> Circuit<C, I> {
             );
             cur.push(next);
         }
-        let cur = cur.last().unwrap();
-        let mut constraints_satisfied = true;
-        for out in cur.iter().take(self.expected_num_output_zeroes) {
-            if !out.is_zero() {
-                constraints_satisfied = false;
-                break;
-            }
-        }
-        (
-            cur[self.expected_num_output_zeroes..self.num_actual_outputs].to_vec(),
-            constraints_satisfied,
-        )
+        cur.pop().unwrap()
     }
 
     fn apply_segment_with_public_inputs(
@@ -790,10 +805,9 @@ impl<C: Config, I: InputType> Circuit<C, I> {
             for input in cu.inputs.iter() {
                 inputs.push(cur[input.layer()][input.offset()]);
             }
-            let outputs = hints::stub_impl(cu.gate_type, &inputs, 1);
-            for (i, output) in outputs.iter().enumerate() {
-                nxt[cu.output + i] += *output * cu.coef.get_value_with_public_inputs(public_inputs);
-            }
+            let output = CustomGateRegistry::eval(cu.gate_type, &inputs)
+                .unwrap_or_else(|e| panic!("{e}"));
+            nxt[cu.output] += output * cu.coef.get_value_with_public_inputs(public_inputs);
         }
         for (sub_id, allocs) in seg.child_segs.iter() {
             let subc = &self.segments[*sub_id];
@@ -874,26 +888,16 @@ impl<C: Config, I: InputType> Circuit<C, I> {
             nxt[cs.output] += cs.coef.get_value_with_public_inputs_simd(public_inputs);
         }
         for cu in seg.gate_customs.iter() {
-            let mut inputs = vec![Vec::with_capacity(cu.inputs.len()); SF::PACK_SIZE];
-            for input in cu.inputs.iter() {
-                let tmp = cur[input.layer()][input.offset()].unpack();
-                for i in 0..SF::PACK_SIZE {
-                    inputs[i].push(tmp[i]);
-                }
-            }
-            let mut outputs = Vec::with_capacity(SF::PACK_SIZE);
-            for x in inputs.iter() {
-                outputs.push(hints::stub_impl(cu.gate_type, x, 1));
-            }
-            for i in 0..outputs[0].len() {
-                let mut tmp = Vec::with_capacity(SF::PACK_SIZE);
-                for x in outputs.iter() {
-                    tmp.push(x[i]);
-                }
-                let output = SF::pack(&tmp);
-                nxt[cu.output + i] +=
-                    output * cu.coef.get_value_with_public_inputs_simd(public_inputs);
-            }
+            let inputs: Vec<Vec<CircuitField<C>>> = cu
+                .inputs
+                .iter()
+                .map(|input| cur[input.layer()][input.offset()].unpack())
+                .collect();
+            let mut outputs = vec![CircuitField::<C>::zero(); SF::PACK_SIZE];
+            CustomGateRegistry::eval_simd(cu.gate_type, &inputs, &mut outputs)
+                .unwrap_or_else(|e| panic!("{e}"));
+            nxt[cu.output] +=
+                SF::pack(&outputs) * cu.coef.get_value_with_public_inputs_simd(public_inputs);
         }
         for (sub_id, allocs) in seg.child_segs.iter() {
             let subc = &self.segments[*sub_id];
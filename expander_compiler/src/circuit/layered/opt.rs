@@ -582,6 +582,7 @@ impl<C: Config, I: InputType> Circuit<C, I> {
             expected_num_output_zeroes: self.expected_num_output_zeroes,
             segments: new_segments,
             layer_ids: new_layers,
+            source_map: self.source_map.clone(),
         }
     }
 
@@ -700,6 +701,7 @@ impl<C: Config, I: InputType> Circuit<C, I> {
             expected_num_output_zeroes: self.expected_num_output_zeroes,
             segments: new_segments,
             layer_ids: new_layers,
+            source_map: self.source_map.clone(),
         }
     }
 }
@@ -741,7 +743,7 @@ mod tests {
                 Error::InternalError(s) => {
                     panic!("{}", s);
                 }
-                Error::UserError(_) => {
+                Error::UserError(_) | Error::HintError(_) => {
                     return None;
                 }
             },
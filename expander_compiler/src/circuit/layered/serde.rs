@@ -166,6 +166,7 @@ impl<C: Config, I: InputType> ExpSerde for Circuit<C, I> {
             expected_num_output_zeroes,
             segments,
             layer_ids,
+            source_map: Default::default(),
         })
     }
 }
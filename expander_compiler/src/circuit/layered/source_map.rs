@@ -0,0 +1,311 @@
+use std::{cmp::Ordering, collections::HashMap, fmt, hash::Hash, sync::Arc};
+
+use crate::{
+    circuit::{
+        config::{CircuitField, Config},
+        input_mapping::{InputMapping, EMPTY},
+        ir::{
+            common::{
+                origin::{Origin, Origins},
+                EvalResult, Instruction as _,
+            },
+            hint_less::{Instruction, RootCircuit},
+            source::location::{LocationTable, SourceLocation},
+        },
+    },
+    field::FieldArith,
+};
+
+use super::{witness::Witness, Circuit, InputType};
+
+// Source locations of the instructions and constraints of a hint-less circuit
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct CircuitSourceMap {
+    pub instructions: Vec<Option<SourceLocation>>,
+    pub constraints: Vec<Vec<SourceLocation>>,
+    // indices into the ir::source circuit with the same id
+    pub constraint_origins: Vec<Vec<Origin>>,
+}
+
+// Debug side table of a layered circuit.
+// Constraints are merged (and randomly combined) when the circuit is layered, so they are
+// checked on the hint-less ir instead, where each of them still points back to the
+// builder calls that asserted it.
+#[derive(Debug, Clone)]
+pub struct SourceMap<C: Config> {
+    pub circuit: RootCircuit<C>,
+    // maps the inputs of `circuit` to the inputs of the layered circuit
+    pub input_mapping: InputMapping,
+    pub circuits: HashMap<usize, CircuitSourceMap>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallFrame {
+    pub circuit_id: usize,
+    pub instruction_index: usize,
+    pub location: Option<SourceLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintFailure<C: Config> {
+    pub witness_index: usize,
+    pub circuit_id: usize,
+    // index into the constraints of the hint-less circuit
+    pub constraint_index: usize,
+    pub value: CircuitField<C>,
+    // ir::source constraints (or instructions) this constraint was built from
+    pub origins: Vec<Origin>,
+    pub locations: Vec<SourceLocation>,
+    // sub circuit calls leading to the constraint, outermost first
+    pub call_stack: Vec<CallFrame>,
+}
+
+impl<C: Config> SourceMap<C> {
+    pub fn new(
+        circuit: RootCircuit<C>,
+        input_mapping: InputMapping,
+        origins: &Origins,
+        locations: &LocationTable,
+    ) -> Self {
+        let mut circuits = HashMap::new();
+        for (id, c) in circuit.circuits.iter() {
+            let co = origins.circuits.get(id);
+            let locate = |o: &Origin| match o {
+                Origin::Unknown => None,
+                Origin::Instruction(i) => locations.instruction(*id, *i),
+                Origin::Constraint(i) => locations.constraint(*id, *i),
+            };
+            let instructions = (0..c.instructions.len())
+                .map(|i| co.and_then(|co| co.instructions.get(i)).and_then(locate))
+                .collect();
+            let constraints = (0..c.constraints.len())
+                .map(|i| {
+                    let mut res: Vec<SourceLocation> = Vec::new();
+                    if let Some(os) = co.and_then(|co| co.constraints.get(i)) {
+                        for loc in os.iter().filter_map(locate) {
+                            if !res.contains(&loc) {
+                                res.push(loc);
+                            }
+                        }
+                    }
+                    res
+                })
+                .collect();
+            let constraint_origins = (0..c.constraints.len())
+                .map(|i| {
+                    co.and_then(|co| co.constraints.get(i))
+                        .cloned()
+                        .unwrap_or_default()
+                })
+                .collect();
+            circuits.insert(
+                *id,
+                CircuitSourceMap {
+                    instructions,
+                    constraints,
+                    constraint_origins,
+                },
+            );
+        }
+        SourceMap {
+            circuit,
+            input_mapping,
+            circuits,
+        }
+    }
+
+    pub fn instruction_location(
+        &self,
+        circuit_id: usize,
+        instruction_index: usize,
+    ) -> Option<SourceLocation> {
+        self.circuits
+            .get(&circuit_id)
+            .and_then(|c| c.instructions.get(instruction_index).copied().flatten())
+    }
+
+    // inputs and public_inputs are those of the layered circuit
+    pub fn failed_constraints(
+        &self,
+        inputs: &[CircuitField<C>],
+        public_inputs: &[CircuitField<C>],
+    ) -> Vec<ConstraintFailure<C>> {
+        let hl_inputs = (0..self.input_mapping.cur_size())
+            .map(|i| {
+                let pos = self.input_mapping.map(i);
+                if pos == EMPTY || pos >= inputs.len() {
+                    CircuitField::<C>::zero()
+                } else {
+                    inputs[pos]
+                }
+            })
+            .collect();
+        let mut stack = Vec::new();
+        let mut failures = Vec::new();
+        self.check_sub(0, hl_inputs, public_inputs, &mut stack, &mut failures);
+        failures
+    }
+
+    fn check_sub(
+        &self,
+        circuit_id: usize,
+        inputs: Vec<CircuitField<C>>,
+        public_inputs: &[CircuitField<C>],
+        stack: &mut Vec<CallFrame>,
+        failures: &mut Vec<ConstraintFailure<C>>,
+    ) -> Vec<CircuitField<C>> {
+        let circuit = &self.circuit.circuits[&circuit_id];
+        let mut values = vec![CircuitField::<C>::zero(); 1];
+        values.extend(inputs);
+        for (insn_index, insn) in circuit.instructions.iter().enumerate() {
+            if let Instruction::ConstantLike(coef) = insn {
+                values.push(coef.get_value_with_public_inputs(public_inputs));
+                continue;
+            }
+            match insn.eval_unsafe(&values) {
+                EvalResult::Value(v) => values.push(v),
+                EvalResult::Values(vs) => values.extend(vs),
+                EvalResult::SubCircuitCall(sub_circuit_id, sub_inputs) => {
+                    let sub_inputs = sub_inputs.iter().map(|x| values[*x]).collect();
+                    stack.push(CallFrame {
+                        circuit_id,
+                        instruction_index: insn_index,
+                        location: self.instruction_location(circuit_id, insn_index),
+                    });
+                    let outputs =
+                        self.check_sub(sub_circuit_id, sub_inputs, public_inputs, stack, failures);
+                    stack.pop();
+                    values.extend(outputs);
+                }
+                EvalResult::Error(_) => unreachable!(),
+            }
+        }
+        for (i, con) in circuit.constraints.iter().enumerate() {
+            if !values[*con].is_zero() {
+                let csm = &self.circuits[&circuit_id];
+                failures.push(ConstraintFailure {
+                    witness_index: 0,
+                    circuit_id,
+                    constraint_index: i,
+                    value: values[*con],
+                    origins: csm.constraint_origins[i].clone(),
+                    locations: csm.constraints[i].clone(),
+                    call_stack: stack.clone(),
+                });
+            }
+        }
+        circuit.outputs.iter().map(|x| values[*x]).collect()
+    }
+}
+
+impl fmt::Display for CallFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "circuit {} instruction {}",
+            self.circuit_id, self.instruction_index
+        )?;
+        if let Some(loc) = &self.location {
+            write!(f, " at {loc}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: Config> fmt::Display for ConstraintFailure<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "witness {}: constraint {} of circuit {} failed with value {:?}",
+            self.witness_index, self.constraint_index, self.circuit_id, self.value
+        )?;
+        for origin in self.origins.iter() {
+            match origin {
+                Origin::Instruction(i) => write!(f, "\n  from source instruction {i}")?,
+                Origin::Constraint(i) => write!(f, "\n  from source constraint {i}")?,
+                Origin::Unknown => {}
+            }
+        }
+        for loc in self.locations.iter() {
+            write!(f, "\n  asserted at {loc}")?;
+        }
+        for frame in self.call_stack.iter().rev() {
+            write!(f, "\n  called from {frame}")?;
+        }
+        Ok(())
+    }
+}
+
+// Optional source map attached to a layered circuit.
+// It is not serialized and does not take part in comparisons or hashing, so attaching one
+// never changes the identity of a circuit.
+#[derive(Clone)]
+pub struct SourceMapRef<C: Config>(Option<Arc<SourceMap<C>>>);
+
+impl<C: Config> SourceMapRef<C> {
+    pub fn new(source_map: SourceMap<C>) -> Self {
+        SourceMapRef(Some(Arc::new(source_map)))
+    }
+
+    pub fn get(&self) -> Option<&SourceMap<C>> {
+        self.0.as_deref()
+    }
+}
+
+impl<C: Config> Default for SourceMapRef<C> {
+    fn default() -> Self {
+        SourceMapRef(None)
+    }
+}
+
+impl<C: Config> fmt::Debug for SourceMapRef<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(_) => write!(f, "Some(SourceMap)"),
+            None => write!(f, "None"),
+        }
+    }
+}
+
+impl<C: Config> PartialEq for SourceMapRef<C> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<C: Config> Eq for SourceMapRef<C> {}
+
+impl<C: Config> PartialOrd for SourceMapRef<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: Config> Ord for SourceMapRef<C> {
+    fn cmp(&self, _other: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+impl<C: Config> Hash for SourceMapRef<C> {
+    fn hash<H: std::hash::Hasher>(&self, _state: &mut H) {}
+}
+
+impl<C: Config, I: InputType> Circuit<C, I> {
+    pub fn source_map(&self) -> Option<&SourceMap<C>> {
+        self.source_map.get()
+    }
+
+    // Returns None if the circuit was compiled without a source map
+    pub fn failed_constraints(&self, witness: &Witness<C>) -> Option<Vec<ConstraintFailure<C>>> {
+        let source_map = self.source_map()?;
+        let mut res = Vec::new();
+        for (i, (inputs, public_inputs)) in witness.iter_scalar().enumerate() {
+            for mut failure in source_map.failed_constraints(&inputs, &public_inputs) {
+                failure.witness_index = i;
+                res.push(failure);
+            }
+        }
+        Some(res)
+    }
+}
@@ -1,7 +1,10 @@
+use serde::{Deserialize, Serialize};
+
 use crate::circuit::config::Config;
 
 use super::{Circuit, InputType, InputUsize};
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Stats {
     // number of layers in the final circuit
     pub num_layers: usize,
@@ -25,15 +28,63 @@ pub struct Stats {
     pub total_cost: usize,
 }
 
+// Gates of a single layer, with child segments expanded
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayerStats {
+    pub num_gates: usize,
+    pub num_used_gates: usize,
+    pub num_expanded_mul: usize,
+    pub num_expanded_add: usize,
+    pub num_expanded_cst: usize,
+    pub num_expanded_custom: usize,
+}
+
 struct CircuitStats {
     num_expanded_mul: usize,
     num_expanded_add: usize,
     num_expanded_cst: usize,
+    num_expanded_custom: usize,
 }
 
 impl<C: Config, I: InputType> Circuit<C, I> {
-    pub fn get_stats(&self) -> Stats {
+    fn expanded_segment_stats(&self) -> Vec<CircuitStats> {
         let mut m: Vec<CircuitStats> = Vec::with_capacity(self.segments.len());
+        for seg in self.segments.iter() {
+            let mut r = CircuitStats {
+                num_expanded_mul: seg.gate_muls.len(),
+                num_expanded_add: seg.gate_adds.len(),
+                num_expanded_cst: seg.gate_consts.len(),
+                num_expanded_custom: seg.gate_customs.len(),
+            };
+            for (sub_id, allocs) in seg.child_segs.iter() {
+                r.num_expanded_mul += m[*sub_id].num_expanded_mul * allocs.len();
+                r.num_expanded_add += m[*sub_id].num_expanded_add * allocs.len();
+                r.num_expanded_cst += m[*sub_id].num_expanded_cst * allocs.len();
+                r.num_expanded_custom += m[*sub_id].num_expanded_custom * allocs.len();
+            }
+            m.push(r);
+        }
+        m
+    }
+
+    pub fn get_layer_stats(&self) -> Vec<LayerStats> {
+        let m = self.expanded_segment_stats();
+        let (_, output_mask) = self.compute_masks();
+        self.layer_ids
+            .iter()
+            .map(|id| LayerStats {
+                num_gates: self.segments[*id].num_outputs,
+                num_used_gates: output_mask[*id].iter().filter(|x| **x).count(),
+                num_expanded_mul: m[*id].num_expanded_mul,
+                num_expanded_add: m[*id].num_expanded_add,
+                num_expanded_cst: m[*id].num_expanded_cst,
+                num_expanded_custom: m[*id].num_expanded_custom,
+            })
+            .collect()
+    }
+
+    pub fn get_stats(&self) -> Stats {
+        let m = self.expanded_segment_stats();
         let mut ar = Stats {
             num_layers: 0,
             num_segments: 0,
@@ -48,24 +99,10 @@ impl<C: Config, I: InputType> Circuit<C, I> {
             num_used_gates: 0,
             total_cost: 0,
         };
-        for i in 0..self.segments.len() {
-            let num_self_mul = self.segments[i].gate_muls.len();
-            let num_self_add = self.segments[i].gate_adds.len();
-            let num_self_cst = self.segments[i].gate_consts.len();
-            let mut r = CircuitStats {
-                num_expanded_mul: num_self_mul,
-                num_expanded_add: num_self_add,
-                num_expanded_cst: num_self_cst,
-            };
-            for (sub_id, allocs) in self.segments[i].child_segs.iter() {
-                r.num_expanded_mul += m[*sub_id].num_expanded_mul * allocs.len();
-                r.num_expanded_add += m[*sub_id].num_expanded_add * allocs.len();
-                r.num_expanded_cst += m[*sub_id].num_expanded_cst * allocs.len();
-            }
-            ar.num_total_mul += num_self_mul;
-            ar.num_total_add += num_self_add;
-            ar.num_total_cst += num_self_cst;
-            m.push(r);
+        for seg in self.segments.iter() {
+            ar.num_total_mul += seg.gate_muls.len();
+            ar.num_total_add += seg.gate_adds.len();
+            ar.num_total_cst += seg.gate_consts.len();
         }
         for x in self.layer_ids.iter() {
             ar.num_expanded_mul += m[*x].num_expanded_mul;
@@ -82,6 +82,7 @@ fn simple() {
             },
         ],
         layer_ids: vec![1, 2],
+        source_map: Default::default(),
     };
     assert!(circuit.validate().is_ok());
     for _ in 0..100 {
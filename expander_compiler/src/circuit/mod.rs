@@ -1,5 +1,6 @@
 pub mod config;
 pub mod costs;
+pub mod custom_gate;
 pub mod input_mapping;
 pub mod ir;
 pub mod layered;
@@ -0,0 +1,311 @@
+use std::{
+    fs,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serdes::{ExpSerde, SerdeResult};
+use tiny_keccak::Hasher;
+
+use crate::{
+    circuit::{
+        config::Config,
+        ir::{self, common::IrConfig},
+        layered::{self, InputType},
+    },
+    utils::error::Error,
+};
+
+use super::CompileOptions;
+
+const DEFAULT_MAX_SIZE: u64 = 4 << 30;
+const EXTENSION: &str = "ecc";
+
+// Version of the cached artifacts, part of every key. It must be bumped whenever a change to
+// the ir, the compile passes, layering or serialization changes what a circuit compiles to,
+// so that artifacts of an older compiler are never served.
+pub const ARTIFACT_VERSION: u32 = 2;
+
+// On-disk cache of compile artifacts (the hint-normalized witness ir and the layered
+// circuit), keyed by a hash of the source circuit, the options and the config.
+// Every entry starts with the hash of its contents, entries that don't match it are misses.
+// Entries are evicted least recently used first once the cache grows over max_size bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactCache {
+    dir: PathBuf,
+    max_size: u64,
+}
+
+impl ArtifactCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        ArtifactCache {
+            dir: dir.into(),
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    pub fn key<C: Config, I: InputType>(
+        r_source: &ir::source::RootCircuit<C>,
+        options: &CompileOptions,
+    ) -> Result<String, Error> {
+        let mut buf = Vec::new();
+        serialize_sorted(r_source, &mut buf)
+            .map_err(|e| Error::InternalError(format!("failed to serialize circuit: {e:?}")))?;
+        let mut hasher = tiny_keccak::Keccak::v256();
+        hasher.update(&ARTIFACT_VERSION.to_le_bytes());
+        hasher.update(&C::CONFIG_ID.to_le_bytes());
+        hasher.update(&[I::CROSS_LAYER_RELAY as u8]);
+        hasher.update(&options.mul_fanout_limit.unwrap_or(0).to_le_bytes());
+        hasher.update(&[options.allow_input_reorder as u8]);
+        hasher.update(&options.opt_level.to_le_bytes());
+        hasher.update(&buf);
+        let mut hash = [0u8; 32];
+        hasher.finalize(&mut hash);
+        Ok(hash.iter().map(|x| format!("{x:02x}")).collect())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.{EXTENSION}"))
+    }
+
+    // Key of a single circuit, from RootCircuit::content_hashes.
+    // stage names the transformation whose result is stored.
+    pub fn sub_circuit_key(stage: &str, content_hash: &[u8; 32]) -> String {
+        let mut hasher = tiny_keccak::Keccak::v256();
+        hasher.update(&ARTIFACT_VERSION.to_le_bytes());
+        hasher.update(b"sub circuit");
+        hasher.update(stage.as_bytes());
+        hasher.update(content_hash);
+        let mut hash = [0u8; 32];
+        hasher.finalize(&mut hash);
+        hash.iter().map(|x| format!("{x:02x}")).collect()
+    }
+
+    pub fn load<C: Config, I: InputType>(
+        &self,
+        key: &str,
+    ) -> Option<(ir::hint_normalized::RootCircuit<C>, layered::Circuit<C, I>)> {
+        self.load_with(key, |reader| {
+            let irw = ir::hint_normalized::RootCircuit::<C>::deserialize_from(&mut *reader)?;
+            let lc = layered::Circuit::<C, I>::deserialize_from(&mut *reader)?;
+            Ok((irw, lc))
+        })
+    }
+
+    pub fn store<C: Config, I: InputType>(
+        &self,
+        key: &str,
+        irw: &ir::hint_normalized::RootCircuit<C>,
+        lc: &layered::Circuit<C, I>,
+    ) -> Result<(), Error> {
+        let mut buf = Vec::new();
+        irw.serialize_into(&mut buf)
+            .and_then(|_| lc.serialize_into(&mut buf))
+            .map_err(|e| Error::InternalError(format!("failed to serialize artifacts: {e:?}")))?;
+        self.store_bytes(key, &buf)
+    }
+
+    pub fn load_sub_circuit<Irc: IrConfig>(&self, key: &str) -> Option<ir::common::Circuit<Irc>>
+    where
+        Irc::Instruction: ExpSerde,
+        Irc::Constraint: ExpSerde,
+    {
+        self.load_with(key, |reader| {
+            ir::common::Circuit::<Irc>::deserialize_from(reader)
+        })
+    }
+
+    pub fn store_sub_circuit<Irc: IrConfig>(
+        &self,
+        key: &str,
+        circuit: &ir::common::Circuit<Irc>,
+    ) -> Result<(), Error>
+    where
+        Irc::Instruction: ExpSerde,
+        Irc::Constraint: ExpSerde,
+    {
+        let mut buf = Vec::new();
+        circuit
+            .serialize_into(&mut buf)
+            .map_err(|e| Error::InternalError(format!("failed to serialize circuit: {e:?}")))?;
+        self.store_bytes(key, &buf)
+    }
+
+    // Segments of a sub circuit call, with child segment ids local to the list, and the ids
+    // of the segments of its layers
+    pub fn load_segments<C: Config, I: InputType>(
+        &self,
+        key: &str,
+    ) -> Option<(Vec<layered::Segment<C, I>>, Vec<usize>)> {
+        self.load_with(key, |reader| {
+            let segments = Vec::<layered::Segment<C, I>>::deserialize_from(&mut *reader)?;
+            let layers = Vec::<usize>::deserialize_from(&mut *reader)?;
+            Ok((segments, layers))
+        })
+    }
+
+    pub fn store_segments<C: Config, I: InputType>(
+        &self,
+        key: &str,
+        segments: &[layered::Segment<C, I>],
+        layers: &[usize],
+    ) -> Result<(), Error> {
+        let mut buf = Vec::new();
+        segments
+            .to_vec()
+            .serialize_into(&mut buf)
+            .and_then(|_| layers.to_vec().serialize_into(&mut buf))
+            .map_err(|e| Error::InternalError(format!("failed to serialize segments: {e:?}")))?;
+        self.store_bytes(key, &buf)
+    }
+
+    // A missing, corrupted or unreadable entry is a miss, corrupted and unreadable entries are
+    // removed
+    fn load_with<T, F>(&self, key: &str, f: F) -> Option<T>
+    where
+        F: FnOnce(&mut &[u8]) -> SerdeResult<T>,
+    {
+        let path = self.path(key);
+        let data = fs::read(&path).ok()?;
+        let res = match data.split_at_checked(32) {
+            Some((hash, mut body)) if *hash == content_hash(body) => f(&mut body).ok(),
+            _ => None,
+        };
+        match res {
+            Ok(res) => {
+                // mark as recently used
+                let _ = fs::File::options()
+                    .append(true)
+                    .open(&path)
+                    .and_then(|f| f.set_modified(SystemTime::now()));
+                Some(res)
+            }
+            None => {
+                let _ = fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    fn store_bytes(&self, key: &str, buf: &[u8]) -> Result<(), Error> {
+        let io_err = |e: std::io::Error| Error::InternalError(format!("artifact cache: {e}"));
+        let size = buf.len() as u64 + 32;
+        if size > self.max_size {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir).map_err(io_err)?;
+        self.evict(self.max_size - size)?;
+        // write to a temporary file first so that readers never see a partial entry, the
+        // random suffix keeps concurrent writers of the same key apart
+        let tmp = self.dir.join(format!(
+            "{key}.{}.{:016x}.tmp",
+            std::process::id(),
+            rand::random::<u64>()
+        ));
+        {
+            let mut writer = BufWriter::new(fs::File::create(&tmp).map_err(io_err)?);
+            writer.write_all(&content_hash(buf)).map_err(io_err)?;
+            writer.write_all(buf).map_err(io_err)?;
+            writer.flush().map_err(io_err)?;
+        }
+        fs::rename(&tmp, self.path(key)).map_err(io_err)
+    }
+
+    pub fn invalidate(&self, key: &str) -> Result<(), Error> {
+        match fs::remove_file(self.path(key)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(Error::InternalError(format!("artifact cache: {e}")))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn clear(&self) -> Result<(), Error> {
+        for (path, _, _) in self.entries()? {
+            fs::remove_file(path)
+                .map_err(|e| Error::InternalError(format!("artifact cache: {e}")))?;
+        }
+        Ok(())
+    }
+
+    // total size of the entries in bytes
+    pub fn size(&self) -> Result<u64, Error> {
+        Ok(self.entries()?.iter().map(|(_, size, _)| size).sum())
+    }
+
+    // removes the least recently used entries until at most target bytes are left
+    fn evict(&self, target: u64) -> Result<(), Error> {
+        let mut entries = self.entries()?;
+        entries.sort_by_key(|(_, _, modified)| *modified);
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        for (path, size, _) in entries {
+            if total <= target {
+                break;
+            }
+            if fs::remove_file(path).is_ok() {
+                total -= size;
+            }
+        }
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<(PathBuf, u64, SystemTime)>, Error> {
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(Error::InternalError(format!("artifact cache: {e}"))),
+        };
+        let mut res = Vec::new();
+        for entry in dir.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|x| x.to_str()) != Some(EXTENSION) {
+                continue;
+            }
+            if let Ok(meta) = entry.metadata() {
+                let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                res.push((path, meta.len(), modified));
+            }
+        }
+        Ok(res)
+    }
+}
+
+fn content_hash(buf: &[u8]) -> [u8; 32] {
+    let mut hasher = tiny_keccak::Keccak::v256();
+    hasher.update(buf);
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    hash
+}
+
+// circuits are a HashMap, serialize them in id order so that the key is stable
+fn serialize_sorted<C: Config>(
+    r_source: &ir::source::RootCircuit<C>,
+    buf: &mut Vec<u8>,
+) -> SerdeResult<()> {
+    r_source.num_public_inputs.serialize_into(&mut *buf)?;
+    r_source
+        .expected_num_output_zeroes
+        .serialize_into(&mut *buf)?;
+    let mut ids: Vec<&usize> = r_source.circuits.keys().collect();
+    ids.sort();
+    for id in ids {
+        id.serialize_into(&mut *buf)?;
+        r_source.circuits[id].serialize_into(&mut *buf)?;
+    }
+    Ok(())
+}
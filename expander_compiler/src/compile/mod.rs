@@ -1,15 +1,27 @@
+use std::collections::HashMap;
+
 use crate::{
     builder,
     circuit::{
         config::Config,
         input_mapping::InputMapping,
-        ir,
-        layered::{self, InputType},
+        ir::{self, common::origin::Origins, source::location::LocationTable},
+        layered::{
+            self,
+            source_map::{SourceMap, SourceMapRef},
+            InputType,
+        },
     },
     layering,
     utils::error::Error,
 };
 
+pub mod cache;
+pub mod report;
+
+use cache::ArtifactCache;
+use report::CompileReport;
+
 #[cfg(test)]
 mod random_circuit_tests;
 #[cfg(test)]
@@ -20,6 +32,10 @@ pub struct CompileOptions {
     pub mul_fanout_limit: Option<usize>,
     pub allow_input_reorder: bool,
     pub opt_level: usize,
+    // attach a source map to the layered circuit, see layered::source_map
+    pub source_map: bool,
+    // reuse compile artifacts from disk, ignored when source_map is set
+    pub cache: Option<ArtifactCache>,
 }
 
 impl Default for CompileOptions {
@@ -28,6 +44,8 @@ impl Default for CompileOptions {
             mul_fanout_limit: None,
             allow_input_reorder: true,
             opt_level: 3,
+            source_map: false,
+            cache: None,
         }
     }
 }
@@ -45,6 +63,14 @@ impl CompileOptions {
         self.opt_level = opt_level;
         self
     }
+    pub fn with_source_map(mut self) -> Self {
+        self.source_map = true;
+        self
+    }
+    pub fn with_cache(mut self, cache: ArtifactCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
     pub fn validate(&self) -> Result<(), Error> {
         if self.mul_fanout_limit.is_some() && self.mul_fanout_limit.unwrap() <= 1 {
             return Err(Error::UserError("mul_fanout_limit must be > 1".to_string()));
@@ -76,116 +102,208 @@ where
     }
 }
 
-fn print_info(info: &str) {
-    print!(
-        "\x1b[90m{}\x1b[0m \x1b[32mINF\x1b[0m {} ",
-        chrono::Local::now().format("%H:%M:%S"),
-        info
-    );
-}
-
-fn print_stat(stat_name: &str, stat: usize, is_last: bool) {
-    print!("\x1b[36m{stat_name}=\x1b[0m{stat}");
-    if !is_last {
-        print!(" ");
-    } else {
-        println!();
+fn optimize_until_fixed_point_with_origins<T, F>(
+    x: &T,
+    im: &mut InputMapping,
+    origins: &mut Origins,
+    f: F,
+) -> T
+where
+    T: Clone + Eq,
+    F: Fn(&T) -> (T, InputMapping, Origins),
+{
+    let (mut y, imy, oy) = f(x);
+    if *x == y {
+        return y;
+    }
+    im.compose_in_place(&imy);
+    *origins = oy.compose(origins);
+    loop {
+        let (z, imz, oz) = f(&y);
+        if y == z {
+            return y;
+        }
+        y = z;
+        im.compose_in_place(&imz);
+        *origins = oz.compose(origins);
     }
 }
 
 pub fn compile_step_1<C: Config>(
     r_source: &ir::source::RootCircuit<C>,
     options: CompileOptions,
+    report: &mut CompileReport,
 ) -> Result<(ir::hint_normalized::RootCircuit<C>, InputMapping), Error> {
+    let (r, src_im, _) = compile_step_1_with_origins(r_source, options, report)?;
+    Ok((r, src_im))
+}
+
+// same as compile_step_1, but also tracks where each instruction and constraint of the
+// result comes from in r_source
+pub fn compile_step_1_with_origins<C: Config>(
+    r_source: &ir::source::RootCircuit<C>,
+    options: CompileOptions,
+    report: &mut CompileReport,
+) -> Result<(ir::hint_normalized::RootCircuit<C>, InputMapping, Origins), Error> {
     r_source.validate()?;
+    report.add_ir_stats("source", r_source);
 
     let mut src_im = InputMapping::new_identity(r_source.input_size());
+    let mut origins = Origins::identity(r_source);
 
     let mut r_source = r_source.clone();
 
-    let r_source_opt = if options.opt_level >= 3 {
-        r_source.detect_chains();
-        optimize_until_fixed_point(&r_source, &mut src_im, |r| {
-            let (mut r, im) = r.remove_unreachable();
-            r.reassign_duplicate_sub_circuit_outputs(false);
-            r.detect_chains();
-            (r, im)
-        })
-    } else if options.opt_level >= 1 {
-        r_source.detect_chains();
-        let (r, im) = r_source.remove_unreachable();
-        src_im.compose_in_place(&im);
-        r
-    } else {
-        r_source
-    };
+    let r_source_opt = report.time("source_opt", || {
+        if options.opt_level >= 3 {
+            r_source.detect_chains();
+            optimize_until_fixed_point_with_origins(&r_source, &mut src_im, &mut origins, |r| {
+                let (mut r, im, mut o) = r.remove_unreachable_with_origins();
+                r.reassign_duplicate_sub_circuit_outputs(false);
+                r.detect_chains();
+                o.extend_to(&r);
+                (r, im, o)
+            })
+        } else if options.opt_level >= 1 {
+            r_source.detect_chains();
+            let (r, im, o) = r_source.remove_unreachable_with_origins();
+            src_im.compose_in_place(&im);
+            origins = o.compose(&origins);
+            r
+        } else {
+            r_source
+        }
+    });
     r_source_opt
         .validate()
         .map_err(|e| e.prepend("source ir circuit invalid"))?;
 
-    let r_hint_normalized = builder::hint_normalize::process(&r_source_opt)
+    let (r_hint_normalized, o, num_reused) = report
+        .time("hint_normalize", || match &options.cache {
+            // source maps need the origins of every circuit, so they are rebuilt
+            Some(cache) if !options.source_map => hint_normalize_with_cache(&r_source_opt, cache),
+            _ => {
+                builder::hint_normalize::process_with_origins(&r_source_opt).map(|(r, o)| (r, o, 0))
+            }
+        })
         .map_err(|e| e.prepend("hint normalization failed"))?;
+    origins = o.compose(&origins);
+    report.reused_sub_circuits += num_reused;
 
-    let r_hint_normalized_opt = if options.opt_level >= 2 {
-        optimize_until_fixed_point(&r_hint_normalized, &mut src_im, |r| {
-            let (mut r, im) = r.remove_unreachable();
-            r.reassign_duplicate_sub_circuit_outputs(false);
-            (r, im)
-        })
-    } else if options.opt_level >= 1 {
-        let (r, im) = r_hint_normalized.remove_unreachable();
-        src_im.compose_in_place(&im);
-        r
-    } else {
-        r_hint_normalized
-    };
+    let r_hint_normalized_opt = report.time("hint_normalized_opt", || {
+        if options.opt_level >= 2 {
+            optimize_until_fixed_point_with_origins(
+                &r_hint_normalized,
+                &mut src_im,
+                &mut origins,
+                |r| {
+                    let (mut r, im, mut o) = r.remove_unreachable_with_origins();
+                    r.reassign_duplicate_sub_circuit_outputs(false);
+                    o.extend_to(&r);
+                    (r, im, o)
+                },
+            )
+        } else if options.opt_level >= 1 {
+            let (r, im, o) = r_hint_normalized.remove_unreachable_with_origins();
+            src_im.compose_in_place(&im);
+            origins = o.compose(&origins);
+            r
+        } else {
+            r_hint_normalized
+        }
+    });
     r_hint_normalized_opt
         .validate()
         .map_err(|e| e.prepend("hint normalized ir circuit invalid"))?;
-    Ok((r_hint_normalized_opt, src_im))
+    report.add_ir_stats("hint normalized", &r_hint_normalized_opt);
+    report.add_input_mapping("source", &src_im);
+    Ok((r_hint_normalized_opt, src_im, origins))
+}
+
+// Hint normalization does not look into sub circuits, so each circuit is looked up in the
+// artifact cache by its content hash first. This way unchanged gadgets are reused when only
+// the surrounding circuit changes.
+fn hint_normalize_with_cache<C: Config>(
+    r_source: &ir::source::RootCircuit<C>,
+    cache: &ArtifactCache,
+) -> Result<(ir::hint_normalized::RootCircuit<C>, Origins, usize), Error> {
+    let hashes = r_source.content_hashes();
+    let key = |id: &usize| ArtifactCache::sub_circuit_key("hint_normalize", &hashes[id]);
+    let mut reused = HashMap::new();
+    for (id, circuit) in r_source.circuits.iter() {
+        let callees = circuit.callees();
+        if let Some(c) = cache.load_sub_circuit::<ir::hint_normalized::Irc<C>>(&key(id)) {
+            if c.callees().iter().all(|i| *i < callees.len()) {
+                reused.insert(*id, c.replace_sub_circuit_ids(|i| callees[i]));
+            }
+        }
+    }
+    let skip = reused.keys().cloned().collect();
+    let (mut r, origins) = builder::hint_normalize::process_skipping(r_source, &skip)?;
+    // only circuits that missed the cache are written, reused ones are already stored
+    for (id, circuit) in r.circuits.iter().filter(|(id, _)| !reused.contains_key(id)) {
+        // stored with the sub circuit ids of the source circuit replaced by local ones
+        let callees = r_source.circuits[id].callees();
+        let local =
+            circuit.replace_sub_circuit_ids(|x| callees.iter().position(|y| *y == x).unwrap());
+        // failing to write the cache must not fail the compilation
+        let _ = cache.store_sub_circuit(&key(id), &local);
+    }
+    let num_reused = reused.len();
+    r.circuits.extend(reused);
+    Ok((r, origins, num_reused))
 }
 
 pub fn compile_step_2<C: Config, I: InputType>(
     r_hint_less: ir::hint_less::RootCircuit<C>,
     options: CompileOptions,
+    report: &mut CompileReport,
 ) -> Result<(ir::dest::RootCircuit<C>, InputMapping), Error> {
     let mut hl_im = InputMapping::new_identity(r_hint_less.input_size());
 
-    let r_hint_less_opt = if options.opt_level >= 2 {
-        optimize_until_fixed_point(&r_hint_less, &mut hl_im, |r| {
-            let (mut r, im) = r.remove_unreachable();
-            r.reassign_duplicate_sub_circuit_outputs(false);
-            (r, im)
-        })
-    } else {
-        r_hint_less
-    };
+    let r_hint_less_opt = report.time("hint_less_opt", || {
+        if options.opt_level >= 2 {
+            optimize_until_fixed_point(&r_hint_less, &mut hl_im, |r| {
+                let (mut r, im) = r.remove_unreachable();
+                r.reassign_duplicate_sub_circuit_outputs(false);
+                (r, im)
+            })
+        } else {
+            r_hint_less
+        }
+    });
     r_hint_less_opt
         .validate()
         .map_err(|e| e.prepend("hint less ir circuit invalid"))?;
 
-    let r_dest_relaxed = builder::final_build_opt::process(&r_hint_less_opt)
+    let r_dest_relaxed = report
+        .time("final_build", || {
+            builder::final_build_opt::process(&r_hint_less_opt)
+        })
         .map_err(|e| e.prepend("final build failed"))?;
 
-    let r_dest_relaxed_opt = if options.opt_level >= 2 {
-        optimize_until_fixed_point(&r_dest_relaxed, &mut hl_im, |r| {
-            let (mut r, im) = r.remove_unreachable();
-            r.reassign_duplicate_sub_circuit_outputs(false);
-            (r, im)
-        })
-    } else if options.opt_level >= 1 {
-        let (r, im) = r_dest_relaxed.remove_unreachable();
-        hl_im.compose_in_place(&im);
-        r
-    } else {
-        r_dest_relaxed
-    };
+    let r_dest_relaxed_opt = report.time("dest_relaxed_opt", || {
+        if options.opt_level >= 2 {
+            optimize_until_fixed_point(&r_dest_relaxed, &mut hl_im, |r| {
+                let (mut r, im) = r.remove_unreachable();
+                r.reassign_duplicate_sub_circuit_outputs(false);
+                (r, im)
+            })
+        } else if options.opt_level >= 1 {
+            let (r, im) = r_dest_relaxed.remove_unreachable();
+            hl_im.compose_in_place(&im);
+            r
+        } else {
+            r_dest_relaxed
+        }
+    });
     r_dest_relaxed_opt
         .validate()
         .map_err(|e| e.prepend("dest relaxed ir circuit invalid"))?;
 
     let r_dest_relaxed_opt = if let Some(limit) = options.mul_fanout_limit {
-        let r = r_dest_relaxed_opt.solve_mul_fanout_limit(limit);
+        let r = report.time("mul_fanout_limit", || {
+            r_dest_relaxed_opt.solve_mul_fanout_limit(limit)
+        });
         r.validate()
             .map_err(|e| e.prepend("dest relaxed ir circuit invalid"))?;
         r
@@ -196,9 +314,11 @@ pub fn compile_step_2<C: Config, I: InputType>(
     let r_dest_relaxed_p2 = if C::ENABLE_RANDOM_COMBINATION {
         r_dest_relaxed_opt
     } else {
-        let mut r1 = r_dest_relaxed_opt.export_constraints();
-        r1.reassign_duplicate_sub_circuit_outputs(false);
-        let (r2, im) = r1.remove_unreachable();
+        let (r2, im) = report.time("export_constraints", || {
+            let mut r1 = r_dest_relaxed_opt.export_constraints();
+            r1.reassign_duplicate_sub_circuit_outputs(false);
+            r1.remove_unreachable()
+        });
         hl_im.compose_in_place(&im);
         r2.validate()
             .map_err(|e| e.prepend("dest relaxed ir circuit invalid"))?;
@@ -208,65 +328,74 @@ pub fn compile_step_2<C: Config, I: InputType>(
     let mut r_dest_relaxed_p3 = if I::CROSS_LAYER_RELAY {
         r_dest_relaxed_p2
     } else {
-        let r = layering::ir_split::split_to_single_layer(&r_dest_relaxed_p2);
+        let r = report.time("split_to_single_layer", || {
+            layering::ir_split::split_to_single_layer(&r_dest_relaxed_p2)
+        });
         r.validate()
             .map_err(|e| e.prepend("dest relaxed ir circuit invalid"))?;
 
+        report.time("single_layer_opt", || {
+            if options.opt_level >= 2 {
+                optimize_until_fixed_point(&r, &mut hl_im, |r| {
+                    let (mut r, im) = r.remove_unreachable();
+                    r.reassign_duplicate_sub_circuit_outputs(false);
+                    (r, im)
+                })
+            } else if options.opt_level >= 1 {
+                let (r, im) = r.remove_unreachable();
+                hl_im.compose_in_place(&im);
+                r
+            } else {
+                r
+            }
+        })
+    };
+
+    if options.opt_level == 1 {
+        r_dest_relaxed_p3.reassign_duplicate_sub_circuit_outputs(true);
+    }
+
+    let r_dest = report.time("solve_duplicates", || r_dest_relaxed_p3.solve_duplicates());
+
+    let r_dest_opt = report.time("dest_opt", || {
         if options.opt_level >= 2 {
-            optimize_until_fixed_point(&r, &mut hl_im, |r| {
+            optimize_until_fixed_point(&r_dest, &mut hl_im, |r| {
                 let (mut r, im) = r.remove_unreachable();
                 r.reassign_duplicate_sub_circuit_outputs(false);
                 (r, im)
             })
         } else if options.opt_level >= 1 {
-            let (r, im) = r.remove_unreachable();
+            let (r, im) = r_dest.remove_unreachable();
             hl_im.compose_in_place(&im);
             r
         } else {
-            r
+            r_dest
         }
-    };
-
-    if options.opt_level == 1 {
-        r_dest_relaxed_p3.reassign_duplicate_sub_circuit_outputs(true);
-    }
-
-    let r_dest = r_dest_relaxed_p3.solve_duplicates();
-
-    let r_dest_opt = if options.opt_level >= 2 {
-        optimize_until_fixed_point(&r_dest, &mut hl_im, |r| {
-            let (mut r, im) = r.remove_unreachable();
-            r.reassign_duplicate_sub_circuit_outputs(false);
-            (r, im)
-        })
-    } else if options.opt_level >= 1 {
-        let (r, im) = r_dest.remove_unreachable();
-        hl_im.compose_in_place(&im);
-        r
-    } else {
-        r_dest
-    };
+    });
     r_dest_opt
         .validate()
         .map_err(|e| e.prepend("dest ir circuit invalid"))?;
     r_dest_opt
         .validate_circuit_has_inputs()
         .map_err(|e| e.prepend("dest ir circuit invalid"))?;
+    report.add_ir_stats("dest", &r_dest_opt);
+    report.add_input_mapping("hint less", &hl_im);
     Ok((r_dest_opt, hl_im))
 }
 
 pub fn compile_step_3<C: Config, I: InputType>(
     mut lc: layered::Circuit<C, I>,
     options: CompileOptions,
+    report: &mut CompileReport,
 ) -> Result<layered::Circuit<C, I>, Error> {
     lc.validate()
         .map_err(|e| e.prepend("layered circuit invalid"))?;
 
     if options.opt_level >= 1 {
-        lc.dedup_gates();
+        report.time("dedup_gates", || lc.dedup_gates());
     }
     if options.opt_level >= 3 {
-        loop {
+        report.time("layered_opt", || loop {
             let lc1 = lc.expand_small_segments();
             let lc2 = if lc1.segments.len() <= 100 {
                 lc1.find_common_parts()
@@ -277,11 +406,12 @@ pub fn compile_step_3<C: Config, I: InputType>(
                 break;
             }
             lc = lc2;
-        }
+        });
     }
     lc.validate()
         .map_err(|e| e.prepend("layered circuit invalid1"))?;
     lc.sort_everything(); // for deterministic output
+    report.set_layered(&lc);
     Ok(lc)
 }
 
@@ -289,81 +419,137 @@ pub fn compile_step_4<C: Config>(
     r_hint_exported: ir::hint_normalized::RootCircuit<C>,
     src_im: &mut InputMapping,
     options: CompileOptions,
+    report: &mut CompileReport,
 ) -> Result<ir::hint_normalized::RootCircuit<C>, Error> {
     r_hint_exported
         .validate()
         .map_err(|e| e.prepend("final hint exported circuit invalid"))?;
-    let r_hint_exported_opt = if options.opt_level >= 2 {
-        optimize_until_fixed_point(&r_hint_exported, src_im, |r| {
-            let (r, im) = r.remove_unreachable();
-            (r, im)
-        })
-    } else {
-        r_hint_exported
-    };
+    let r_hint_exported_opt = report.time("hint_exported_opt", || {
+        if options.opt_level >= 2 {
+            optimize_until_fixed_point(&r_hint_exported, src_im, |r| {
+                let (r, im) = r.remove_unreachable();
+                (r, im)
+            })
+        } else {
+            r_hint_exported
+        }
+    });
     Ok(r_hint_exported_opt)
 }
 
 pub fn compile<C: Config, I: InputType>(
     r_source: &ir::source::RootCircuit<C>,
 ) -> Result<(ir::hint_normalized::RootCircuit<C>, layered::Circuit<C, I>), Error> {
-    compile_with_options(r_source, CompileOptions::default())
+    let (irw, lc, _) = compile_with_options(r_source, CompileOptions::default())?;
+    Ok((irw, lc))
 }
 
-pub fn print_ir_stats<C: Config>(r_hint_normalized: &ir::hint_normalized::RootCircuit<C>) {
-    let ho_stats = r_hint_normalized.get_stats();
-    print_info("built hint normalized ir");
-    print_stat("numInputs", ho_stats.num_inputs, false);
-    print_stat("numConstraints", ho_stats.num_constraints, false);
-    print_stat("numInsns", ho_stats.num_insns, false);
-    print_stat("numVars", ho_stats.num_variables, false);
-    print_stat("numTerms", ho_stats.num_terms, true);
+pub fn compile_with_options<C: Config, I: InputType>(
+    r_source: &ir::source::RootCircuit<C>,
+    options: CompileOptions,
+) -> Result<
+    (
+        ir::hint_normalized::RootCircuit<C>,
+        layered::Circuit<C, I>,
+        CompileReport,
+    ),
+    Error,
+> {
+    compile_with_locations(r_source, &LocationTable::default(), options)
 }
 
-pub fn print_layered_circuit_stats<C: Config, I: InputType>(lc: &layered::Circuit<C, I>) {
-    let lc_stats = lc.get_stats();
-    print_info("built layered circuit");
-    print_stat("numSegment", lc_stats.num_segments, false);
-    print_stat("numLayer", lc_stats.num_layers, false);
-    print_stat("numUsedInputs", lc_stats.num_inputs, false);
-    print_stat("numUsedVariables", lc_stats.num_used_gates, false);
-    print_stat("numVariables", lc_stats.num_total_gates, false);
-    print_stat("numAdd", lc_stats.num_expanded_add, false);
-    print_stat("numCst", lc_stats.num_expanded_cst, false);
-    print_stat("numMul", lc_stats.num_expanded_mul, false);
-    print_stat("totalCost", lc_stats.total_cost, true);
+// locations are only used when options.source_map is set
+pub fn compile_with_locations<C: Config, I: InputType>(
+    r_source: &ir::source::RootCircuit<C>,
+    locations: &LocationTable,
+    options: CompileOptions,
+) -> Result<
+    (
+        ir::hint_normalized::RootCircuit<C>,
+        layered::Circuit<C, I>,
+        CompileReport,
+    ),
+    Error,
+> {
+    options.validate()?;
+    let mut report = CompileReport::default();
+
+    // source maps are not serialized, so they always need a fresh compile
+    let cache = match &options.cache {
+        Some(cache) if !options.source_map => cache.clone(),
+        _ => {
+            let (irw, lc) = compile_uncached(r_source, locations, options, &mut report)?;
+            return Ok((irw, lc, report));
+        }
+    };
+    let key = ArtifactCache::key::<C, I>(r_source, &options)?;
+    if let Some((irw, lc)) = cache.load::<C, I>(&key) {
+        report.from_cache = true;
+        report.add_ir_stats("hint normalized", &irw);
+        report.set_layered(&lc);
+        return Ok((irw, lc, report));
+    }
+    let (irw, lc) = compile_uncached(r_source, locations, options, &mut report)?;
+    // failing to write the cache must not fail the compilation
+    if let Err(e) = cache.store(&key, &irw, &lc) {
+        report
+            .warnings
+            .push(format!("failed to store compile artifacts: {e:?}"));
+    }
+    Ok((irw, lc, report))
 }
 
-pub fn compile_with_options<C: Config, I: InputType>(
+fn compile_uncached<C: Config, I: InputType>(
     r_source: &ir::source::RootCircuit<C>,
+    locations: &LocationTable,
     options: CompileOptions,
+    report: &mut CompileReport,
 ) -> Result<(ir::hint_normalized::RootCircuit<C>, layered::Circuit<C, I>), Error> {
-    options.validate()?;
-
-    let (r_hint_normalized_opt, mut src_im) = compile_step_1(r_source, options.clone())?;
-
-    print_ir_stats(&r_hint_normalized_opt);
+    let (r_hint_normalized_opt, mut src_im, origins) =
+        compile_step_1_with_origins(r_source, options.clone(), report)?;
 
     let (r_hint_less, mut r_hint_exported) = r_hint_normalized_opt.remove_and_export_hints();
     r_hint_exported
         .validate()
         .map_err(|e| e.prepend("hint exported circuit invalid"))?;
 
-    let (r_dest_opt, mut hl_im) = compile_step_2::<C, I>(r_hint_less, options.clone())?;
+    let source_map_base = if options.source_map {
+        let origins = r_hint_normalized_opt.hint_less_origins().compose(&origins);
+        Some((r_hint_less.clone(), origins))
+    } else {
+        None
+    };
 
-    let (lc, dest_im) = layering::compile(
-        &r_dest_opt,
-        layering::CompileOptions {
-            allow_input_reorder: options.allow_input_reorder,
-        },
-    );
+    let (r_dest_opt, mut hl_im) = compile_step_2::<C, I>(r_hint_less, options.clone(), report)?;
 
-    let lc = compile_step_3(lc, options.clone())?;
+    let layering_options = layering::CompileOptions {
+        allow_input_reorder: options.allow_input_reorder,
+    };
+    let (lc, dest_im, num_reused) = report.time("layering", || match &options.cache {
+        Some(cache) if !options.source_map => {
+            layering::compile_with_cache(&r_dest_opt, layering_options, cache)
+        }
+        _ => {
+            let (lc, im) = layering::compile(&r_dest_opt, layering_options);
+            (lc, im, 0)
+        }
+    });
+    report.reused_layered_sub_circuits += num_reused;
+    report.add_input_mapping("dest", &dest_im);
 
-    print_layered_circuit_stats(&lc);
+    let mut lc = compile_step_3(lc, options.clone(), report)?;
 
     hl_im.compose_in_place(&dest_im);
 
+    if let Some((r_hint_less, origins)) = source_map_base {
+        lc.source_map = SourceMapRef::new(SourceMap::new(
+            r_hint_less,
+            hl_im.clone(),
+            &origins,
+            locations,
+        ));
+    }
+
     let rhe_c0 = r_hint_exported.circuits.get_mut(&0).unwrap();
     rhe_c0.outputs = hl_im
         .map_inputs(&rhe_c0.outputs)
@@ -371,7 +557,8 @@ pub fn compile_with_options<C: Config, I: InputType>(
         .map(|&x| x.max(1))
         .collect();
 
-    let mut r_hint_exported_opt = compile_step_4(r_hint_exported, &mut src_im, options.clone())?;
+    let mut r_hint_exported_opt =
+        compile_step_4(r_hint_exported, &mut src_im, options.clone(), report)?;
     r_hint_exported_opt.add_back_removed_inputs(&src_im);
     r_hint_exported_opt
         .validate()
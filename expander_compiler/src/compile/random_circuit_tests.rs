@@ -26,7 +26,7 @@ fn do_test_with_options<C: Config, I: InputType>(
         assert_eq!(root.validate(), Ok(()));
         let res = compile_with_options::<_, I>(&root, compile_options.clone());
         match res {
-            Ok((ir_hint_normalized, layered_circuit)) => {
+            Ok((ir_hint_normalized, layered_circuit, _)) => {
                 assert_eq!(ir_hint_normalized.validate(), Ok(()));
                 assert_eq!(layered_circuit.validate(), Ok(()));
                 assert_eq!(ir_hint_normalized.input_size(), root.input_size());
@@ -0,0 +1,160 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    circuit::{
+        config::Config,
+        input_mapping::InputMapping,
+        ir::{
+            self,
+            common::{stats::Stats as IrStats, IrConfig},
+        },
+        layered::{
+            self,
+            stats::{LayerStats, Stats as LayeredStats},
+            InputType,
+        },
+    },
+    utils::error::Error,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IrStageReport {
+    pub stage: String,
+    pub stats: IrStats,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PassReport {
+    pub pass: String,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputMappingReport {
+    pub stage: String,
+    // number of inputs before and after the stage
+    pub cur_size: usize,
+    pub next_size: usize,
+}
+
+// What the compiler did, returned instead of being printed.
+// Call print() for the human readable form or to_json() for dashboards.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompileReport {
+    pub ir_stats: Vec<IrStageReport>,
+    pub layered_stats: Option<LayeredStats>,
+    pub layers: Vec<LayerStats>,
+    pub passes: Vec<PassReport>,
+    pub input_mappings: Vec<InputMappingReport>,
+    // the artifacts were loaded from the artifact cache
+    pub from_cache: bool,
+    // circuits whose hint normalized form was reused from the artifact cache
+    pub reused_sub_circuits: usize,
+    // sub circuit calls whose layered segments were reused from the artifact cache
+    pub reused_layered_sub_circuits: usize,
+    pub warnings: Vec<String>,
+}
+
+impl CompileReport {
+    pub fn time<T>(&mut self, pass: &str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let res = f();
+        self.passes.push(PassReport {
+            pass: pass.to_string(),
+            duration: start.elapsed(),
+        });
+        res
+    }
+
+    pub fn add_ir_stats<Irc: IrConfig>(&mut self, stage: &str, rc: &ir::common::RootCircuit<Irc>) {
+        self.ir_stats.push(IrStageReport {
+            stage: stage.to_string(),
+            stats: rc.get_stats(),
+        });
+    }
+
+    pub fn add_input_mapping(&mut self, stage: &str, im: &InputMapping) {
+        self.input_mappings.push(InputMappingReport {
+            stage: stage.to_string(),
+            cur_size: im.cur_size(),
+            next_size: im.next_size(),
+        });
+    }
+
+    pub fn set_layered<C: Config, I: InputType>(&mut self, lc: &layered::Circuit<C, I>) {
+        self.layered_stats = Some(lc.get_stats());
+        self.layers = lc.get_layer_stats();
+    }
+
+    pub fn ir_stats(&self, stage: &str) -> Option<&IrStats> {
+        self.ir_stats
+            .iter()
+            .find(|x| x.stage == stage)
+            .map(|x| &x.stats)
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        self.passes.iter().map(|x| x.duration).sum()
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string(self)
+            .map_err(|e| Error::InternalError(format!("failed to serialize report: {e}")))
+    }
+
+    pub fn print(&self) {
+        print!("{self}");
+    }
+}
+
+fn write_info(f: &mut fmt::Formatter<'_>, info: &str) -> fmt::Result {
+    write!(f, "\x1b[32mINF\x1b[0m {info}")
+}
+
+fn write_stat(f: &mut fmt::Formatter<'_>, stat_name: &str, stat: usize) -> fmt::Result {
+    write!(f, " \x1b[36m{stat_name}=\x1b[0m{stat}")
+}
+
+impl fmt::Display for CompileReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.from_cache {
+            write_info(f, "loaded compile artifacts from cache")?;
+            writeln!(f)?;
+        }
+        for x in self.ir_stats.iter() {
+            write_info(f, &format!("built {} ir", x.stage))?;
+            write_stat(f, "numInputs", x.stats.num_inputs)?;
+            write_stat(f, "numConstraints", x.stats.num_constraints)?;
+            write_stat(f, "numInsns", x.stats.num_insns)?;
+            write_stat(f, "numVars", x.stats.num_variables)?;
+            write_stat(f, "numTerms", x.stats.num_terms)?;
+            writeln!(f)?;
+        }
+        if let Some(lc_stats) = &self.layered_stats {
+            write_info(f, "built layered circuit")?;
+            write_stat(f, "numSegment", lc_stats.num_segments)?;
+            write_stat(f, "numLayer", lc_stats.num_layers)?;
+            write_stat(f, "numUsedInputs", lc_stats.num_inputs)?;
+            write_stat(f, "numUsedVariables", lc_stats.num_used_gates)?;
+            write_stat(f, "numVariables", lc_stats.num_total_gates)?;
+            write_stat(f, "numAdd", lc_stats.num_expanded_add)?;
+            write_stat(f, "numCst", lc_stats.num_expanded_cst)?;
+            write_stat(f, "numMul", lc_stats.num_expanded_mul)?;
+            write_stat(f, "totalCost", lc_stats.total_cost)?;
+            writeln!(f)?;
+        }
+        for x in self.passes.iter() {
+            write_info(f, &format!("pass {} took {:?}", x.pass, x.duration))?;
+            writeln!(f)?;
+        }
+        for x in self.warnings.iter() {
+            writeln!(f, "\x1b[33mWRN\x1b[0m {x}")?;
+        }
+        Ok(())
+    }
+}
@@ -36,3 +36,134 @@ fn simple_div() {
     assert_eq!(o[0], CField::from(10));
     assert!(!cond);
 }
+
+#[test]
+fn artifact_cache() {
+    let mut root = ir::source::RootCircuit::<C>::default();
+    root.circuits.insert(
+        0,
+        ir::source::Circuit {
+            instructions: vec![ir::source::Instruction::Mul(vec![1, 2])],
+            constraints: vec![],
+            outputs: vec![3],
+            num_inputs: 2,
+        },
+    );
+    let dir = std::env::temp_dir().join(format!("ecc_artifact_cache_{}", std::process::id()));
+    let cache = super::cache::ArtifactCache::new(&dir);
+    let options = super::CompileOptions::default().with_cache(cache.clone());
+    let key = super::cache::ArtifactCache::key::<C, NormalInputType>(&root, &options).unwrap();
+    assert_eq!(
+        key,
+        super::cache::ArtifactCache::key::<C, NormalInputType>(&root, &options).unwrap()
+    );
+    let other_options = options.clone().with_opt_level(1);
+    assert_ne!(
+        key,
+        super::cache::ArtifactCache::key::<C, NormalInputType>(&root, &other_options).unwrap()
+    );
+
+    assert!(cache.load::<C, NormalInputType>(&key).is_none());
+    let (irw, lc, report) =
+        super::compile_with_options::<_, NormalInputType>(&root, options.clone()).unwrap();
+    assert!(!report.from_cache);
+    assert_eq!(cache.load::<C, NormalInputType>(&key), Some((irw, lc)));
+    let (_, _, report) =
+        super::compile_with_options::<_, NormalInputType>(&root, options.clone()).unwrap();
+    assert!(report.from_cache);
+
+    // a corrupted entry is a miss and gets replaced
+    let path = dir.join(format!("{key}.ecc"));
+    let mut data = std::fs::read(&path).unwrap();
+    let last = data.len() - 1;
+    data[last] ^= 1;
+    std::fs::write(&path, data).unwrap();
+    assert!(cache.load::<C, NormalInputType>(&key).is_none());
+    let (_, _, report) = super::compile_with_options::<_, NormalInputType>(&root, options).unwrap();
+    assert!(!report.from_cache);
+    assert!(cache.load::<C, NormalInputType>(&key).is_some());
+    cache.invalidate(&key).unwrap();
+    assert!(cache.load::<C, NormalInputType>(&key).is_none());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn artifact_cache_layered_sub_circuits() {
+    let mut root = ir::source::RootCircuit::<C>::default();
+    root.circuits.insert(
+        0,
+        ir::source::Circuit {
+            instructions: vec![
+                ir::source::Instruction::SubCircuitCall {
+                    sub_circuit_id: 1,
+                    inputs: vec![1, 2],
+                    num_outputs: 1,
+                },
+                ir::source::Instruction::Mul(vec![4, 3]),
+            ],
+            constraints: vec![],
+            outputs: vec![5],
+            num_inputs: 3,
+        },
+    );
+    root.circuits.insert(
+        1,
+        ir::source::Circuit {
+            instructions: vec![ir::source::Instruction::Mul(vec![1, 2])],
+            constraints: vec![],
+            outputs: vec![3],
+            num_inputs: 2,
+        },
+    );
+    let dir = std::env::temp_dir().join(format!("ecc_layered_cache_{}", std::process::id()));
+    let cache = super::cache::ArtifactCache::new(&dir);
+    let options = super::CompileOptions::default().with_cache(cache.clone());
+    let key = super::cache::ArtifactCache::key::<C, NormalInputType>(&root, &options).unwrap();
+    let (_, lc, report) =
+        super::compile_with_options::<_, NormalInputType>(&root, options.clone()).unwrap();
+    assert_eq!(report.reused_layered_sub_circuits, 0);
+
+    // without the whole circuit, only the root is layered again
+    cache.invalidate(&key).unwrap();
+    let (_, lc2, report) =
+        super::compile_with_options::<_, NormalInputType>(&root, options).unwrap();
+    assert!(!report.from_cache);
+    assert_eq!(report.reused_sub_circuits, 2);
+    assert_eq!(report.reused_layered_sub_circuits, 1);
+    assert_eq!(lc, lc2);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn compile_report() {
+    let mut root = ir::source::RootCircuit::<C>::default();
+    root.circuits.insert(
+        0,
+        ir::source::Circuit {
+            instructions: vec![
+                ir::source::Instruction::Mul(vec![1, 2]),
+                ir::source::Instruction::Mul(vec![3, 3]),
+            ],
+            constraints: vec![],
+            outputs: vec![4],
+            num_inputs: 2,
+        },
+    );
+    let (_, lc, report) =
+        super::compile_with_options::<_, NormalInputType>(&root, super::CompileOptions::default())
+            .unwrap();
+    assert_eq!(report.ir_stats("source").unwrap().num_inputs, 2);
+    assert!(report.ir_stats("hint normalized").is_some());
+    assert!(report.ir_stats("dest").is_some());
+    assert_eq!(report.layers.len(), lc.layer_ids.len());
+    assert_eq!(
+        report.layered_stats.as_ref().unwrap().num_layers,
+        lc.layer_ids.len()
+    );
+    assert!(report.passes.iter().any(|x| x.pass == "layering"));
+    assert_eq!(report.input_mappings[0].cur_size, 2);
+
+    let json = report.to_json().unwrap();
+    let decoded: super::report::CompileReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, report);
+}
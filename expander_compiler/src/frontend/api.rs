@@ -1,6 +1,6 @@
 use arith::Field;
 
-use crate::circuit::config::Config;
+use crate::{circuit::config::Config, hints::registry::ToHintId};
 
 use super::{
     builder::{ToVariableOrValue, Variable},
@@ -33,6 +33,7 @@ pub trait BasicAPI<C: Config> {
         checked: bool,
     ) -> Variable;
     fn neg(&mut self, x: impl ToVariableOrValue<CircuitField<C>>) -> Variable;
+    #[track_caller]
     fn inverse(&mut self, x: impl ToVariableOrValue<CircuitField<C>>) -> Variable {
         self.div(1, x, true)
     }
@@ -44,7 +45,7 @@ pub trait BasicAPI<C: Config> {
     ) -> Vec<Variable>;
     /// return 1 if x > y; 0 otherwise
     /// this is a very expensive operation -- it uses bit decompositions for comparison
-    /// consider use lookup table for better performance
+    /// consider lt, leq or cmp_bounded when both operands are known to be small
     fn gt(
         &mut self,
         x: impl ToVariableOrValue<CircuitField<C>>,
@@ -52,15 +53,64 @@ pub trait BasicAPI<C: Config> {
     ) -> Variable;
     /// return 1 if x >= y; 0 otherwise
     /// this is a very expensive operation -- it uses bit decompositions for comparison
-    /// consider use lookup table for better performance
+    /// consider lt, leq or cmp_bounded when both operands are known to be small
     fn geq(
         &mut self,
         x: impl ToVariableOrValue<CircuitField<C>>,
         y: impl ToVariableOrValue<CircuitField<C>>,
     ) -> Variable;
+    /// return 1 if x < y; 0 otherwise
+    /// x and y are constrained to be less than 2^bits, as in cmp_bounded but without its
+    /// equality test
+    fn lt(
+        &mut self,
+        x: impl ToVariableOrValue<CircuitField<C>>,
+        y: impl ToVariableOrValue<CircuitField<C>>,
+        bits: usize,
+    ) -> Variable;
+    /// return 1 if x <= y; 0 otherwise
+    /// x and y are constrained to be less than 2^bits, as in lt
+    fn leq(
+        &mut self,
+        x: impl ToVariableOrValue<CircuitField<C>>,
+        y: impl ToVariableOrValue<CircuitField<C>>,
+        bits: usize,
+    ) -> Variable;
+    /// assert 0 <= x < 2^bits
+    /// range checks are collected over the whole circuit, including sub circuits, and the
+    /// compiler batches them into a shared LogUp table when that is cheaper than bit decomposition
+    fn assert_range(&mut self, x: impl ToVariableOrValue<CircuitField<C>>, bits: usize);
+    /// return 1 if x > y, 0 if x == y and -1 if x < y
+    /// x and y are constrained to be less than 2^bits, which costs a few range checks
+    /// instead of the full bit decompositions of gt
+    fn cmp_bounded(
+        &mut self,
+        x: impl ToVariableOrValue<CircuitField<C>>,
+        y: impl ToVariableOrValue<CircuitField<C>>,
+        bits: usize,
+    ) -> Variable;
+    /// return if_true if cond is 1 and if_false if cond is 0
+    /// cond is constrained to be boolean
+    fn select(
+        &mut self,
+        cond: impl ToVariableOrValue<CircuitField<C>>,
+        if_true: impl ToVariableOrValue<CircuitField<C>>,
+        if_false: impl ToVariableOrValue<CircuitField<C>>,
+    ) -> Variable;
+    /// return values[i] where i is given by the little endian bits in selectors
+    /// values.len() must be 2^selectors.len(), and every selector is constrained to be boolean
+    fn mux(&mut self, selectors: &[Variable], values: &[Variable]) -> Variable;
+    /// return values[index], index is constrained to be less than values.len()
+    /// this costs a few gates per value; if index is available in bits, use mux instead
+    fn lookup(
+        &mut self,
+        index: impl ToVariableOrValue<CircuitField<C>>,
+        values: &[Variable],
+    ) -> Variable;
     fn assert_is_zero(&mut self, x: impl ToVariableOrValue<CircuitField<C>>);
     fn assert_is_non_zero(&mut self, x: impl ToVariableOrValue<CircuitField<C>>);
     fn assert_is_bool(&mut self, x: impl ToVariableOrValue<CircuitField<C>>);
+    #[track_caller]
     fn assert_is_equal(
         &mut self,
         x: impl ToVariableOrValue<CircuitField<C>>,
@@ -69,6 +119,7 @@ pub trait BasicAPI<C: Config> {
         let diff = self.sub(x, y);
         self.assert_is_zero(diff);
     }
+    #[track_caller]
     fn assert_is_different(
         &mut self,
         x: impl ToVariableOrValue<CircuitField<C>>,
@@ -78,12 +129,17 @@ pub trait BasicAPI<C: Config> {
         self.assert_is_non_zero(diff);
     }
     fn get_random_value(&mut self) -> Variable;
+    /// `hint` is either the string key the hint was registered under, or the `HintHandle`
+    /// returned by `HintRegistry::register`.
     fn new_hint(
         &mut self,
-        hint_key: &str,
+        hint: impl ToHintId,
         inputs: &[Variable],
         num_outputs: usize,
     ) -> Vec<Variable>;
+    /// Applies a custom gate, either a builtin Expander gate or one registered with
+    /// `CustomGateRegistry`. The gate stays a single gate down to the layered circuit.
+    fn custom_gate(&mut self, gate_type: usize, inputs: &[Variable]) -> Variable;
     fn constant(&mut self, x: impl ToVariableOrValue<CircuitField<C>>) -> Variable;
     // try to get the value of a compile-time constant variable
     // this function has different behavior in normal and debug mode, in debug mode it always returns Some(value)
@@ -93,6 +149,7 @@ pub trait BasicAPI<C: Config> {
     ) -> Option<CircuitField<C>>;
 
     #[allow(clippy::wrong_self_convention)]
+    #[track_caller]
     fn from_binary(&mut self, xs: &[Variable]) -> Variable {
         if xs.is_empty() {
             return self.constant(0);
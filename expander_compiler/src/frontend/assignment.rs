@@ -0,0 +1,179 @@
+// Circuits declared with `declare_circuit!` or `#[derive(Circuit)]` can be saved to and loaded
+// from a document keyed by their field names, with nested arrays as JSON arrays and field
+// elements as decimal or `0x` prefixed hex strings. Documents go through `serde_json::Value`,
+// so any serde format works, e.g. `ciborium::from_reader::<Value, _>` for CBOR.
+
+use ethnum::U256;
+use serde::{de::DeserializeOwned, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::{field::Field, utils::error::Error};
+
+pub trait SerdeAssignment: Sized {
+    fn to_document(&self) -> Value;
+    /// `path` locates the document in the whole assignment, it prefixes error messages.
+    fn from_document(doc: &Value, path: &str) -> Result<Self, Error>;
+}
+
+pub fn assignment_from_json<A: SerdeAssignment>(json: &str) -> Result<A, Error> {
+    let doc: Value = serde_json::from_str(json)
+        .map_err(|e| Error::UserError(format!("invalid assignment document: {e}")))?;
+    A::from_document(&doc, "")
+}
+
+pub fn assignment_to_json<A: SerdeAssignment>(assignment: &A) -> String {
+    assignment.to_document().to_string()
+}
+
+/// Loads an assignment from any serde format.
+pub fn assignment_from_deserializer<'de, A: SerdeAssignment, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<A, Error> {
+    let doc = <Value as serde::Deserialize>::deserialize(deserializer)
+        .map_err(|e| Error::UserError(format!("invalid assignment document: {e}")))?;
+    A::from_document(&doc, "")
+}
+
+/// Saves an assignment to any serde format.
+pub fn assignment_to_serializer<A: SerdeAssignment, S: Serializer>(
+    assignment: &A,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    assignment.to_document().serialize(serializer)
+}
+
+fn describe(path: &str) -> &str {
+    if path.is_empty() {
+        "assignment"
+    } else {
+        path
+    }
+}
+
+fn describe_value(doc: &Value) -> &'static str {
+    match doc {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+pub fn join_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+pub fn element_to_document<F: Field>(x: &F) -> Value {
+    Value::String(x.to_u256().to_string())
+}
+
+// Accepts decimal or 0x prefixed hex strings and integer numbers, all optionally negated
+pub fn element_from_document<F: Field>(doc: &Value, path: &str) -> Result<F, Error> {
+    let invalid = || {
+        Error::UserError(format!(
+            "{}: expected a field element, got {doc}",
+            describe(path)
+        ))
+    };
+    let (negative, value) = match doc {
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(x), _) => (false, U256::from(x)),
+            (None, Some(x)) => (true, U256::from(x.unsigned_abs())),
+            _ => return Err(invalid()),
+        },
+        Value::String(s) => {
+            let s = s.trim();
+            let (negative, s) = match s.strip_prefix('-') {
+                Some(s) => (true, s),
+                None => (false, s),
+            };
+            let value = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+                Some(hex) => U256::from_str_radix(hex, 16),
+                None => U256::from_str_radix(s, 10),
+            };
+            (negative, value.map_err(|_| invalid())?)
+        }
+        _ => return Err(invalid()),
+    };
+    if value >= F::MODULUS {
+        return Err(Error::UserError(format!(
+            "{}: {doc} is not below the field modulus {}",
+            describe(path),
+            F::MODULUS
+        )));
+    }
+    let x = F::from_u256(value);
+    Ok(if negative { -x } else { x })
+}
+
+pub fn array_from_document<'a>(
+    doc: &'a Value,
+    path: &str,
+    len: Option<usize>,
+) -> Result<&'a Vec<Value>, Error> {
+    let items = match doc {
+        Value::Array(items) => items,
+        _ => {
+            return Err(Error::UserError(format!(
+                "{}: expected an array, got {}",
+                describe(path),
+                describe_value(doc)
+            )))
+        }
+    };
+    match len {
+        Some(len) if items.len() != len => Err(Error::UserError(format!(
+            "{}: expected an array of {len} elements, got {}",
+            describe(path),
+            items.len()
+        ))),
+        _ => Ok(items),
+    }
+}
+
+/// Checks that `doc` is an object with exactly the given fields.
+pub fn check_fields(doc: &Value, path: &str, names: &[&str]) -> Result<(), Error> {
+    let map = match doc {
+        Value::Object(map) => map,
+        _ => {
+            return Err(Error::UserError(format!(
+                "{}: expected an object, got {}",
+                describe(path),
+                describe_value(doc)
+            )))
+        }
+    };
+    for name in names.iter() {
+        if !map.contains_key(*name) {
+            return Err(Error::UserError(format!(
+                "{}: missing field",
+                join_path(path, name)
+            )));
+        }
+    }
+    for key in map.keys() {
+        if !names.contains(&key.as_str()) {
+            return Err(Error::UserError(format!(
+                "{}: unknown field",
+                join_path(path, key)
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Fields that hold no variables are plain serde values
+pub fn primitive_to_document<T: Serialize>(x: &T) -> Value {
+    serde_json::to_value(x).unwrap()
+}
+
+pub fn primitive_from_document<T: DeserializeOwned>(doc: &Value, path: &str) -> Result<T, Error> {
+    <T as serde::Deserialize>::deserialize(doc)
+        .map_err(|e| Error::UserError(format!("{}: {e}", describe(path))))
+}
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::convert::From;
+use std::panic::Location;
 
 use ethnum::U256;
 use tiny_keccak::Hasher;
@@ -9,13 +10,20 @@ use crate::{
         config::Config,
         ir::{
             expr::{LinComb, LinCombTerm},
-            source::{self, Constraint as SourceConstraint, Instruction as SourceInstruction},
+            source::{
+                self,
+                location::{CircuitLocations, LocationTable, SourceLocation},
+                Constraint as SourceConstraint, Instruction as SourceInstruction,
+            },
         },
         layered::Coef,
     },
     field::{Field, FieldArith},
-    hints::{self, registry::hint_key_to_id},
-    utils::function_id::get_function_id,
+    hints::{self, registry::ToHintId},
+    utils::{
+        error::{Error, HintError},
+        function_id::get_function_id,
+    },
 };
 
 use super::{
@@ -23,6 +31,10 @@ use super::{
     CircuitField,
 };
 
+mod range_check;
+
+pub(crate) use range_check::max_range_bits;
+
 #[derive(Clone)]
 pub struct Builder<C: Config> {
     instructions: Vec<SourceInstruction<C>>,
@@ -30,6 +42,14 @@ pub struct Builder<C: Config> {
     var_const_id: Vec<usize>,
     const_values: Vec<CircuitField<C>>,
     num_inputs: usize,
+    instruction_locations: Vec<Option<SourceLocation>>,
+    constraint_locations: Vec<Option<SourceLocation>>,
+    // pending assert_range calls, lowered together when the circuit is built
+    range_checks: Vec<(Variable, usize)>,
+    // hint keys that could not be turned into an id, reported when the circuit is compiled
+    hint_errors: Vec<HintError>,
+    // keys of the hints added by string key, kept with the witness solver to name them in errors
+    hint_keys: HashMap<usize, String>,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -116,18 +136,55 @@ impl<C: Config> Builder<C> {
                 num_inputs,
                 var_const_id: vec![0; num_inputs + 1],
                 const_values: vec![CircuitField::<C>::zero()],
+                instruction_locations: Vec::new(),
+                constraint_locations: Vec::new(),
+                range_checks: Vec::new(),
+                hint_errors: Vec::new(),
+                hint_keys: HashMap::new(),
             },
             (1..=num_inputs).map(|id| Variable { id }).collect(),
         )
     }
 
     pub fn build(self, outputs: &[Variable]) -> source::Circuit<C> {
-        source::Circuit {
-            instructions: self.instructions,
-            constraints: self.constraints,
-            num_inputs: self.num_inputs,
-            outputs: outputs.iter().map(|v| v.id).collect(),
-        }
+        self.build_with_locations(outputs).0
+    }
+
+    pub fn build_with_locations(
+        mut self,
+        outputs: &[Variable],
+    ) -> (source::Circuit<C>, CircuitLocations) {
+        self.lower_range_checks();
+        self.instruction_locations
+            .resize(self.instructions.len(), None);
+        self.constraint_locations
+            .resize(self.constraints.len(), None);
+        (
+            source::Circuit {
+                instructions: self.instructions,
+                constraints: self.constraints,
+                num_inputs: self.num_inputs,
+                outputs: outputs.iter().map(|v| v.id).collect(),
+            },
+            CircuitLocations {
+                instructions: self.instruction_locations,
+                constraints: self.constraint_locations,
+            },
+        )
+    }
+
+    // range checks that should be lowered by the caller of this circuit instead
+    fn take_range_checks(&mut self) -> Vec<(Variable, usize)> {
+        std::mem::take(&mut self.range_checks)
+    }
+
+    // attributes everything added since the last call to `location`
+    pub fn record_location(&mut self, location: &'static Location<'static>) {
+        let location = Some(SourceLocation::from(location));
+        self.instruction_locations
+            .resize(self.instructions.len(), location);
+        self.constraint_locations
+            .resize(self.constraints.len(), location);
     }
 
     fn convert_to_variable<T: ToVariableOrValue<CircuitField<C>>>(&mut self, value: T) -> Variable {
@@ -151,6 +208,39 @@ impl<C: Config> Builder<C> {
             id: self.var_const_id.len() - 1,
         }
     }
+
+    // 1 if x >= y; 0 otherwise, with x and y range checked to bits
+    fn geq_bounded(
+        &mut self,
+        x: impl ToVariableOrValue<CircuitField<C>>,
+        y: impl ToVariableOrValue<CircuitField<C>>,
+        bits: usize,
+    ) -> Variable {
+        if bits + 1 > range_check::max_range_bits::<C>() {
+            panic!(
+                "bounded comparisons support at most {} bits",
+                range_check::max_range_bits::<C>() - 1
+            );
+        }
+        self.assert_range(x.clone(), bits);
+        self.assert_range(y.clone(), bits);
+        let xc = self.constant_value(x.clone());
+        let yc = self.constant_value(y.clone());
+        if let (Some(xv), Some(yv)) = (xc, yc) {
+            return self.constant((xv.to_u256() >= yv.to_u256()) as u32);
+        }
+
+        // d = x - y + 2^bits lies in [1, 2^(bits + 1)), its top bit tells whether x >= y
+        let offset = CircuitField::<C>::from_u256(U256::ONE << bits as u32);
+        let diff = self.sub(x, y);
+        let d = self.add(diff, offset);
+        let geq = self.unconstrained_int_div(d, offset);
+        self.assert_is_bool(geq);
+        let high = self.mul(geq, offset);
+        let low = self.sub(d, high);
+        self.assert_range(low, bits);
+        geq
+    }
 }
 
 impl<C: Config> BasicAPI<C> for Builder<C> {
@@ -382,6 +472,75 @@ impl<C: Config> BasicAPI<C> for Builder<C> {
         (0..num_bits).map(|_| self.new_var()).collect()
     }
 
+    fn select(
+        &mut self,
+        cond: impl ToVariableOrValue<CircuitField<C>>,
+        if_true: impl ToVariableOrValue<CircuitField<C>>,
+        if_false: impl ToVariableOrValue<CircuitField<C>>,
+    ) -> Variable {
+        let if_true = self.convert_to_variable(if_true);
+        let if_false = self.convert_to_variable(if_false);
+        let cond = self.convert_to_variable(cond);
+        self.mux(&[cond], &[if_false, if_true])
+    }
+
+    fn mux(&mut self, selectors: &[Variable], values: &[Variable]) -> Variable {
+        ensure_variables_valid(selectors);
+        ensure_variables_valid(values);
+        if selectors.len() >= usize::BITS as usize || values.len() != 1 << selectors.len() {
+            panic!(
+                "mux with {} selectors needs 2^{} values, got {}",
+                selectors.len(),
+                selectors.len(),
+                values.len()
+            );
+        }
+        let mut index = Some(0);
+        for (i, s) in selectors.iter().enumerate() {
+            match self.constant_value(s) {
+                Some(sv) => {
+                    self.assert_is_bool(sv);
+                    if !sv.is_zero() {
+                        index = index.map(|x| x | 1 << i);
+                    }
+                }
+                None => index = None,
+            }
+        }
+        if let Some(index) = index {
+            return values[index];
+        }
+        self.instructions.push(SourceInstruction::Mux {
+            selectors: selectors.iter().map(|v| v.id).collect(),
+            values: values.iter().map(|v| v.id).collect(),
+        });
+        self.new_var()
+    }
+
+    fn lookup(
+        &mut self,
+        index: impl ToVariableOrValue<CircuitField<C>>,
+        values: &[Variable],
+    ) -> Variable {
+        ensure_variables_valid(values);
+        if values.is_empty() {
+            panic!("lookup needs at least 1 value");
+        }
+        if let Some(iv) = self.constant_value(index.clone()) {
+            let iv = iv.to_u256();
+            if iv >= U256::from(values.len() as u64) {
+                panic!("lookup index out of range");
+            }
+            return values[iv.as_usize()];
+        }
+        let index = self.convert_to_variable(index);
+        self.instructions.push(SourceInstruction::Lookup {
+            index: index.id,
+            values: values.iter().map(|v| v.id).collect(),
+        });
+        self.new_var()
+    }
+
     fn assert_is_zero(&mut self, x: impl ToVariableOrValue<CircuitField<C>>) {
         let xc = self.constant_value(x.clone());
         if let Some(xv) = xc {
@@ -424,6 +583,73 @@ impl<C: Config> BasicAPI<C> for Builder<C> {
         });
     }
 
+    fn assert_range(&mut self, x: impl ToVariableOrValue<CircuitField<C>>, bits: usize) {
+        if bits > range_check::max_range_bits::<C>() {
+            panic!(
+                "assert_range supports at most {} bits",
+                range_check::max_range_bits::<C>()
+            );
+        }
+        let xc = self.constant_value(x.clone());
+        if let Some(xv) = xc {
+            if xv.to_u256() >> bits as u32 != U256::ZERO {
+                panic!("assert_range failed");
+            }
+            return;
+        }
+        if bits == 0 {
+            self.assert_is_zero(x);
+            return;
+        }
+        let x = self.convert_to_variable(x);
+        self.range_checks.push((x, bits));
+    }
+
+    fn cmp_bounded(
+        &mut self,
+        x: impl ToVariableOrValue<CircuitField<C>>,
+        y: impl ToVariableOrValue<CircuitField<C>>,
+        bits: usize,
+    ) -> Variable {
+        let geq = self.geq_bounded(x.clone(), y.clone(), bits);
+        let xc = self.constant_value(x.clone());
+        let yc = self.constant_value(y.clone());
+        if let (Some(xv), Some(yv)) = (xc, yc) {
+            let res = match xv.to_u256().cmp(&yv.to_u256()) {
+                std::cmp::Ordering::Greater => CircuitField::<C>::one(),
+                std::cmp::Ordering::Equal => CircuitField::<C>::zero(),
+                std::cmp::Ordering::Less => -CircuitField::<C>::one(),
+            };
+            return self.constant(res);
+        }
+
+        // 2 * geq - 1 - eq
+        let diff = self.sub(x, y);
+        let eq = self.is_zero(diff);
+        let t = self.add(geq, geq);
+        let t = self.sub(t, eq);
+        self.sub(t, 1)
+    }
+
+    fn lt(
+        &mut self,
+        x: impl ToVariableOrValue<CircuitField<C>>,
+        y: impl ToVariableOrValue<CircuitField<C>>,
+        bits: usize,
+    ) -> Variable {
+        let geq = self.geq_bounded(x, y, bits);
+        self.sub(1, geq)
+    }
+
+    fn leq(
+        &mut self,
+        x: impl ToVariableOrValue<CircuitField<C>>,
+        y: impl ToVariableOrValue<CircuitField<C>>,
+        bits: usize,
+    ) -> Variable {
+        self.geq_bounded(y, x, bits)
+    }
+
     fn get_random_value(&mut self) -> Variable {
         self.instructions
             .push(SourceInstruction::ConstantLike(Coef::Random));
@@ -432,19 +658,42 @@ impl<C: Config> BasicAPI<C> for Builder<C> {
 
     fn new_hint(
         &mut self,
-        hint_key: &str,
+        hint: impl ToHintId,
         inputs: &[Variable],
         num_outputs: usize,
     ) -> Vec<Variable> {
         ensure_variables_valid(inputs);
+        // the instruction is kept on error so that the variable ids stay consistent
+        let hint_id = match hint.to_hint_id() {
+            Ok(id) => {
+                if let Some(key) = hint.hint_key() {
+                    self.hint_keys.insert(id, key.to_string());
+                }
+                id
+            }
+            Err(e) => {
+                let id = e.hint_id;
+                self.hint_errors.push(e);
+                id
+            }
+        };
         self.instructions.push(SourceInstruction::Hint {
-            hint_id: hint_key_to_id(hint_key),
+            hint_id,
             inputs: inputs.iter().map(|v| v.id).collect(),
             num_outputs,
         });
         (0..num_outputs).map(|_| self.new_var()).collect()
     }
 
+    fn custom_gate(&mut self, gate_type: usize, inputs: &[Variable]) -> Variable {
+        ensure_variables_valid(inputs);
+        self.instructions.push(SourceInstruction::CustomGate {
+            gate_type,
+            inputs: inputs.iter().map(|v| v.id).collect(),
+        });
+        self.new_var()
+    }
+
     fn constant(&mut self, value: impl ToVariableOrValue<CircuitField<C>>) -> Variable {
         self.convert_to_variable(value)
     }
@@ -606,19 +855,24 @@ pub struct RootBuilder<C: Config> {
     num_public_inputs: usize,
     current_builders: Vec<(usize, Builder<C>)>,
     sub_circuits: HashMap<usize, source::Circuit<C>>,
+    sub_circuit_locations: HashMap<usize, CircuitLocations>,
     sub_circuit_output_structure: HashMap<usize, Vec<usize>>,
+    // bits of the range checks each sub circuit leaves to its callers, they are passed
+    // up as extra outputs after the regular ones so the root can batch all of them
+    sub_circuit_range_checks: HashMap<usize, Vec<usize>>,
     full_hash_id: HashMap<usize, [u8; 32]>,
     outputs: Vec<Variable>,
 }
 
 macro_rules! root_binary_op {
     ($name:ident) => {
+        #[track_caller]
         fn $name(
             &mut self,
             x: impl ToVariableOrValue<CircuitField<C>>,
             y: impl ToVariableOrValue<CircuitField<C>>,
         ) -> Variable {
-            self.last_builder().$name(x, y)
+            self.with_location(|b| b.$name(x, y))
         }
     };
 }
@@ -631,84 +885,162 @@ impl<C: Config> BasicAPI<C> for RootBuilder<C> {
     root_binary_op!(or);
     root_binary_op!(and);
 
+    #[track_caller]
     fn neg(&mut self, x: impl ToVariableOrValue<CircuitField<C>>) -> Variable {
-        self.last_builder().neg(x)
+        self.with_location(|b| b.neg(x))
     }
+    #[track_caller]
     fn div(
         &mut self,
         x: impl ToVariableOrValue<CircuitField<C>>,
         y: impl ToVariableOrValue<CircuitField<C>>,
         checked: bool,
     ) -> Variable {
-        self.last_builder().div(x, y, checked)
+        self.with_location(|b| b.div(x, y, checked))
     }
 
+    #[track_caller]
     fn is_zero(&mut self, x: impl ToVariableOrValue<CircuitField<C>>) -> Variable {
-        self.last_builder().is_zero(x)
+        self.with_location(|b| b.is_zero(x))
     }
 
+    #[track_caller]
     fn to_binary(
         &mut self,
         x: impl ToVariableOrValue<CircuitField<C>>,
         num_bits: usize,
     ) -> Vec<Variable> {
-        self.last_builder().to_binary(x, num_bits)
+        self.with_location(|b| b.to_binary(x, num_bits))
     }
 
+    #[track_caller]
+    fn select(
+        &mut self,
+        cond: impl ToVariableOrValue<CircuitField<C>>,
+        if_true: impl ToVariableOrValue<CircuitField<C>>,
+        if_false: impl ToVariableOrValue<CircuitField<C>>,
+    ) -> Variable {
+        self.with_location(|b| b.select(cond, if_true, if_false))
+    }
+
+    #[track_caller]
+    fn mux(&mut self, selectors: &[Variable], values: &[Variable]) -> Variable {
+        self.with_location(|b| b.mux(selectors, values))
+    }
+
+    #[track_caller]
+    fn lookup(
+        &mut self,
+        index: impl ToVariableOrValue<CircuitField<C>>,
+        values: &[Variable],
+    ) -> Variable {
+        self.with_location(|b| b.lookup(index, values))
+    }
+
+    #[track_caller]
     fn assert_is_zero(&mut self, x: impl ToVariableOrValue<CircuitField<C>>) {
-        self.last_builder().assert_is_zero(x)
+        self.with_location(|b| b.assert_is_zero(x))
     }
 
+    #[track_caller]
     fn assert_is_non_zero(&mut self, x: impl ToVariableOrValue<CircuitField<C>>) {
-        self.last_builder().assert_is_non_zero(x)
+        self.with_location(|b| b.assert_is_non_zero(x))
     }
 
+    #[track_caller]
     fn assert_is_bool(&mut self, x: impl ToVariableOrValue<CircuitField<C>>) {
-        self.last_builder().assert_is_bool(x)
+        self.with_location(|b| b.assert_is_bool(x))
     }
 
+    #[track_caller]
+    fn assert_range(&mut self, x: impl ToVariableOrValue<CircuitField<C>>, bits: usize) {
+        self.with_location(|b| b.assert_range(x, bits))
+    }
+
+    #[track_caller]
+    fn cmp_bounded(
+        &mut self,
+        x: impl ToVariableOrValue<CircuitField<C>>,
+        y: impl ToVariableOrValue<CircuitField<C>>,
+        bits: usize,
+    ) -> Variable {
+        self.with_location(|b| b.cmp_bounded(x, y, bits))
+    }
+
+    #[track_caller]
+    fn lt(
+        &mut self,
+        x: impl ToVariableOrValue<CircuitField<C>>,
+        y: impl ToVariableOrValue<CircuitField<C>>,
+        bits: usize,
+    ) -> Variable {
+        self.with_location(|b| b.lt(x, y, bits))
+    }
+
+    #[track_caller]
+    fn leq(
+        &mut self,
+        x: impl ToVariableOrValue<CircuitField<C>>,
+        y: impl ToVariableOrValue<CircuitField<C>>,
+        bits: usize,
+    ) -> Variable {
+        self.with_location(|b| b.leq(x, y, bits))
+    }
+
+    #[track_caller]
     fn get_random_value(&mut self) -> Variable {
-        self.last_builder().get_random_value()
+        self.with_location(|b| b.get_random_value())
     }
 
+    #[track_caller]
     fn new_hint(
         &mut self,
-        hint_key: &str,
+        hint: impl ToHintId,
         inputs: &[Variable],
         num_outputs: usize,
     ) -> Vec<Variable> {
-        self.last_builder().new_hint(hint_key, inputs, num_outputs)
+        self.with_location(|b| b.new_hint(hint, inputs, num_outputs))
+    }
+
+    #[track_caller]
+    fn custom_gate(&mut self, gate_type: usize, inputs: &[Variable]) -> Variable {
+        self.with_location(|b| b.custom_gate(gate_type, inputs))
     }
 
+    #[track_caller]
     fn constant(&mut self, x: impl ToVariableOrValue<CircuitField<C>>) -> Variable {
-        self.last_builder().constant(x)
+        self.with_location(|b| b.constant(x))
     }
 
+    #[track_caller]
     fn constant_value(
         &mut self,
         x: impl ToVariableOrValue<CircuitField<C>>,
     ) -> Option<CircuitField<C>> {
-        self.last_builder().constant_value(x)
+        self.with_location(|b| b.constant_value(x))
     }
 
+    #[track_caller]
     fn gt(
         &mut self,
         x: impl ToVariableOrValue<CircuitField<C>>,
         y: impl ToVariableOrValue<CircuitField<C>>,
     ) -> Variable {
-        self.last_builder().gt(x, y)
+        self.with_location(|b| b.gt(x, y))
     }
 
+    #[track_caller]
     fn geq(
         &mut self,
         x: impl ToVariableOrValue<CircuitField<C>>,
         y: impl ToVariableOrValue<CircuitField<C>>,
     ) -> Variable {
-        self.last_builder().geq(x, y)
+        self.with_location(|b| b.geq(x, y))
     }
 }
 
 impl<C: Config> RootAPI<C> for RootBuilder<C> {
+    #[track_caller]
     fn memorized_simple_call<F: Fn(&mut Self, &Vec<Variable>) -> Vec<Variable> + 'static>(
         &mut self,
         f: F,
@@ -739,17 +1071,19 @@ impl<C: Config> RootAPI<C> for RootBuilder<C> {
         circuit_id
     }
 
+    #[track_caller]
     fn call_sub_circuit<F: FnOnce(&mut Self, &Vec<Variable>) -> Vec<Variable>>(
         &mut self,
         circuit_id: usize,
         inputs: &[Variable],
         f: F,
     ) -> Vec<Variable> {
+        let location = Location::caller();
         if !self.sub_circuits.contains_key(&circuit_id) {
             self.actually_call_sub_circuit(circuit_id, inputs.len(), f);
         }
         let sub = self.sub_circuits.get(&circuit_id).unwrap();
-        let outputs: Vec<Variable> = (0..sub.outputs.len())
+        let mut outputs: Vec<Variable> = (0..sub.outputs.len())
             .map(|_| self.last_builder().new_var())
             .collect();
         self.last_builder()
@@ -759,6 +1093,11 @@ impl<C: Config> RootAPI<C> for RootBuilder<C> {
                 inputs: inputs.iter().map(|v| v.id).collect(),
                 num_outputs: outputs.len(),
             });
+        self.last_builder().record_location(location);
+        let range_bits = &self.sub_circuit_range_checks[&circuit_id];
+        let checked = outputs.split_off(outputs.len() - range_bits.len());
+        let checks: Vec<(Variable, usize)> = checked.into_iter().zip(range_bits.clone()).collect();
+        self.last_builder().range_checks.extend(checks);
         outputs
     }
 
@@ -796,14 +1135,17 @@ impl<C: Config> RootBuilder<C> {
             builder0
                 .instructions
                 .push(SourceInstruction::ConstantLike(Coef::PublicInput(i)));
+            builder0.instruction_locations.push(None);
         }
         (
             RootBuilder {
                 num_public_inputs,
                 current_builders: vec![(0, builder0)],
                 sub_circuits: HashMap::new(),
+                sub_circuit_locations: HashMap::new(),
                 full_hash_id: HashMap::new(),
                 sub_circuit_output_structure: HashMap::new(),
+                sub_circuit_range_checks: HashMap::new(),
                 outputs: Vec::new(),
             },
             inputs,
@@ -812,22 +1154,65 @@ impl<C: Config> RootBuilder<C> {
     }
 
     pub fn build(self) -> source::RootCircuit<C> {
+        self.build_with_locations().0
+    }
+
+    // also returns where each instruction and constraint was added in the circuit code
+    pub fn build_with_locations(self) -> (source::RootCircuit<C>, LocationTable) {
         let mut circuits = self.sub_circuits;
+        let mut locations = self.sub_circuit_locations;
         assert_eq!(self.current_builders.len(), 1);
         for (circuit_id, builder) in self.current_builders {
-            circuits.insert(circuit_id, builder.build(&self.outputs));
+            let (circuit, circuit_locations) = builder.build_with_locations(&self.outputs);
+            circuits.insert(circuit_id, circuit);
+            locations.insert(circuit_id, circuit_locations);
         }
-        source::RootCircuit {
-            circuits,
-            num_public_inputs: self.num_public_inputs,
-            expected_num_output_zeroes: 0,
+        (
+            source::RootCircuit {
+                circuits,
+                num_public_inputs: self.num_public_inputs,
+                expected_num_output_zeroes: 0,
+            },
+            LocationTable {
+                circuits: locations,
+            },
+        )
+    }
+
+    // Fails with the first hint key rejected while building, e.g. one colliding with a
+    // builtin hint id
+    pub fn check_hints(&self) -> Result<(), Error> {
+        let mut errors = self
+            .current_builders
+            .iter()
+            .flat_map(|(_, b)| &b.hint_errors);
+        match errors.next() {
+            Some(e) => Err(Error::HintError(e.clone())),
+            None => Ok(()),
         }
     }
 
+    // Keys of all hints added by string key so far
+    pub fn hint_keys(&self) -> HashMap<usize, String> {
+        self.current_builders
+            .iter()
+            .flat_map(|(_, b)| b.hint_keys.clone())
+            .collect()
+    }
+
     pub fn last_builder(&mut self) -> &mut Builder<C> {
         &mut self.current_builders.last_mut().unwrap().1
     }
 
+    #[track_caller]
+    fn with_location<T>(&mut self, f: impl FnOnce(&mut Builder<C>) -> T) -> T {
+        let location = Location::caller();
+        let builder = self.last_builder();
+        let res = f(&mut *builder);
+        builder.record_location(location);
+        res
+    }
+
     fn actually_call_sub_circuit<F: FnOnce(&mut Self, &Vec<Variable>) -> Vec<Variable>>(
         &mut self,
         circuit_id: usize,
@@ -836,16 +1221,28 @@ impl<C: Config> RootBuilder<C> {
     ) {
         let (sub_builder, sub_inputs) = Builder::new(n);
         self.current_builders.push((circuit_id, sub_builder));
-        let sub_outputs = f(self, &sub_inputs);
-        let (_, sub_builder) = self.current_builders.pop().unwrap();
-        let sub = sub_builder.build(&sub_outputs);
+        let mut sub_outputs = f(self, &sub_inputs);
+        let (_, mut sub_builder) = self.current_builders.pop().unwrap();
+        let range_checks = sub_builder.take_range_checks();
+        sub_outputs.extend(range_checks.iter().map(|(x, _)| *x));
+        self.sub_circuit_range_checks.insert(
+            circuit_id,
+            range_checks.iter().map(|(_, bits)| *bits).collect(),
+        );
+        let hint_errors = std::mem::take(&mut sub_builder.hint_errors);
+        self.last_builder().hint_errors.extend(hint_errors);
+        let hint_keys = std::mem::take(&mut sub_builder.hint_keys);
+        self.last_builder().hint_keys.extend(hint_keys);
+        let (sub, sub_locations) = sub_builder.build_with_locations(&sub_outputs);
         self.sub_circuits.insert(circuit_id, sub);
+        self.sub_circuit_locations.insert(circuit_id, sub_locations);
     }
 }
 
 impl<C: Config> UnconstrainedAPI<C> for RootBuilder<C> {
+    #[track_caller]
     fn unconstrained_identity(&mut self, x: impl ToVariableOrValue<CircuitField<C>>) -> Variable {
-        self.last_builder().unconstrained_identity(x)
+        self.with_location(|b| b.unconstrained_identity(x))
     }
     root_binary_op!(unconstrained_add);
     root_binary_op!(unconstrained_mul);
@@ -0,0 +1,145 @@
+use ethnum::U256;
+
+use crate::{
+    circuit::{config::Config, ir::source::Instruction as SourceInstruction},
+    field::FieldArith,
+    hints::BuiltinHintIds,
+};
+
+use super::{
+    super::{
+        api::{BasicAPI, UnconstrainedAPI},
+        CircuitField,
+    },
+    Builder, Variable,
+};
+
+// The table never gets larger than 2^MAX_TABLE_BITS entries
+const MAX_TABLE_BITS: usize = 16;
+
+// Largest number of bits a range check may have, so that 2^bits doesn't wrap around the field
+pub fn max_range_bits<C: Config>() -> usize {
+    (256 - CircuitField::<C>::MODULUS.leading_zeros() as usize) - 1
+}
+
+// Number of lookups needed to check every range with a table of 2^k entries,
+// a chunk narrower than the table is looked up twice, once shifted to the top
+fn num_queries(checks: &[(Variable, usize)], k: usize) -> usize {
+    checks
+        .iter()
+        .map(|(_, bits)| bits.div_ceil(k) + (bits % k != 0) as usize)
+        .sum()
+}
+
+// Rough gate counts of both strategies: a fraction costs about 3 multiplications in the
+// LogUp sums, a bit costs a boolean constraint and a term in the recomposition
+fn choose_table_bits(checks: &[(Variable, usize)]) -> Option<usize> {
+    let bits_cost: usize = checks.iter().map(|(_, bits)| 2 * bits).sum();
+    let (k, logup_cost) = (1..=MAX_TABLE_BITS)
+        .map(|k| (k, 3 * ((1 << k) + num_queries(checks, k))))
+        .min_by_key(|(_, cost)| *cost)
+        .unwrap();
+    if logup_cost < bits_cost {
+        Some(k)
+    } else {
+        None
+    }
+}
+
+type Rational = (Variable, Variable);
+
+impl<C: Config> Builder<C> {
+    // Lowers all range checks collected in this circuit, either by bit decomposition
+    // or with a single LogUp argument over the table [0, 2^k)
+    pub(super) fn lower_range_checks(&mut self) {
+        let checks = std::mem::take(&mut self.range_checks);
+        if checks.is_empty() {
+            return;
+        }
+        match choose_table_bits(&checks) {
+            Some(k) => self.logup_range_checks(&checks, k),
+            None => {
+                for (x, bits) in checks {
+                    self.to_binary(x, bits);
+                }
+            }
+        }
+    }
+
+    fn logup_range_checks(&mut self, checks: &[(Variable, usize)], k: usize) {
+        let mut queries = Vec::with_capacity(num_queries(checks, k));
+        for &(x, bits) in checks.iter() {
+            let num_chunks = bits.div_ceil(k);
+            let chunks = if num_chunks == 1 {
+                vec![x]
+            } else {
+                let chunks: Vec<Variable> = (0..num_chunks)
+                    .map(|i| {
+                        let shift = CircuitField::<C>::from_u256(U256::ONE << (i * k) as u32);
+                        let t = self.unconstrained_int_div(x, shift);
+                        self.unconstrained_mod(t, 1u32 << k)
+                    })
+                    .collect();
+                let mut sum = self.constant(0);
+                for (i, chunk) in chunks.iter().enumerate() {
+                    let coef = CircuitField::<C>::from_u256(U256::ONE << (i * k) as u32);
+                    let t = self.mul(chunk, coef);
+                    sum = self.add(sum, t);
+                }
+                self.assert_is_equal(sum, x);
+                chunks
+            };
+            queries.extend(chunks.iter().cloned());
+            // top < 2^k and top * 2^(k - top_bits) < 2^k imply top < 2^top_bits
+            let top_bits = bits - (num_chunks - 1) * k;
+            if top_bits < k {
+                let shifted = self.mul(chunks[num_chunks - 1], 1u32 << (k - top_bits));
+                queries.push(shifted);
+            }
+        }
+
+        let table_size = 1 << k;
+        self.instructions.push(SourceInstruction::Hint {
+            hint_id: BuiltinHintIds::RangeCount as u64 as usize,
+            inputs: queries.iter().map(|v| v.id).collect(),
+            num_outputs: table_size,
+        });
+        let counts: Vec<Variable> = (0..table_size).map(|_| self.new_var()).collect();
+
+        // sum 1 / (alpha - q) over queries == sum count_v / (alpha - v) over the table
+        let alpha = self.get_random_value();
+        let one = self.constant(1);
+        let query_terms: Vec<Rational> =
+            queries.iter().map(|q| (one, self.sub(alpha, q))).collect();
+        let table_terms: Vec<Rational> = counts
+            .iter()
+            .enumerate()
+            .map(|(v, count)| (*count, self.sub(alpha, v as u32)))
+            .collect();
+        let (qn, qd) = self.sum_rationals(query_terms);
+        let (tn, td) = self.sum_rationals(table_terms);
+        let lhs = self.mul(qn, td);
+        let rhs = self.mul(tn, qd);
+        self.assert_is_equal(lhs, rhs);
+    }
+
+    // Adds the fractions pairwise, so the depth is logarithmic in their number
+    fn sum_rationals(&mut self, mut terms: Vec<Rational>) -> Rational {
+        while terms.len() > 1 {
+            let mut next = Vec::with_capacity(terms.len().div_ceil(2));
+            for pair in terms.chunks(2) {
+                if let [(an, ad), (bn, bd)] = pair {
+                    let t1 = self.mul(an, bd);
+                    let t2 = self.mul(bn, ad);
+                    let n = self.add(t1, t2);
+                    let d = self.mul(ad, bd);
+                    next.push((n, d));
+                } else {
+                    next.push(pair[0]);
+                }
+            }
+            terms = next;
+        }
+        terms[0]
+    }
+}
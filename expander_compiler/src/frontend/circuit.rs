@@ -112,6 +112,79 @@ macro_rules! declare_circuit_default {
     };
 }
 
+#[macro_export]
+macro_rules! declare_circuit_to_document {
+    ($field_value:expr, @type Variable) => {
+        $crate::frontend::internal::element_to_document(&$field_value)
+    };
+
+    ($field_value:expr, @type PublicVariable) => {
+        $crate::frontend::internal::element_to_document(&$field_value)
+    };
+
+    ($field_value:expr, @type [$elem:tt; $n:expr]) => {
+        $crate::frontend::internal::serde_json::Value::Array(
+            $field_value
+                .iter()
+                .map(|_x| $crate::frontend::internal::declare_circuit_to_document!(*_x, @type $elem))
+                .collect(),
+        )
+    };
+
+    ($field_value:expr, @type [$elem:tt]) => {
+        $crate::frontend::internal::serde_json::Value::Array(
+            $field_value
+                .iter()
+                .map(|_x| $crate::frontend::internal::declare_circuit_to_document!(*_x, @type $elem))
+                .collect(),
+        )
+    };
+
+    ($field_value:expr, @type $other:ty) => {
+        $crate::frontend::internal::primitive_to_document(&$field_value)
+    };
+}
+
+#[macro_export]
+macro_rules! declare_circuit_from_document {
+    ($doc:expr, $path:expr, @type Variable) => {
+        $crate::frontend::internal::element_from_document($doc, $path)?
+    };
+
+    ($doc:expr, $path:expr, @type PublicVariable) => {
+        $crate::frontend::internal::element_from_document($doc, $path)?
+    };
+
+    ($doc:expr, $path:expr, @type [$elem:tt; $n:expr]) => {{
+        let _items = $crate::frontend::internal::array_from_document($doc, $path, Some($n))?;
+        let mut _res: Vec<$crate::frontend::internal::declare_circuit_field_type!(@type $elem)> =
+            Vec::with_capacity($n);
+        for (_i, _item) in _items.iter().enumerate() {
+            let _p = format!("{}[{}]", $path, _i);
+            _res.push($crate::frontend::internal::declare_circuit_from_document!(_item, &_p, @type $elem));
+        }
+        match <[_; $n]>::try_from(_res) {
+            Ok(_x) => _x,
+            Err(_) => unreachable!(),
+        }
+    }};
+
+    ($doc:expr, $path:expr, @type [$elem:tt]) => {{
+        let _items = $crate::frontend::internal::array_from_document($doc, $path, None)?;
+        let mut _res: Vec<$crate::frontend::internal::declare_circuit_field_type!(@type $elem)> =
+            Vec::with_capacity(_items.len());
+        for (_i, _item) in _items.iter().enumerate() {
+            let _p = format!("{}[{}]", $path, _i);
+            _res.push($crate::frontend::internal::declare_circuit_from_document!(_item, &_p, @type $elem));
+        }
+        _res
+    }};
+
+    ($doc:expr, $path:expr, @type $other:ty) => {
+        $crate::frontend::internal::primitive_from_document($doc, $path)?
+    };
+}
+
 #[macro_export]
 macro_rules! declare_circuit {
     ($struct_name:ident { $($field_name:ident : $field_type:tt),* $(,)? }) => {
@@ -137,6 +210,29 @@ macro_rules! declare_circuit {
             }
         }
 
+        impl<T: $crate::field::Field> $crate::frontend::SerdeAssignment for $struct_name<T> {
+            fn to_document(&self) -> $crate::frontend::internal::serde_json::Value {
+                let mut _map = $crate::frontend::internal::serde_json::Map::new();
+                $(_map.insert(
+                    stringify!($field_name).to_string(),
+                    $crate::frontend::internal::declare_circuit_to_document!(self.$field_name, @type $field_type),
+                );)*
+                $crate::frontend::internal::serde_json::Value::Object(_map)
+            }
+            fn from_document(
+                doc: &$crate::frontend::internal::serde_json::Value,
+                path: &str,
+            ) -> Result<Self, $crate::frontend::Error> {
+                $crate::frontend::internal::check_fields(doc, path, &[$(stringify!($field_name)),*])?;
+                Ok(Self {
+                    $($field_name: {
+                        let _p = $crate::frontend::internal::join_path(path, stringify!($field_name));
+                        $crate::frontend::internal::declare_circuit_from_document!(&doc[stringify!($field_name)], &_p, @type $field_type)
+                    }),*
+                })
+            }
+        }
+
         impl<T: Clone> Clone for $struct_name<T> {
             fn clone(&self) -> Self {
                 Self {
@@ -159,8 +255,10 @@ pub use declare_circuit;
 pub use declare_circuit_default;
 pub use declare_circuit_dump_into;
 pub use declare_circuit_field_type;
+pub use declare_circuit_from_document;
 pub use declare_circuit_load_from;
 pub use declare_circuit_num_vars;
+pub use declare_circuit_to_document;
 
 use crate::circuit::config::Config;
 
@@ -1,8 +1,11 @@
 use std::collections::HashMap;
 
+use ethnum::U256;
+
 use crate::{
     circuit::{
         config::Config,
+        custom_gate::CustomGateRegistry,
         ir::{
             common::{EvalResult, Instruction},
             source::{BoolBinOpType, Instruction as IrInstruction, UnconstrainedBinOpType},
@@ -11,8 +14,9 @@ use crate::{
     field::FieldArith,
     hints::{
         self,
-        registry::{hint_key_to_id, HintCaller},
+        registry::{HintCaller, ToHintId},
     },
+    utils::error::Error,
 };
 
 use super::{
@@ -133,6 +137,41 @@ impl<C: Config, H: HintCaller<CircuitField<C>>> BasicAPI<C> for DebugBuilder<C,
             .map(|v| self.return_as_variable(v))
             .collect()
     }
+    fn select(
+        &mut self,
+        cond: impl ToVariableOrValue<CircuitField<C>>,
+        if_true: impl ToVariableOrValue<CircuitField<C>>,
+        if_false: impl ToVariableOrValue<CircuitField<C>>,
+    ) -> Variable {
+        let cond = self.convert_to_id(cond);
+        let if_true = self.convert_to_id(if_true);
+        let if_false = self.convert_to_id(if_false);
+        self.eval_ir_insn(IrInstruction::Mux {
+            selectors: vec![cond],
+            values: vec![if_false, if_true],
+        })
+    }
+    fn mux(&mut self, selectors: &[Variable], values: &[Variable]) -> Variable {
+        ensure_variables_valid(selectors);
+        ensure_variables_valid(values);
+        assert_eq!(values.len(), 1 << selectors.len());
+        self.eval_ir_insn(IrInstruction::Mux {
+            selectors: selectors.iter().map(|v| get_variable_id(*v)).collect(),
+            values: values.iter().map(|v| get_variable_id(*v)).collect(),
+        })
+    }
+    fn lookup(
+        &mut self,
+        index: impl ToVariableOrValue<CircuitField<C>>,
+        values: &[Variable],
+    ) -> Variable {
+        ensure_variables_valid(values);
+        let index = self.convert_to_id(index);
+        self.eval_ir_insn(IrInstruction::Lookup {
+            index,
+            values: values.iter().map(|v| get_variable_id(*v)).collect(),
+        })
+    }
     fn assert_is_zero(&mut self, x: impl ToVariableOrValue<CircuitField<C>>) {
         let x = self.convert_to_value(x);
         assert!(x.is_zero());
@@ -145,23 +184,70 @@ impl<C: Config, H: HintCaller<CircuitField<C>>> BasicAPI<C> for DebugBuilder<C,
         let x = self.convert_to_value(x);
         assert!(x.is_zero() || x == CircuitField::<C>::one());
     }
+    fn assert_range(&mut self, x: impl ToVariableOrValue<CircuitField<C>>, bits: usize) {
+        let x = self.convert_to_value(x);
+        assert!(x.to_u256() >> bits as u32 == U256::ZERO);
+    }
+    fn cmp_bounded(
+        &mut self,
+        x: impl ToVariableOrValue<CircuitField<C>>,
+        y: impl ToVariableOrValue<CircuitField<C>>,
+        bits: usize,
+    ) -> Variable {
+        let x = self.convert_to_value(x);
+        let y = self.convert_to_value(y);
+        self.assert_range(x, bits);
+        self.assert_range(y, bits);
+        let res = match x.to_u256().cmp(&y.to_u256()) {
+            std::cmp::Ordering::Greater => CircuitField::<C>::one(),
+            std::cmp::Ordering::Equal => CircuitField::<C>::zero(),
+            std::cmp::Ordering::Less => -CircuitField::<C>::one(),
+        };
+        self.return_as_variable(res)
+    }
+    fn lt(
+        &mut self,
+        x: impl ToVariableOrValue<CircuitField<C>>,
+        y: impl ToVariableOrValue<CircuitField<C>>,
+        bits: usize,
+    ) -> Variable {
+        let x = self.convert_to_value(x);
+        let y = self.convert_to_value(y);
+        self.assert_range(x, bits);
+        self.assert_range(y, bits);
+        self.return_as_variable(CircuitField::<C>::from((x.to_u256() < y.to_u256()) as u32))
+    }
+    fn leq(
+        &mut self,
+        x: impl ToVariableOrValue<CircuitField<C>>,
+        y: impl ToVariableOrValue<CircuitField<C>>,
+        bits: usize,
+    ) -> Variable {
+        let x = self.convert_to_value(x);
+        let y = self.convert_to_value(y);
+        self.assert_range(x, bits);
+        self.assert_range(y, bits);
+        self.return_as_variable(CircuitField::<C>::from((x.to_u256() <= y.to_u256()) as u32))
+    }
     fn get_random_value(&mut self) -> Variable {
         let v = CircuitField::<C>::random_unsafe(&mut rand::thread_rng());
         self.return_as_variable(v)
     }
     fn new_hint(
         &mut self,
-        hint_key: &str,
+        hint: impl ToHintId,
         inputs: &[Variable],
         num_outputs: usize,
     ) -> Vec<Variable> {
         ensure_variables_valid(inputs);
         let inputs: Vec<CircuitField<C>> =
             inputs.iter().map(|v| self.convert_to_value(v)).collect();
-        match self
-            .hint_caller
-            .call(hint_key_to_id(hint_key), &inputs, num_outputs)
-        {
+        let outputs = hint
+            .to_hint_id()
+            .map_err(Error::HintError)
+            .and_then(|id| self.hint_caller.call(id, &inputs, num_outputs))
+            .map_err(|e| e.with_hint_key(hint.hint_key()));
+        match outputs {
             Ok(outputs) => outputs
                 .into_iter()
                 .map(|v| self.return_as_variable(v))
@@ -169,6 +255,15 @@ impl<C: Config, H: HintCaller<CircuitField<C>>> BasicAPI<C> for DebugBuilder<C,
             Err(e) => panic!("Hint error: {e:?}"),
         }
     }
+    fn custom_gate(&mut self, gate_type: usize, inputs: &[Variable]) -> Variable {
+        ensure_variables_valid(inputs);
+        let inputs: Vec<CircuitField<C>> =
+            inputs.iter().map(|v| self.convert_to_value(v)).collect();
+        match CustomGateRegistry::eval(gate_type, &inputs) {
+            Ok(output) => self.return_as_variable(output),
+            Err(e) => panic!("Custom gate error: {e:?}"),
+        }
+    }
     fn constant(&mut self, x: impl ToVariableOrValue<CircuitField<C>>) -> Variable {
         let x = self.convert_to_value(x);
         self.return_as_variable(x)
@@ -1,34 +1,48 @@
+use std::collections::HashMap;
+
 use builder::RootBuilder;
 
+use crate::circuit::ir::source::location::LocationTable;
 use crate::circuit::layered::{CrossLayerInputType, NormalInputType};
 use crate::circuit::{ir, layered};
 
 pub mod api;
+pub mod assignment;
 pub mod builder;
 pub mod circuit;
 pub mod debug;
 pub mod sub_circuit;
+pub mod types;
 pub mod variables;
 pub mod witness;
 
 pub use circuit::declare_circuit;
 pub type API<C> = builder::RootBuilder<C>;
 pub use crate::circuit::config::*;
-pub use crate::compile::CompileOptions;
+pub use crate::circuit::custom_gate::{CustomGate, CustomGateRegistry};
+pub use crate::compile::{cache::ArtifactCache, report::CompileReport, CompileOptions};
 pub use crate::field::{BN254Fr, Field, FieldArith, GF2, M31};
-pub use crate::hints::registry::{EmptyHintCaller, HintCaller, HintRegistry};
+pub use crate::hints::registry::{EmptyHintCaller, HintCaller, HintHandle, HintRegistry, ToHintId};
 pub use crate::utils::error::Error;
 pub use api::{BasicAPI, RootAPI};
+pub use assignment::SerdeAssignment;
 pub use builder::Variable;
 pub use circuit::Define;
-pub use macros::memorized;
+pub use macros::{memorized, Circuit};
+pub use types::{Bool, Bytes, UInt};
 pub use witness::WitnessSolver;
 
 pub mod internal {
     pub use super::circuit::{
         declare_circuit_default, declare_circuit_dump_into, declare_circuit_field_type,
-        declare_circuit_load_from, declare_circuit_num_vars,
+        declare_circuit_from_document, declare_circuit_load_from, declare_circuit_num_vars,
+        declare_circuit_to_document,
+    };
+    pub use super::assignment::{
+        array_from_document, check_fields, element_from_document, element_to_document, join_path,
+        primitive_from_document, primitive_to_document,
     };
+    pub use serde_json;
     pub use super::variables::{DumpLoadTwoVariables, DumpLoadVariables};
     // pub use crate::utils::serde::Serde;
 }
@@ -36,11 +50,24 @@ pub mod internal {
 pub mod extra {
 
     pub use super::api::UnconstrainedAPI;
+    pub use super::assignment::{
+        assignment_from_deserializer, assignment_from_json, assignment_to_json,
+        assignment_to_serializer,
+    };
     pub use super::debug::DebugBuilder;
     pub use super::sub_circuit::{
         HashStructureAndPrimitive, JoinVecVariables, RebuildVecVariables,
     };
-    pub use crate::hints::registry::{EmptyHintCaller, HintCaller, HintRegistry};
+    pub use crate::circuit::custom_gate::{
+        Pow1Gate, Pow5Gate, EXPANDER_POW1_GATE_TYPE, EXPANDER_POW5_GATE_TYPE,
+        MAX_CUSTOM_GATE_DEGREE,
+    };
+    pub use crate::circuit::ir::common::origin::Origin;
+    pub use crate::circuit::ir::source::location::SourceLocation;
+    pub use crate::circuit::layered::explain::ConstraintViolation;
+    pub use crate::circuit::layered::source_map::{CallFrame, ConstraintFailure, SourceMap};
+    pub use crate::hints::registry::{EmptyHintCaller, HintCaller, HintHandle, HintRegistry};
+    pub use crate::utils::error::{CircuitLocation, HintError, HintErrorKind};
     // pub use crate::utils::serde::Serde;
 
     use super::{internal, CircuitField, Config, Define, Variable};
@@ -79,16 +106,19 @@ mod tests;
 pub struct CompileResult<C: Config> {
     pub witness_solver: WitnessSolver<C>,
     pub layered_circuit: layered::Circuit<C, NormalInputType>,
+    pub report: CompileReport,
 }
 
 pub struct CompileResultCrossLayer<C: Config> {
     pub witness_solver: WitnessSolver<C>,
     pub layered_circuit: layered::Circuit<C, CrossLayerInputType>,
+    pub report: CompileReport,
 }
 
+// Also returns the keys of the hints added by string key
 fn build<C: Config, Cir: internal::DumpLoadTwoVariables<Variable> + Define<C> + Clone>(
     circuit: &Cir,
-) -> ir::source::RootCircuit<C> {
+) -> Result<(ir::source::RootCircuit<C>, LocationTable, HashMap<usize, String>), Error> {
     let (num_inputs, num_public_inputs) = circuit.num_vars();
     let (mut root_builder, input_variables, public_input_variables) =
         RootBuilder::<C>::new(num_inputs, num_public_inputs);
@@ -97,18 +127,26 @@ fn build<C: Config, Cir: internal::DumpLoadTwoVariables<Variable> + Define<C> +
     let mut public_vars_ptr = public_input_variables.as_slice();
     circuit.load_from(&mut vars_ptr, &mut public_vars_ptr);
     circuit.define(&mut root_builder);
-    root_builder.build()
+    root_builder.check_hints()?;
+    let hint_keys = root_builder.hint_keys();
+    let (root, locations) = root_builder.build_with_locations();
+    Ok((root, locations, hint_keys))
 }
 
 pub fn compile<C: Config, Cir: internal::DumpLoadTwoVariables<Variable> + Define<C> + Clone>(
     circuit: &Cir,
     options: CompileOptions,
 ) -> Result<CompileResult<C>, Error> {
-    let root = build(circuit);
-    let (irw, lc) = crate::compile::compile_with_options::<C, _>(&root, options)?;
+    let (root, locations, hint_keys) = build(circuit)?;
+    let (irw, lc, report) =
+        crate::compile::compile_with_locations::<C, _>(&root, &locations, options)?;
     Ok(CompileResult {
-        witness_solver: WitnessSolver { circuit: irw },
+        witness_solver: WitnessSolver {
+            circuit: irw,
+            hint_keys,
+        },
         layered_circuit: lc,
+        report,
     })
 }
 
@@ -119,10 +157,15 @@ pub fn compile_cross_layer<
     circuit: &Cir,
     options: CompileOptions,
 ) -> Result<CompileResultCrossLayer<C>, Error> {
-    let root = build(circuit);
-    let (irw, lc) = crate::compile::compile_with_options::<C, _>(&root, options)?;
+    let (root, locations, hint_keys) = build(circuit)?;
+    let (irw, lc, report) =
+        crate::compile::compile_with_locations::<C, _>(&root, &locations, options)?;
     Ok(CompileResultCrossLayer {
-        witness_solver: WitnessSolver { circuit: irw },
+        witness_solver: WitnessSolver {
+            circuit: irw,
+            hint_keys,
+        },
         layered_circuit: lc,
+        report,
     })
 }
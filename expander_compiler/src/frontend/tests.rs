@@ -2,10 +2,12 @@ use crate::frontend::M31Config as C;
 use crate::{
     compile::CompileOptions,
     field::{FieldArith, M31},
-    frontend::{compile, RootAPI},
+    frontend::{compile, Circuit, Error, RootAPI},
 };
 
-use super::{builder::Variable, circuit::*, variables::DumpLoadTwoVariables};
+use super::{
+    assignment::SerdeAssignment, builder::Variable, circuit::*, variables::DumpLoadTwoVariables,
+};
 
 declare_circuit!(Circuit1 {
     a: Variable,
@@ -88,3 +90,68 @@ fn test_circuit_eval_simple() {
     let output = compile_result.layered_circuit.run(&witness);
     assert_eq!(output, vec![false]);
 }
+
+#[derive(Circuit, Clone, Default, Debug, PartialEq)]
+struct Point<T> {
+    x: T,
+    y: T,
+}
+
+#[derive(Circuit, Clone, Debug, PartialEq)]
+struct Circuit3<T, const N: usize> {
+    a: [Point<T>; N],
+    #[public]
+    b: Point<T>,
+    c: (T, [T; 2]),
+    d: Vec<Vec<T>>,
+    #[public]
+    e: Vec<T>,
+    f: u64,
+}
+
+#[test]
+fn test_circuit_derive() {
+    use crate::field::M31 as F;
+    let p = |x: u32| Point {
+        x: F::from(x),
+        y: F::from(x + 1),
+    };
+    let c = Circuit3::<F, 3> {
+        a: [p(1), p(3), p(5)],
+        b: p(7),
+        c: (F::from(9), [F::from(10), F::from(11)]),
+        d: vec![vec![F::from(12)], vec![F::from(13), F::from(14)]],
+        e: vec![F::from(15)],
+        f: 16,
+    };
+    assert_eq!(c.num_vars(), (6 + 3 + 3, 2 + 1));
+    let mut vars = vec![];
+    let mut public_vars = vec![];
+    c.dump_into(&mut vars, &mut public_vars);
+    assert_eq!((vars.len(), public_vars.len()), c.num_vars());
+    assert_eq!(public_vars, vec![F::from(7), F::from(8), F::from(15)]);
+    let mut c2 = Circuit3::<F, 3> {
+        a: Default::default(),
+        b: Default::default(),
+        c: Default::default(),
+        d: vec![vec![F::zero()], vec![F::zero(); 2]],
+        e: vec![F::zero()],
+        f: 16,
+    };
+    let vars_ref = &mut vars.as_slice();
+    let public_vars_ref = &mut public_vars.as_slice();
+    c2.load_from(vars_ref, public_vars_ref);
+    assert_eq!(vars_ref.len(), 0);
+    assert_eq!(public_vars_ref.len(), 0);
+    assert_eq!(c, c2);
+
+    let doc = c.to_document();
+    assert_eq!(doc["c"][1][0], "10");
+    assert_eq!(Circuit3::<F, 3>::from_document(&doc, "").unwrap(), c);
+    let mut doc = doc;
+    doc["a"][2] = serde_json::json!({ "x": 1 });
+    assert_eq!(
+        Circuit3::<F, 3>::from_document(&doc, "").unwrap_err(),
+        Error::UserError("a[2].y: missing field".to_string())
+    );
+}
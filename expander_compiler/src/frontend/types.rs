@@ -0,0 +1,303 @@
+use crate::circuit::config::Config;
+
+use super::{api::BasicAPI, builder::max_range_bits, Variable};
+
+/// A variable constrained to be 0 or 1.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Bool(Variable);
+
+impl Bool {
+    /// Constrains `x` to be boolean.
+    pub fn new<C: Config, B: BasicAPI<C>>(api: &mut B, x: Variable) -> Self {
+        api.assert_is_bool(x);
+        Bool(x)
+    }
+
+    /// Wraps `x` without any constraint, the caller ensures it is boolean.
+    pub fn new_unchecked(x: Variable) -> Self {
+        Bool(x)
+    }
+
+    pub fn constant<C: Config, B: BasicAPI<C>>(api: &mut B, value: bool) -> Self {
+        Bool(api.constant(value as u32))
+    }
+
+    pub fn variable(&self) -> Variable {
+        self.0
+    }
+
+    pub fn not<C: Config, B: BasicAPI<C>>(&self, api: &mut B) -> Self {
+        Bool(api.sub(1, self.0))
+    }
+
+    pub fn and<C: Config, B: BasicAPI<C>>(&self, api: &mut B, other: &Bool) -> Self {
+        Bool(api.and(self.0, other.0))
+    }
+
+    pub fn or<C: Config, B: BasicAPI<C>>(&self, api: &mut B, other: &Bool) -> Self {
+        Bool(api.or(self.0, other.0))
+    }
+
+    pub fn xor<C: Config, B: BasicAPI<C>>(&self, api: &mut B, other: &Bool) -> Self {
+        Bool(api.xor(self.0, other.0))
+    }
+
+    /// Returns `if_true` if this is 1 and `if_false` otherwise.
+    pub fn select<C: Config, B: BasicAPI<C>>(
+        &self,
+        api: &mut B,
+        if_true: Variable,
+        if_false: Variable,
+    ) -> Variable {
+        api.select(self.0, if_true, if_false)
+    }
+
+    pub fn assert_is_equal<C: Config, B: BasicAPI<C>>(&self, api: &mut B, other: &Bool) {
+        api.assert_is_equal(self.0, other.0);
+    }
+}
+
+impl From<Bool> for Variable {
+    fn from(x: Bool) -> Self {
+        x.0
+    }
+}
+
+/// An N-bit unsigned integer, kept as its little endian bits.
+///
+/// Bitwise operations, rotations and shifts work on the bits directly. Additions are done
+/// on packed limbs as wide as the field allows, or with a ripple carry adder over GF(2).
+#[derive(Clone, Copy, Debug)]
+pub struct UInt<const N: usize> {
+    bits: [Bool; N],
+}
+
+impl<const N: usize> UInt<N> {
+    /// Decomposes `x` into N bits, which also checks that `x < 2^N`.
+    /// `x` must fit in the field, use `from_bits` for wider integers or GF(2).
+    pub fn new<C: Config, B: BasicAPI<C>>(api: &mut B, x: Variable) -> Self {
+        if N > max_range_bits::<C>() {
+            panic!("UInt<{N}> doesn't fit in a single field element");
+        }
+        let bits = api.to_binary(x, N);
+        Self::from_bits_unchecked(&bits)
+    }
+
+    /// Constrains every bit to be boolean, `bits` are little endian.
+    pub fn from_bits<C: Config, B: BasicAPI<C>>(api: &mut B, bits: &[Variable]) -> Self {
+        for x in bits.iter() {
+            api.assert_is_bool(x);
+        }
+        Self::from_bits_unchecked(bits)
+    }
+
+    pub fn from_bits_unchecked(bits: &[Variable]) -> Self {
+        if bits.len() != N {
+            panic!("UInt<{N}> needs {N} bits, got {}", bits.len());
+        }
+        UInt {
+            bits: std::array::from_fn(|i| Bool::new_unchecked(bits[i])),
+        }
+    }
+
+    pub fn from_bools(bits: [Bool; N]) -> Self {
+        UInt { bits }
+    }
+
+    pub fn constant<C: Config, B: BasicAPI<C>>(api: &mut B, value: u64) -> Self {
+        if N < 64 && value >> N != 0 {
+            panic!("{value} doesn't fit in UInt<{N}>");
+        }
+        UInt {
+            bits: std::array::from_fn(|i| Bool::constant(api, i < 64 && (value >> i) & 1 == 1)),
+        }
+    }
+
+    /// Little endian bits.
+    pub fn bits(&self) -> &[Bool; N] {
+        &self.bits
+    }
+
+    /// The integer as a single variable, only for integers that fit in the field.
+    pub fn to_variable<C: Config, B: BasicAPI<C>>(&self, api: &mut B) -> Variable {
+        if N > max_range_bits::<C>() {
+            panic!("UInt<{N}> doesn't fit in a single field element");
+        }
+        let bits: Vec<Variable> = self.bits.iter().map(|b| b.variable()).collect();
+        api.from_binary(&bits)
+    }
+
+    /// Big endian bytes of the integer, N must be a multiple of 8.
+    pub fn to_be_bytes(&self) -> Vec<UInt<8>> {
+        if N % 8 != 0 {
+            panic!("UInt<{N}> is not made of whole bytes");
+        }
+        self.bits
+            .chunks(8)
+            .rev()
+            .map(|c| UInt::from_bools(c.try_into().unwrap()))
+            .collect()
+    }
+
+    pub fn from_be_bytes(bytes: &[UInt<8>]) -> Self {
+        if bytes.len() * 8 != N {
+            panic!("UInt<{N}> needs {} bytes, got {}", N / 8, bytes.len());
+        }
+        UInt {
+            bits: std::array::from_fn(|i| bytes[bytes.len() - 1 - i / 8].bits[i % 8]),
+        }
+    }
+
+    pub fn not<C: Config, B: BasicAPI<C>>(&self, api: &mut B) -> Self {
+        UInt {
+            bits: std::array::from_fn(|i| self.bits[i].not(api)),
+        }
+    }
+
+    pub fn and<C: Config, B: BasicAPI<C>>(&self, api: &mut B, other: &Self) -> Self {
+        UInt {
+            bits: std::array::from_fn(|i| self.bits[i].and(api, &other.bits[i])),
+        }
+    }
+
+    pub fn or<C: Config, B: BasicAPI<C>>(&self, api: &mut B, other: &Self) -> Self {
+        UInt {
+            bits: std::array::from_fn(|i| self.bits[i].or(api, &other.bits[i])),
+        }
+    }
+
+    pub fn xor<C: Config, B: BasicAPI<C>>(&self, api: &mut B, other: &Self) -> Self {
+        UInt {
+            bits: std::array::from_fn(|i| self.bits[i].xor(api, &other.bits[i])),
+        }
+    }
+
+    pub fn rotate_right(&self, k: usize) -> Self {
+        UInt {
+            bits: std::array::from_fn(|i| self.bits[(i + k) % N]),
+        }
+    }
+
+    pub fn rotate_left(&self, k: usize) -> Self {
+        self.rotate_right(N - k % N)
+    }
+
+    pub fn shift_right<C: Config, B: BasicAPI<C>>(&self, api: &mut B, k: usize) -> Self {
+        let zero = Bool::constant(api, false);
+        UInt {
+            bits: std::array::from_fn(|i| if i + k < N { self.bits[i + k] } else { zero }),
+        }
+    }
+
+    pub fn shift_left<C: Config, B: BasicAPI<C>>(&self, api: &mut B, k: usize) -> Self {
+        let zero = Bool::constant(api, false);
+        UInt {
+            bits: std::array::from_fn(|i| if i >= k { self.bits[i - k] } else { zero }),
+        }
+    }
+
+    /// Addition modulo 2^N.
+    pub fn add<C: Config, B: BasicAPI<C>>(&self, api: &mut B, other: &Self) -> Self {
+        Self::add_many(api, &[*self, *other])
+    }
+
+    /// Sum of all the integers modulo 2^N, cheaper than adding them one by one.
+    pub fn add_many<C: Config, B: BasicAPI<C>>(api: &mut B, xs: &[Self]) -> Self {
+        match xs.len() {
+            0 => return Self::constant(api, 0),
+            1 => return xs[0],
+            _ => {}
+        }
+        // every limb sum is below xs.len() * 2^limb, it takes carry_bits more bits than a limb
+        let carry_bits = usize::BITS as usize - (xs.len() - 1).leading_zeros() as usize;
+        if max_range_bits::<C>() <= carry_bits {
+            return xs[1..]
+                .iter()
+                .fold(xs[0], |acc, x| acc.ripple_carry_add(api, x));
+        }
+        let limb_bits = max_range_bits::<C>() - carry_bits;
+        let mut bits = Vec::with_capacity(N);
+        let mut carry = api.constant(0);
+        let mut start = 0;
+        while start < N {
+            let end = N.min(start + limb_bits);
+            let mut sum = carry;
+            for x in xs.iter() {
+                let limb: Vec<Variable> = x.bits[start..end].iter().map(|b| b.variable()).collect();
+                let limb = api.from_binary(&limb);
+                sum = api.add(sum, limb);
+            }
+            let sum_bits = api.to_binary(sum, end - start + carry_bits);
+            bits.extend_from_slice(&sum_bits[..end - start]);
+            carry = api.from_binary(&sum_bits[end - start..]);
+            start = end;
+        }
+        Self::from_bits_unchecked(&bits)
+    }
+
+    // For fields too small to hold a limb and its carry
+    fn ripple_carry_add<C: Config, B: BasicAPI<C>>(&self, api: &mut B, other: &Self) -> Self {
+        let mut carry = Bool::constant(api, false);
+        UInt {
+            bits: std::array::from_fn(|i| {
+                let (a, b) = (self.bits[i], other.bits[i]);
+                let t = a.xor(api, &b);
+                let sum = t.xor(api, &carry);
+                // carry = a & b | carry & (a ^ b), the two terms are never both 1
+                let ab = a.and(api, &b);
+                let tc = t.and(api, &carry);
+                carry = ab.xor(api, &tc);
+                sum
+            }),
+        }
+    }
+
+    pub fn assert_is_equal<C: Config, B: BasicAPI<C>>(&self, api: &mut B, other: &Self) {
+        for (a, b) in self.bits.iter().zip(other.bits.iter()) {
+            a.assert_is_equal(api, b);
+        }
+    }
+}
+
+/// N bytes, each one an `UInt<8>`.
+#[derive(Clone, Copy, Debug)]
+pub struct Bytes<const N: usize> {
+    bytes: [UInt<8>; N],
+}
+
+impl<const N: usize> Bytes<N> {
+    /// Checks that every variable is below 256.
+    pub fn new<C: Config, B: BasicAPI<C>>(api: &mut B, bytes: &[Variable; N]) -> Self {
+        Bytes {
+            bytes: std::array::from_fn(|i| UInt::new(api, bytes[i])),
+        }
+    }
+
+    pub fn from_uints(bytes: [UInt<8>; N]) -> Self {
+        Bytes { bytes }
+    }
+
+    pub fn constant<C: Config, B: BasicAPI<C>>(api: &mut B, value: &[u8; N]) -> Self {
+        Bytes {
+            bytes: std::array::from_fn(|i| UInt::constant(api, value[i] as u64)),
+        }
+    }
+
+    pub fn bytes(&self) -> &[UInt<8>; N] {
+        &self.bytes
+    }
+
+    /// Big endian words made of M / 8 bytes each, N * 8 must be a multiple of M.
+    pub fn to_be_words<const M: usize>(&self) -> Vec<UInt<M>> {
+        if M % 8 != 0 || (N * 8) % M != 0 {
+            panic!("{N} bytes can't be split into UInt<{M}> words");
+        }
+        self.bytes.chunks(M / 8).map(UInt::from_be_bytes).collect()
+    }
+
+    pub fn assert_is_equal<C: Config, B: BasicAPI<C>>(&self, api: &mut B, other: &Self) {
+        for (a, b) in self.bytes.iter().zip(other.bytes.iter()) {
+            a.assert_is_equal(api, b);
+        }
+    }
+}
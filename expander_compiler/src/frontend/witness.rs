@@ -1,10 +1,10 @@
 pub use crate::circuit::ir::hint_normalized::witness_solver::WitnessSolver;
 use crate::{
-    circuit::layered::witness::Witness,
+    circuit::layered::{explain::ConstraintViolation, witness::Witness, Circuit, InputType},
     hints::registry::{EmptyHintCaller, HintCaller},
 };
 
-use super::{internal, CircuitField, Config, Error};
+use super::{assignment::SerdeAssignment, internal, CircuitField, Config, Error};
 
 impl<C: Config> WitnessSolver<C> {
     pub fn solve_witness<Cir: internal::DumpLoadTwoVariables<CircuitField<C>>>(
@@ -25,6 +25,38 @@ impl<C: Config> WitnessSolver<C> {
         self.solve_witness_from_raw_inputs(vars, public_vars, hint_caller)
     }
 
+    /// Solves the witness for an assignment given as a document, e.g. parsed JSON or CBOR.
+    /// Fields that don't match the circuit are reported by their path in the document.
+    pub fn solve_witness_from_document<Cir>(
+        &self,
+        doc: &serde_json::Value,
+    ) -> Result<Witness<C>, Error>
+    where
+        Cir: internal::DumpLoadTwoVariables<CircuitField<C>> + SerdeAssignment,
+    {
+        self.solve_witness_from_document_with_hints::<Cir>(doc, &EmptyHintCaller)
+    }
+
+    pub fn solve_witness_from_document_with_hints<Cir>(
+        &self,
+        doc: &serde_json::Value,
+        hint_caller: &impl HintCaller<CircuitField<C>>,
+    ) -> Result<Witness<C>, Error>
+    where
+        Cir: internal::DumpLoadTwoVariables<CircuitField<C>> + SerdeAssignment,
+    {
+        let assignment = Cir::from_document(doc, "")?;
+        self.solve_witness_with_hints(&assignment, hint_caller)
+    }
+
+    pub fn solve_witness_from_json<Cir>(&self, json: &str) -> Result<Witness<C>, Error>
+    where
+        Cir: internal::DumpLoadTwoVariables<CircuitField<C>> + SerdeAssignment,
+    {
+        let assignment: Cir = super::assignment::assignment_from_json(json)?;
+        self.solve_witness(&assignment)
+    }
+
     pub fn solve_witnesses<Cir: internal::DumpLoadTwoVariables<CircuitField<C>>>(
         &self,
         assignments: &[Cir],
@@ -48,4 +80,26 @@ impl<C: Config> WitnessSolver<C> {
             hint_caller,
         )
     }
+
+    // Solves the witness and lists the constraints it violates in the layered circuit
+    pub fn explain<Cir: internal::DumpLoadTwoVariables<CircuitField<C>>, I: InputType>(
+        &self,
+        assignment: &Cir,
+        layered_circuit: &Circuit<C, I>,
+    ) -> Result<Vec<ConstraintViolation<C>>, Error> {
+        self.explain_with_hints(assignment, &EmptyHintCaller, layered_circuit)
+    }
+
+    pub fn explain_with_hints<
+        Cir: internal::DumpLoadTwoVariables<CircuitField<C>>,
+        I: InputType,
+    >(
+        &self,
+        assignment: &Cir,
+        hint_caller: &impl HintCaller<CircuitField<C>>,
+        layered_circuit: &Circuit<C, I>,
+    ) -> Result<Vec<ConstraintViolation<C>>, Error> {
+        let witness = self.solve_witness_with_hints(assignment, hint_caller)?;
+        Ok(layered_circuit.explain(&witness))
+    }
 }
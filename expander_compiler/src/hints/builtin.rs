@@ -27,6 +27,7 @@ pub enum BuiltinHintIds {
     Lesser,
     Greater,
     ToBinary,
+    RangeCount,
 }
 
 #[cfg(not(target_pointer_width = "64"))]
@@ -61,6 +62,9 @@ impl BuiltinHintIds {
             x if x == BuiltinHintIds::Lesser as u64 as usize => Some(BuiltinHintIds::Lesser),
             x if x == BuiltinHintIds::Greater as u64 as usize => Some(BuiltinHintIds::Greater),
             x if x == BuiltinHintIds::ToBinary as u64 as usize => Some(BuiltinHintIds::ToBinary),
+            x if x == BuiltinHintIds::RangeCount as u64 as usize => {
+                Some(BuiltinHintIds::RangeCount)
+            }
             _ => None,
         }
     }
@@ -150,6 +154,18 @@ fn validate_builtin_hint(
                 ));
             }
         }
+        BuiltinHintIds::RangeCount => {
+            if num_inputs == 0 {
+                return Err(Error::InternalError(
+                    "range_count requires at least 1 input".to_string(),
+                ));
+            }
+            if num_outputs == 0 {
+                return Err(Error::InternalError(
+                    "range_count requires at least 1 output".to_string(),
+                ));
+            }
+        }
     }
     Ok(())
 }
@@ -173,12 +189,14 @@ pub fn validate_hint(hint_id: usize, num_inputs: usize, num_outputs: usize) -> R
     }
 }
 
+// Fails on inputs a hint cannot be solved for, like a value that does not fit in the outputs
+// of ToBinary
 pub fn impl_builtin_hint<F: Field>(
     hint_id: BuiltinHintIds,
     inputs: &[F],
     num_outputs: usize,
-) -> Vec<F> {
-    match hint_id {
+) -> Result<Vec<F>, Error> {
+    Ok(match hint_id {
         BuiltinHintIds::Identity => inputs.iter().take(num_outputs).cloned().collect(),
         BuiltinHintIds::Div => binop_hint(inputs, |x, y| match y.inv() {
             Some(inv) => x * inv,
@@ -235,8 +253,9 @@ pub fn impl_builtin_hint<F: Field>(
         BuiltinHintIds::GreaterEq => binop_hint(inputs, |x, y| F::from((x >= y) as u32)),
         BuiltinHintIds::Lesser => binop_hint(inputs, |x, y| F::from((x < y) as u32)),
         BuiltinHintIds::Greater => binop_hint(inputs, |x, y| F::from((x > y) as u32)),
-        BuiltinHintIds::ToBinary => to_binary(inputs[0], num_outputs).unwrap(), // TODO: error propagation
-    }
+        BuiltinHintIds::ToBinary => to_binary(inputs[0], num_outputs)?,
+        BuiltinHintIds::RangeCount => range_count(inputs, num_outputs),
+    })
 }
 
 fn binop_hint<F: Field, G: Fn(F, F) -> F>(inputs: &[F], f: G) -> Vec<F> {
@@ -250,6 +269,18 @@ fn binop_hint_on_u256<F: Field, G: Fn(U256, U256) -> U256>(inputs: &[F], f: G) -
     vec![F::from_u256(z_u256)]
 }
 
+// outputs[v] is the number of inputs equal to v, inputs out of range are not counted
+fn range_count<F: Field>(inputs: &[F], num_outputs: usize) -> Vec<F> {
+    let mut counts = vec![0u32; num_outputs];
+    for x in inputs.iter() {
+        let x = x.to_u256();
+        if x < U256::from(num_outputs as u64) {
+            counts[x.as_u64() as usize] += 1;
+        }
+    }
+    counts.into_iter().map(F::from).collect()
+}
+
 pub fn to_binary<F: Field>(x: F, num_outputs: usize) -> Result<Vec<F>, Error> {
     let mut outputs = Vec::with_capacity(num_outputs);
     let mut y = x.to_u256();
@@ -265,10 +296,14 @@ pub fn to_binary<F: Field>(x: F, num_outputs: usize) -> Result<Vec<F>, Error> {
     Ok(outputs)
 }
 
-pub fn stub_impl<F: Field>(hint_id: usize, inputs: &Vec<F>, num_outputs: usize) -> Vec<F> {
+pub fn stub_impl<F: Field>(
+    hint_id: usize,
+    inputs: &Vec<F>,
+    num_outputs: usize,
+) -> Result<Vec<F>, Error> {
     match BuiltinHintIds::from_usize(hint_id) {
         Some(hint_id) => impl_builtin_hint(hint_id, inputs, num_outputs),
-        None => stub_impl_general(hint_id, inputs, num_outputs),
+        None => Ok(stub_impl_general(hint_id, inputs, num_outputs)),
     }
 }
 
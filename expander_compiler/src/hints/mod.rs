@@ -14,7 +14,7 @@ pub fn safe_impl<F: Field>(
     num_outputs: usize,
 ) -> Result<Vec<F>, Error> {
     match BuiltinHintIds::from_usize(hint_id) {
-        Some(hint_id) => Ok(impl_builtin_hint(hint_id, inputs, num_outputs)),
+        Some(hint_id) => impl_builtin_hint(hint_id, inputs, num_outputs),
         None => hint_caller.call(hint_id, inputs, num_outputs),
     }
 }
@@ -1,19 +1,108 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use tiny_keccak::Hasher;
 
-use crate::{field::Field, utils::error::Error};
+use crate::{
+    circuit::{
+        config::Config,
+        ir::hint_normalized::{witness_solver::WitnessSolver, Instruction},
+    },
+    field::Field,
+    utils::error::{CircuitLocation, Error, HintError, HintErrorKind},
+};
 
 use super::{stub_impl, BuiltinHintIds};
 
-type HintFn<F> = fn(&[F], &mut [F]) -> Result<(), Error>;
+type HintFn<F> = Arc<dyn Fn(&[F], &mut [F]) -> Result<(), Error> + Send + Sync>;
 
 #[derive(Default, Clone)]
 pub struct HintRegistry<F: Field> {
     hints: HashMap<usize, HintFn<F>>,
 }
 
-pub fn hint_key_to_id(key: &str) -> usize {
+/// A typed reference to a registered hint.
+///
+/// It is returned by `HintRegistry::register` and can be passed to `BasicAPI::new_hint`
+/// in place of the string key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HintHandle {
+    id: usize,
+}
+
+impl HintHandle {
+    pub fn from_key(key: &str) -> Self {
+        HintHandle {
+            id: hint_key_to_id(key),
+        }
+    }
+
+    pub fn try_from_key(key: &str) -> Result<Self, Error> {
+        Ok(HintHandle {
+            id: try_hint_key_to_id(key)?,
+        })
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+}
+
+/// Anything that identifies a hint: a string key or a `HintHandle`.
+///
+/// A key that hashes into the range of builtin hint ids is rejected with a `HintError`.
+pub trait ToHintId {
+    fn to_hint_id(&self) -> Result<usize, HintError>;
+
+    /// The string key, if known, so that errors about the hint can name it.
+    fn hint_key(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl ToHintId for &str {
+    fn to_hint_id(&self) -> Result<usize, HintError> {
+        key_to_id(self)
+    }
+
+    fn hint_key(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl ToHintId for String {
+    fn to_hint_id(&self) -> Result<usize, HintError> {
+        key_to_id(self)
+    }
+
+    fn hint_key(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl ToHintId for &String {
+    fn to_hint_id(&self) -> Result<usize, HintError> {
+        key_to_id(self)
+    }
+
+    fn hint_key(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl ToHintId for HintHandle {
+    fn to_hint_id(&self) -> Result<usize, HintError> {
+        Ok(self.id)
+    }
+}
+
+impl ToHintId for &HintHandle {
+    fn to_hint_id(&self) -> Result<usize, HintError> {
+        Ok(self.id)
+    }
+}
+
+fn key_to_id(key: &str) -> Result<usize, HintError> {
     let mut hasher = tiny_keccak::Keccak::v256();
     hasher.update(key.as_bytes());
     let mut hash = [0u8; 32];
@@ -21,28 +110,110 @@ pub fn hint_key_to_id(key: &str) -> usize {
 
     let res = usize::from_le_bytes(hash[0..8].try_into().unwrap());
     if BuiltinHintIds::from_usize(res).is_some() {
-        panic!("Hint id {res} collides with a builtin hint id");
+        return Err(HintError::new(
+            HintErrorKind::CollidesWithBuiltin,
+            Some(key.to_string()),
+            res,
+        ));
     }
-    res
+    Ok(res)
+}
+
+pub fn try_hint_key_to_id(key: &str) -> Result<usize, Error> {
+    key_to_id(key).map_err(Error::HintError)
+}
+
+pub fn hint_key_to_id(key: &str) -> usize {
+    try_hint_key_to_id(key).unwrap_or_else(|e| panic!("{e}"))
+}
+
+// The key is filled in by the caller that knows it, e.g. the witness solver
+pub fn hint_not_found(id: usize) -> Error {
+    Error::HintError(HintError::new(HintErrorKind::NotFound, None, id))
 }
 
 impl<F: Field> HintRegistry<F> {
     pub fn new() -> Self {
         Self::default()
     }
-    pub fn register(&mut self, key: &str, hint: fn(&[F], &mut [F]) -> Result<(), Error>) {
-        let id = hint_key_to_id(key);
-        if self.hints.contains_key(&id) {
-            panic!("Hint with id {id} already exists");
+
+    /// Registers a hint under the given key.
+    ///
+    /// Both plain functions and closures are accepted, so a hint may capture its own
+    /// parameters (e.g. a modulus or a lookup table).
+    pub fn register<H>(&mut self, key: &str, hint: H) -> HintHandle
+    where
+        H: Fn(&[F], &mut [F]) -> Result<(), Error> + Send + Sync + 'static,
+    {
+        self.try_register(key, hint)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Same as `register`, but returns an error instead of panicking when the key collides
+    /// with a builtin hint or an already registered one.
+    pub fn try_register<H>(&mut self, key: &str, hint: H) -> Result<HintHandle, Error>
+    where
+        H: Fn(&[F], &mut [F]) -> Result<(), Error> + Send + Sync + 'static,
+    {
+        let handle = HintHandle::try_from_key(key)?;
+        if self.hints.contains_key(&handle.id) {
+            return Err(Error::HintError(HintError::new(
+                HintErrorKind::AlreadyRegistered,
+                Some(key.to_string()),
+                handle.id,
+            )));
         }
-        self.hints.insert(id, hint);
+        self.hints.insert(handle.id, Arc::new(hint));
+        Ok(handle)
     }
+
+    pub fn contains(&self, hint: impl ToHintId) -> bool {
+        hint.to_hint_id()
+            .is_ok_and(|id| self.hints.contains_key(&id))
+    }
+
     pub fn call(&self, id: usize, args: &[F], num_outputs: usize) -> Result<Vec<F>, Error> {
         if let Some(hint) = self.hints.get(&id) {
             let mut outputs = vec![F::zero(); num_outputs];
             hint(args, &mut outputs).map(|_| outputs)
         } else {
-            panic!("Hint with id {id} not found");
+            Err(hint_not_found(id))
+        }
+    }
+
+    /// Lists every non-builtin hint used by the circuit that is not registered here.
+    pub fn unregistered_hints<C: Config>(&self, solver: &WitnessSolver<C>) -> Vec<HintError> {
+        let root = &solver.circuit;
+        let mut circuit_ids: Vec<usize> = root.circuits.keys().cloned().collect();
+        circuit_ids.sort();
+        let mut res = Vec::new();
+        for circuit_id in circuit_ids {
+            for (i, insn) in root.circuits[&circuit_id].instructions.iter().enumerate() {
+                if let Instruction::Hint { hint_id, .. } = insn {
+                    if BuiltinHintIds::from_usize(*hint_id).is_some()
+                        || self.hints.contains_key(hint_id)
+                    {
+                        continue;
+                    }
+                    let key = solver.hint_keys.get(hint_id).cloned();
+                    let mut e = HintError::new(HintErrorKind::NotFound, key, *hint_id);
+                    e.location.push(CircuitLocation {
+                        circuit_id,
+                        instruction_index: i,
+                    });
+                    res.push(e);
+                }
+            }
+        }
+        res
+    }
+
+    /// Checks that every hint used by the circuit is registered, so that missing hints are
+    /// reported before witness solving starts.
+    pub fn validate_against<C: Config>(&self, solver: &WitnessSolver<C>) -> Result<(), Error> {
+        match self.unregistered_hints(solver).into_iter().next() {
+            Some(e) => Err(Error::HintError(e)),
+            None => Ok(()),
         }
     }
 }
@@ -69,12 +240,12 @@ impl<F: Field + 'static> HintCaller<F> for HintRegistry<F> {
 
 impl<F: Field> HintCaller<F> for EmptyHintCaller {
     fn call(&self, id: usize, _: &[F], _: usize) -> Result<Vec<F>, Error> {
-        Err(Error::UserError(format!("hint with id {id} not found")))
+        Err(hint_not_found(id))
     }
 }
 
 impl<F: Field> HintCaller<F> for StubHintCaller {
     fn call(&self, id: usize, args: &[F], num_outputs: usize) -> Result<Vec<F>, Error> {
-        Ok(stub_impl(id, &args.to_vec(), num_outputs))
+        stub_impl(id, &args.to_vec(), num_outputs)
     }
 }
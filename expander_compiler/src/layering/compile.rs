@@ -7,6 +7,7 @@ use crate::circuit::{
     ir::expr::Expression,
     layered::{Coef, InputType, Segment},
 };
+use crate::compile::cache::ArtifactCache;
 use crate::utils::pool::Pool;
 
 use super::layer_layout::merge_layouts;
@@ -42,6 +43,11 @@ pub struct CompileContext<'a, C: Config, I: InputType> {
     pub root_has_constraints: bool,
 
     pub opts: CompileOptions,
+
+    // segments of sub circuit calls are cached by content hash
+    pub cache: Option<&'a ArtifactCache>,
+    pub content_hashes: HashMap<usize, [u8; 32]>,
+    pub num_reused_segments: usize,
 }
 
 pub struct IrContext<'a, C: Config> {
@@ -7,6 +7,7 @@ use crate::{
         ir,
         layered::{self, InputType, InputUsize},
     },
+    compile::cache::ArtifactCache,
     utils::pool::Pool,
 };
 
@@ -27,6 +28,26 @@ pub fn compile<C: Config, I: InputType>(
     rc: &ir::dest::RootCircuit<C>,
     opts: CompileOptions,
 ) -> (layered::Circuit<C, I>, InputMapping) {
+    let (lc, im, _) = compile_inner(rc, opts, None);
+    (lc, im)
+}
+
+// Same as compile, but the segments of each sub circuit call are looked up in the artifact
+// cache by the content hash of the sub circuit and its layer layouts, so that only changed
+// circuits are layered again. Also returns the number of calls whose segments were reused.
+pub fn compile_with_cache<C: Config, I: InputType>(
+    rc: &ir::dest::RootCircuit<C>,
+    opts: CompileOptions,
+    cache: &ArtifactCache,
+) -> (layered::Circuit<C, I>, InputMapping, usize) {
+    compile_inner(rc, opts, Some(cache))
+}
+
+fn compile_inner<C: Config, I: InputType>(
+    rc: &ir::dest::RootCircuit<C>,
+    opts: CompileOptions,
+    cache: Option<&ArtifactCache>,
+) -> (layered::Circuit<C, I>, InputMapping, usize) {
     let mut ctx = compile::CompileContext {
         rc,
         circuits: HashMap::new(),
@@ -40,6 +61,12 @@ pub fn compile<C: Config, I: InputType>(
         input_order: Vec::new(),
         root_has_constraints: false,
         opts,
+        cache,
+        content_hashes: match cache {
+            Some(_) => rc.content_hashes(),
+            None => HashMap::new(),
+        },
+        num_reused_segments: 0,
     };
     ctx.compile();
     let t: &I::InputUsize = &ctx.compiled_circuits[ctx.layers[0]].num_inputs;
@@ -53,7 +80,9 @@ pub fn compile<C: Config, I: InputType>(
             expected_num_output_zeroes: output_zeroes,
             segments: ctx.compiled_circuits,
             layer_ids: ctx.layers,
+            source_map: Default::default(),
         },
         InputMapping::new(l0_size, ctx.input_order),
+        ctx.num_reused_segments,
     )
 }
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use tiny_keccak::Hasher;
+
 use crate::{
     circuit::{
         config::Config,
@@ -10,6 +12,7 @@ use crate::{
             InputUsize, Segment,
         },
     },
+    compile::cache::ArtifactCache,
     field::FieldArith,
     frontend::CircuitField,
     utils::pool::Pool,
@@ -127,6 +130,126 @@ impl<'a, C: Config, I: InputType> CompileContext<'a, C, I> {
     }
 
     pub fn connect_wires(&mut self, layout_ids: &[usize]) -> Vec<usize> {
+        let circuit_id = self.layer_layout_pool.get(layout_ids[0]).circuit_id;
+        // the root is always layered again, the whole circuit is cached in compile_with_options
+        let (cache, key) = match self.cache {
+            Some(cache) if circuit_id != 0 => {
+                (cache, self.segment_cache_key(circuit_id, layout_ids))
+            }
+            _ => return self.connect_wires_uncached(layout_ids),
+        };
+        if let Some((segments, layers)) = cache.load_segments::<C, I>(&key) {
+            if let Some(ids) = self.splice_segments(segments, &layers, layout_ids.len() - 1) {
+                self.num_reused_segments += 1;
+                return ids;
+            }
+        }
+
+        let start = self.compiled_circuits.len();
+        let res = self.connect_wires_uncached(layout_ids);
+        // only segments created by this call can be stored, with ids relative to start
+        let segments = &self.compiled_circuits[start..];
+        let is_local = res.iter().all(|x| *x >= start)
+            && segments
+                .iter()
+                .all(|seg| seg.child_segs.iter().all(|(id, _)| *id >= start));
+        if is_local {
+            let segments = segments
+                .iter()
+                .map(|seg| {
+                    let mut seg = seg.clone();
+                    for (id, _) in seg.child_segs.iter_mut() {
+                        *id -= start;
+                    }
+                    seg
+                })
+                .collect::<Vec<_>>();
+            let layers = res.iter().map(|x| x - start).collect::<Vec<_>>();
+            // failing to write the cache must not fail the compilation
+            let _ = cache.store_segments(&key, &segments, &layers);
+        }
+        res
+    }
+
+    // Appends segments loaded from the cache and returns the ids of the segments of each layer.
+    // Segments only reference earlier ones, anything else is treated as a miss.
+    fn splice_segments(
+        &mut self,
+        segments: Vec<Segment<C, I>>,
+        layers: &[usize],
+        num_layers: usize,
+    ) -> Option<Vec<usize>> {
+        let is_valid = layers.len() == num_layers
+            && layers.iter().all(|x| *x < segments.len())
+            && segments
+                .iter()
+                .enumerate()
+                .all(|(i, seg)| seg.child_segs.iter().all(|(id, _)| *id < i));
+        if !is_valid {
+            return None;
+        }
+        let start = self.compiled_circuits.len();
+        for mut seg in segments {
+            for (id, _) in seg.child_segs.iter_mut() {
+                *id += start;
+            }
+            self.compiled_circuits.push(seg);
+        }
+        Some(layers.iter().map(|x| x + start).collect())
+    }
+
+    // The segments of a call only depend on the sub circuit and the layouts of its layers.
+    // Layouts are identified by their contents, with nested sub layouts written out.
+    fn segment_cache_key(&self, circuit_id: usize, layout_ids: &[usize]) -> String {
+        let mut buf = Vec::new();
+        buf.push(I::CROSS_LAYER_RELAY as u8);
+        buf.extend_from_slice(&self.content_hashes[&circuit_id]);
+        for id in layout_ids.iter() {
+            self.write_layout(*id, &mut buf);
+        }
+        let mut hasher = tiny_keccak::Keccak::v256();
+        hasher.update(&buf);
+        let mut hash = [0u8; 32];
+        hasher.finalize(&mut hash);
+        ArtifactCache::sub_circuit_key("layering", &hash)
+    }
+
+    fn write_layout(&self, id: usize, buf: &mut Vec<u8>) {
+        let layout = self.layer_layout_pool.get(id);
+        write_usize(buf, layout.layer);
+        write_usize(buf, layout.size);
+        match &layout.inner {
+            LayerLayoutInner::Dense { placement } => {
+                write_usize(buf, 0);
+                write_usize(buf, placement.len());
+                for x in placement.iter() {
+                    write_usize(buf, *x);
+                }
+            }
+            LayerLayoutInner::Sparse {
+                placement,
+                sub_layout,
+            } => {
+                write_usize(buf, 1);
+                let mut items: Vec<(usize, usize)> =
+                    placement.iter().map(|(k, v)| (*k, *v)).collect();
+                items.sort();
+                write_usize(buf, items.len());
+                for (k, v) in items {
+                    write_usize(buf, k);
+                    write_usize(buf, v);
+                }
+                write_usize(buf, sub_layout.len());
+                for x in sub_layout.iter() {
+                    write_usize(buf, x.offset);
+                    write_usize(buf, x.insn_id);
+                    self.write_layout(x.id, buf);
+                }
+            }
+        }
+    }
+
+    fn connect_wires_uncached(&mut self, layout_ids: &[usize]) -> Vec<usize> {
         let layouts = layout_ids
             .iter()
             .map(|x| self.layer_layout_pool.get(*x).clone())
@@ -469,3 +592,7 @@ impl<'a, C: Config, I: InputType> CompileContext<'a, C, I> {
         ress_ids
     }
 }
+
+fn write_usize(buf: &mut Vec<u8>, x: usize) {
+    buf.extend_from_slice(&(x as u64).to_le_bytes());
+}
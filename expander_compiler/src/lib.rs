@@ -1,6 +1,9 @@
 #![feature(min_specialization)]
 #![allow(clippy::manual_div_ceil)]
 
+// lets the Circuit derive refer to this crate by name inside the crate too
+extern crate self as expander_compiler;
+
 pub mod builder;
 pub mod circuit;
 pub mod compile;
@@ -1,14 +1,55 @@
-use std::fmt;
+use std::{collections::HashMap, fmt};
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error {
     UserError(String),
     InternalError(String),
+    HintError(HintError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintErrorKind {
+    // no hint with this id is known to the hint caller
+    NotFound,
+    // the hint key hashes into the range reserved for builtin hints
+    CollidesWithBuiltin,
+    // a hint with the same id is already registered
+    AlreadyRegistered,
+}
+
+// An instruction inside a circuit of the IR, identified by the circuit id and its index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitLocation {
+    pub circuit_id: usize,
+    pub instruction_index: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HintError {
+    pub kind: HintErrorKind,
+    pub hint_key: Option<String>,
+    pub hint_id: usize,
+    // call stack from the root circuit down to the hint instruction, empty if unknown
+    pub location: Vec<CircuitLocation>,
+    // prefixes added by Error::prepend, outermost first
+    pub context: Vec<String>,
+}
+
+impl HintError {
+    pub fn new(kind: HintErrorKind, hint_key: Option<String>, hint_id: usize) -> Self {
+        HintError {
+            kind,
+            hint_key,
+            hint_id,
+            location: Vec::new(),
+            context: Vec::new(),
+        }
+    }
 }
 
 impl Error {
     pub fn is_user(&self) -> bool {
-        matches!(self, Error::UserError(_))
+        matches!(self, Error::UserError(_) | Error::HintError(_))
     }
 
     pub fn is_internal(&self) -> bool {
@@ -19,7 +60,76 @@ impl Error {
         match self {
             Error::UserError(s) => Error::UserError(format!("{prefix}: {s}")),
             Error::InternalError(s) => Error::InternalError(format!("{prefix}: {s}")),
+            Error::HintError(e) => {
+                let mut e = e.clone();
+                e.context.insert(0, prefix.to_string());
+                Error::HintError(e)
+            }
+        }
+    }
+
+    // Records that the error happened while evaluating the given instruction.
+    // Errors are propagated from the innermost sub circuit outwards, so each caller is
+    // pushed in front of the existing call stack.
+    pub fn at_location(mut self, circuit_id: usize, instruction_index: usize) -> Error {
+        if let Error::HintError(e) = &mut self {
+            e.location.insert(
+                0,
+                CircuitLocation {
+                    circuit_id,
+                    instruction_index,
+                },
+            );
+        }
+        self
+    }
+
+    // Names the hint of a hint error that doesn't know its key yet
+    pub fn with_hint_key(mut self, key: Option<&str>) -> Error {
+        if let (Error::HintError(e), Some(key)) = (&mut self, key) {
+            e.hint_key.get_or_insert_with(|| key.to_string());
+        }
+        self
+    }
+
+    // Same as with_hint_key, looking the key up by the hint id
+    pub fn with_hint_keys(self, keys: &HashMap<usize, String>) -> Error {
+        let key = match &self {
+            Error::HintError(e) => keys.get(&e.hint_id).cloned(),
+            _ => None,
+        };
+        self.with_hint_key(key.as_deref())
+    }
+}
+
+impl fmt::Display for HintErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HintErrorKind::NotFound => write!(f, "not found"),
+            HintErrorKind::CollidesWithBuiltin => write!(f, "collides with a builtin hint id"),
+            HintErrorKind::AlreadyRegistered => write!(f, "already registered"),
+        }
+    }
+}
+
+impl fmt::Display for HintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for prefix in self.context.iter() {
+            write!(f, "{prefix}: ")?;
+        }
+        match &self.hint_key {
+            Some(key) => write!(f, "hint \"{}\" (id {}) {}", key, self.hint_id, self.kind)?,
+            None => write!(f, "hint with id {} {}", self.hint_id, self.kind)?,
+        }
+        for (i, loc) in self.location.iter().enumerate() {
+            let sep = if i == 0 { " at" } else { " ->" };
+            write!(
+                f,
+                "{sep} circuit {} instruction {}",
+                loc.circuit_id, loc.instruction_index
+            )?;
         }
+        Ok(())
     }
 }
 
@@ -28,6 +138,7 @@ impl fmt::Display for Error {
         match self {
             Error::UserError(s) => write!(f, "{s}"),
             Error::InternalError(s) => write!(f, "{s}"),
+            Error::HintError(e) => write!(f, "{e}"),
         }
     }
 }
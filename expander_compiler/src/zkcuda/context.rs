@@ -5,18 +5,20 @@ use serdes::ExpSerde;
 use crate::{
     circuit::config::{CircuitField, Config, SIMDField},
     field::FieldArith,
+    frontend::{Variable, API},
     hints::registry::{EmptyHintCaller, HintCaller},
     utils::{error::Error, pool::Pool},
     zkcuda::shape::keep_shape_until,
 };
 
 use super::{
-    kernel::{compile_primitive, Kernel, KernelPrimitive},
+    kernel::{compile_primitive, compile_with_spec_and_shapes, IOVecSpec, Kernel, KernelPrimitive},
     shape::{
         keep_shape_products_until, keep_shape_since, merge_shape_products, prefix_products,
         prefix_products_to_shape, shape_padded_mapping, shape_prepend, shape_vec_len,
         shape_vec_padded_len, BitOrder, Reshape, Shape, ShapeHistory, Transpose,
     },
+    tensor_kernels::{broadcast_offsets, broadcast_shape},
     vec_shaped::{
         flatten_shaped, flatten_shaped_pack_simd, unflatten_shaped, unflatten_shaped_unpack_simd,
         VecShaped,
@@ -474,6 +476,192 @@ impl<C: Config, H: HintCaller<CircuitField<C>>> Context<C, H> {
         Ok(())
     }
 
+    // The device memory operations below build a kernel copying the selected elements and call
+    // it once, so the result is a new device memory tied to its sources by the constraints of
+    // that kernel. The copies are part of the ComputationGraph and checked by the verifier like
+    // any other kernel call, while the handles only change through Reshape and Transpose.
+
+    // A memory of out_shape whose i-th element is element sources[i].1 of inputs[sources[i].0]
+    fn call_copy_kernel(
+        &mut self,
+        inputs: &[DeviceMemoryHandle],
+        out_shape: Shape,
+        sources: Vec<(usize, usize)>,
+    ) -> Result<DeviceMemoryHandle, Error> {
+        let shapes = inputs
+            .iter()
+            .map(|handle| ensure_handle(handle.clone()).shape_history.shape())
+            .collect::<Vec<_>>();
+        let mut specs = shapes
+            .iter()
+            .map(|shape| IOVecSpec {
+                len: shape_vec_len(shape),
+                is_input: true,
+                is_output: false,
+            })
+            .collect::<Vec<_>>();
+        specs.push(IOVecSpec {
+            len: shape_vec_len(&out_shape),
+            is_input: false,
+            is_output: true,
+        });
+        let mut io_shapes = shapes.clone();
+        io_shapes.push(out_shape.clone());
+        let n = inputs.len();
+        let kernel = compile_with_spec_and_shapes(
+            |_api: &mut API<C>, io: &mut Vec<Vec<Variable>>| {
+                for (i, (input, offset)) in sources.iter().enumerate() {
+                    io[n][i] = io[*input][*offset];
+                }
+            },
+            &specs,
+            &io_shapes,
+        )?;
+        // a single instance, so every input is passed whole
+        let mut ios = inputs
+            .iter()
+            .zip(shapes.iter())
+            .map(|(handle, shape)| handle.reshape(&shape_prepend(shape, 1)))
+            .collect::<Vec<_>>();
+        ios.push(None);
+        self.call_kernel(&kernel, 1, &mut ios)?;
+        Ok(ios[n].reshape(&out_shape))
+    }
+
+    // The elements at the given indices along axis, in that order. Indices are fixed when the
+    // computation graph is built and may repeat.
+    pub fn gather(
+        &mut self,
+        handle: &DeviceMemoryHandle,
+        axis: usize,
+        indices: &[usize],
+    ) -> Result<DeviceMemoryHandle, Error> {
+        let shape = ensure_handle(handle.clone()).shape_history.shape();
+        if axis >= shape.len() {
+            return Err(Error::UserError(format!(
+                "axis {axis} out of range for shape {shape:?}"
+            )));
+        }
+        if indices.is_empty() || indices.iter().any(|&i| i >= shape[axis]) {
+            return Err(Error::UserError(format!(
+                "invalid indices {indices:?} along axis {axis} of shape {shape:?}"
+            )));
+        }
+        let outer = shape_vec_len(&shape[..axis]);
+        let inner = shape_vec_len(&shape[axis + 1..]);
+        let mut sources = Vec::with_capacity(outer * indices.len() * inner);
+        for o in 0..outer {
+            for &j in indices {
+                for i in 0..inner {
+                    sources.push((0, (o * shape[axis] + j) * inner + i));
+                }
+            }
+        }
+        let mut out_shape = shape;
+        out_shape[axis] = indices.len();
+        self.call_copy_kernel(std::slice::from_ref(handle), out_shape, sources)
+    }
+
+    // The sub-range of the elements along axis
+    pub fn slice(
+        &mut self,
+        handle: &DeviceMemoryHandle,
+        axis: usize,
+        range: std::ops::Range<usize>,
+    ) -> Result<DeviceMemoryHandle, Error> {
+        self.gather(handle, axis, &range.collect::<Vec<_>>())
+    }
+
+    // Joins memories whose shapes only differ along axis
+    pub fn concat(
+        &mut self,
+        handles: &[DeviceMemoryHandle],
+        axis: usize,
+    ) -> Result<DeviceMemoryHandle, Error> {
+        let shapes = handles
+            .iter()
+            .map(|handle| ensure_handle(handle.clone()).shape_history.shape())
+            .collect::<Vec<_>>();
+        let Some(first) = shapes.first() else {
+            return Err(Error::UserError("nothing to concatenate".to_string()));
+        };
+        if axis >= first.len() {
+            return Err(Error::UserError(format!(
+                "axis {axis} out of range for shape {first:?}"
+            )));
+        }
+        let compatible = |shape: &Shape| {
+            shape.len() == first.len()
+                && (0..shape.len()).all(|d| d == axis || shape[d] == first[d])
+        };
+        if !shapes.iter().all(compatible) {
+            return Err(Error::UserError(format!(
+                "shapes {shapes:?} cannot be concatenated along axis {axis}"
+            )));
+        }
+        let outer = shape_vec_len(&first[..axis]);
+        let inner = shape_vec_len(&first[axis + 1..]);
+        let mut out_shape = first.clone();
+        out_shape[axis] = shapes.iter().map(|shape| shape[axis]).sum();
+        let mut sources = Vec::with_capacity(shape_vec_len(&out_shape));
+        for o in 0..outer {
+            for (input, shape) in shapes.iter().enumerate() {
+                for offset in o * shape[axis] * inner..(o + 1) * shape[axis] * inner {
+                    sources.push((input, offset));
+                }
+            }
+        }
+        self.call_copy_kernel(handles, out_shape, sources)
+    }
+
+    // Joins memories of the same shape along a new axis
+    pub fn stack(
+        &mut self,
+        handles: &[DeviceMemoryHandle],
+        axis: usize,
+    ) -> Result<DeviceMemoryHandle, Error> {
+        let mut expanded = Vec::with_capacity(handles.len());
+        for handle in handles {
+            let mut shape = ensure_handle(handle.clone()).shape_history.shape();
+            if axis > shape.len() {
+                return Err(Error::UserError(format!(
+                    "axis {axis} out of range for shape {shape:?}"
+                )));
+            }
+            shape.insert(axis, 1);
+            expanded.push(handle.reshape(&shape));
+        }
+        let shapes = expanded
+            .iter()
+            .map(|handle| ensure_handle(handle.clone()).shape_history.shape())
+            .collect::<Vec<_>>();
+        if shapes.windows(2).any(|w| w[0] != w[1]) {
+            return Err(Error::UserError(format!(
+                "shapes {shapes:?} cannot be stacked"
+            )));
+        }
+        self.concat(&expanded, axis)
+    }
+
+    // Repeats the memory to the given shape, with the broadcasting rules of numpy
+    pub fn broadcast_to(
+        &mut self,
+        handle: &DeviceMemoryHandle,
+        shape: &[usize],
+    ) -> Result<DeviceMemoryHandle, Error> {
+        let from = ensure_handle(handle.clone()).shape_history.shape();
+        if from.len() > shape.len() || broadcast_shape(&from, shape)? != shape {
+            return Err(Error::UserError(format!(
+                "shape {from:?} cannot be broadcast to {shape:?}"
+            )));
+        }
+        let sources = broadcast_offsets(&from, shape)
+            .into_iter()
+            .map(|offset| (0, offset))
+            .collect();
+        self.call_copy_kernel(std::slice::from_ref(handle), shape.to_vec(), sources)
+    }
+
     fn get_current_device_memory_shapes(&self) -> Vec<Shape> {
         self.device_memories
             .iter()
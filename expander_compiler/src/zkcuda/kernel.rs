@@ -1,14 +1,14 @@
 use crate::circuit::input_mapping::EMPTY;
 use crate::circuit::ir::common::Instruction;
 use crate::compile::{
-    compile_step_1, compile_step_2, compile_step_3, print_ir_stats, print_layered_circuit_stats,
-    CompileOptions,
+    compile_step_1, compile_step_2, compile_step_3, report::CompileReport, CompileOptions,
 };
 use crate::frontend::{BasicAPI, Error, Variable, API};
 use crate::zkcuda::shape::{shape_padded_mapping, shape_vec_len, shape_vec_padded_len, Shape};
 use crate::{
     circuit::{
         config::Config,
+        custom_gate::CustomGateRegistry,
         input_mapping::InputMapping,
         ir,
         layered::{Circuit as LayeredCircuit, NormalInputType},
@@ -149,18 +149,19 @@ where
             }
         }
     }
+    root_builder.check_hints()?;
     let mut r_source = root_builder.build();
     assert_eq!(r_source.circuits[&0].outputs.len(), 0);
     r_source.circuits.get_mut(&0).unwrap().outputs = output_vars.clone();
-    let (r, src_im) = compile_step_1(&r_source, CompileOptions::default())?;
+    let mut report = CompileReport::default();
+    let (r, src_im) = compile_step_1(&r_source, CompileOptions::default(), &mut report)?;
     for (i, x) in src_im.mapping().iter().enumerate() {
         assert_eq!(*x, i);
     }
-    print_ir_stats(&r);
     let mut r2 = r.clone();
     r2.circuits.get_mut(&0).unwrap().constraints = Vec::new();
     let mut tmp_im = InputMapping::new_identity(r2.input_size());
-    let r2 = compile_step_4(r2, &mut tmp_im, CompileOptions::default())?;
+    let r2 = compile_step_4(r2, &mut tmp_im, CompileOptions::default(), &mut report)?;
     // No inputs should be removed in this step.
     for (i, x) in tmp_im.mapping().iter().take(n_in).enumerate() {
         assert_eq!(i, *x);
@@ -194,9 +195,14 @@ pub fn compile_primitive<C: Config>(
     r_hint_exported
         .validate()
         .map_err(|e| e.prepend("hint exported circuit invalid"))?;
+    let mut report = CompileReport::default();
     let mut tmp_im = InputMapping::new_identity(r_hint_exported.input_size());
-    let mut r_hint_exported_opt =
-        compile_step_4(r_hint_exported, &mut tmp_im, CompileOptions::default())?;
+    let mut r_hint_exported_opt = compile_step_4(
+        r_hint_exported,
+        &mut tmp_im,
+        CompileOptions::default(),
+        &mut report,
+    )?;
     // No inputs should be removed in this step.
     for (i, x) in tmp_im.mapping().iter().enumerate() {
         assert_eq!(i, *x);
@@ -228,7 +234,7 @@ pub fn compile_primitive<C: Config>(
     let num_unused_outputs = rl_c0.num_inputs;
     rl_c0.outputs = (1..=rl_c0.num_inputs).collect();
     let (mut r_dest_opt, hl_im) =
-        compile_step_2::<C, NormalInputType>(r_hint_less, CompileOptions::default())?;
+        compile_step_2::<C, NormalInputType>(r_hint_less, CompileOptions::default(), &mut report)?;
     for (i, x) in hl_im.mapping().iter().enumerate() {
         assert_eq!(i, *x);
     }
@@ -253,8 +259,8 @@ pub fn compile_primitive<C: Config>(
             assert_eq!(*x, EMPTY);
         }
     }
-    let lc = compile_step_3(lc, CompileOptions::default())?;
-    print_layered_circuit_stats(&lc);
+    let lc = compile_step_3(lc, CompileOptions::default(), &mut report)?;
+    CustomGateRegistry::validate_export(&lc)?;
 
     Ok(Kernel {
         hint_solver: if num_hints > 0 {
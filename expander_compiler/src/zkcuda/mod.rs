@@ -3,6 +3,7 @@ pub mod kernel;
 pub mod mpi_mem_share;
 pub mod proving_system;
 pub mod shape;
+pub mod tensor_kernels;
 pub mod vec_shaped;
 
 #[cfg(test)]
@@ -187,6 +187,7 @@ impl<C: Config> MPISharedMemory for Circuit<C, NormalInputType> {
             expected_num_output_zeroes,
             segments,
             layer_ids,
+            source_map: Default::default(),
         }
     }
 
@@ -116,7 +116,10 @@ where
         is_broadcast: &[bool],
     ) -> bool {
         let timer = Timer::new("verify", true);
-        let mut expander_circuit = kernel.layered_circuit().export_to_expander_flatten();
+        let mut expander_circuit = kernel
+            .layered_circuit()
+            .export_to_expander_flatten()
+            .unwrap();
 
         for i in 0..parallel_count {
             let mut transcript = C::TranscriptConfig::new();
@@ -28,7 +28,12 @@ where
     ECCConfig: Config,
     ECCConfig::FieldConfig: FieldEngine<CircuitField = F::CircuitField>,
 {
-    let mut expander_circuit = kernel.layered_circuit().export_to_expander().flatten();
+    // compile_primitive checks that kernels can be exported
+    let mut expander_circuit = kernel
+        .layered_circuit()
+        .export_to_expander()
+        .unwrap()
+        .flatten();
     expander_circuit.pre_process_gkr();
 
     let (max_num_input_var, max_num_output_var) = super::utils::max_n_vars(&expander_circuit);
@@ -74,7 +74,10 @@ impl<C: GKREngine, ECCConfig: Config<FieldConfig = C::FieldConfig>> ProvingSyste
                     return false;
                 }
                 let kernel = &computation_graph.kernels()[template.kernel_id()];
-                let mut expander_circuit = kernel.layered_circuit().export_to_expander_flatten();
+                let mut expander_circuit = kernel
+                    .layered_circuit()
+                    .export_to_expander_flatten()
+                    .unwrap();
 
                 for i in 0..parallel_count {
                     let mut transcript = C::TranscriptConfig::new();
@@ -67,7 +67,10 @@ where
     C: GKREngine,
     ECCConfig: Config<FieldConfig = C::FieldConfig>,
 {
-    let mut expander_circuit = kernel.layered_circuit().export_to_expander_flatten();
+    let mut expander_circuit = kernel
+        .layered_circuit()
+        .export_to_expander_flatten()
+        .unwrap();
 
     let mut transcript = C::TranscriptConfig::new();
     expander_circuit.fill_rnd_coefs(&mut transcript);
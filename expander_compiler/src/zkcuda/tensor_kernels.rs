@@ -0,0 +1,276 @@
+// Kernels of common tensor ops, compiled for the shapes given at runtime instead of written with
+// #[kernel]. Shapes are those of one instance: call_kernel! adds the parallel dimension in front,
+// or broadcasts an input passed without it. Every builder returns a KernelPrimitive whose
+// arguments are the inputs followed by the output.
+use crate::circuit::config::Config;
+use crate::frontend::{BasicAPI, Error, Variable, API};
+
+use super::kernel::{compile_with_spec_and_shapes, IOVecSpec, KernelPrimitive};
+use super::shape::{shape_vec_len, Shape};
+
+fn input_spec(shape: &[usize]) -> IOVecSpec {
+    IOVecSpec {
+        len: shape_vec_len(shape),
+        is_input: true,
+        is_output: false,
+    }
+}
+
+fn output_spec(shape: &[usize]) -> IOVecSpec {
+    IOVecSpec {
+        len: shape_vec_len(shape),
+        is_input: false,
+        is_output: true,
+    }
+}
+
+fn check_shape(shape: &[usize]) -> Result<(), Error> {
+    if shape.contains(&0) {
+        return Err(Error::UserError(format!(
+            "empty dimension in shape {shape:?}"
+        )));
+    }
+    Ok(())
+}
+
+// The sizes of the dimensions before the axis, along it and after it
+fn split_axis(shape: &[usize], axis: usize) -> Result<(usize, usize, usize), Error> {
+    check_shape(shape)?;
+    if axis >= shape.len() {
+        return Err(Error::UserError(format!(
+            "axis {axis} out of range for shape {shape:?}"
+        )));
+    }
+    Ok((
+        shape_vec_len(&shape[..axis]),
+        shape[axis],
+        shape_vec_len(&shape[axis + 1..]),
+    ))
+}
+
+// The shape of a and b broadcast together, aligned on their last dimension as in numpy
+pub fn broadcast_shape(a: &[usize], b: &[usize]) -> Result<Shape, Error> {
+    check_shape(a)?;
+    check_shape(b)?;
+    let n = a.len().max(b.len());
+    let dim = |s: &[usize], i: usize| {
+        if i + s.len() < n {
+            1
+        } else {
+            s[i + s.len() - n]
+        }
+    };
+    (0..n)
+        .map(|i| match (dim(a, i), dim(b, i)) {
+            (x, y) if x == y || y == 1 => Ok(x),
+            (1, y) => Ok(y),
+            _ => Err(Error::UserError(format!(
+                "shapes {a:?} and {b:?} cannot be broadcast together"
+            ))),
+        })
+        .collect()
+}
+
+// For every element of out_shape, the offset of the element of shape it is broadcast from
+pub(crate) fn broadcast_offsets(shape: &[usize], out_shape: &[usize]) -> Vec<usize> {
+    let skip = out_shape.len() - shape.len();
+    (0..shape_vec_len(out_shape))
+        .map(|mut index| {
+            let mut offset = 0;
+            let mut stride = 1;
+            for (i, &out_dim) in out_shape.iter().enumerate().rev() {
+                let pos = index % out_dim;
+                index /= out_dim;
+                if i >= skip && shape[i - skip] != 1 {
+                    offset += pos * stride;
+                    stride *= shape[i - skip];
+                }
+            }
+            offset
+        })
+        .collect()
+}
+
+// Applies f to every element
+pub fn map_kernel<C, F>(shape: &[usize], f: F) -> Result<KernelPrimitive<C>, Error>
+where
+    C: Config,
+    F: Fn(&mut API<C>, Variable) -> Variable,
+{
+    check_shape(shape)?;
+    compile_with_spec_and_shapes(
+        |api: &mut API<C>, io: &mut Vec<Vec<Variable>>| {
+            for i in 0..io[0].len() {
+                io[1][i] = f(api, io[0][i]);
+            }
+        },
+        &[input_spec(shape), output_spec(shape)],
+        &[shape.to_vec(), shape.to_vec()],
+    )
+}
+
+// Applies f elementwise to a and b broadcast together
+pub fn broadcast_kernel<C, F>(
+    a_shape: &[usize],
+    b_shape: &[usize],
+    f: F,
+) -> Result<KernelPrimitive<C>, Error>
+where
+    C: Config,
+    F: Fn(&mut API<C>, Variable, Variable) -> Variable,
+{
+    let out_shape = broadcast_shape(a_shape, b_shape)?;
+    let a_offsets = broadcast_offsets(a_shape, &out_shape);
+    let b_offsets = broadcast_offsets(b_shape, &out_shape);
+    compile_with_spec_and_shapes(
+        |api: &mut API<C>, io: &mut Vec<Vec<Variable>>| {
+            for (i, (a, b)) in a_offsets.iter().zip(&b_offsets).enumerate() {
+                io[2][i] = f(api, io[0][*a], io[1][*b]);
+            }
+        },
+        &[
+            input_spec(a_shape),
+            input_spec(b_shape),
+            output_spec(&out_shape),
+        ],
+        &[a_shape.to_vec(), b_shape.to_vec(), out_shape],
+    )
+}
+
+pub fn add_kernel<C: Config>(
+    a_shape: &[usize],
+    b_shape: &[usize],
+) -> Result<KernelPrimitive<C>, Error> {
+    broadcast_kernel(a_shape, b_shape, |api, a, b| api.add(a, b))
+}
+
+pub fn mul_kernel<C: Config>(
+    a_shape: &[usize],
+    b_shape: &[usize],
+) -> Result<KernelPrimitive<C>, Error> {
+    broadcast_kernel(a_shape, b_shape, |api, a, b| api.mul(a, b))
+}
+
+// Folds the elements along axis with f, the output has the shape without that axis
+pub fn reduce_kernel<C, F>(shape: &[usize], axis: usize, f: F) -> Result<KernelPrimitive<C>, Error>
+where
+    C: Config,
+    F: Fn(&mut API<C>, Variable, Variable) -> Variable,
+{
+    let (outer, len, inner) = split_axis(shape, axis)?;
+    let mut out_shape = shape.to_vec();
+    out_shape.remove(axis);
+    compile_with_spec_and_shapes(
+        |api: &mut API<C>, io: &mut Vec<Vec<Variable>>| {
+            for o in 0..outer {
+                for i in 0..inner {
+                    let at = |j: usize| (o * len + j) * inner + i;
+                    let mut acc = io[0][at(0)];
+                    for j in 1..len {
+                        acc = f(api, acc, io[0][at(j)]);
+                    }
+                    io[1][o * inner + i] = acc;
+                }
+            }
+        },
+        &[input_spec(shape), output_spec(&out_shape)],
+        &[shape.to_vec(), out_shape],
+    )
+}
+
+pub fn reduce_sum_kernel<C: Config>(
+    shape: &[usize],
+    axis: usize,
+) -> Result<KernelPrimitive<C>, Error> {
+    reduce_kernel(shape, axis, |api, a, b| api.add(a, b))
+}
+
+pub fn reduce_product_kernel<C: Config>(
+    shape: &[usize],
+    axis: usize,
+) -> Result<KernelPrimitive<C>, Error> {
+    reduce_kernel(shape, axis, |api, a, b| api.mul(a, b))
+}
+
+// Inclusive scan along axis with f: the j-th output is the fold of the first j + 1 elements
+pub fn scan_kernel<C, F>(shape: &[usize], axis: usize, f: F) -> Result<KernelPrimitive<C>, Error>
+where
+    C: Config,
+    F: Fn(&mut API<C>, Variable, Variable) -> Variable,
+{
+    let (outer, len, inner) = split_axis(shape, axis)?;
+    compile_with_spec_and_shapes(
+        |api: &mut API<C>, io: &mut Vec<Vec<Variable>>| {
+            for o in 0..outer {
+                for i in 0..inner {
+                    let at = |j: usize| (o * len + j) * inner + i;
+                    let mut acc = io[0][at(0)];
+                    io[1][at(0)] = acc;
+                    for j in 1..len {
+                        acc = f(api, acc, io[0][at(j)]);
+                        io[1][at(j)] = acc;
+                    }
+                }
+            }
+        },
+        &[input_spec(shape), output_spec(shape)],
+        &[shape.to_vec(), shape.to_vec()],
+    )
+}
+
+pub fn prefix_sum_kernel<C: Config>(
+    shape: &[usize],
+    axis: usize,
+) -> Result<KernelPrimitive<C>, Error> {
+    scan_kernel(shape, axis, |api, a, b| api.add(a, b))
+}
+
+pub fn prefix_product_kernel<C: Config>(
+    shape: &[usize],
+    axis: usize,
+) -> Result<KernelPrimitive<C>, Error> {
+    scan_kernel(shape, axis, |api, a, b| api.mul(a, b))
+}
+
+// A tile of a matrix product: [m, k] times [k, n] into [m, n]. Larger products run one tile
+// per instance, with the partial products of the tiles along k added by reduce_sum_kernel.
+pub fn matmul_kernel<C: Config>(m: usize, k: usize, n: usize) -> Result<KernelPrimitive<C>, Error> {
+    check_shape(&[m, k, n])?;
+    compile_with_spec_and_shapes(
+        |api: &mut API<C>, io: &mut Vec<Vec<Variable>>| {
+            for i in 0..m {
+                for j in 0..n {
+                    let mut acc = api.mul(io[0][i * k], io[1][j]);
+                    for t in 1..k {
+                        let p = api.mul(io[0][i * k + t], io[1][t * n + j]);
+                        acc = api.add(acc, p);
+                    }
+                    io[2][i * n + j] = acc;
+                }
+            }
+        },
+        &[
+            input_spec(&[m, k]),
+            input_spec(&[k, n]),
+            output_spec(&[m, n]),
+        ],
+        &[vec![m, k], vec![k, n], vec![m, n]],
+    )
+}
+
+// The dot product of two vectors of len elements, into a scalar
+pub fn dot_kernel<C: Config>(len: usize) -> Result<KernelPrimitive<C>, Error> {
+    check_shape(&[len])?;
+    compile_with_spec_and_shapes(
+        |api: &mut API<C>, io: &mut Vec<Vec<Variable>>| {
+            let mut acc = api.mul(io[0][0], io[1][0]);
+            for i in 1..len {
+                let p = api.mul(io[0][i], io[1][i]);
+                acc = api.add(acc, p);
+            }
+            io[2][0] = acc;
+        },
+        &[input_spec(&[len]), input_spec(&[len]), output_spec(&[])],
+        &[vec![len], vec![len], vec![]],
+    )
+}
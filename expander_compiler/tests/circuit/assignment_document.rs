@@ -0,0 +1,125 @@
+use expander_compiler::frontend::{
+    extra::{assignment_from_json, assignment_to_json},
+    *,
+};
+
+declare_circuit!(Circuit {
+    x: [[Variable; 2]; 2],
+    y: PublicVariable,
+    z: [Variable],
+});
+
+impl<C: Config> Define<C> for Circuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        let mut sum = builder.constant(0);
+        for x in self.x.iter().flatten().chain(self.z.iter()) {
+            sum = builder.add(sum, x);
+        }
+        builder.assert_is_equal(sum, self.y);
+    }
+}
+
+#[derive(Circuit, Clone, Copy, Default)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Circuit, Clone, Default)]
+pub struct Segment<T> {
+    pub ends: (Point<T>, Point<T>),
+    #[public]
+    pub length2: T,
+    pub label: u64,
+}
+
+#[test]
+fn test_declared_circuit_document() {
+    let circuit = Circuit::<Variable> {
+        z: vec![Variable::default(); 3],
+        ..Default::default()
+    };
+    let compile_result = compile::<M31Config, _>(&circuit, CompileOptions::default()).unwrap();
+    let run = |json: &str| {
+        let witness = compile_result
+            .witness_solver
+            .solve_witness_from_json::<Circuit<M31>>(json)
+            .unwrap();
+        compile_result.layered_circuit.run(&witness)[0]
+    };
+    // 1 + 2 + 3 + 4 + 5 + 6 + (p - 6) = 15
+    assert!(run(
+        r#"{"x": [[1, "2"], ["0x3", "0X4"]], "y": "15", "z": [5, "6", "-6"]}"#
+    ));
+    assert!(!run(r#"{"x": [[1, 2], [3, 4]], "y": 16, "z": [5, 6, -6]}"#));
+
+    let assignment: Circuit<M31> =
+        assignment_from_json(r#"{"x": [[1, 2], [3, 4]], "y": 15, "z": [5]}"#).unwrap();
+    assert_eq!(assignment.x[1][0], M31::from(3));
+    assert_eq!(assignment.z, vec![M31::from(5)]);
+    let json = assignment_to_json(&assignment);
+    let reloaded: Circuit<M31> = assignment_from_json(&json).unwrap();
+    assert_eq!(reloaded.x, assignment.x);
+    assert_eq!(reloaded.y, assignment.y);
+    assert_eq!(reloaded.z, assignment.z);
+}
+
+fn load_error<A: SerdeAssignment>(json: &str) -> String {
+    match assignment_from_json::<A>(json) {
+        Err(Error::UserError(msg)) => msg,
+        Err(e) => panic!("unexpected error {e:?}"),
+        Ok(_) => panic!("{json} should not load"),
+    }
+}
+
+#[test]
+fn test_document_errors() {
+    assert_eq!(
+        load_error::<Circuit<M31>>(r#"{"x": [[1, 2], [3]], "y": 0, "z": []}"#),
+        "x[1]: expected an array of 2 elements, got 1"
+    );
+    assert_eq!(
+        load_error::<Circuit<M31>>(r#"{"x": [[1, 2], [3, 4]], "z": []}"#),
+        "y: missing field"
+    );
+    assert_eq!(
+        load_error::<Circuit<M31>>(r#"{"x": [[1, 2], [3, 4]], "y": 0, "z": [], "w": 0}"#),
+        "w: unknown field"
+    );
+    assert_eq!(
+        load_error::<Circuit<M31>>(r#"{"x": [[1, 2], [3, 4]], "y": "2147483647", "z": []}"#),
+        "y: \"2147483647\" is not below the field modulus 2147483647"
+    );
+    assert_eq!(
+        load_error::<Circuit<M31>>(r#"{"x": [[1, 2], [3, 4]], "y": 0, "z": [true]}"#),
+        "z[0]: expected a field element, got true"
+    );
+    assert_eq!(
+        load_error::<Segment<M31>>(
+            r#"{"ends": [{"x": 0, "y": 0}, {"x": 1}], "length2": 1, "label": 7}"#
+        ),
+        "ends[1].y: missing field"
+    );
+    assert!(load_error::<Segment<M31>>(
+        r#"{"ends": [{"x": 0, "y": 0}, {"x": 1, "y": 1}], "length2": 1, "label": "a"}"#
+    )
+    .starts_with("label: "));
+}
+
+#[test]
+fn test_derived_circuit_document() {
+    let segment: Segment<M31> = assignment_from_json(
+        r#"{"ends": [{"x": "0x10", "y": 2}, {"x": "-1", "y": "3"}], "length2": 5, "label": 7}"#,
+    )
+    .unwrap();
+    assert_eq!(segment.ends.0.x, M31::from(16));
+    assert_eq!(segment.ends.1.x, -M31::one());
+    assert_eq!(segment.label, 7);
+
+    let doc = segment.to_document();
+    assert_eq!(doc["ends"][1]["x"], "2147483646");
+    assert_eq!(doc["label"], 7);
+    let reloaded = Segment::<M31>::from_document(&doc, "").unwrap();
+    assert_eq!(reloaded.ends.1.y, segment.ends.1.y);
+    assert_eq!(reloaded.length2, segment.length2);
+}
@@ -0,0 +1,107 @@
+use expander_compiler::frontend::*;
+
+declare_circuit!(Circuit {
+    sum: PublicVariable,
+    x: [Variable; 2],
+});
+
+impl Define<M31Config> for Circuit<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, builder: &mut Builder) {
+        let sum = builder.add(self.x[0], self.x[1]);
+        let sum = builder.mul(sum, 3);
+        builder.assert_is_equal(sum, self.sum);
+    }
+}
+
+fn cache_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("ecc_cache_{name}_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_compile_cache_reuse() {
+    let cache = ArtifactCache::new(cache_dir("reuse"));
+    let options = CompileOptions::default().with_cache(cache.clone());
+    let first = compile(&Circuit::default(), options.clone()).unwrap();
+    assert!(cache.size().unwrap() > 0);
+    let second = compile(&Circuit::default(), options).unwrap();
+    assert_eq!(first.witness_solver.circuit, second.witness_solver.circuit);
+    assert_eq!(first.layered_circuit, second.layered_circuit);
+
+    let assignment = Circuit::<M31> {
+        sum: M31::from(9),
+        x: [M31::from(1), M31::from(2)],
+    };
+    let witness = second.witness_solver.solve_witness(&assignment).unwrap();
+    assert_eq!(second.layered_circuit.run(&witness), vec![true]);
+
+    cache.clear().unwrap();
+    assert_eq!(cache.size().unwrap(), 0);
+    let _ = std::fs::remove_dir_all(cache.dir());
+}
+
+#[test]
+fn test_compile_cache_size_limit() {
+    let cache = ArtifactCache::new(cache_dir("limit"));
+    let options = CompileOptions::default().with_cache(cache.clone());
+    compile(&Circuit::default(), options.clone()).unwrap();
+    let size = cache.size().unwrap();
+    assert!(size > 0);
+
+    // a second entry does not fit next to the first one, so the first is evicted
+    let cache = cache.with_max_size(size + size / 2);
+    let options = options.with_cache(cache.clone()).with_opt_level(1);
+    compile(&Circuit::default(), options).unwrap();
+    assert!(cache.size().unwrap() <= cache.max_size());
+
+    // the first entry was the least recently used one
+    let options = CompileOptions::default().with_cache(cache.clone());
+    let res = compile(&Circuit::default(), options).unwrap();
+    assert!(!res.report.from_cache);
+    let _ = std::fs::remove_dir_all(cache.dir());
+}
+
+declare_circuit!(WiringA { x: [Variable; 3] });
+
+declare_circuit!(WiringB { x: [Variable; 3] });
+
+#[allow(clippy::ptr_arg)]
+fn gadget<C: Config, B: RootAPI<C>>(builder: &mut B, inputs: &Vec<Variable>) -> Vec<Variable> {
+    let t = builder.mul(inputs[0], inputs[1]);
+    vec![builder.add(t, inputs[0])]
+}
+
+impl Define<M31Config> for WiringA<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, builder: &mut Builder) {
+        let y = builder.memorized_simple_call(gadget, &self.x[..2]);
+        builder.assert_is_equal(y[0], self.x[2]);
+    }
+}
+
+impl Define<M31Config> for WiringB<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, builder: &mut Builder) {
+        let y = builder.memorized_simple_call(gadget, &self.x[..2]);
+        let z = builder.add(y[0], self.x[0]);
+        builder.assert_is_equal(z, self.x[2]);
+    }
+}
+
+#[test]
+fn test_compile_cache_sub_circuit_reuse() {
+    let cache = ArtifactCache::new(cache_dir("sub"));
+    let options = CompileOptions::default().with_cache(cache.clone());
+    let a = compile(&WiringA::default(), options.clone()).unwrap();
+    assert_eq!(a.report.reused_sub_circuits, 0);
+    assert_eq!(a.report.reused_layered_sub_circuits, 0);
+    let b = compile(&WiringB::default(), options).unwrap();
+    assert!(!b.report.from_cache);
+    assert_eq!(b.report.reused_sub_circuits, 1);
+
+    let assignment = WiringB::<M31> {
+        x: [M31::from(2), M31::from(3), M31::from(10)],
+    };
+    let witness = b.witness_solver.solve_witness(&assignment).unwrap();
+    assert_eq!(b.layered_circuit.run(&witness), vec![true]);
+    let _ = std::fs::remove_dir_all(cache.dir());
+}
@@ -0,0 +1,169 @@
+use arith::{Field, SimdField};
+use expander_binary::executor;
+use expander_compiler::frontend::{
+    extra::{debug_eval, EmptyHintCaller, EXPANDER_POW5_GATE_TYPE},
+    *,
+};
+use gkr_engine::MPIConfig;
+
+declare_circuit!(SboxCircuit {
+    x: [Variable; 4],
+    y: [Variable; 4],
+});
+
+// The degree 5 S-box of Poseidon over M31, one gate per element
+impl Define<M31Config> for SboxCircuit<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, builder: &mut Builder) {
+        for i in 0..4 {
+            let x5 = builder.custom_gate(EXPANDER_POW5_GATE_TYPE, &[self.x[i]]);
+            builder.assert_is_equal(x5, self.y[i]);
+        }
+    }
+}
+
+fn sbox_assignment(seed: u32) -> SboxCircuit<M31> {
+    let mut res = SboxCircuit::<M31>::default();
+    for i in 0..4 {
+        let x = M31::from(seed * 7 + i as u32 + 1);
+        res.x[i] = x;
+        res.y[i] = x * x * x * x * x;
+    }
+    res
+}
+
+#[test]
+fn test_custom_gate_sbox_m31() {
+    let compile_result = compile(&SboxCircuit::default(), CompileOptions::default()).unwrap();
+    let lc = &compile_result.layered_circuit;
+    let num_customs: usize = lc.segments.iter().map(|s| s.gate_customs.len()).sum();
+    assert_eq!(num_customs, 4);
+
+    let n_witnesses = SIMDField::<M31Config>::PACK_SIZE;
+    let mut assignments: Vec<_> = (0..n_witnesses as u32).map(sbox_assignment).collect();
+    let output =
+        debug_eval::<M31Config, _, _, _>(&SboxCircuit::default(), &assignments[0], EmptyHintCaller);
+    assert!(output.is_empty());
+    assignments[1].y[2] += M31::ONE;
+    let witness = compile_result
+        .witness_solver
+        .solve_witnesses(&assignments)
+        .unwrap();
+    let output = lc.run(&witness);
+    for (i, x) in output.iter().enumerate() {
+        assert_eq!(*x, i != 1);
+    }
+
+    // the gate is exported as Expander's own pow5 uni gate
+    CustomGateRegistry::validate_export(lc).unwrap();
+    let assignments: Vec<_> = (0..n_witnesses as u32).map(sbox_assignment).collect();
+    let witness = compile_result
+        .witness_solver
+        .solve_witnesses(&assignments)
+        .unwrap();
+    let mut expander_circuit = lc.export_to_expander_flatten().unwrap();
+    let mpi_config = MPIConfig::prover_new();
+    let (simd_input, simd_public_input) = witness.to_simd();
+    expander_circuit.layers[0].input_vals = simd_input;
+    expander_circuit.public_input = simd_public_input;
+    expander_circuit.evaluate();
+    let (claimed_v, proof) =
+        executor::prove::<M31Config>(&mut expander_circuit, mpi_config.clone());
+    assert!(executor::verify::<M31Config>(
+        &mut expander_circuit,
+        mpi_config,
+        &proof,
+        &claimed_v
+    ));
+}
+
+// x^3 + y, a gate only this test knows about
+const CUBE_ADD_GATE_TYPE: usize = 0xc0be;
+
+struct CubeAddGate;
+
+impl CustomGate<M31> for CubeAddGate {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn degree(&self) -> usize {
+        3
+    }
+
+    fn eval(&self, inputs: &[M31]) -> M31 {
+        inputs[0] * inputs[0] * inputs[0] + inputs[1]
+    }
+}
+
+// x^7, over the degree Expander supports
+struct Pow7Gate;
+
+impl CustomGate<M31> for Pow7Gate {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn degree(&self) -> usize {
+        7
+    }
+
+    fn eval(&self, inputs: &[M31]) -> M31 {
+        let x2 = inputs[0] * inputs[0];
+        x2 * x2 * x2 * inputs[0]
+    }
+}
+
+declare_circuit!(CubeAddCircuit {
+    x: Variable,
+    y: Variable,
+    z: Variable,
+});
+
+impl Define<M31Config> for CubeAddCircuit<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, builder: &mut Builder) {
+        let t = builder.custom_gate(CUBE_ADD_GATE_TYPE, &[self.x, self.y]);
+        builder.assert_is_equal(t, self.z);
+    }
+}
+
+#[test]
+fn test_custom_gate_registered() {
+    let compile_result = compile(&CubeAddCircuit::default(), CompileOptions::default()).unwrap();
+    let solver = &compile_result.witness_solver;
+    let assignment = CubeAddCircuit::<M31> {
+        x: M31::from(3),
+        y: M31::from(4),
+        z: M31::from(31),
+    };
+    assert!(CustomGateRegistry::validate_against(&solver.circuit).is_err());
+    assert!(solver.solve_witness(&assignment).is_err());
+    assert!(CustomGateRegistry::eval(CUBE_ADD_GATE_TYPE, &[M31::from(3), M31::from(4)]).is_err());
+    // unregistered types are exported unchanged
+    CustomGateRegistry::validate_export(&compile_result.layered_circuit).unwrap();
+
+    CustomGateRegistry::register(CUBE_ADD_GATE_TYPE, CubeAddGate);
+    assert!(CustomGateRegistry::try_register(CUBE_ADD_GATE_TYPE, CubeAddGate).is_err());
+    assert!(
+        CustomGateRegistry::try_register::<M31, _>(EXPANDER_POW5_GATE_TYPE, extra::Pow5Gate)
+            .is_err()
+    );
+    CustomGateRegistry::validate_against(&solver.circuit).unwrap();
+
+    let witness = solver.solve_witness(&assignment).unwrap();
+    assert_eq!(compile_result.layered_circuit.run(&witness), vec![true]);
+    let assignment = CubeAddCircuit::<M31> {
+        x: M31::from(3),
+        y: M31::from(4),
+        z: M31::from(30),
+    };
+    let witness = solver.solve_witness(&assignment).unwrap();
+    assert_eq!(compile_result.layered_circuit.run(&witness), vec![false]);
+    assert!(CustomGateRegistry::expander_gate_type::<M31>(CUBE_ADD_GATE_TYPE).is_err());
+    // the gate has two inputs, so it cannot become a uni gate of Expander
+    assert!(CustomGateRegistry::validate_export(&compile_result.layered_circuit).is_err());
+    assert!(compile_result
+        .layered_circuit
+        .export_to_expander_flatten()
+        .is_err());
+    assert!(CustomGateRegistry::try_register(0xc0bf, Pow7Gate).is_err());
+}
@@ -0,0 +1,87 @@
+use expander_compiler::frontend::{
+    extra::{debug_eval, EmptyHintCaller},
+    internal::DumpLoadTwoVariables,
+    *,
+};
+
+/// A point on y^2 = x^3 + 1
+#[derive(Circuit, Clone, Copy, Default)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+/// Checks that every point is on the curve and that their coordinates sum up to `sum`
+#[derive(Circuit, Clone)]
+pub struct OnCurveCircuit<T, const N: usize> {
+    pub points: [Point<T>; N],
+    #[public]
+    pub sum: Point<T>,
+}
+
+// derive(Default) doesn't cover arrays of any length
+impl<T: Default + Copy, const N: usize> Default for OnCurveCircuit<T, N> {
+    fn default() -> Self {
+        Self {
+            points: [Point::default(); N],
+            sum: Point::default(),
+        }
+    }
+}
+
+impl<C: Config, const N: usize> Define<C> for OnCurveCircuit<Variable, N> {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        let mut sum_x = builder.constant(0);
+        let mut sum_y = builder.constant(0);
+        for p in self.points.iter() {
+            let y2 = builder.mul(p.y, p.y);
+            let x3 = builder.mul(p.x, p.x);
+            let x3 = builder.mul(x3, p.x);
+            let rhs = builder.add(x3, 1);
+            builder.assert_is_equal(y2, rhs);
+            sum_x = builder.add(sum_x, p.x);
+            sum_y = builder.add(sum_y, p.y);
+        }
+        builder.assert_is_equal(sum_x, self.sum.x);
+        builder.assert_is_equal(sum_y, self.sum.y);
+    }
+}
+
+fn assignment(points: [(u32, u32); 2]) -> OnCurveCircuit<M31, 2> {
+    let points = points.map(|(x, y)| Point {
+        x: M31::from(x),
+        y: M31::from(y),
+    });
+    let sum = Point {
+        x: points[0].x + points[1].x,
+        y: points[0].y + points[1].y,
+    };
+    OnCurveCircuit { points, sum }
+}
+
+#[test]
+fn test_derive_circuit() {
+    let compile_result = compile::<M31Config, _>(
+        &OnCurveCircuit::<Variable, 2>::default(),
+        CompileOptions::default(),
+    )
+    .unwrap();
+    let run = |a: &OnCurveCircuit<M31, 2>| {
+        let witness = compile_result.witness_solver.solve_witness(a).unwrap();
+        compile_result.layered_circuit.run(&witness)[0]
+    };
+    let good = assignment([(0, 1), (2, 3)]);
+    assert!(run(&good));
+    debug_eval::<M31Config, _, _, _>(
+        &OnCurveCircuit::<Variable, 2>::default(),
+        &good,
+        EmptyHintCaller,
+    );
+    assert!(!run(&assignment([(0, 1), (2, 4)])));
+
+    // the sum is the only public input
+    let mut bad = good.clone();
+    bad.sum.y += M31::one();
+    assert!(!run(&bad));
+    assert_eq!(good.num_vars(), (4, 2));
+}
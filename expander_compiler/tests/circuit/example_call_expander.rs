@@ -44,7 +44,10 @@ fn example<C: Config>() {
         assert!(*x);
     }
 
-    let mut expander_circuit = compile_result.layered_circuit.export_to_expander_flatten();
+    let mut expander_circuit = compile_result
+        .layered_circuit
+        .export_to_expander_flatten()
+        .unwrap();
 
     let mpi_config = MPIConfig::prover_new();
 
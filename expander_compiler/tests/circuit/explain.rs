@@ -0,0 +1,112 @@
+use expander_compiler::frontend::{extra::Origin, *};
+
+declare_circuit!(Circuit {
+    x: Variable,
+    y: Variable,
+});
+
+impl<C: Config> Define<C> for Circuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        let sq = builder.mul(self.x, self.x);
+        builder.assert_is_equal(sq, self.y);
+        builder.assert_is_non_zero(self.x);
+    }
+}
+const EQUAL_LINE: u32 = line!() - 4;
+const NON_ZERO_LINE: u32 = line!() - 4;
+
+#[test]
+fn test_explain_m31() {
+    let compile_result = compile::<M31Config, _>(
+        &Circuit::default(),
+        CompileOptions::default().with_source_map(),
+    )
+    .unwrap();
+    let lc = &compile_result.layered_circuit;
+    let solver = &compile_result.witness_solver;
+
+    let assignment = Circuit::<M31> {
+        x: M31::from(3),
+        y: M31::from(9),
+    };
+    assert!(solver.explain(&assignment, lc).unwrap().is_empty());
+
+    let assignment = Circuit::<M31> {
+        x: M31::from(3),
+        y: M31::from(10),
+    };
+    let violations = solver.explain(&assignment, lc).unwrap();
+    assert_eq!(violations.len(), 1);
+    let violation = &violations[0];
+    assert_eq!(violation.witness_index, 0);
+    assert_eq!(violation.layer, lc.layer_ids.len());
+    assert_eq!(violation.gate_index, 0);
+    assert_eq!(violation.sources.len(), 1);
+    let source = &violation.sources[0];
+    assert!(!source.value.is_zero());
+    assert!(matches!(source.origins[..], [Origin::Constraint(_)]));
+    assert_eq!(source.locations[0].line(), EQUAL_LINE);
+    assert!(violation
+        .to_string()
+        .starts_with("witness 0: output gate 0 of layer"));
+}
+
+#[test]
+fn test_explain_gf2() {
+    let compile_result = compile::<GF2Config, _>(
+        &Circuit::default(),
+        CompileOptions::default().with_source_map(),
+    )
+    .unwrap();
+    let lc = &compile_result.layered_circuit;
+    let solver = &compile_result.witness_solver;
+
+    // GF2 exports every constraint as its own output
+    let assignment = Circuit::<GF2> {
+        x: GF2::from(0),
+        y: GF2::from(1),
+    };
+    let violations = solver.explain(&assignment, lc).unwrap();
+    assert_eq!(violations.len(), 2);
+    assert_ne!(violations[0].gate_index, violations[1].gate_index);
+    // which constraint went to which output is not known, failed_constraints lists them
+    for violation in violations.iter() {
+        assert_eq!(violation.value, GF2::from(1));
+        assert!(violation.sources.is_empty());
+    }
+    let witness = solver.solve_witness(&assignment).unwrap();
+    let mut lines: Vec<u32> = lc
+        .failed_constraints(&witness)
+        .unwrap()
+        .iter()
+        .map(|failure| failure.locations[0].line())
+        .collect();
+    lines.sort();
+    assert_eq!(lines, vec![EQUAL_LINE, NON_ZERO_LINE]);
+
+    // only the slot of the equality fails now
+    let assignment = Circuit::<GF2> {
+        x: GF2::from(1),
+        y: GF2::from(0),
+    };
+    let violations = solver.explain(&assignment, lc).unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].sources.len(), 1);
+    assert_eq!(violations[0].sources[0].locations[0].line(), EQUAL_LINE);
+}
+
+#[test]
+fn test_explain_without_source_map() {
+    let compile_result =
+        compile::<M31Config, _>(&Circuit::default(), CompileOptions::default()).unwrap();
+    let assignment = Circuit::<M31> {
+        x: M31::from(0),
+        y: M31::from(1),
+    };
+    let violations = compile_result
+        .witness_solver
+        .explain(&assignment, &compile_result.layered_circuit)
+        .unwrap();
+    assert_eq!(violations.len(), 1);
+    assert!(violations[0].sources.is_empty());
+}
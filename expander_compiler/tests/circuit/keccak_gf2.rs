@@ -312,6 +312,7 @@ fn keccak_gf2_main() {
     let CompileResult {
         witness_solver,
         layered_circuit,
+        ..
     } = compile_result;
     keccak_gf2_test(witness_solver, layered_circuit, "gf2");
 }
@@ -323,6 +324,7 @@ fn keccak_gf2_main_cross_layer() {
     let CompileResultCrossLayer {
         witness_solver,
         layered_circuit,
+        ..
     } = compile_result;
     keccak_gf2_test(witness_solver, layered_circuit, "gf2_cross_layer");
 }
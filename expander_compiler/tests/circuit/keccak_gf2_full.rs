@@ -231,6 +231,7 @@ fn keccak_gf2_full() {
     let CompileResult {
         witness_solver,
         layered_circuit,
+        ..
     } = compile_result;
 
     let mut assignment = Keccak256Circuit::<GF2>::default();
@@ -285,7 +286,7 @@ fn keccak_gf2_full() {
     println!("test 3 passed");
 
     // alternatively, you can specify the particular config like gkr_field_config::GF2ExtConfig
-    let mut expander_circuit = layered_circuit.export_to_expander_flatten();
+    let mut expander_circuit = layered_circuit.export_to_expander_flatten().unwrap();
 
     let (simd_input, simd_public_input) = witness.to_simd();
     println!("{} {}", simd_input.len(), simd_public_input.len());
@@ -236,6 +236,7 @@ fn keccak_gf2_full_crosslayer() {
     let CompileResultCrossLayer {
         witness_solver,
         layered_circuit,
+        ..
     } = compile_result;
 
     let mut assignment = Keccak256Circuit::<GF2>::default();
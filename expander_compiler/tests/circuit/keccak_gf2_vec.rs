@@ -231,6 +231,7 @@ fn keccak_gf2_vec() {
     let CompileResult {
         witness_solver,
         layered_circuit,
+        ..
     } = compile_result;
 
     let mut assignment = Keccak256Circuit::<GF2>::default();
@@ -294,6 +294,7 @@ fn keccak_big_field<C: Config, const N_WITNESSES: usize>(field_name: &str) {
     let CompileResult {
         witness_solver,
         layered_circuit,
+        ..
     } = compile_result;
 
     let mut assignment = Keccak256Circuit::<CircuitField<C>>::default();
@@ -1,5 +1,10 @@
+mod assignment_document;
+mod compile_cache;
+mod custom_gate;
+mod derive_circuit;
 mod example;
 mod example_call_expander;
+mod explain;
 mod keccak_gf2;
 mod keccak_gf2_full;
 mod keccak_gf2_full_crosslayer;
@@ -9,8 +14,12 @@ mod keccak_non_gf2;
 mod mul_fanout_limit;
 mod multithreading_witness;
 
+mod range_check;
 mod simple_add_m31;
+mod select_mux;
+mod source_map;
 mod sub_circuit_macro;
 mod to_binary_builtin;
 mod to_binary_hint;
 mod to_binary_unconstrained_api;
+mod typed_uint;
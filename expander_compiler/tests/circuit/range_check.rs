@@ -0,0 +1,122 @@
+use expander_compiler::frontend::{
+    extra::{debug_eval, EmptyHintCaller},
+    *,
+};
+
+declare_circuit!(Circuit {
+    xs: [Variable; 32],
+    a: Variable,
+    b: Variable,
+    cmp: Variable,
+    lt: Variable,
+});
+
+// Range checks inside sub circuits are batched with the ones of the root
+#[memorized]
+fn checked_inc<C: Config, B: RootAPI<C>>(api: &mut B, x: Variable) -> Variable {
+    api.assert_range(x, 10);
+    api.add(x, 1)
+}
+
+impl<C: Config> Define<C> for Circuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        for x in self.xs.iter() {
+            builder.assert_range(x, 12);
+        }
+        for x in self.xs.iter().take(4) {
+            let y = checked_inc(builder, *x);
+            builder.assert_range(y, 11);
+        }
+        let cmp = builder.cmp_bounded(self.a, self.b, 16);
+        builder.assert_is_equal(cmp, self.cmp);
+        let lt = builder.lt(self.a, self.b, 16);
+        builder.assert_is_equal(lt, self.lt);
+        let leq = builder.leq(self.a, self.a, 16);
+        builder.assert_is_equal(leq, 1);
+
+        // constant operands are checked at compile time
+        builder.assert_range(255, 8);
+        let cmp = builder.cmp_bounded(3, 5, 4);
+        let t = builder.add(cmp, 1);
+        builder.assert_is_zero(t);
+        let lt = builder.lt(5, 3, 4);
+        builder.assert_is_zero(lt);
+    }
+}
+
+fn assignment(a: u32, b: u32) -> Circuit<M31> {
+    let mut res = Circuit::<M31>::default();
+    for (i, x) in res.xs.iter_mut().enumerate() {
+        *x = M31::from(i as u32 * 127);
+    }
+    res.a = M31::from(a);
+    res.b = M31::from(b);
+    res.cmp = match a.cmp(&b) {
+        std::cmp::Ordering::Greater => M31::one(),
+        std::cmp::Ordering::Equal => M31::zero(),
+        std::cmp::Ordering::Less => -M31::one(),
+    };
+    res.lt = M31::from((a < b) as u32);
+    res
+}
+
+#[test]
+fn test_range_check_batched() {
+    let compile_result =
+        compile::<M31Config, _>(&Circuit::default(), CompileOptions::default()).unwrap();
+    let run = |a: &Circuit<M31>| {
+        let witness = compile_result.witness_solver.solve_witness(a).unwrap();
+        compile_result.layered_circuit.run(&witness)[0]
+    };
+    for (a, b) in [(0, 0), (1, 2), (2, 1), (65535, 0), (1234, 1234)] {
+        let assignment = assignment(a, b);
+        assert!(run(&assignment));
+        debug_eval::<M31Config, _, _, _>(&Circuit::default(), &assignment, EmptyHintCaller);
+    }
+
+    let mut a = assignment(1, 2);
+    a.cmp = M31::one();
+    assert!(!run(&a));
+
+    // out of range values in the root and in a sub circuit
+    let mut a = assignment(1, 2);
+    a.xs[10] = M31::from(4096);
+    assert!(!run(&a));
+    let mut a = assignment(1, 2);
+    a.xs[1] = M31::from(1024);
+    assert!(!run(&a));
+    let mut a = assignment(1, 2);
+    a.xs[1] = -M31::one();
+    assert!(!run(&a));
+    assert!(!run(&assignment(65536, 2)));
+}
+
+declare_circuit!(SmallCircuit { x: Variable });
+
+impl<C: Config> Define<C> for SmallCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        builder.assert_range(self.x, 8);
+    }
+}
+
+fn run_single(x: u32) -> Result<bool, Error> {
+    let compile_result =
+        compile::<M31Config, _>(&SmallCircuit::default(), CompileOptions::default()).unwrap();
+    let assignment = SmallCircuit::<M31> { x: M31::from(x) };
+    let witness = compile_result.witness_solver.solve_witness(&assignment)?;
+    Ok(compile_result.layered_circuit.run(&witness)[0])
+}
+
+// a single range check is lowered to a bit decomposition
+#[test]
+fn test_range_check_single() {
+    assert!(run_single(0).unwrap());
+    assert!(run_single(255).unwrap());
+}
+
+// like to_binary, the decomposition hint can't solve values out of range
+#[test]
+fn test_range_check_single_out_of_range() {
+    assert!(run_single(256).is_err());
+    assert!(run_single(1 << 20).is_err());
+}
@@ -0,0 +1,75 @@
+use expander_compiler::frontend::{
+    extra::{debug_eval, EmptyHintCaller},
+    *,
+};
+
+declare_circuit!(Circuit {
+    cond: Variable,
+    sel: [Variable; 2],
+    index: Variable,
+    values: [Variable; 4],
+    selected: Variable,
+    muxed: Variable,
+    looked_up: Variable,
+});
+
+impl<C: Config> Define<C> for Circuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        let selected = builder.select(self.cond, self.values[0], self.values[1]);
+        builder.assert_is_equal(selected, self.selected);
+        let muxed = builder.mux(&self.sel, &self.values);
+        builder.assert_is_equal(muxed, self.muxed);
+        let looked_up = builder.lookup(self.index, &self.values);
+        builder.assert_is_equal(looked_up, self.looked_up);
+
+        // constant selectors and indices are folded away
+        let one = builder.constant(1);
+        let x = builder.select(one, self.values[2], self.values[3]);
+        builder.assert_is_equal(x, self.values[2]);
+        let x = builder.lookup(3, &self.values);
+        builder.assert_is_equal(x, self.values[3]);
+    }
+}
+
+fn assignment(cond: u32, sel: [u32; 2], index: u32) -> Circuit<M31> {
+    let values = [10u32, 11, 12, 13].map(M31::from);
+    let pick = |i: u32| values.get(i as usize).copied().unwrap_or_default();
+    Circuit::<M31> {
+        cond: M31::from(cond),
+        sel: sel.map(M31::from),
+        index: M31::from(index),
+        values,
+        selected: if cond == 1 { values[0] } else { values[1] },
+        muxed: pick(sel[0] + 2 * sel[1]),
+        looked_up: pick(index),
+    }
+}
+
+#[test]
+fn test_select_mux_lookup() {
+    let compile_result =
+        compile::<M31Config, _>(&Circuit::default(), CompileOptions::default()).unwrap();
+    let run = |a: &Circuit<M31>| {
+        let witness = compile_result.witness_solver.solve_witness(a).unwrap();
+        compile_result.layered_circuit.run(&witness)[0]
+    };
+    for cond in 0..2 {
+        for s0 in 0..2 {
+            for s1 in 0..2 {
+                for index in 0..4 {
+                    let a = assignment(cond, [s0, s1], index);
+                    assert!(run(&a));
+                    debug_eval::<M31Config, _, _, _>(&Circuit::default(), &a, EmptyHintCaller);
+                }
+            }
+        }
+    }
+    let mut a = assignment(1, [1, 0], 2);
+    a.muxed = M31::from(10);
+    assert!(!run(&a));
+
+    // non boolean selectors and out of range indices are rejected
+    assert!(!run(&assignment(2, [0, 0], 0)));
+    assert!(!run(&assignment(0, [0, 2], 0)));
+    assert!(!run(&assignment(0, [0, 0], 4)));
+}
@@ -0,0 +1,88 @@
+use expander_compiler::{circuit::layered::witness::Witness, frontend::*};
+
+declare_circuit!(Circuit {
+    x: Variable,
+    y: Variable,
+});
+
+#[allow(clippy::ptr_arg)]
+fn check_square<C: Config, B: RootAPI<C>>(
+    builder: &mut B,
+    inputs: &Vec<Variable>,
+) -> Vec<Variable> {
+    let sq = builder.mul(inputs[0], inputs[0]);
+    builder.assert_is_equal(sq, inputs[1]);
+    vec![sq]
+}
+const ASSERT_LINE: u32 = line!() - 3;
+
+impl Define<M31Config> for Circuit<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, builder: &mut Builder) {
+        builder.memorized_simple_call(check_square, &[self.x, self.y]);
+        builder.assert_is_non_zero(self.x);
+    }
+}
+const CALL_LINE: u32 = line!() - 4;
+const NON_ZERO_LINE: u32 = line!() - 4;
+
+fn solve(compile_result: &CompileResult<M31Config>, x: u32, y: u32) -> Witness<M31Config> {
+    let assignment = Circuit::<M31> {
+        x: M31::from(x),
+        y: M31::from(y),
+    };
+    compile_result
+        .witness_solver
+        .solve_witness(&assignment)
+        .unwrap()
+}
+
+#[test]
+fn test_source_map_locations() {
+    let compile_result = compile(
+        &Circuit::default(),
+        CompileOptions::default().with_source_map(),
+    )
+    .unwrap();
+    let lc = &compile_result.layered_circuit;
+
+    let witness = solve(&compile_result, 3, 9);
+    assert_eq!(lc.run(&witness), vec![true]);
+    assert_eq!(lc.failed_constraints(&witness), Some(vec![]));
+
+    let witness = solve(&compile_result, 3, 10);
+    assert_eq!(lc.run(&witness), vec![false]);
+    let failures = lc.failed_constraints(&witness).unwrap();
+    assert_eq!(failures.len(), 1);
+    let failure = &failures[0];
+    assert_eq!(failure.witness_index, 0);
+    // the constraint is in the sub circuit, where x * x - y = -1
+    assert_ne!(failure.circuit_id, 0);
+    assert!(failure.value == M31::one() || failure.value == -M31::one());
+    assert!(!failure.origins.is_empty());
+    assert!(failure.locations[0].file().ends_with("source_map.rs"));
+    assert_eq!(failure.locations[0].line(), ASSERT_LINE);
+    assert_eq!(failure.call_stack.len(), 1);
+    assert_eq!(failure.call_stack[0].circuit_id, 0);
+    assert_eq!(failure.call_stack[0].location.unwrap().line(), CALL_LINE);
+    let message = failure.to_string();
+    assert!(message.contains(&format!("source_map.rs:{ASSERT_LINE}")));
+    assert!(message.contains("called from circuit 0"));
+
+    let witness = solve(&compile_result, 0, 0);
+    let failures = lc.failed_constraints(&witness).unwrap();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].circuit_id, 0);
+    assert_eq!(failures[0].locations[0].line(), NON_ZERO_LINE);
+    assert!(failures[0].call_stack.is_empty());
+}
+
+#[test]
+fn test_source_map_disabled() {
+    let compile_result = compile(&Circuit::default(), CompileOptions::default()).unwrap();
+    let witness = solve(&compile_result, 3, 10);
+    assert!(compile_result.layered_circuit.source_map().is_none());
+    assert_eq!(
+        compile_result.layered_circuit.failed_constraints(&witness),
+        None
+    );
+}
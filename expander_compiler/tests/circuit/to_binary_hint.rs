@@ -58,3 +58,107 @@ fn test_300() {
         assert_eq!(output, vec![i < 256]);
     }
 }
+
+declare_circuit!(ScaledCircuit {
+    x: Variable,
+    y: PublicVariable,
+});
+
+impl Define<M31Config> for ScaledCircuit<Variable> {
+    fn define<Builder: RootAPI<M31Config>>(&self, builder: &mut Builder) {
+        let handle = HintHandle::from_key("your_hint_namespace.scale");
+        let scaled = builder.new_hint(handle, &[self.x], 1)[0];
+        builder.assert_is_equal(scaled, self.y);
+    }
+}
+
+#[test]
+fn test_closure_hint() {
+    let factor = M31::from(7);
+    let mut hint_registry = HintRegistry::<M31>::new();
+    let handle = hint_registry.register("your_hint_namespace.scale", move |x, y| {
+        y[0] = x[0] * factor;
+        Ok(())
+    });
+    assert_eq!(handle, HintHandle::from_key("your_hint_namespace.scale"));
+    assert!(hint_registry.contains(handle));
+
+    let compile_result = compile(&ScaledCircuit::default(), CompileOptions::default()).unwrap();
+    for (x, y, expected) in [(3, 21, true), (3, 20, false)] {
+        let assignment = ScaledCircuit::<M31> {
+            x: M31::from(x),
+            y: M31::from(y),
+        };
+        let witness = compile_result
+            .witness_solver
+            .solve_witness_with_hints(&assignment, &hint_registry)
+            .unwrap();
+        let output = compile_result.layered_circuit.run(&witness);
+        assert_eq!(output, vec![expected]);
+    }
+}
+
+#[test]
+fn test_missing_hint() {
+    let compile_result = compile(&Circuit::default(), CompileOptions::default()).unwrap();
+    let hint_registry = HintRegistry::<M31>::new();
+    let assignment = Circuit::<M31> {
+        input: M31::from(3),
+    };
+
+    let err = hint_registry
+        .validate_against(&compile_result.witness_solver)
+        .unwrap_err()
+        .prepend("validating hints");
+    assert!(err
+        .to_string()
+        .starts_with("validating hints: hint \"your_hint_namespace.sub_namespace.tobinary\""));
+    let e = match err {
+        Error::HintError(e) => e,
+        err => panic!("expected a hint error, got {err:?}"),
+    };
+    assert_eq!(e.kind, extra::HintErrorKind::NotFound);
+    assert_eq!(
+        e.hint_key.as_deref(),
+        Some("your_hint_namespace.sub_namespace.tobinary")
+    );
+    assert_eq!(e.location.len(), 1);
+    assert_eq!(e.context, vec!["validating hints".to_string()]);
+
+    let err = compile_result
+        .witness_solver
+        .solve_witness_with_hints(&assignment, &hint_registry)
+        .unwrap_err();
+    let e = match err {
+        Error::HintError(e) => e,
+        err => panic!("expected a hint error, got {err:?}"),
+    };
+    assert_eq!(e.kind, extra::HintErrorKind::NotFound);
+    assert_eq!(
+        e.hint_id,
+        HintHandle::from_key("your_hint_namespace.sub_namespace.tobinary").id()
+    );
+    assert_eq!(
+        e.hint_key.as_deref(),
+        Some("your_hint_namespace.sub_namespace.tobinary")
+    );
+    assert_eq!(e.location[0].circuit_id, 0);
+}
+
+#[test]
+fn test_duplicate_hint() {
+    let mut hint_registry = HintRegistry::<M31>::new();
+    hint_registry
+        .try_register("your_hint_namespace.dup", to_binary_hint)
+        .unwrap();
+    let err = hint_registry
+        .try_register("your_hint_namespace.dup", to_binary_hint)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::HintError(extra::HintError {
+            kind: extra::HintErrorKind::AlreadyRegistered,
+            ..
+        })
+    ));
+}
@@ -0,0 +1,140 @@
+use expander_compiler::frontend::*;
+use rand::{Rng, SeedableRng};
+
+declare_circuit!(Circuit {
+    a: [Variable; 32],
+    b: [Variable; 32],
+    c: [Variable; 32],
+    sum: [Variable; 32],
+    sum3: [Variable; 32],
+    sigma: [Variable; 32],
+    ch: [Variable; 32],
+});
+
+impl<C: Config> Define<C> for Circuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        let a = UInt::<32>::from_bits(builder, &self.a);
+        let b = UInt::<32>::from_bits(builder, &self.b);
+        let c = UInt::<32>::from_bits(builder, &self.c);
+
+        let sum = a.add(builder, &b);
+        sum.assert_is_equal(builder, &UInt::from_bits_unchecked(&self.sum));
+        let sum3 = UInt::add_many(builder, &[a, b, c]);
+        sum3.assert_is_equal(builder, &UInt::from_bits_unchecked(&self.sum3));
+
+        // sigma0 of SHA-256
+        let r7 = a.rotate_right(7);
+        let r18 = a.rotate_right(18);
+        let s3 = a.shift_right(builder, 3);
+        let sigma = r7.xor(builder, &r18);
+        let sigma = sigma.xor(builder, &s3);
+        sigma.assert_is_equal(builder, &UInt::from_bits_unchecked(&self.sigma));
+
+        let ab = a.and(builder, &b);
+        let not_a = a.not(builder);
+        let not_ac = not_a.and(builder, &c);
+        let ch = ab.xor(builder, &not_ac);
+        ch.assert_is_equal(builder, &UInt::from_bits_unchecked(&self.ch));
+
+        // bytes round trip
+        let bytes = Bytes::from_uints(a.to_be_bytes().try_into().unwrap());
+        let words = bytes.to_be_words::<32>();
+        words[0].assert_is_equal(builder, &a);
+    }
+}
+
+fn to_bits<F: From<u32>>(x: u32) -> [F; 32] {
+    std::array::from_fn(|i| F::from((x >> i) & 1))
+}
+
+fn assignment<F: From<u32>>(a: u32, b: u32, c: u32) -> Circuit<F> {
+    Circuit {
+        a: to_bits(a),
+        b: to_bits(b),
+        c: to_bits(c),
+        sum: to_bits(a.wrapping_add(b)),
+        sum3: to_bits(a.wrapping_add(b).wrapping_add(c)),
+        sigma: to_bits(a.rotate_right(7) ^ a.rotate_right(18) ^ (a >> 3)),
+        ch: to_bits((a & b) ^ (!a & c)),
+    }
+}
+
+fn test_uint_ops<C: Config>()
+where
+    CircuitField<C>: From<u32>,
+{
+    let compile_result = compile::<C, _>(&Circuit::default(), CompileOptions::default()).unwrap();
+    let run = |a: &Circuit<CircuitField<C>>| {
+        let witness = compile_result.witness_solver.solve_witness(a).unwrap();
+        compile_result.layered_circuit.run(&witness)[0]
+    };
+    let mut rng = rand::rngs::StdRng::seed_from_u64(12);
+    for _ in 0..8 {
+        let (a, b, c) = (rng.gen(), rng.gen(), rng.gen());
+        assert!(run(&assignment(a, b, c)));
+    }
+    assert!(run(&assignment(u32::MAX, u32::MAX, u32::MAX)));
+    let mut bad = assignment(1, 2, 3);
+    bad.sum = to_bits(4);
+    assert!(!run(&bad));
+}
+
+#[test]
+fn test_uint_ops_m31() {
+    test_uint_ops::<M31Config>();
+}
+
+#[test]
+fn test_uint_ops_gf2() {
+    test_uint_ops::<GF2Config>();
+}
+
+#[test]
+fn test_uint_ops_bn254() {
+    test_uint_ops::<BN254Config>();
+}
+
+declare_circuit!(BytesCircuit {
+    x: [Variable; 4],
+    word: Variable,
+});
+
+impl<C: Config> Define<C> for BytesCircuit<Variable> {
+    fn define<Builder: RootAPI<C>>(&self, builder: &mut Builder) {
+        let bytes = Bytes::new(builder, &self.x);
+        let word = bytes.to_be_words::<16>();
+        let hi = word[0].to_variable(builder);
+        let lo = word[1].to_variable(builder);
+        let t = builder.mul(hi, 1 << 16);
+        let t = builder.add(t, lo);
+        builder.assert_is_equal(t, self.word);
+    }
+}
+
+fn run_bytes(x: [u32; 4], word: u32) -> bool {
+    let compile_result =
+        compile::<M31Config, _>(&BytesCircuit::default(), CompileOptions::default()).unwrap();
+    let assignment = BytesCircuit::<M31> {
+        x: x.map(M31::from),
+        word: M31::from(word),
+    };
+    let witness = compile_result
+        .witness_solver
+        .solve_witness(&assignment)
+        .unwrap();
+    compile_result.layered_circuit.run(&witness)[0]
+}
+
+#[test]
+fn test_bytes() {
+    assert!(run_bytes([0x12, 0x34, 0x56, 0x78], 0x12345678));
+    assert!(!run_bytes([0x12, 0x34, 0x56, 0x79], 0x12345678));
+}
+
+// 0x134 * 2^16 + 0x5678 == 0x1345678 would pass without the range check,
+// which can't even be solved
+#[test]
+#[should_panic]
+fn test_bytes_out_of_range() {
+    run_bytes([0x00, 0x134, 0x56, 0x78], 0x01345678);
+}
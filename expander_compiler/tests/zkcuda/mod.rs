@@ -1,3 +1,5 @@
+mod zkcuda_device_memory;
 mod zkcuda_examples;
 mod zkcuda_keccak;
 mod zkcuda_matmul;
+mod zkcuda_tensor_kernels;
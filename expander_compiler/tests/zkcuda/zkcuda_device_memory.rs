@@ -0,0 +1,75 @@
+use expander_compiler::frontend::*;
+use expander_compiler::zkcuda::proving_system::{Expander, ProvingSystem};
+use expander_compiler::zkcuda::tensor_kernels::add_kernel;
+use expander_compiler::zkcuda::{context::*, kernel::*};
+
+fn matrix(rows: usize, cols: usize) -> Vec<Vec<M31>> {
+    (0..rows)
+        .map(|i| (0..cols).map(|j| M31::from((i * 10 + j) as u32)).collect())
+        .collect()
+}
+
+#[test]
+fn zkcuda_device_memory_transforms() {
+    let add: KernelPrimitive<M31Config> = add_kernel(&[4], &[4]).unwrap();
+    let mut ctx: Context<M31Config> = Context::default();
+
+    let a = matrix(4, 4);
+    let a_device = ctx.copy_to_device(&a);
+    let top = ctx.slice(&a_device, 0, 0..2).unwrap();
+    let bottom = ctx.slice(&a_device, 0, 2..4).unwrap();
+    let (top_in, bottom_in) = (top.clone(), bottom.clone());
+    let mut sum = None;
+    call_kernel!(ctx, add, 2, top_in, bottom_in, mut sum).unwrap();
+    let joined = ctx.concat(&[top.clone(), sum.clone()], 0).unwrap();
+    let stacked = ctx.stack(&[top, bottom], 1).unwrap();
+    let gathered = ctx.gather(&a_device, 1, &[3, 0, 0]).unwrap();
+    let row = ctx.copy_to_device(&a[1]);
+    let repeated = ctx.broadcast_to(&row, &[2, 4]).unwrap();
+    // the results feed kernels like any other memory
+    let mut doubled = None;
+    call_kernel!(ctx, add, 2, repeated, sum, mut doubled).unwrap();
+
+    let joined: Vec<Vec<M31>> = ctx.copy_to_host(joined);
+    let stacked: Vec<Vec<Vec<M31>>> = ctx.copy_to_host(stacked);
+    let gathered: Vec<Vec<M31>> = ctx.copy_to_host(gathered);
+    let doubled: Vec<Vec<M31>> = ctx.copy_to_host(doubled);
+    for i in 0..2 {
+        for j in 0..4 {
+            let s = a[i][j] + a[i + 2][j];
+            assert_eq!(joined[i][j], a[i][j]);
+            assert_eq!(joined[i + 2][j], s);
+            assert_eq!(stacked[i][0][j], a[i][j]);
+            assert_eq!(stacked[i][1][j], a[i + 2][j]);
+            assert_eq!(doubled[i][j], a[1][j] + s);
+        }
+    }
+    for i in 0..4 {
+        assert_eq!(gathered[i], vec![a[i][3], a[i][0], a[i][0]]);
+    }
+
+    type P = Expander<M31Config>;
+    let computation_graph = ctx.compile_computation_graph().unwrap();
+    ctx.solve_witness().unwrap();
+    let (prover_setup, verifier_setup) = P::setup(&computation_graph);
+    let proof = P::prove(
+        &prover_setup,
+        &computation_graph,
+        ctx.export_device_memories(),
+    );
+    assert!(P::verify(&verifier_setup, &computation_graph, &proof));
+}
+
+#[test]
+fn zkcuda_device_memory_transform_errors() {
+    let mut ctx: Context<M31Config> = Context::default();
+    let a = ctx.copy_to_device(&matrix(4, 4));
+    let b = ctx.copy_to_device(&matrix(3, 3));
+    assert!(ctx.slice(&a, 0, 3..5).is_err());
+    assert!(ctx.slice(&a, 2, 0..1).is_err());
+    assert!(ctx.gather(&a, 1, &[]).is_err());
+    assert!(ctx.concat(&[a.clone(), b.clone()], 0).is_err());
+    assert!(ctx.stack(&[a.clone(), b], 0).is_err());
+    assert!(ctx.broadcast_to(&a, &[4, 2]).is_err());
+    assert!(ctx.broadcast_to(&a, &[4]).is_err());
+}
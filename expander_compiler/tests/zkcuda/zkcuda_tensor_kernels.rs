@@ -0,0 +1,160 @@
+use expander_compiler::frontend::*;
+use expander_compiler::zkcuda::proving_system::{Expander, ProvingSystem};
+use expander_compiler::zkcuda::shape::Reshape;
+use expander_compiler::zkcuda::tensor_kernels::*;
+use expander_compiler::zkcuda::{context::*, kernel::*};
+
+fn matrix(rows: usize, cols: usize, seed: usize) -> Vec<Vec<M31>> {
+    (0..rows)
+        .map(|i| {
+            (0..cols)
+                .map(|j| M31::from(((i * 31 + j * 7 + seed) % 97 + 1) as u32))
+                .collect()
+        })
+        .collect()
+}
+
+fn prove_and_verify(ctx: &mut Context<M31Config>) {
+    type P = Expander<M31Config>;
+    let computation_graph = ctx.compile_computation_graph().unwrap();
+    ctx.solve_witness().unwrap();
+    let (prover_setup, verifier_setup) = P::setup(&computation_graph);
+    let proof = P::prove(
+        &prover_setup,
+        &computation_graph,
+        ctx.export_device_memories(),
+    );
+    assert!(P::verify(&verifier_setup, &computation_graph, &proof));
+}
+
+#[test]
+fn zkcuda_tensor_map_reduce_scan() {
+    const N: usize = 4;
+    let square: KernelPrimitive<M31Config> = map_kernel(&[8], |api, x| api.mul(x, x)).unwrap();
+    let sum_rows: KernelPrimitive<M31Config> = reduce_sum_kernel(&[2, 4], 1).unwrap();
+    let product_cols: KernelPrimitive<M31Config> = reduce_product_kernel(&[2, 4], 0).unwrap();
+    let prefix_sum: KernelPrimitive<M31Config> = prefix_sum_kernel(&[8], 0).unwrap();
+    let mut ctx: Context<M31Config> = Context::default();
+
+    let input = matrix(N, 8, 3);
+    let a = ctx.copy_to_device(&input);
+    let mut squares = None;
+    call_kernel!(ctx, square, N, a, mut squares).unwrap();
+    let squares_2x4 = squares.reshape(&[N, 2, 4]);
+    let mut rows = None;
+    call_kernel!(ctx, sum_rows, N, squares_2x4, mut rows).unwrap();
+    let squares_2x4 = squares.reshape(&[N, 2, 4]);
+    let mut cols = None;
+    call_kernel!(ctx, product_cols, N, squares_2x4, mut cols).unwrap();
+    let mut prefix = None;
+    call_kernel!(ctx, prefix_sum, N, squares, mut prefix).unwrap();
+
+    let rows: Vec<Vec<M31>> = ctx.copy_to_host(rows);
+    let cols: Vec<Vec<M31>> = ctx.copy_to_host(cols);
+    let prefix: Vec<Vec<M31>> = ctx.copy_to_host(prefix);
+    for (p, x) in input.iter().enumerate() {
+        let sq = x.iter().map(|v| *v * *v).collect::<Vec<_>>();
+        let row_sums = sq
+            .chunks(4)
+            .map(|r| r.iter().fold(M31::zero(), |acc, v| acc + *v))
+            .collect::<Vec<_>>();
+        assert_eq!(rows[p], row_sums);
+        let col_products = (0..4).map(|j| sq[j] * sq[4 + j]).collect::<Vec<_>>();
+        assert_eq!(cols[p], col_products);
+        let mut acc = M31::zero();
+        let scan = sq
+            .iter()
+            .map(|v| {
+                acc += *v;
+                acc
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(prefix[p], scan);
+    }
+
+    prove_and_verify(&mut ctx);
+}
+
+#[test]
+fn zkcuda_tensor_broadcast() {
+    const N: usize = 2;
+    // [4, 4] + [4] inside each instance, [4, 1] * [4, 4] with the left operand shared
+    let add: KernelPrimitive<M31Config> = add_kernel(&[4, 4], &[4]).unwrap();
+    let mul: KernelPrimitive<M31Config> = mul_kernel(&[4, 1], &[4, 4]).unwrap();
+    let mut ctx: Context<M31Config> = Context::default();
+
+    let a = (0..N).map(|p| matrix(4, 4, p)).collect::<Vec<_>>();
+    let b = matrix(N, 4, 10);
+    let c = matrix(4, 1, 20);
+    let a_device = ctx.copy_to_device(&a);
+    let b_device = ctx.copy_to_device(&b);
+    let mut sum = None;
+    call_kernel!(ctx, add, N, a_device, b_device, mut sum).unwrap();
+    let c_device = ctx.copy_to_device(&c);
+    let mut product = None;
+    call_kernel!(ctx, mul, N, c_device, sum, mut product).unwrap();
+
+    let product: Vec<Vec<Vec<M31>>> = ctx.copy_to_host(product);
+    for p in 0..N {
+        for i in 0..4 {
+            for j in 0..4 {
+                assert_eq!(product[p][i][j], c[i][0] * (a[p][i][j] + b[p][j]));
+            }
+        }
+    }
+
+    prove_and_verify(&mut ctx);
+}
+
+#[test]
+fn zkcuda_tensor_matmul_tiles() {
+    // [4, 8] x [8, 2] as two tiles along the inner dimension, summed afterwards
+    const TILES: usize = 2;
+    let tile: KernelPrimitive<M31Config> = matmul_kernel(4, 4, 2).unwrap();
+    let sum_tiles: KernelPrimitive<M31Config> = reduce_sum_kernel(&[TILES, 8], 0).unwrap();
+    let dot: KernelPrimitive<M31Config> = dot_kernel(8).unwrap();
+    let mut ctx: Context<M31Config> = Context::default();
+
+    let a = matrix(4, 8, 1);
+    let b = matrix(8, 2, 2);
+    let a_tiles = (0..TILES)
+        .map(|t| a.iter().map(|row| row[t * 4..t * 4 + 4].to_vec()).collect())
+        .collect::<Vec<Vec<Vec<M31>>>>();
+    let b_tiles = (0..TILES)
+        .map(|t| b[t * 4..t * 4 + 4].to_vec())
+        .collect::<Vec<_>>();
+    let a_device = ctx.copy_to_device(&a_tiles);
+    let b_device = ctx.copy_to_device(&b_tiles);
+    let mut partial = None;
+    call_kernel!(ctx, tile, TILES, a_device, b_device, mut partial).unwrap();
+    let partial = partial.reshape(&[1, TILES, 8]);
+    let mut c = None;
+    call_kernel!(ctx, sum_tiles, 1, partial, mut c).unwrap();
+
+    let x = ctx.copy_to_device(&a);
+    let column = (0..8).map(|k| b[k][1]).collect::<Vec<_>>();
+    let y = ctx.copy_to_device(&column);
+    let mut dots = None;
+    call_kernel!(ctx, dot, 4, x, y, mut dots).unwrap();
+
+    let c = c.reshape(&[4, 2]);
+    let c: Vec<Vec<M31>> = ctx.copy_to_host(c);
+    let dots: Vec<M31> = ctx.copy_to_host(dots);
+    for i in 0..4 {
+        for j in 0..2 {
+            let expected = (0..8).fold(M31::zero(), |acc, k| acc + a[i][k] * b[k][j]);
+            assert_eq!(c[i][j], expected);
+        }
+        assert_eq!(dots[i], c[i][1]);
+    }
+
+    prove_and_verify(&mut ctx);
+}
+
+#[test]
+fn zkcuda_tensor_shape_errors() {
+    assert!(add_kernel::<M31Config>(&[3, 4], &[3]).is_err());
+    assert!(reduce_sum_kernel::<M31Config>(&[3, 4], 2).is_err());
+    assert!(prefix_sum_kernel::<M31Config>(&[0, 4], 0).is_err());
+    assert_eq!(broadcast_shape(&[2, 1, 4], &[3, 1]).unwrap(), vec![2, 3, 4]);
+}